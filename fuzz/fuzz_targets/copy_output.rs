@@ -0,0 +1,32 @@
+//! Feeds arbitrary bytes to the `COPY` output parser, split into two chunks at an
+//! arbitrary point, in each format and with and without a header.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pgde::__private::parse_copy_output;
+use pgde::CopyOptions;
+
+fuzz_target!(|data: &[u8]| {
+    let (control, data) = match data.split_first() {
+        Some(v) => v,
+        None => return,
+    };
+
+    let split = (*control as usize >> 2) % (data.len() + 1);
+    let (first, second) = data.split_at(split);
+
+    let options = match control & 1 {
+        0 => CopyOptions::csv(),
+        _ => CopyOptions::text(),
+    }
+    .header(control & 2 != 0);
+
+    let whole = parse_copy_output(&[data], &options);
+    let chunked = parse_copy_output(&[first, second], &options);
+
+    match (whole, chunked) {
+        (Ok(whole), Ok(chunked)) => assert_eq!(whole, chunked),
+        (Err(_), Err(_)) => (),
+        _ => panic!("chunked output parsed differently"),
+    }
+});
@@ -0,0 +1,59 @@
+//! Feeds arbitrary bytes to the `FromSql` implementations of the crate's value types,
+//! under every type they accept.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pgde::{
+    ArrayColumn, CoercedBool, Interval, Number, RawValue, Seconds, TextValue, UtcTimestamp,
+};
+use smallvec::SmallVec;
+use tokio_postgres::types::{FromSql, Type};
+
+const TYPES: [Type; 20] = [
+    Type::BOOL,
+    Type::BPCHAR,
+    Type::CHAR,
+    Type::DATE,
+    Type::FLOAT4,
+    Type::FLOAT8,
+    Type::INT2,
+    Type::INT4,
+    Type::INT8,
+    Type::INT4_ARRAY,
+    Type::INTERVAL,
+    Type::MONEY,
+    Type::NUMERIC,
+    Type::OID,
+    Type::TEXT,
+    Type::TEXT_ARRAY,
+    Type::TIMESTAMP,
+    Type::TIMESTAMPTZ,
+    Type::UUID,
+    Type::VARCHAR,
+];
+
+/// Decodes `raw` as `T` when `T` accepts `ty`, discarding the result.
+fn decode<'a, T: FromSql<'a>>(ty: &Type, raw: &'a [u8]) {
+    if T::accepts(ty) {
+        let _ = T::from_sql(ty, raw);
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let (control, raw) = match data.split_first() {
+        Some(v) => v,
+        None => return,
+    };
+
+    let ty = &TYPES[*control as usize % TYPES.len()];
+
+    decode::<ArrayColumn<SmallVec<[Option<i32>; 4]>>>(ty, raw);
+    decode::<ArrayColumn<SmallVec<[Option<String>; 4]>>>(ty, raw);
+    decode::<CoercedBool>(ty, raw);
+    decode::<Interval>(ty, raw);
+    decode::<Number>(ty, raw);
+    decode::<RawValue>(ty, raw);
+    decode::<Seconds>(ty, raw);
+    decode::<TextValue>(ty, raw);
+    decode::<UtcTimestamp>(ty, raw);
+});
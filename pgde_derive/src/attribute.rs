@@ -0,0 +1,277 @@
+//! The `row_consumer` attribute macro, which derives `RowConsumer` like the derive macro
+//! and can also add items to the struct, or configure every struct of a module.
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::meta::ParseNestedMeta;
+use syn::{Item, ItemStruct, LitStr};
+
+/// The configuration given to `#[row_consumer(...)]`.
+#[derive(Default)]
+pub(crate) struct RowConsumerArgs {
+    /// The table of the struct, passed on as `#[pgde(table = "...")]`.
+    table: Option<LitStr>,
+    /// The schema of the table, passed on as `#[pgde(schema = "...")]`.
+    schema: Option<LitStr>,
+    /// Whether to implement `CopyConsumer`, passed on as `#[pgde(copy)]`.
+    copy: bool,
+    /// The soft delete column of the table, passed on as `#[pgde(soft_delete = "...")]`.
+    soft_delete: Option<LitStr>,
+    /// Whether to implement `Summary`, passed on as `#[pgde(summary)]`.
+    summary: bool,
+    /// Whether to implement `RowHash`, passed on as `#[pgde(row_hash)]`.
+    row_hash: bool,
+    /// Whether to generate a partial struct whose fields are all optional.
+    partial: bool,
+    /// Whether to generate a constant holding the column name of every field.
+    columns: bool,
+}
+
+impl RowConsumerArgs {
+    /// Parses one `#[row_consumer(...)]` argument.
+    pub(crate) fn parse(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("table") {
+            self.table = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("schema") {
+            self.schema = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("copy") {
+            self.copy = true;
+        } else if meta.path.is_ident("soft_delete") {
+            self.soft_delete = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("summary") {
+            self.summary = true;
+        } else if meta.path.is_ident("row_hash") {
+            self.row_hash = true;
+        } else if meta.path.is_ident("partial") {
+            self.partial = true;
+        } else if meta.path.is_ident("columns") {
+            self.columns = true;
+        } else {
+            return Err(meta.error("unsupported row_consumer argument"));
+        }
+
+        Ok(())
+    }
+}
+
+/// Applies `#[row_consumer(...)]` to a struct, or to every struct of an inline module.
+pub(crate) fn row_consumer(
+    args: &RowConsumerArgs,
+    item: Item,
+) -> syn::Result<proc_macro2::TokenStream> {
+    match item {
+        Item::Struct(item) => rewrite_struct(args, item),
+        Item::Mod(mut item) => {
+            if let Some(v) = &args.table {
+                return Err(syn::Error::new(
+                    v.span(),
+                    "row_consumer(table) is not supported on modules",
+                ));
+            }
+
+            if let Some((_, items)) = item.content.as_mut() {
+                for v in items.iter_mut() {
+                    if let Item::Struct(s) = v {
+                        *v = Item::Verbatim(rewrite_struct(args, s.clone())?);
+                    }
+                }
+            }
+
+            Ok(quote! { #item })
+        }
+        item => Err(syn::Error::new_spanned(
+            item,
+            "row_consumer is only supported on structs and inline modules",
+        )),
+    }
+}
+
+/// Adds `#[derive(RowConsumer)]` and the configured `#[pgde(...)]` attributes to a struct,
+/// along with the configured partial struct and column constants. Attributes already on
+/// the struct take precedence over the configuration.
+fn rewrite_struct(
+    args: &RowConsumerArgs,
+    item: ItemStruct,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &item.ident;
+    let vis = &item.vis;
+
+    let fields = match &item.fields {
+        syn::Fields::Named(v) => v,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &item,
+                "row_consumer is only supported on structs with named fields",
+            ))
+        }
+    };
+
+    let mut struct_attributes = Vec::new();
+
+    if let Some(v) = &args.table {
+        struct_attributes.push(quote! { table = #v });
+    }
+
+    if let Some(v) = &args.schema {
+        struct_attributes.push(quote! { schema = #v });
+    }
+
+    if args.copy {
+        struct_attributes.push(quote! { copy });
+    }
+
+    if let Some(v) = &args.soft_delete {
+        struct_attributes.push(quote! { soft_delete = #v });
+    }
+
+    if args.summary {
+        struct_attributes.push(quote! { summary });
+    }
+
+    if args.row_hash {
+        struct_attributes.push(quote! { row_hash });
+    }
+
+    let struct_attributes = match struct_attributes.len() {
+        0 => quote! {},
+        _ => quote! { #[pgde(#(#struct_attributes),*)] },
+    };
+
+    if args.partial && !item.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &item.generics,
+            "row_consumer(partial) is not supported on generic structs",
+        ));
+    }
+
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+
+    let partial = match args.partial {
+        true => {
+            let partial_name = format_ident!("{}Partial", name);
+            let mut partial_fields = Vec::with_capacity(fields.named.len());
+            let mut partial_setters = Vec::with_capacity(fields.named.len());
+            let mut column_names = Vec::with_capacity(fields.named.len());
+
+            for f in fields.named.iter() {
+                let (field_name, field_vis, field_type) = (&f.ident, &f.vis, &f.ty);
+
+                let column_name = match field_name {
+                    Some(v) => v.unraw().to_string(),
+                    None => continue,
+                };
+
+                let attributes = crate::parse_field_attributes(f)?;
+                let reader =
+                    crate::field_reader(&partial_name, f, quote! { #column_name }, &attributes);
+
+                partial_fields.push(quote! { #field_vis #field_name: Option<#field_type> });
+                partial_setters.push(quote! {
+                    #field_name: match row.columns().iter().any(|v| v.name() == #column_name) {
+                        true => {
+                            let previous = errors.len();
+                            let value = #reader;
+
+                            match errors.len() == previous {
+                                true => Some(value),
+                                false => None,
+                            }
+                        }
+                        false => None,
+                    }
+                });
+                column_names.push(column_name);
+            }
+
+            let doc = format!(
+                "The columns of [`{}`] that a row holds, read by name. Generated by `row_consumer`.",
+                name
+            );
+
+            quote! {
+                #[doc = #doc]
+                #[derive(Default)]
+                #vis struct #partial_name {
+                    #(#partial_fields),*
+                }
+
+                impl pgde::RowConsumer for #partial_name {
+                    const NAMED_COLUMNS: Option<&'static [&'static str]> = Some(&[#(#column_names),*]);
+
+                    fn from_row(row: Row) -> Result<Self, (Self, Vec<String>)>
+                    where
+                        Self: Sized,
+                    {
+                        <Self as pgde::RowRefConsumer>::from_row_ref(&row)
+                    }
+                }
+
+                impl pgde::RowRefConsumer for #partial_name {
+                    fn from_row_ref(row: &Row) -> Result<Self, (Self, Vec<String>)>
+                    where
+                        Self: Sized,
+                    {
+                        let mut errors : Vec<String> = Vec::new();
+
+                        let class_instance = Self {
+                            #(#partial_setters),*
+                        };
+
+                        match errors.len() {
+                            0 => Ok(class_instance),
+                            _ => Err((class_instance, errors)),
+                        }
+                    }
+                }
+            }
+        }
+        false => quote! {},
+    };
+
+    let columns = match args.columns {
+        true => {
+            let mut names: Vec<(String, &syn::Ident)> = Vec::with_capacity(fields.named.len());
+            let mut constants = Vec::with_capacity(fields.named.len());
+
+            for field_name in fields.named.iter().filter_map(|f| f.ident.as_ref()) {
+                let column_name = field_name.unraw().to_string();
+                let constant = column_name.to_uppercase();
+
+                if let Some((_, v)) = names.iter().find(|(v, _)| *v == constant) {
+                    return Err(syn::Error::new(
+                        field_name.span(),
+                        format!(
+                            "fields `{}` and `{}` both become constant `{}` of row_consumer(columns)",
+                            v, field_name, constant
+                        ),
+                    ));
+                }
+
+                let ident = format_ident!("{}", constant);
+                let doc = format!("The column name of field `{}`.", column_name);
+
+                constants.push(quote! {
+                    #[doc = #doc]
+                    pub const #ident: &'static str = #column_name;
+                });
+                names.push((constant, field_name));
+            }
+
+            quote! {
+                impl #impl_generics #name #ty_generics #where_clause {
+                    #(#constants)*
+                }
+            }
+        }
+        false => quote! {},
+    };
+
+    Ok(quote! {
+        #[derive(::pgde_derive::RowConsumer)]
+        #struct_attributes
+        #item
+
+        #partial
+
+        #columns
+    })
+}
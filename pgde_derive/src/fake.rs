@@ -0,0 +1,93 @@
+//! The `FakeRow` derive macro, which generates deterministic fake values for the fields
+//! of a struct.
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Data, DeriveInput, Fields, LitInt};
+
+/// Parses the column length of a field from `#[pgde(max_len = ...)]`, skipping the other
+/// `#[pgde(...)]` attributes, which belong to `RowConsumer`.
+fn parse_max_len(field: &syn::Field) -> syn::Result<Option<usize>> {
+    let mut max_len = None;
+
+    for attr in field.attrs.iter().filter(|v| v.path().is_ident("pgde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("max_len") {
+                let value: LitInt = meta.value()?.parse()?;
+                max_len = Some(value.base10_parse::<usize>()?);
+            } else if meta.input.peek(syn::Token![=]) {
+                meta.value()?.parse::<syn::Expr>()?;
+            } else if meta.input.peek(syn::token::Paren) {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                content.parse::<TokenStream>()?;
+            }
+
+            Ok(())
+        })?;
+    }
+
+    Ok(max_len)
+}
+
+/// Implements `FakeRow` on a struct, generating every field with its type's `FakeValue`
+/// implementation, given the field's name and column length.
+pub(crate) fn derive_fake_row(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(v) => match &v.fields {
+            Fields::Named(v) => v,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    input,
+                    "FakeRow is only supported on structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "FakeRow is only supported on structs with named fields",
+            ))
+        }
+    };
+
+    let mut setters = Vec::with_capacity(fields.named.len());
+
+    for f in fields.named.iter() {
+        let field_name = match &f.ident {
+            Some(v) => v,
+            None => continue,
+        };
+
+        let column = field_name.unraw().to_string();
+        let ty = &f.ty;
+
+        let max_len = match parse_max_len(f)? {
+            Some(v) => quote! { Some(#v) },
+            None => quote! { None },
+        };
+
+        setters.push(quote! {
+            #field_name: <#ty as pgde::FakeValue>::fake(
+                rng,
+                &pgde::FakeField {
+                    name: #column,
+                    max_len: #max_len,
+                },
+            )
+        });
+    }
+
+    Ok(quote! {
+        impl #impl_generics pgde::FakeRow for #name #ty_generics #where_clause {
+            fn fake_with(rng: &mut pgde::FakeRng) -> Self {
+                #name {
+                    #(#setters,)*
+                }
+            }
+        }
+    })
+}
@@ -2,99 +2,1617 @@
 //! provide a `from_row` implementation. Please refer to pgde for use, examples, and
 //! limitations.
 extern crate proc_macro;
+mod attribute;
+mod fake;
+mod params;
+
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{parse_macro_input, Data, DeriveInput, Fields, Ident, PathArguments, Type};
+use quote::{format_ident, quote, ToTokens};
+use syn::ext::IdentExt;
+use syn::{
+    parse_macro_input, Attribute, Data, DataEnum, DeriveInput, Expr, Field, Fields,
+    GenericArgument, Generics, Ident, LitStr, PathArguments, Type, Variant, Visibility,
+};
 
-/// A macro for deriving a `from_row` implementation onto a struct.
-#[proc_macro_derive(RowConsumer)]
+/// A macro for deriving `RowConsumer` and `RowRefConsumer` implementations, along with
+/// `Table` metadata, a `<Struct>Columns` enum, and `TryFrom<Row>`, onto a struct, or onto
+/// an enum that is either fieldless or tagged. A struct that implements `TryFrom<Row>` or
+/// `TryFrom<&Row>` itself may skip the derived implementations with
+/// `#[pgde(skip_try_from)]`.
+///
+/// Structs read their fields from columns by position, so consuming a query that returns
+/// a different number of columns than the struct has fields fails with
+/// `ConsumeError::ColumnCountMismatch`. Structs with a lifetime parameter borrow text and
+/// `bytea` fields, such as `&'a str` and `&'a [u8]`, from the row, and implement
+/// `BorrowedRowConsumer` in place of `RowConsumer` and `Table`.
+///
+/// The `<Struct>Columns` enum has a variant for every field in upper camel case, so fields
+/// whose variants clash, such as `user_id` and `_user_id`, fail to compile. The enum may be
+/// renamed with `#[pgde(columns_enum = "...")]` on the struct, or skipped with
+/// `#[pgde(skip_columns_enum)]`.
+///
+/// Fieldless enums that implement `Default` are read from the first column of a query,
+/// from text by variant name or by the value given with `#[pgde(rename = "...")]`, or,
+/// with `#[pgde(repr = "int")]` on the enum, from integers by discriminant. Enums with
+/// struct variants may instead be marked with `#[pgde(tag = "...")]`, naming a text column
+/// that selects the variant, whose fields are then read from the remaining columns by
+/// name. Columns read by name fail with `ConsumeError::AmbiguousColumns` when a query
+/// returns more than one of them, e.g. the `id` columns of a join.
+#[proc_macro_derive(RowConsumer, attributes(pgde))]
 pub fn derive_row_consumer(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let name = input.ident;
     let data = input.data;
-    parse_field_setters(&name, &data)
+
+    if let Data::Enum(ref data) = data {
+        return match parse_enum_variants(&name, &input.attrs, data) {
+            Ok(v) => TokenStream::from(v),
+            Err(e) => TokenStream::from(e.to_compile_error()),
+        };
+    }
+
+    match parse_struct_attributes(&name, &input.attrs) {
+        Ok(attributes) => {
+            parse_field_setters(&name, &input.vis, &input.generics, &data, &attributes)
+        }
+        Err(e) => TokenStream::from(e.to_compile_error()),
+    }
+}
+
+/// An attribute macro that derives `RowConsumer` onto a struct like the derive macro,
+/// taking the `table`, `schema`, `copy`, `soft_delete`, `summary`, and `row_hash` struct
+/// attributes as arguments. With `partial`, it also generates a `<Struct>Partial` struct
+/// whose fields are optional and read by name, with their field attributes, when their
+/// column is present, and with
+/// `columns`, an associated constant holding the column name of every field. Applied to
+/// an inline module, it applies its arguments, other than `table`, to every struct of the
+/// module.
+#[proc_macro_attribute]
+pub fn row_consumer(args: TokenStream, input: TokenStream) -> TokenStream {
+    let mut parsed = attribute::RowConsumerArgs::default();
+    let parser = syn::meta::parser(|meta| parsed.parse(meta));
+    parse_macro_input!(args with parser);
+
+    let item = parse_macro_input!(input as syn::Item);
+
+    match attribute::row_consumer(&parsed, item) {
+        Ok(v) => TokenStream::from(v),
+        Err(e) => TokenStream::from(e.to_compile_error()),
+    }
+}
+
+/// How an enum deriving `RowConsumer` is represented in its column.
+#[derive(PartialEq)]
+enum EnumRepr {
+    /// Variants are read from text by name, or by the value given with `rename`.
+    Text,
+    /// Variants are read from integers by discriminant.
+    Int,
+}
+
+/// Enum level configuration supplied through `#[pgde(...)]` attributes.
+struct EnumAttributes {
+    /// How the variant is represented in its column.
+    repr: EnumRepr,
+    /// The column naming the variant of an enum with struct variants, if any.
+    tag: Option<LitStr>,
+}
+
+/// Parses the `#[pgde(...)]` attributes of an enum deriving `RowConsumer` into
+/// [`EnumAttributes`].
+fn parse_enum_attributes(attrs: &[Attribute]) -> syn::Result<EnumAttributes> {
+    let mut attributes = EnumAttributes {
+        repr: EnumRepr::Text,
+        tag: None,
+    };
+
+    for attr in attrs.iter().filter(|v| v.path().is_ident("pgde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("repr") {
+                let value: LitStr = meta.value()?.parse()?;
+
+                attributes.repr = match value.value().as_str() {
+                    "text" => EnumRepr::Text,
+                    "int" => EnumRepr::Int,
+                    _ => {
+                        return Err(syn::Error::new(
+                            value.span(),
+                            "pgde(repr) must be \"text\" or \"int\"",
+                        ))
+                    }
+                };
+
+                Ok(())
+            } else if meta.path.is_ident("tag") {
+                attributes.tag = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported pgde enum attribute"))
+            }
+        })?;
+    }
+
+    match (&attributes.tag, &attributes.repr) {
+        (Some(v), EnumRepr::Int) => Err(syn::Error::new(
+            v.span(),
+            "pgde(tag) is not supported with pgde(repr = \"int\")",
+        )),
+        _ => Ok(attributes),
+    }
+}
+
+/// A function that takes a given enum's [`Ident`], tag column, and variants and returns a
+/// [`proc_macro2::TokenStream`] implementing `RowConsumer`, which reads the tag column as
+/// text and then reads the remaining columns by name into the fields of the matching
+/// variant.
+fn parse_tagged_variants(
+    enum_name: &Ident,
+    tag: &LitStr,
+    data: &DataEnum,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let mut arms = Vec::with_capacity(data.variants.len());
+    let mut column_names = vec![tag.value()];
+
+    for variant in data.variants.iter() {
+        let variant_name = &variant.ident;
+        let attributes = parse_variant_attributes(variant)?;
+
+        if attributes.other {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "pgde(other) is not supported with pgde(tag)",
+            ));
+        }
+
+        let text = match attributes.rename {
+            Some(v) => v,
+            None => LitStr::new(&variant_name.to_string(), variant_name.span()),
+        };
+
+        match &variant.fields {
+            Fields::Unit => arms.push(quote! { Ok(#text) => #enum_name::#variant_name }),
+            Fields::Named(fields) => {
+                let mut field_setters = Vec::with_capacity(fields.named.len());
+
+                for f in fields.named.iter() {
+                    let field_name = &f.ident;
+
+                    let column_name = match field_name {
+                        Some(v) => v.unraw().to_string(),
+                        None => continue,
+                    };
+
+                    let attributes = parse_field_attributes(f)?;
+                    let reader = field_reader(enum_name, f, quote! { #column_name }, &attributes);
+                    field_setters.push(quote! { #field_name: #reader });
+
+                    if !column_names.contains(&column_name) {
+                        column_names.push(column_name);
+                    }
+                }
+
+                arms.push(quote! {
+                    Ok(#text) => #enum_name::#variant_name { #(#field_setters),* }
+                });
+            }
+            Fields::Unnamed(_) => {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "pgde(tag) requires unit variants or variants with named fields",
+                ))
+            }
+        }
+    }
+
+    Ok(quote! {
+        impl pgde::RowConsumer for #enum_name {
+            const NAMED_COLUMNS: Option<&'static [&'static str]> = Some(&[#(#column_names),*]);
+
+            fn from_row(row: Row) -> Result<Self, (Self, Vec<String>)>
+            where
+                Self: Sized,
+            {
+                <Self as pgde::RowRefConsumer>::from_row_ref(&row)
+            }
+        }
+
+        impl pgde::RowRefConsumer for #enum_name {
+            fn from_row_ref(row: &Row) -> Result<Self, (Self, Vec<String>)>
+            where
+                Self: Sized,
+            {
+                let mut errors : Vec<String> = Vec::new();
+
+                let class_instance = match row.try_get::<&str, &str>(#tag) {
+                    #(#arms,)*
+                    _ => {
+                        errors.push(format!("Conversion error occurred for tag \"{}\" on class \"{}\"", #tag, stringify!(#enum_name)));
+                        <#enum_name>::default()
+                    }
+                };
+
+                match errors.len() {
+                    0 => Ok(class_instance),
+                    _ => Err((class_instance, errors)),
+                }
+            }
+        }
+    })
+}
+
+/// A function that takes a given enum's [`Ident`], attributes, and variants and returns a
+/// [`proc_macro2::TokenStream`] implementing `RowConsumer`. Fieldless enums read the first
+/// column as text or as an integer and map it to a variant, while enums marked with
+/// `#[pgde(tag = "...")]` are read by `parse_tagged_variants`.
+fn parse_enum_variants(
+    enum_name: &Ident,
+    attrs: &[Attribute],
+    data: &DataEnum,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let repr = match parse_enum_attributes(attrs)? {
+        EnumAttributes { tag: Some(v), .. } => return parse_tagged_variants(enum_name, &v, data),
+        EnumAttributes { repr, .. } => repr,
+    };
+    let mut arms = Vec::with_capacity(data.variants.len());
+
+    for variant in data.variants.iter() {
+        let variant_name = &variant.ident;
+        let attributes = parse_variant_attributes(variant)?;
+
+        match (&variant.fields, attributes.other, &repr) {
+            (Fields::Unit, false, EnumRepr::Text) => {
+                let text = match attributes.rename {
+                    Some(v) => v,
+                    None => LitStr::new(&variant_name.to_string(), variant_name.span()),
+                };
+
+                arms.push(quote! { Some(#text) => Ok(#enum_name::#variant_name) });
+            }
+            (Fields::Unit, false, EnumRepr::Int) => {
+                if let Some(v) = attributes.rename {
+                    return Err(syn::Error::new_spanned(
+                        v,
+                        "pgde(rename) is not supported with pgde(repr = \"int\")",
+                    ));
+                }
+
+                arms.push(quote! {
+                    Some(v) if v == #enum_name::#variant_name as i64 => Ok(#enum_name::#variant_name)
+                });
+            }
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "RowConsumer is only supported on enums with unit variants",
+                ))
+            }
+        }
+    }
+
+    let value = match repr {
+        EnumRepr::Text => quote! {
+            match row.try_get::<usize, &str>(0) {
+                Ok(v) => match row.columns().first().map(|c| c.type_()) {
+                    Some(&pgde::__private::Type::BPCHAR) => Some(v.trim_end_matches(' ')),
+                    _ => Some(v),
+                },
+                Err(_) => None,
+            }
+        },
+        EnumRepr::Int => quote! {
+            match (
+                row.try_get::<usize, i64>(0),
+                row.try_get::<usize, i32>(0),
+                row.try_get::<usize, i16>(0),
+            ) {
+                (Ok(v), _, _) => Some(v),
+                (_, Ok(v), _) => Some(i64::from(v)),
+                (_, _, Ok(v)) => Some(i64::from(v)),
+                _ => None,
+            }
+        },
+    };
+
+    Ok(quote! {
+        impl pgde::RowConsumer for #enum_name {
+            fn from_row(row: Row) -> Result<Self, (Self, Vec<String>)>
+            where
+                Self: Sized,
+            {
+                <Self as pgde::RowRefConsumer>::from_row_ref(&row)
+            }
+        }
+
+        impl pgde::RowRefConsumer for #enum_name {
+            fn from_row_ref(row: &Row) -> Result<Self, (Self, Vec<String>)>
+            where
+                Self: Sized,
+            {
+                match #value {
+                    #(#arms,)*
+                    _ => Err((
+                        <#enum_name>::default(),
+                        vec![format!("Conversion error occurred for class \"{}\"", stringify!(#enum_name))],
+                    )),
+                }
+            }
+        }
+    })
+}
+
+/// Struct level configuration supplied through `#[pgde(...)]` attributes.
+struct StructAttributes {
+    /// The table the struct is read from, defaulting to the struct's name.
+    table: LitStr,
+    /// The schema of the table, if any.
+    schema: Option<LitStr>,
+    /// Whether to implement `CopyConsumer`, parsing fields from `COPY` output.
+    copy: bool,
+    /// The column marking rows as deleted when it is not null, if any.
+    soft_delete: Option<LitStr>,
+    /// Whether to implement `Summary`, which requires feature `summary`.
+    summary: bool,
+    /// Whether to implement `RowHash`.
+    row_hash: bool,
+    /// The name of the generated columns enum, `<Struct>Columns` unless renamed with
+    /// `#[pgde(columns_enum = "...")]`, or `None` when skipped with
+    /// `#[pgde(skip_columns_enum)]`.
+    columns_enum: Option<Ident>,
+    /// Whether to implement `TryFrom<Row>` and `TryFrom<&Row>`, unless skipped with
+    /// `#[pgde(skip_try_from)]`.
+    try_from: bool,
+}
+
+/// Parses the `#[pgde(...)]` attributes of a struct into [`StructAttributes`].
+fn parse_struct_attributes(
+    class_name: &Ident,
+    attrs: &[Attribute],
+) -> syn::Result<StructAttributes> {
+    let mut attributes = StructAttributes {
+        table: LitStr::new(&class_name.unraw().to_string(), class_name.span()),
+        schema: None,
+        copy: false,
+        soft_delete: None,
+        summary: false,
+        row_hash: false,
+        columns_enum: Some(format_ident!("{}Columns", class_name)),
+        try_from: true,
+    };
+
+    for attr in attrs.iter().filter(|v| v.path().is_ident("pgde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("table") {
+                attributes.table = parse_identifier(meta.value()?.parse()?)?;
+                Ok(())
+            } else if meta.path.is_ident("schema") {
+                attributes.schema = Some(parse_identifier(meta.value()?.parse()?)?);
+                Ok(())
+            } else if meta.path.is_ident("copy") {
+                attributes.copy = true;
+                Ok(())
+            } else if meta.path.is_ident("soft_delete") {
+                attributes.soft_delete = Some(parse_identifier(meta.value()?.parse()?)?);
+                Ok(())
+            } else if meta.path.is_ident("row_hash") {
+                attributes.row_hash = true;
+                Ok(())
+            } else if meta.path.is_ident("columns_enum") {
+                let value: LitStr = meta.value()?.parse()?;
+                attributes.columns_enum = Some(value.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("skip_columns_enum") {
+                attributes.columns_enum = None;
+                Ok(())
+            } else if meta.path.is_ident("skip_try_from") {
+                attributes.try_from = false;
+                Ok(())
+            } else if meta.path.is_ident("summary") {
+                match cfg!(feature = "summary") {
+                    true => {
+                        attributes.summary = true;
+                        Ok(())
+                    }
+                    false => Err(meta.error("pgde(summary) requires feature `summary` of pgde")),
+                }
+            } else {
+                Err(meta.error("unsupported pgde struct attribute"))
+            }
+        })?;
+    }
+
+    Ok(attributes)
+}
+
+/// Validates an identifier given to a struct attribute the way `pgde::ident::Ident` does,
+/// so that invalid table and schema names fail at compile time.
+fn parse_identifier(ident: LitStr) -> syn::Result<LitStr> {
+    let value = ident.value();
+
+    match (value.is_empty(), value.contains('\0'), value.len() > 63) {
+        (true, _, _) => Err(syn::Error::new(ident.span(), "identifier is empty")),
+        (_, true, _) => Err(syn::Error::new(
+            ident.span(),
+            "identifier contains a NUL character",
+        )),
+        (_, _, true) => Err(syn::Error::new(
+            ident.span(),
+            "identifier is longer than 63 bytes",
+        )),
+        _ => Ok(ident),
+    }
+}
+
+/// A macro for deriving `TextEnum`, `FromSql`, and `ToSql` implementations onto an enum
+/// whose values are stored as text.
+#[proc_macro_derive(TextEnum, attributes(pgde))]
+pub fn derive_text_enum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match parse_text_variants(&input.ident, &input.data) {
+        Ok(v) => TokenStream::from(v),
+        Err(e) => TokenStream::from(e.to_compile_error()),
+    }
+}
+
+/// A macro for deriving a `QueryParams` implementation onto a struct whose fields are
+/// search filters. Fields filter the column of the same name, or the one given with
+/// `#[pgde(column = "...")]`, by equality, or by the comparison given with
+/// `#[pgde(op = "...")]`, and `Option` fields are skipped when `None`.
+#[proc_macro_derive(QueryParams, attributes(pgde))]
+pub fn derive_query_params(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match params::derive_query_params(&input) {
+        Ok(v) => TokenStream::from(v),
+        Err(e) => TokenStream::from(e.to_compile_error()),
+    }
 }
 
-/// A function that takes a given [`TokenStream`]'s [`Ident`] and [`Data`] and returns a
-/// [`TokenStream`] for implementing a `from_row` from a struct's fields.
-fn parse_field_setters(class_name: &Ident, data: &Data) -> TokenStream {
+/// A macro for deriving a `FakeRow` implementation onto a struct, generating each field
+/// with its type's `FakeValue` implementation. Strings are cut to the column length given
+/// with `#[pgde(max_len = ...)]`, and other `#[pgde(...)]` attributes are left to
+/// `RowConsumer`.
+#[proc_macro_derive(FakeRow, attributes(pgde))]
+pub fn derive_fake_row(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match fake::derive_fake_row(&input) {
+        Ok(v) => TokenStream::from(v),
+        Err(e) => TokenStream::from(e.to_compile_error()),
+    }
+}
+
+/// The ways a field's column may be read, selected through `#[pgde(...)]` attributes.
+#[derive(Clone)]
+enum Reader {
+    /// Read the column as a PostgreSQL `interval` and convert it into a duration type.
+    Interval,
+    /// Read the column as a number of seconds and convert it into a duration type.
+    Seconds,
+    /// Read an array column into a collection that implements `ArrayCollection`.
+    Array,
+    /// Read a `bit(n)`, `varbit`, or integer column into a `bitflags!` type, optionally
+    /// dropping unknown bits.
+    Bitflags { truncate: bool },
+    /// Read the column through a definition type generated by `impl_row_consumer!`, for
+    /// types defined outside of the crate.
+    Remote(syn::Path),
+    /// Read an integer or floating point column into a numeric type at most as wide,
+    /// failing for values out of its range.
+    Narrow,
+    /// Read an integer or floating point column into a numeric type at least as wide.
+    Widen,
+    /// Read a `numeric` column into a floating point number, applying the named
+    /// `PrecisionPolicy` variant to values it cannot hold exactly.
+    Numeric { policy: Ident },
+    /// Read a `bool`, `0`/`1` integer, or `t`/`f`/`true`/`false` text column into a
+    /// `bool`.
+    Coerce,
+    /// Read a text column into a `String`, optionally trimming surrounding whitespace and
+    /// treating empty strings as NULL. Unlike the other readers, both options may be set
+    /// by separate attributes on the same field.
+    Text { trim: bool, empty_as_none: bool },
+    /// Read a `timestamp` or `timestamptz` column as UTC into a date time type, whether
+    /// naive or time zone aware.
+    Utc,
+    /// Read the column as text and parse it into the field's type, or the type within an
+    /// `Option`, with `FromStr`.
+    AsText,
+}
+
+/// Field level configuration supplied through `#[pgde(...)]` attributes.
+#[derive(Default)]
+struct FieldAttributes {
+    reader: Option<Reader>,
+    /// The value of the field when its column is NULL, from `#[pgde(null_as = "...")]`.
+    null_as: Option<Expr>,
+    /// Whether the field is redacted from summaries, from `#[pgde(sensitive)]`.
+    sensitive: bool,
+    /// The `NanPolicy` variant applied to the value read, from `#[pgde(nan(...))]`.
+    nan: Option<Ident>,
+    /// Whether the field is part of the struct's `KeyedBy` key, from `#[pgde(key)]`.
+    key: bool,
+}
+
+/// Parses the `#[pgde(...)]` attributes of a field into [`FieldAttributes`].
+fn parse_field_attributes(field: &Field) -> syn::Result<FieldAttributes> {
+    let mut attributes = FieldAttributes::default();
+
+    for attr in field.attrs.iter().filter(|v| v.path().is_ident("pgde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("null_as") {
+                let value: LitStr = meta.value()?.parse()?;
+
+                if option_inner(&field.ty).is_some() {
+                    return Err(meta.error("pgde null_as is not supported on Option fields"));
+                }
+
+                attributes.null_as = Some(value.parse()?);
+                return Ok(());
+            }
+
+            if meta.path.is_ident("sensitive") {
+                attributes.sensitive = true;
+                return Ok(());
+            }
+
+            if meta.path.is_ident("key") {
+                attributes.key = true;
+                return Ok(());
+            }
+
+            if meta.path.is_ident("nan") {
+                let mut policy = None;
+
+                meta.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("accept") {
+                        policy = Some("Accept");
+                        Ok(())
+                    } else if meta.path.is_ident("none") {
+                        policy = Some("AsNone");
+                        Ok(())
+                    } else if meta.path.is_ident("error") {
+                        policy = Some("Error");
+                        Ok(())
+                    } else {
+                        Err(meta.error("unsupported pgde nan policy"))
+                    }
+                })?;
+
+                attributes.nan = match policy {
+                    Some(v) => Some(format_ident!("{}", v)),
+                    None => return Err(meta.error("pgde nan requires a policy")),
+                };
+                return Ok(());
+            }
+
+            // The column length is read by `FakeRow`, and does not change how the column
+            // is read.
+            if meta.path.is_ident("max_len") {
+                let _: syn::LitInt = meta.value()?.parse()?;
+                return Ok(());
+            }
+
+            if meta.path.is_ident("trim") || meta.path.is_ident("empty_as_none") {
+                let (mut trim, mut empty_as_none) = match attributes.reader {
+                    Some(Reader::Text {
+                        trim,
+                        empty_as_none,
+                    }) => (trim, empty_as_none),
+                    Some(_) => {
+                        return Err(
+                            meta.error("only one pgde reading attribute may be used per field")
+                        )
+                    }
+                    None => (false, false),
+                };
+
+                match meta.path.is_ident("trim") {
+                    true => trim = true,
+                    false => empty_as_none = true,
+                }
+
+                attributes.reader = Some(Reader::Text {
+                    trim,
+                    empty_as_none,
+                });
+                return Ok(());
+            }
+
+            let reader = if meta.path.is_ident("interval") {
+                Reader::Interval
+            } else if meta.path.is_ident("seconds") {
+                Reader::Seconds
+            } else if meta.path.is_ident("array") {
+                Reader::Array
+            } else if meta.path.is_ident("bitflags") {
+                let mut truncate = false;
+
+                if meta.input.peek(syn::token::Paren) {
+                    meta.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("truncate") {
+                            truncate = true;
+                            Ok(())
+                        } else {
+                            Err(meta.error("unsupported pgde bitflags option"))
+                        }
+                    })?;
+                }
+
+                Reader::Bitflags { truncate }
+            } else if meta.path.is_ident("remote") {
+                let value: LitStr = meta.value()?.parse()?;
+                Reader::Remote(value.parse()?)
+            } else if meta.path.is_ident("narrow") {
+                Reader::Narrow
+            } else if meta.path.is_ident("widen") {
+                Reader::Widen
+            } else if meta.path.is_ident("numeric") {
+                let mut policy = "Error";
+
+                if meta.input.peek(syn::token::Paren) {
+                    meta.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("error") {
+                            policy = "Error";
+                            Ok(())
+                        } else if meta.path.is_ident("round") {
+                            policy = "Round";
+                            Ok(())
+                        } else if meta.path.is_ident("truncate") {
+                            policy = "Truncate";
+                            Ok(())
+                        } else {
+                            Err(meta.error("unsupported pgde numeric policy"))
+                        }
+                    })?;
+                }
+
+                Reader::Numeric {
+                    policy: format_ident!("{}", policy),
+                }
+            } else if meta.path.is_ident("coerce") {
+                Reader::Coerce
+            } else if meta.path.is_ident("assume_utc") || meta.path.is_ident("to_utc") {
+                Reader::Utc
+            } else if meta.path.is_ident("as_text") {
+                Reader::AsText
+            } else {
+                return Err(meta.error("unsupported pgde field attribute"));
+            };
+
+            match attributes.reader {
+                Some(_) => Err(meta.error("only one pgde reading attribute may be used per field")),
+                None => {
+                    attributes.reader = Some(reader);
+                    Ok(())
+                }
+            }
+        })?;
+    }
+
+    Ok(attributes)
+}
+
+/// A function that takes a field and its attributes and returns a [`TokenStream`]
+/// expression that records a conversion error for the field and evaluates to the value
+/// given by [`field_default`].
+fn field_error(
+    class_name: &Ident,
+    field: &Field,
+    attributes: &FieldAttributes,
+) -> proc_macro2::TokenStream {
+    let field_name = &field.ident;
+    let default = field_default(field, attributes);
+
+    quote! {
+        {
+            errors.push(format!("Conversion error occurred for field \"{}\" on class \"{}\"", stringify!(#field_name), stringify!(#class_name)));
+            #default
+        }
+    }
+}
+
+/// Returns a [`TokenStream`] expression for the value of a field that could not be read,
+/// which is the field type's default value, or the fallback of its remote definition or
+/// UTC reader.
+fn field_default(field: &Field, attributes: &FieldAttributes) -> proc_macro2::TokenStream {
+    let field_type = &field.ty;
+
+    match &attributes.reader {
+        Some(Reader::Remote(path)) => {
+            quote! { <#path as pgde::RemoteColumn<#field_type>>::fallback() }
+        }
+        Some(Reader::Utc) => quote! { <#field_type as pgde::UtcColumn>::fallback() },
+        _ => quote! { <#field_type>::default() },
+    }
+}
+
+/// Converts a column name into an upper camel case variant name, e.g. `user_id` into
+/// `UserId`.
+fn upper_camel_case(name: &str) -> String {
+    name.split('_')
+        .filter(|v| !v.is_empty())
+        .map(|v| {
+            let mut chars = v.chars();
+
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Returns the variants of a columns enum, named after the given fields in upper camel
+/// case, failing when a field's name is not a valid variant, e.g. `Self` for a field
+/// `self_`, or when two fields, e.g. `user_id` and `_user_id`, share a variant.
+fn column_variants(enum_name: &Ident, fields: &[&Ident]) -> syn::Result<Vec<Ident>> {
+    let mut variants: Vec<Ident> = Vec::with_capacity(fields.len());
+
+    for (i, field) in fields.iter().enumerate() {
+        let name = upper_camel_case(&field.unraw().to_string());
+
+        let variant = match syn::parse_str::<Ident>(&name) {
+            Ok(_) => format_ident!("{}", name),
+            Err(_) => {
+                return Err(syn::Error::new(
+                    field.span(),
+                    format!(
+                        "field `{}` becomes `{}`, which is not a valid variant of `{}`; rename the field, or skip the enum with `#[pgde(skip_columns_enum)]`",
+                        field, name, enum_name
+                    ),
+                ))
+            }
+        };
+
+        if let Some(j) = variants.iter().position(|v| *v == variant) {
+            return Err(syn::Error::new(
+                field.span(),
+                format!(
+                    "fields `{}` and `{}` both become variant `{}` of `{}`; rename a field, or skip the enum with `#[pgde(skip_columns_enum)]`",
+                    fields[j], fields[i], name, enum_name
+                ),
+            ));
+        }
+
+        variants.push(variant);
+    }
+
+    Ok(variants)
+}
+
+/// Returns the type wrapped by an `Option`, if the type is an `Option`.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Path(v) if v.qself.is_none() => match v.path.segments.last() {
+            Some(segment) if segment.ident == "Option" => match &segment.arguments {
+                PathArguments::AngleBracketed(v) if v.args.len() == 1 => match v.args.first() {
+                    Some(GenericArgument::Type(v)) => Some(v),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// A function that takes a field, its position in a `COPY` record, and its attributes and
+/// returns a [`TokenStream`] expression that parses the value into the field's type. Since
+/// `COPY` output is text, only the attributes that apply to text are supported.
+fn copy_field_reader(
+    class_name: &Ident,
+    field: &Field,
+    index: usize,
+    attributes: &FieldAttributes,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let field_type = &field.ty;
+    let on_error = field_error(class_name, field, attributes);
+
+    let parse = |ty: &Type| match ty {
+        Type::Path(v) if v.path.is_ident("bool") => {
+            quote! { pgde::__private::parse_copy_bool(v) }
+        }
+        _ => quote! { v.parse::<#ty>().ok() },
+    };
+
+    let reader = match (&attributes.reader, option_inner(field_type)) {
+        (
+            Some(Reader::Text {
+                trim,
+                empty_as_none,
+            }),
+            _,
+        ) => quote! {
+            match fields.get(#index) {
+                Some(v) => match <#field_type as pgde::TextColumn>::from_text(v.clone(), #trim, #empty_as_none) {
+                    Some(v) => v,
+                    None => #on_error,
+                },
+                None => #on_error,
+            }
+        },
+        (None | Some(Reader::AsText), Some(inner)) => {
+            let parse = parse(inner);
+
+            quote! {
+                match fields.get(#index) {
+                    Some(Some(v)) => match #parse {
+                        Some(v) => Some(v),
+                        None => #on_error,
+                    },
+                    Some(None) => None,
+                    None => #on_error,
+                }
+            }
+        }
+        (None | Some(Reader::AsText), None) => {
+            let parse = parse(field_type);
+
+            quote! {
+                match fields.get(#index) {
+                    Some(Some(v)) => match #parse {
+                        Some(v) => v,
+                        None => #on_error,
+                    },
+                    _ => #on_error,
+                }
+            }
+        }
+        (Some(reader), _) => {
+            let name = match reader {
+                Reader::Interval => "interval",
+                Reader::Seconds => "seconds",
+                Reader::Array => "array",
+                Reader::Bitflags { .. } => "bitflags",
+                Reader::Remote(_) => "remote",
+                Reader::Narrow => "narrow",
+                Reader::Widen => "widen",
+                Reader::Numeric { .. } => "numeric",
+                Reader::Coerce => "coerce",
+                Reader::Utc => "assume_utc` and `pgde(to_utc)",
+                Reader::Text { .. } => "trim` and `pgde(empty_as_none)",
+                Reader::AsText => "as_text",
+            };
+
+            return Err(syn::Error::new_spanned(
+                field,
+                format!(
+                    "`pgde({})` is not supported by `pgde(copy)`, which parses fields from text",
+                    name
+                ),
+            ));
+        }
+    };
+
+    let reader = match &attributes.nan {
+        Some(policy) => quote! {
+            match <#field_type as pgde::NanColumn>::from_nan(#reader, pgde::NanPolicy::#policy) {
+                Some(v) => v,
+                None => #on_error,
+            }
+        },
+        None => reader,
+    };
+
+    Ok(match &attributes.null_as {
+        Some(null_as) => quote! {
+            match fields.get(#index) {
+                Some(None) => #null_as,
+                _ => #reader,
+            }
+        },
+        None => reader,
+    })
+}
+
+/// A function that takes a field, its column's index or name, and its attributes and
+/// returns a [`TokenStream`] expression that reads the column into the field's type.
+fn field_reader(
+    class_name: &Ident,
+    field: &Field,
+    index: proc_macro2::TokenStream,
+    attributes: &FieldAttributes,
+) -> proc_macro2::TokenStream {
+    let field_type = &field.ty;
+    let on_error = field_error(class_name, field, attributes);
+
+    let value = match &attributes.nan {
+        Some(policy) => quote! {
+            match <#field_type as pgde::NanColumn>::from_nan(v, pgde::NanPolicy::#policy) {
+                Some(v) => v,
+                None => #on_error,
+            }
+        },
+        None => quote! { v },
+    };
+
+    let reader = match field_conversion(field, attributes) {
+        Some((column_type, convert)) => quote! {
+            match row.try_get::<_, #column_type>(#index) {
+                Ok(v) => match #convert {
+                    Some(v) => #value,
+                    None => #on_error,
+                },
+                Err(_) => #on_error,
+            }
+        },
+        None => quote! {
+            match row.try_get::<_, #field_type>(#index) {
+                Ok(v) => #value,
+                Err(_) => #on_error,
+            }
+        },
+    };
+
+    match &attributes.null_as {
+        Some(null_as) => quote! {
+            match row.try_get::<_, pgde::__private::NullCheck>(#index) {
+                Ok(pgde::__private::NullCheck(true)) => #null_as,
+                _ => #reader,
+            }
+        },
+        None => reader,
+    }
+}
+
+/// A function that takes a field and its attributes and returns a [`TokenStream`] for the
+/// type its column is read as.
+fn field_column_type(field: &Field, attributes: &FieldAttributes) -> proc_macro2::TokenStream {
+    let field_type = &field.ty;
+
+    match field_conversion(field, attributes) {
+        Some((column_type, _)) => column_type,
+        None => quote! { #field_type },
+    }
+}
+
+/// A function that takes a field and its attributes and returns, for fields with a
+/// reading attribute, the type its column is read as and an expression converting the
+/// read value `v` into an `Option` of the field's type.
+fn field_conversion(
+    field: &Field,
+    attributes: &FieldAttributes,
+) -> Option<(proc_macro2::TokenStream, proc_macro2::TokenStream)> {
+    let field_type = &field.ty;
+
+    match &attributes.reader {
+        Some(Reader::Interval) => Some((
+            quote! { Option<pgde::Interval> },
+            quote! { <#field_type as pgde::DurationColumn>::from_interval(v) },
+        )),
+        Some(Reader::Seconds) => Some((
+            quote! { Option<pgde::Seconds> },
+            quote! { <#field_type as pgde::DurationColumn>::from_seconds(v) },
+        )),
+        Some(Reader::Array) => Some((
+            quote! { pgde::ArrayColumn<#field_type> },
+            quote! { Some(v.0) },
+        )),
+        Some(Reader::Bitflags { truncate }) => Some((
+            quote! { pgde::FlagBits },
+            quote! { v.to_flags::<#field_type>(#truncate) },
+        )),
+        Some(Reader::Remote(path)) => Some((
+            quote! { <#path as pgde::RemoteColumn<#field_type>>::Via },
+            quote! { <#path as pgde::RemoteColumn<#field_type>>::convert(v) },
+        )),
+        Some(Reader::Narrow) => Some((
+            quote! { Option<pgde::Number> },
+            quote! { <#field_type as pgde::NumericColumn>::from_narrowed(v) },
+        )),
+        Some(Reader::Widen) => Some((
+            quote! { Option<pgde::Number> },
+            quote! { <#field_type as pgde::NumericColumn>::from_widened(v) },
+        )),
+        Some(Reader::Numeric { policy }) => Some((
+            quote! { Option<pgde::Numeric> },
+            quote! {
+                <#field_type as pgde::FloatColumn>::from_numeric(v, pgde::PrecisionPolicy::#policy)
+            },
+        )),
+        Some(Reader::Coerce) => Some((
+            quote! { Option<pgde::CoercedBool> },
+            quote! { <#field_type as pgde::CoerceColumn>::from_coerced(v) },
+        )),
+        Some(Reader::Text {
+            trim,
+            empty_as_none,
+        }) => Some((
+            quote! { Option<String> },
+            quote! { <#field_type as pgde::TextColumn>::from_text(v, #trim, #empty_as_none) },
+        )),
+        Some(Reader::Utc) => Some((
+            quote! { Option<pgde::UtcTimestamp> },
+            quote! { <#field_type as pgde::UtcColumn>::from_utc(v) },
+        )),
+        Some(Reader::AsText) => Some((
+            quote! { Option<pgde::TextValue> },
+            match option_inner(field_type) {
+                Some(inner) => quote! {
+                    match v {
+                        Some(v) => v.0.parse::<#inner>().ok().map(Some),
+                        None => Some(None),
+                    }
+                },
+                None => quote! { v.and_then(|v| v.0.parse::<#field_type>().ok()) },
+            },
+        )),
+        None => None,
+    }
+}
+
+/// A function that takes a given [`TokenStream`]'s [`Ident`], [`Generics`], and [`Data`]
+/// and returns a [`TokenStream`] for implementing `from_row_ref` from a struct's fields. A
+/// struct with a lifetime parameter borrows from the row, so `from_row_borrowed` is
+/// implemented instead.
+fn parse_field_setters(
+    class_name: &Ident,
+    visibility: &Visibility,
+    generics: &Generics,
+    data: &Data,
+    struct_attributes: &StructAttributes,
+) -> TokenStream {
     match *data {
         Data::Struct(ref data) => match data.fields {
             Fields::Named(ref fields) => {
-                let field_setters = fields.named.iter().enumerate().map(|(i, f)| {
+                let mut field_setters = Vec::with_capacity(fields.named.len());
+                let mut selected_setters = Vec::with_capacity(fields.named.len());
+                let mut column_names = Vec::with_capacity(fields.named.len());
+                let mut copy_setters = Vec::with_capacity(fields.named.len());
+                let mut type_checks = Vec::with_capacity(fields.named.len());
+                let mut signature_fields = Vec::with_capacity(fields.named.len());
+                let mut column_fields = Vec::with_capacity(fields.named.len());
+                let mut summary_fields = Vec::with_capacity(fields.named.len());
+                let mut hash_fields = Vec::with_capacity(fields.named.len());
+                let mut failure_checks = Vec::with_capacity(fields.named.len());
+                let mut key_fields = Vec::new();
+
+                for (i, f) in fields.named.iter().enumerate() {
                     let field_name = &f.ident;
-                    let (field_type, field_type_args) = match &f.ty {
-                        Type::Path(field_ty) => match field_ty.path.segments.last() {
-                            Some(path_segment) => match &path_segment.arguments {
-                                PathArguments::AngleBracketed(previous_type_args) => {
-                                    let mut new_field_type = f.ty.clone();
-
-                                    match new_field_type {
-                                        Type::Path(ref mut field_ty) => match field_ty.path.segments.last_mut() {
-                                            Some(v) => {
-                                                v.arguments = PathArguments::None;
-                                                (new_field_type, Some(previous_type_args))
-                                            },
-                                            None => (f.ty.clone(), None)
-                                        },
-                                        _ => (f.ty.clone(), None),
-                                    }
-                                },
-                                _ => (f.ty.clone(), None),
-                            },
-                            None => (f.ty.clone(), None),
-                        },
-                        _ => (f.ty.clone(), None),
+
+                    let column_name = match field_name {
+                        Some(v) => {
+                            column_fields.push(v);
+                            v.unraw().to_string()
+                        }
+                        None => continue,
                     };
 
-                    match field_type_args {
-                        Some(v) => quote! {
-                            #field_name: match row.try_get::<usize, #field_type::#v>(#i) {
-                                Ok(v) => v,
-                                Err(_) => {
-                                    errors.push(format!("Conversion error occurred for field \"{}\" on class \"{}\"", stringify!(#field_name), stringify!(#class_name)));
-                                    #field_type::default()
+                    match parse_field_attributes(f) {
+                        Ok(attributes) => {
+                            let reader = field_reader(class_name, f, quote! { #i }, &attributes);
+                            field_setters.push(quote! { #field_name: #reader });
+
+                            let field_type = &f.ty;
+                            let column_type = field_column_type(f, &attributes);
+                            failure_checks.push(quote! {
+                                {
+                                    let mut errors : Vec<String> = Vec::new();
+                                    let _ : #field_type = #reader;
+
+                                    if !errors.is_empty() {
+                                        failures.push((#column_name, pgde::__private::classify_failure::<#column_type, usize>(row, #i)));
+                                    }
+                                }
+                            });
+
+                            let reader =
+                                field_reader(class_name, f, quote! { #column_name }, &attributes);
+                            let default = field_default(f, &attributes);
+                            selected_setters.push(quote! {
+                                #field_name: match columns.contains(&#column_name) {
+                                    true => #reader,
+                                    false => #default,
+                                }
+                            });
+
+                            summary_fields.push(match attributes.sensitive {
+                                true => quote! { (#column_name, None) },
+                                false => quote! {
+                                    (#column_name, Some(pgde::__private::summary_value(&self.#field_name)))
                                 },
+                            });
+
+                            hash_fields.push(quote! {
+                                hasher.field(#column_name, &self.#field_name);
+                            });
+
+                            if attributes.key {
+                                key_fields.push((field_name, &f.ty));
                             }
-                        },
-                        None => quote! {
-                            #field_name: match row.try_get::<usize, #field_type>(#i) {
-                                Ok(v) => v,
-                                Err(_) => {
-                                    errors.push(format!("Conversion error occurred for field \"{}\" on class \"{}\"", stringify!(#field_name), stringify!(#class_name)));
-                                    #field_type::default()
-                                },
+
+                            if struct_attributes.copy {
+                                match copy_field_reader(class_name, f, i, &attributes) {
+                                    Ok(reader) => {
+                                        copy_setters.push(quote! { #field_name: #reader })
+                                    }
+                                    Err(e) => return TokenStream::from(e.to_compile_error()),
+                                }
                             }
-                        },
+
+                            type_checks.push(quote! {
+                                if let Some(column) = columns.get(#i) {
+                                    if !<#column_type as pgde::__private::FromSql>::accepts(column.type_()) {
+                                        errors.push(format!("Column {} \"{}\" of type {} cannot be read into field \"{}\" on class \"{}\"", #i, column.name(), column.type_(), stringify!(#field_name), stringify!(#class_name)));
+                                    }
+                                }
+                            });
+                        }
+                        Err(e) => return TokenStream::from(e.to_compile_error()),
                     }
-                });
 
-                let implementation = quote! {
-                    impl pgde::RowConsumer for #class_name {
-                        fn from_row(row: Row) -> Result<Self, (Self, Vec<String>)>
-                        where
-                            Self: Sized,
+                    let field_type: String =
+                        f.ty.to_token_stream()
+                            .to_string()
+                            .chars()
+                            .filter(|v| !v.is_whitespace())
+                            .collect();
+                    signature_fields.push(quote! { (#column_name, #field_type) });
+
+                    column_names.push(column_name);
+                }
+
+                let copy_implementation = match struct_attributes.copy {
+                    true => quote! {
+                        impl pgde::CopyConsumer for #class_name {
+                            fn from_copy_fields(fields: &[Option<String>]) -> Result<Self, (Self, Vec<String>)> {
+                                let mut errors : Vec<String> = Vec::new();
+
+                                let class_instance = Self {
+                                    #(#copy_setters),*
+                                };
+
+                                match errors.len() {
+                                    0 => Ok(class_instance),
+                                    _ => Err((class_instance, errors)),
+                                }
+                            }
+                        }
+                    },
+                    false => quote! {},
+                };
+
+                let table = &struct_attributes.table;
+                let schema = match &struct_attributes.schema {
+                    Some(v) => quote! { Some(#v) },
+                    None => quote! { None },
+                };
+                let soft_delete = match &struct_attributes.soft_delete {
+                    Some(v) => quote! { Some(#v) },
+                    None => quote! { None },
+                };
+
+                let summary_implementation = match struct_attributes.summary {
+                    true => {
+                        let (impl_generics, type_generics, where_clause) =
+                            generics.split_for_impl();
+
+                        quote! {
+                            impl #impl_generics pgde::Summary for #class_name #type_generics #where_clause {
+                                fn summary(&self) -> String {
+                                    pgde::__private::format_summary(
+                                        stringify!(#class_name),
+                                        &[#(#summary_fields),*],
+                                    )
+                                }
+                            }
+                        }
+                    }
+                    false => quote! {},
+                };
+
+                let row_hash_implementation = match struct_attributes.row_hash {
+                    true => {
+                        let (impl_generics, type_generics, where_clause) =
+                            generics.split_for_impl();
+
+                        quote! {
+                            impl #impl_generics pgde::RowHash for #class_name #type_generics #where_clause {
+                                fn row_hash(&self) -> u64 {
+                                    let mut hasher = pgde::__private::RowHasher::new();
+                                    #(#hash_fields)*
+                                    hasher.finish()
+                                }
+                            }
+                        }
+                    }
+                    false => quote! {},
+                };
+
+                let keyed_implementation = match key_fields.len() {
+                    0 => quote! {},
+                    _ => {
+                        let (impl_generics, type_generics, where_clause) =
+                            generics.split_for_impl();
+                        let key_names = key_fields.iter().map(|(name, _)| name);
+                        let key_types = key_fields.iter().map(|(_, ty)| ty);
+
+                        let (key_type, key) = match key_fields.as_slice() {
+                            [(name, ty)] => (quote! { #ty }, quote! { self.#name.clone() }),
+                            _ => (
+                                quote! { (#(#key_types),*) },
+                                quote! { (#(self.#key_names.clone()),*) },
+                            ),
+                        };
+
+                        quote! {
+                            impl #impl_generics pgde::KeyedBy<#key_type> for #class_name #type_generics #where_clause {
+                                fn key(&self) -> #key_type {
+                                    #key
+                                }
+                            }
+                        }
+                    }
+                };
+
+                let column_count = column_names.len();
+                let column_check = quote! {
+                    if row.len() < #column_count {
+                        errors.push(format!("Expected {} columns for class \"{}\" but row has {}", #column_count, stringify!(#class_name), row.len()));
+                    }
+                };
+
+                let mut lifetimes = generics.lifetimes();
+
+                let implementation = match (lifetimes.next(), lifetimes.next()) {
+                    (None, _) => {
+                        let (columns_type, columns_implementation) = match &struct_attributes
+                            .columns_enum
                         {
-                            let mut errors : Vec<String> = Vec::new();
+                            Some(columns_name) => {
+                                let column_variants =
+                                    match column_variants(columns_name, &column_fields) {
+                                        Ok(v) => v,
+                                        Err(e) => return TokenStream::from(e.to_compile_error()),
+                                    };
+                                let columns_doc = format!(
+                                    "The columns of [`{}`], one for every field. Generated by the derive macro.",
+                                    class_name
+                                );
+                                let column_indexes = 0..column_variants.len();
+
+                                (
+                                    quote! { #columns_name },
+                                    quote! {
+                                        #[doc = #columns_doc]
+                                        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+                                        #visibility enum #columns_name {
+                                            #(#column_variants),*
+                                        }
+
+                                        impl pgde::TableColumn for #columns_name {
+                                            fn as_str(&self) -> &'static str {
+                                                match self {
+                                                    #(Self::#column_variants => #column_names),*
+                                                }
+                                            }
+
+                                            fn index(&self) -> usize {
+                                                match self {
+                                                    #(Self::#column_variants => #column_indexes),*
+                                                }
+                                            }
+                                        }
+
+                                        impl<'a> From<#columns_name> for &'a str {
+                                            fn from(column: #columns_name) -> &'a str {
+                                                pgde::TableColumn::as_str(&column)
+                                            }
+                                        }
+                                    },
+                                )
+                            }
+                            None => (quote! { pgde::NoColumns }, quote! {}),
+                        };
+
+                        let try_from_implementation = match struct_attributes.try_from {
+                            true => quote! {
+                                impl TryFrom<Row> for #class_name {
+                                    type Error = pgde::RowError;
+
+                                    fn try_from(row: Row) -> Result<Self, Self::Error> {
+                                        match <Self as pgde::RowConsumer>::from_row(row) {
+                                            Ok(v) => Ok(v),
+                                            Err((_, v)) => Err(pgde::RowError(v)),
+                                        }
+                                    }
+                                }
+
+                                impl TryFrom<&Row> for #class_name {
+                                    type Error = pgde::RowError;
+
+                                    fn try_from(row: &Row) -> Result<Self, Self::Error> {
+                                        match <Self as pgde::RowRefConsumer>::from_row_ref(row) {
+                                            Ok(v) => Ok(v),
+                                            Err((_, v)) => Err(pgde::RowError(v)),
+                                        }
+                                    }
+                                }
+                            },
+                            false => quote! {},
+                        };
+
+                        quote! {
+                            impl pgde::RowConsumer for #class_name {
+                                const COLUMN_COUNT: Option<usize> = Some(#column_count);
+
+                                fn check_column_types(columns: &[pgde::__private::Column]) -> Vec<String> {
+                                    let mut errors : Vec<String> = Vec::new();
+                                    #(#type_checks)*
+                                    errors
+                                }
+
+                                fn field_failures(row: &Row) -> Vec<(&'static str, pgde::FieldFailure)> {
+                                    let mut failures = Vec::new();
+                                    #(#failure_checks)*
+                                    failures
+                                }
+
+                                fn mapping_signature() -> pgde::MappingSignature {
+                                    const SIGNATURE: pgde::MappingSignature = pgde::MappingSignature::new(
+                                        stringify!(#class_name),
+                                        &[#(#signature_fields),*],
+                                    );
+                                    SIGNATURE
+                                }
+
+                                fn from_row(row: Row) -> Result<Self, (Self, Vec<String>)>
+                                where
+                                    Self: Sized,
+                                {
+                                    <Self as pgde::RowRefConsumer>::from_row_ref(&row)
+                                }
+                            }
+
+                            impl pgde::RowRefConsumer for #class_name {
+                                fn from_row_ref(row: &Row) -> Result<Self, (Self, Vec<String>)>
+                                where
+                                    Self: Sized,
+                                {
+                                    let mut errors : Vec<String> = Vec::new();
+                                    #column_check
+
+                                    let class_instance = Self {
+                                        #(#field_setters),*
+                                    };
+
+                                    match errors.len() {
+                                        0 => Ok(class_instance),
+                                        _ => Err((class_instance, errors)),
+                                    }
+                                }
+                            }
+
+                            #try_from_implementation
+
+                            #columns_implementation
+
+                            impl pgde::Table for #class_name {
+                                const SCHEMA: Option<&'static str> = #schema;
+                                const TABLE: &'static str = #table;
+                                const COLUMNS: &'static [&'static str] = &[#(#column_names),*];
+                                const SOFT_DELETE: Option<&'static str> = #soft_delete;
+                                const FIELD_COUNT: usize = #column_count;
+
+                                type Columns = #columns_type;
+
+                                fn from_row_selected(row: &Row, columns: &[&str]) -> Result<Self, (Self, Vec<String>)> {
+                                    let mut errors : Vec<String> = Vec::new();
+
+                                    let class_instance = Self {
+                                        #(#selected_setters),*
+                                    };
+
+                                    match errors.len() {
+                                        0 => Ok(class_instance),
+                                        _ => Err((class_instance, errors)),
+                                    }
+                                }
+                            }
+
+                            #copy_implementation
+
+                            #summary_implementation
+
+                            #row_hash_implementation
+
+                            #keyed_implementation
+                        }
+                    }
+                    (Some(lifetime), None) => {
+                        let lifetime = &lifetime.lifetime;
+                        let (impl_generics, type_generics, where_clause) =
+                            generics.split_for_impl();
+
+                        quote! {
+                            impl #impl_generics pgde::BorrowedRowConsumer<#lifetime> for #class_name #type_generics #where_clause {
+                                fn from_row_borrowed(row: &#lifetime Row) -> Result<Self, (Self, Vec<String>)> {
+                                    let mut errors : Vec<String> = Vec::new();
+                                    #column_check
 
-                            let class_instance = Self {
-                                #(#field_setters),*
-                            };
+                                    let class_instance = Self {
+                                        #(#field_setters),*
+                                    };
 
-                            match errors.len() {
-                                0 => Ok(class_instance),
-                                _ => Err((class_instance, errors)),
+                                    match errors.len() {
+                                        0 => Ok(class_instance),
+                                        _ => Err((class_instance, errors)),
+                                    }
+                                }
                             }
+
+                            #summary_implementation
+
+                            #row_hash_implementation
+
+                            #keyed_implementation
                         }
                     }
+                    (Some(_), Some(v)) => {
+                        return TokenStream::from(
+                            syn::Error::new_spanned(
+                                v,
+                                "RowConsumer supports at most one lifetime parameter",
+                            )
+                            .to_compile_error(),
+                        )
+                    }
                 };
 
                 TokenStream::from(implementation)
             }
-            Fields::Unnamed(_) | Fields::Unit => panic!(
-                "RowConsumer is not supported on unit structs nor structs with unnamed fieds"
+            Fields::Unnamed(_) | Fields::Unit => TokenStream::from(
+                syn::Error::new_spanned(
+                    class_name,
+                    "RowConsumer is not supported on unit structs nor structs with unnamed fields",
+                )
+                .to_compile_error(),
             ),
         },
-        Data::Enum(_) | Data::Union(_) => panic!("RowConsumer is not supported on enums or unions"),
+        Data::Enum(_) | Data::Union(_) => TokenStream::from(
+            syn::Error::new_spanned(class_name, "RowConsumer is not supported on unions")
+                .to_compile_error(),
+        ),
+    }
+}
+
+/// Variant level configuration supplied through `#[pgde(...)]` attributes.
+#[derive(Default)]
+struct VariantAttributes {
+    /// The text value of the variant, in place of its name.
+    rename: Option<LitStr>,
+    /// Marks a tuple variant holding a `String` as the catch-all for unknown values.
+    other: bool,
+}
+
+/// Parses the `#[pgde(...)]` attributes of a variant into [`VariantAttributes`].
+fn parse_variant_attributes(variant: &Variant) -> syn::Result<VariantAttributes> {
+    let mut attributes = VariantAttributes::default();
+
+    for attr in variant.attrs.iter().filter(|v| v.path().is_ident("pgde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                attributes.rename = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("other") {
+                attributes.other = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported pgde variant attribute"))
+            }
+        })?;
+    }
+
+    Ok(attributes)
+}
+
+/// A function that takes a given enum's [`Ident`] and [`Data`] and returns a
+/// [`proc_macro2::TokenStream`] implementing `TextEnum`, `FromSql`, and `ToSql`.
+fn parse_text_variants(enum_name: &Ident, data: &Data) -> syn::Result<proc_macro2::TokenStream> {
+    let data = match *data {
+        Data::Enum(ref data) => data,
+        Data::Struct(_) | Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                enum_name,
+                "TextEnum is only supported on enums",
+            ))
+        }
+    };
+
+    let mut from_text_arms = Vec::with_capacity(data.variants.len());
+    let mut as_text_arms = Vec::with_capacity(data.variants.len());
+    let mut other = None;
+
+    for variant in data.variants.iter() {
+        let variant_name = &variant.ident;
+        let attributes = parse_variant_attributes(variant)?;
+
+        match (&variant.fields, attributes.other) {
+            (Fields::Unit, false) => {
+                let text = match attributes.rename {
+                    Some(v) => v,
+                    None => LitStr::new(&variant_name.to_string(), variant_name.span()),
+                };
+
+                from_text_arms.push(quote! { #text => Some(#enum_name::#variant_name) });
+                as_text_arms.push(quote! { #enum_name::#variant_name => #text });
+            }
+            (Fields::Unnamed(ref fields), true) if fields.unnamed.len() == 1 => {
+                if other.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        variant,
+                        "only one variant may be marked with pgde(other)",
+                    ));
+                }
+
+                other = Some(variant_name);
+                as_text_arms.push(quote! { #enum_name::#variant_name(v) => v.as_str() });
+            }
+            (_, true) => {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "pgde(other) requires a tuple variant with a single String field",
+                ))
+            }
+            (_, false) => {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "TextEnum variants must be unit variants or marked with pgde(other)",
+                ))
+            }
+        }
     }
+
+    let unknown = match other {
+        Some(v) => quote! { _ => Some(#enum_name::#v(String::from(value))) },
+        None => quote! { _ => None },
+    };
+
+    Ok(quote! {
+        impl pgde::TextEnum for #enum_name {
+            fn from_text(value: &str) -> Option<Self> {
+                match value {
+                    #(#from_text_arms,)*
+                    #unknown,
+                }
+            }
+
+            fn as_text(&self) -> &str {
+                match self {
+                    #(#as_text_arms,)*
+                }
+            }
+        }
+
+        impl<'a> pgde::__private::FromSql<'a> for #enum_name {
+            fn from_sql(
+                ty: &pgde::__private::Type,
+                raw: &'a [u8],
+            ) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+                pgde::__private::text_enum_from_sql(ty, raw)
+            }
+
+            fn accepts(ty: &pgde::__private::Type) -> bool {
+                pgde::__private::text_enum_accepts(ty)
+            }
+        }
+
+        impl pgde::__private::ToSql for #enum_name {
+            fn to_sql(
+                &self,
+                ty: &pgde::__private::Type,
+                out: &mut pgde::__private::BytesMut,
+            ) -> Result<pgde::__private::IsNull, Box<dyn std::error::Error + Sync + Send>> {
+                <&str as pgde::__private::ToSql>::to_sql(&pgde::TextEnum::as_text(self), ty, out)
+            }
+
+            fn accepts(ty: &pgde::__private::Type) -> bool {
+                pgde::__private::text_enum_accepts(ty)
+            }
+
+            pgde::__private::to_sql_checked!();
+        }
+    })
 }
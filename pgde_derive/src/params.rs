@@ -0,0 +1,126 @@
+//! The `QueryParams` derive macro, which collects the set fields of a struct into the
+//! filters of a `where` clause.
+use crate::option_inner;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Data, DeriveInput, Fields, LitStr};
+
+/// The comparisons a field may filter its column by, set with `#[pgde(op = "...")]`.
+const OPERATORS: &[&str] = &["=", "<>", "<", "<=", ">", ">=", "like", "ilike", "any"];
+
+/// Field level configuration of a `QueryParams` struct.
+struct ParamAttributes {
+    /// The column filtered by the field, from `#[pgde(column = "...")]`.
+    column: Option<LitStr>,
+    /// The comparison of the filter, from `#[pgde(op = "...")]`.
+    op: Option<LitStr>,
+}
+
+/// Parses the `#[pgde(...)]` attributes of a `QueryParams` field.
+fn parse_param_attributes(field: &syn::Field) -> syn::Result<ParamAttributes> {
+    let mut attributes = ParamAttributes {
+        column: None,
+        op: None,
+    };
+
+    for attr in field.attrs.iter().filter(|v| v.path().is_ident("pgde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("column") {
+                attributes.column = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("op") {
+                let op: LitStr = meta.value()?.parse()?;
+
+                if !OPERATORS.contains(&op.value().as_str()) {
+                    return Err(syn::Error::new(
+                        op.span(),
+                        format!("pgde op must be one of {}", OPERATORS.join(", ")),
+                    ));
+                }
+
+                attributes.op = Some(op);
+            } else {
+                return Err(meta.error("unsupported pgde query parameter attribute"));
+            }
+
+            Ok(())
+        })?;
+    }
+
+    Ok(attributes)
+}
+
+/// Implements `QueryParams` on a struct, adding a filter for every field that is not an
+/// `Option`, and for every `Option` field that is `Some`.
+pub(crate) fn derive_query_params(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(v) => match &v.fields {
+            Fields::Named(v) => v,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    input,
+                    "QueryParams is only supported on structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "QueryParams is only supported on structs with named fields",
+            ))
+        }
+    };
+
+    let mut pushes = Vec::with_capacity(fields.named.len());
+
+    for f in fields.named.iter() {
+        let field_name = match &f.ident {
+            Some(v) => v,
+            None => continue,
+        };
+
+        let attributes = parse_param_attributes(f)?;
+
+        let column = match attributes.column {
+            Some(v) => v.value(),
+            None => field_name.unraw().to_string(),
+        };
+
+        let op = match attributes.op {
+            Some(v) => v.value(),
+            None => String::from("="),
+        };
+
+        let filter = quote! {
+            pgde::Filter {
+                column: #column,
+                operator: #op,
+                value: v,
+            }
+        };
+
+        pushes.push(match option_inner(&f.ty) {
+            Some(_) => quote! {
+                if let Some(v) = &self.#field_name {
+                    filters.push(#filter);
+                }
+            },
+            None => quote! {
+                let v = &self.#field_name;
+                filters.push(#filter);
+            },
+        });
+    }
+
+    Ok(quote! {
+        impl #impl_generics pgde::QueryParams for #name #ty_generics #where_clause {
+            fn filters(&self) -> Vec<pgde::Filter<'_>> {
+                let mut filters = Vec::new();
+                #(#pushes)*
+                filters
+            }
+        }
+    })
+}
@@ -0,0 +1,106 @@
+//! Support for consuming one dimensional PostgreSQL arrays into collections other than
+//! `Vec`, decoding elements straight into the collection.
+use std::error::Error;
+use tokio_postgres::types::{FromSql, Kind, Type};
+
+/// A collection that can be filled from the elements of a PostgreSQL array.
+pub trait ArrayCollection: Default {
+    /// The type of the collection's elements.
+    type Element;
+
+    /// Adds an element to the collection, returning `Err` with the element if the
+    /// collection cannot hold any more elements.
+    fn push_element(&mut self, value: Self::Element) -> Result<(), Self::Element>;
+}
+
+/// A wrapper that implements `FromSql` for any [ArrayCollection], used to read array
+/// columns into collections that tokio_postgres does not support, e.g. with the
+/// `#[pgde(array)]` field attribute of the derive macro.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ArrayColumn<C>(pub C);
+
+/// Reads a big endian `i32` at the provided offset.
+fn read_i32(raw: &[u8], offset: usize) -> Result<i32, Box<dyn Error + Sync + Send>> {
+    match raw.get(offset..offset + 4) {
+        Some(v) => Ok(i32::from_be_bytes([v[0], v[1], v[2], v[3]])),
+        None => Err("array value is truncated".into()),
+    }
+}
+
+impl<'a, C> FromSql<'a> for ArrayColumn<C>
+where
+    C: ArrayCollection,
+    C::Element: FromSql<'a>,
+{
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        let member_type = match *ty.kind() {
+            Kind::Array(ref member) => member,
+            _ => return Err("expected array type".into()),
+        };
+
+        let dimensions = read_i32(raw, 0)?;
+
+        let (length, mut offset) = match dimensions {
+            0 => (0, 12),
+            1 => (read_i32(raw, 12)?, 20),
+            _ => return Err("array contains too many dimensions".into()),
+        };
+
+        let mut collection = C::default();
+
+        for _ in 0..length {
+            let element_length = read_i32(raw, offset)?;
+            offset += 4;
+
+            let element = match element_length {
+                -1 => C::Element::from_sql_null(member_type)?,
+                _ => {
+                    let element = usize::try_from(element_length)
+                        .ok()
+                        .and_then(|v| offset.checked_add(v))
+                        .and_then(|end| raw.get(offset..end).map(|v| (end, v)));
+
+                    match element {
+                        Some((end, v)) => {
+                            offset = end;
+                            C::Element::from_sql(member_type, v)?
+                        }
+                        None => return Err("array value is truncated".into()),
+                    }
+                }
+            };
+
+            if collection.push_element(element).is_err() {
+                return Err("array has more elements than the collection can hold".into());
+            }
+        }
+
+        Ok(ArrayColumn(collection))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        match *ty.kind() {
+            Kind::Array(ref member) => C::Element::accepts(member),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<A: smallvec::Array> ArrayCollection for smallvec::SmallVec<A> {
+    type Element = A::Item;
+
+    fn push_element(&mut self, value: A::Item) -> Result<(), A::Item> {
+        self.push(value);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+impl<T, const N: usize> ArrayCollection for arrayvec::ArrayVec<T, N> {
+    type Element = T;
+
+    fn push_element(&mut self, value: T) -> Result<(), T> {
+        self.try_push(value).map_err(|e| e.element())
+    }
+}
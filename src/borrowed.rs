@@ -0,0 +1,93 @@
+//! Consumption of rows into types that borrow from the row, such as `&str` and `&[u8]`.
+use tokio_postgres::Row;
+
+/// The derivable trait for consuming row data into a type that borrows from the row,
+/// avoiding copies of text and `bytea` values that are only inspected transiently.
+/// Deriving `RowConsumer` on a struct with a lifetime parameter implements this trait in
+/// place of `RowConsumer` and `Table`.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::BorrowedRowConsumer;
+/// use pgde_derive::RowConsumer;
+/// use tokio_postgres::{NoTls, Row};
+///
+/// #[derive(RowConsumer)]
+/// struct Foo<'a> {
+///     Name: &'a str,
+///     Blob: &'a [u8],
+/// }
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         match client.query("select 'foo', '\\x0102'::bytea;", &[]).await {
+///             Ok(rows) => match Foo::from_rows_borrowed(&rows) {
+///                 Ok(v) => println!("{} has {} bytes", v[0].Name, v[0].Blob.len()),
+///                 Err(_) => eprintln!("Could not convert rows"),
+///             },
+///             Err(_) => eprintln!("An error occurred while querying database"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub trait BorrowedRowConsumer<'a>: Sized {
+    /// Consumes a row into a value borrowing from it. Upon error, provides field and
+    /// class information in the form of Strings as well as partially converted data.
+    fn from_row_borrowed(row: &'a Row) -> Result<Self, (Self, Vec<String>)>;
+
+    /// The n-row consumer built off of `from_row_borrowed`. Returns successfully
+    /// converted data on error, but provides no breakdown of the errors that occurred.
+    fn from_rows_borrowed(rows: &'a [Row]) -> Result<Vec<Self>, Vec<Self>> {
+        let mut has_issue = false;
+        let mut data = Vec::with_capacity(rows.len());
+
+        for row in rows.iter() {
+            match Self::from_row_borrowed(row) {
+                Ok(v) => data.push(v),
+                Err((v, _)) => {
+                    has_issue = true;
+                    data.push(v);
+                }
+            }
+        }
+
+        match has_issue {
+            false => Ok(data),
+            true => Err(data),
+        }
+    }
+}
+
+/// A macro for implementing `from_row_borrowed` on borrowed types that implement
+/// `FromSql`.
+macro_rules! pg_borrowed_implementation {
+    ( $( $x:ty ),* ) => {
+        $(
+            impl<'a> BorrowedRowConsumer<'a> for $x {
+                fn from_row_borrowed(row: &'a Row) -> Result<Self, (Self, Vec<String>)> {
+                    match row.try_get::<usize, $x>(0) {
+                        Ok(v) => Ok(v),
+                        Err(_) => Err((
+                            <$x>::default(),
+                            vec![format!("Conversion error occurred for class \"{}\"", stringify!($x))],
+                        )),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+pg_borrowed_implementation!(&'a str, &'a [u8], Option<&'a str>, Option<&'a [u8]>);
@@ -0,0 +1,216 @@
+//! An in-memory cache of a reference table, refreshed in the background, for lookup
+//! tables read on every request.
+use crate::{ConsumeError, KeyedBy, RowConsumer};
+use arc_swap::ArcSwap;
+use futures_util::{Stream, StreamExt};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio_postgres::{AsyncMessage, Client};
+
+/// Keeps the rows of a query in memory keyed by [KeyedBy], re-running the query every
+/// interval in a background task. Readers look rows up with `get` or take the whole
+/// table with `snapshot` without waiting on the database. Failed refreshes keep the
+/// previous rows, and rows sharing a key keep the last one. With `listen`, the rows are
+/// also reloaded whenever a writer sends a `NOTIFY` naming one of the cache's `tags`, so
+/// that the cache stays coherent without a short interval. Background tasks are aborted
+/// when the cache is dropped.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::{KeyedBy, RefCache};
+/// use pgde_derive::RowConsumer;
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use tokio_postgres::{NoTls, Row};
+///
+/// #[derive(RowConsumer, Clone)]
+/// struct Setting {
+///     Name: String,
+///     Value: String,
+/// }
+///
+/// impl KeyedBy<String> for Setting {
+///     fn key(&self) -> String {
+///         self.Name.clone()
+///     }
+/// }
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         let query = "select name, setting from pg_settings;";
+///
+///         match RefCache::<String, Setting>::new(Arc::new(client), query, Duration::from_secs(60)).await {
+///             Ok(cache) => match cache.get(&String::from("work_mem")) {
+///                 Some(v) => println!("work_mem is {}", v.Value),
+///                 None => eprintln!("work_mem is not set"),
+///             },
+///             Err(_) => eprintln!("An error occurred while querying database"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub struct RefCache<K, T> {
+    client: Arc<Client>,
+    query: Arc<str>,
+    entries: Arc<ArcSwap<HashMap<K, T>>>,
+    tags: Arc<Vec<String>>,
+    handles: Mutex<Vec<JoinHandle<()>>>,
+}
+
+/// Runs the query of a cache, keying its rows.
+async fn load<K, T>(client: &Client, query: &str) -> Result<HashMap<K, T>, ConsumeError>
+where
+    T: RowConsumer + KeyedBy<K> + Send,
+    K: Eq + Hash + Send,
+{
+    T::consume_map_by_key(client, query, &[], |v| v.key()).await
+}
+
+impl<K, T> RefCache<K, T>
+where
+    T: RowConsumer + KeyedBy<K> + Send + Sync + 'static,
+    K: Eq + Hash + Send + Sync + 'static,
+{
+    /// Loads the rows of `query`, then spawns a task that reloads them every `interval`.
+    /// Returns an error if the first load fails. Panics if `interval` is zero.
+    pub async fn new(
+        client: Arc<Client>,
+        query: impl Into<String>,
+        interval: Duration,
+    ) -> Result<Self, ConsumeError> {
+        let query: Arc<str> = Arc::from(query.into());
+        let entries = Arc::new(ArcSwap::from_pointee(load(&client, &query).await?));
+
+        let handle = {
+            let client = client.clone();
+            let query = query.clone();
+            let entries = entries.clone();
+
+            tokio::spawn(async move {
+                let mut interval =
+                    tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+
+                loop {
+                    interval.tick().await;
+
+                    if let Ok(v) = load(&client, &query).await {
+                        entries.store(Arc::new(v));
+                    }
+                }
+            })
+        };
+
+        Ok(RefCache {
+            client,
+            query,
+            entries,
+            tags: Arc::new(Vec::new()),
+            handles: Mutex::new(vec![handle]),
+        })
+    }
+
+    /// Sets the tags that notifications received by `listen` name to reload the cache,
+    /// e.g. the tables its query reads. A cache without tags reloads on every
+    /// notification of its channel.
+    pub fn tags(mut self, tags: &[&str]) -> Self {
+        self.tags = Arc::new(tags.iter().map(|v| String::from(*v)).collect());
+        self
+    }
+
+    /// Spawns a task that reloads the rows whenever a notification on `channel` arrives
+    /// on `messages`, such as the messages of a connection polled with
+    /// `Connection::poll_message` that ran `LISTEN`. The notification's payload is read as
+    /// a comma separated list of tags, and the rows are reloaded when it is empty or names
+    /// one of the cache's tags. The task runs until the stream ends or the cache is
+    /// dropped.
+    pub fn listen<S, E>(&self, channel: &str, messages: S)
+    where
+        S: Stream<Item = Result<AsyncMessage, E>> + Send + 'static,
+        E: Send + 'static,
+    {
+        let client = self.client.clone();
+        let query = self.query.clone();
+        let entries = self.entries.clone();
+        let tags = self.tags.clone();
+        let channel = String::from(channel);
+
+        let handle = tokio::spawn(async move {
+            let mut messages = std::pin::pin!(messages);
+
+            while let Some(message) = messages.next().await {
+                let notification = match message {
+                    Ok(AsyncMessage::Notification(v)) if v.channel() == channel => v,
+                    _ => continue,
+                };
+
+                let payload = notification.payload().trim();
+
+                let invalidated = payload.is_empty()
+                    || tags.is_empty()
+                    || payload
+                        .split(',')
+                        .any(|v| tags.iter().any(|t| t == v.trim()));
+
+                if invalidated {
+                    if let Ok(v) = load(&client, &query).await {
+                        entries.store(Arc::new(v));
+                    }
+                }
+            }
+        });
+
+        match self.handles.lock() {
+            Ok(mut v) => v.push(handle),
+            Err(v) => v.into_inner().push(handle),
+        }
+    }
+
+    /// Returns a copy of the row with the given key, if any.
+    pub fn get(&self, key: &K) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.entries.load().get(key).cloned()
+    }
+
+    /// Returns the latest rows, which are kept as they are while the snapshot is held.
+    pub fn snapshot(&self) -> Arc<HashMap<K, T>> {
+        self.entries.load_full()
+    }
+
+    /// Reloads the rows now, without waiting for the next interval.
+    pub async fn refresh(&self) -> Result<Arc<HashMap<K, T>>, ConsumeError> {
+        let latest = Arc::new(load(&self.client, &self.query).await?);
+        self.entries.store(latest.clone());
+
+        Ok(latest)
+    }
+}
+
+impl<K, T> Drop for RefCache<K, T> {
+    fn drop(&mut self) {
+        let handles = match self.handles.get_mut() {
+            Ok(v) => v,
+            Err(v) => v.into_inner(),
+        };
+
+        for handle in handles.iter() {
+            handle.abort();
+        }
+    }
+}
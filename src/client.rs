@@ -0,0 +1,505 @@
+//! Wrappers around `tokio_postgres::Client` that add behavior to row consumption.
+#[cfg(feature = "supervisor")]
+pub use crate::supervised::SupervisedClient;
+#[cfg(feature = "throttle")]
+pub use crate::throttled::ThrottledClient;
+
+use crate::{ConsumeError, ConsumeOptions, RowConsumer};
+use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio_postgres::types::ToSql;
+use tokio_postgres::Client;
+
+/// A consumption call that took longer than the threshold of a [TimedClient].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SlowQuery<'a> {
+    /// The text of the query.
+    pub query: &'a str,
+    /// How long the call took, including row conversion.
+    pub duration: Duration,
+    /// The threshold that was exceeded.
+    pub threshold: Duration,
+}
+
+/// A client that reports consumption calls exceeding a latency threshold to a hook,
+/// e.g. to log them or record a metric. The wrapped client is available through
+/// `Deref`, so queries that do not consume rows are unaffected.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::client::TimedClient;
+/// use std::time::Duration;
+/// use tokio_postgres::NoTls;
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = TimedClient::new(v.0, Duration::from_millis(250), |slow| {
+///             eprintln!("slow query took {:?}: {}", slow.duration, slow.query)
+///         });
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         match client.consume::<i32>("select 1;", &[]).await {
+///             Ok(v) => println!("Consumed {} rows", v.len()), // v is of type Vec<i32>
+///             Err(_) => eprintln!("An error occurred while querying database"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub struct TimedClient {
+    client: Client,
+    threshold: Duration,
+    on_slow_query: Box<dyn Fn(SlowQuery<'_>) + Send + Sync>,
+}
+
+impl TimedClient {
+    /// Wraps `client`, calling `on_slow_query` for every consumption call that takes
+    /// longer than `threshold`.
+    pub fn new<F>(client: Client, threshold: Duration, on_slow_query: F) -> Self
+    where
+        F: Fn(SlowQuery<'_>) + Send + Sync + 'static,
+    {
+        TimedClient {
+            client,
+            threshold,
+            on_slow_query: Box::new(on_slow_query),
+        }
+    }
+
+    /// Returns the latency threshold.
+    pub fn threshold(&self) -> Duration {
+        self.threshold
+    }
+
+    /// Sets the latency threshold.
+    pub fn set_threshold(&mut self, threshold: Duration) {
+        self.threshold = threshold;
+    }
+
+    /// Returns the wrapped client.
+    pub fn into_inner(self) -> Client {
+        self.client
+    }
+
+    /// Calls the hook if `started` is further in the past than the threshold.
+    fn check(&self, query: &str, started: Instant) {
+        let duration = started.elapsed();
+
+        if duration > self.threshold {
+            (self.on_slow_query)(SlowQuery {
+                query,
+                duration,
+                threshold: self.threshold,
+            });
+        }
+    }
+
+    /// Consumes row data with `RowConsumer::consume`, timing the call.
+    pub async fn consume<T: RowConsumer>(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<T>, ConsumeError> {
+        let started = Instant::now();
+        let result = T::consume(&self.client, query, params).await;
+        self.check(query, started);
+        result
+    }
+
+    /// Consumes row data with `RowConsumer::consume_with_options`, timing the call.
+    pub async fn consume_with_options<T: RowConsumer + Send>(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+        options: &ConsumeOptions,
+    ) -> Result<Vec<T>, ConsumeError> {
+        let started = Instant::now();
+        let result = T::consume_with_options(&self.client, query, params, options).await;
+        self.check(query, started);
+        result
+    }
+}
+
+impl Deref for TimedClient {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        &self.client
+    }
+}
+
+/// Where a [RoutedClient] sends a call.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Route {
+    /// The primary client.
+    Primary,
+    /// The next replica client, in round-robin order.
+    #[default]
+    Replica,
+}
+
+/// A client that sends consumption calls to replicas in round-robin order and writes to
+/// the primary. Reads go to the primary when there are no replicas, and `consume_on`
+/// overrides the route of a single call, e.g. to read data that was just written.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::client::{Route, RoutedClient};
+/// use tokio_postgres::NoTls;
+///
+/// let conn_string = "host=localhost user=postgres password=password dbname=postgres";
+///
+/// match (
+///     tokio_postgres::connect(conn_string, NoTls).await,
+///     tokio_postgres::connect(conn_string, NoTls).await,
+/// ) {
+///     (Ok(primary), Ok(replica)) => {
+///         tokio::spawn(async move {
+///             if let Err(e) = primary.1.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = replica.1.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         let client = RoutedClient::new(primary.0, vec![replica.0]);
+///
+///         match client.consume::<i32>("select 1;", &[]).await {
+///             Ok(v) => println!("Read {} rows from a replica", v.len()), // v is of type Vec<i32>
+///             Err(_) => eprintln!("An error occurred while querying database"),
+///         };
+///
+///         match client.consume_on::<i32>(Route::Primary, "select 1;", &[]).await {
+///             Ok(v) => println!("Read {} rows from the primary", v.len()), // v is of type Vec<i32>
+///             Err(_) => eprintln!("An error occurred while querying database"),
+///         };
+///     },
+///     _ => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub struct RoutedClient {
+    primary: Client,
+    replicas: Vec<Client>,
+    next: AtomicUsize,
+}
+
+impl RoutedClient {
+    /// Routes writes to `primary` and reads to `replicas`.
+    pub fn new(primary: Client, replicas: Vec<Client>) -> Self {
+        RoutedClient {
+            primary,
+            replicas,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the primary client.
+    pub fn primary(&self) -> &Client {
+        &self.primary
+    }
+
+    /// Returns the replica clients.
+    pub fn replicas(&self) -> &[Client] {
+        &self.replicas
+    }
+
+    /// Returns the client for `route`, advancing the round-robin for replicas.
+    pub fn route(&self, route: Route) -> &Client {
+        match (route, self.replicas.len()) {
+            (Route::Primary, _) | (Route::Replica, 0) => &self.primary,
+            (Route::Replica, n) => &self.replicas[self.next.fetch_add(1, Ordering::Relaxed) % n],
+        }
+    }
+
+    /// Consumes row data with `RowConsumer::consume` on the next replica.
+    pub async fn consume<T: RowConsumer>(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<T>, ConsumeError> {
+        T::consume(self.route(Route::Replica), query, params).await
+    }
+
+    /// Consumes row data with `RowConsumer::consume_with_options` on the next replica.
+    pub async fn consume_with_options<T: RowConsumer + Send>(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+        options: &ConsumeOptions,
+    ) -> Result<Vec<T>, ConsumeError> {
+        T::consume_with_options(self.route(Route::Replica), query, params, options).await
+    }
+
+    /// Consumes row data with `RowConsumer::consume` on the client for `route`.
+    pub async fn consume_on<T: RowConsumer>(
+        &self,
+        route: Route,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<T>, ConsumeError> {
+        T::consume(self.route(route), query, params).await
+    }
+
+    /// Executes a statement on the primary, returning the number of rows modified.
+    pub async fn execute(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, ConsumeError> {
+        match self.primary.execute(query, params).await {
+            Ok(v) => Ok(v),
+            Err(e) => Err(ConsumeError::from(e)),
+        }
+    }
+}
+
+/// The state of the circuit of a [BreakerClient].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Calls reach the database.
+    #[default]
+    Closed,
+    /// Calls fail with [ConsumeError::CircuitOpen] until the cooldown elapses.
+    Open,
+    /// The cooldown elapsed, and a single call is let through to probe the database.
+    HalfOpen,
+}
+
+/// The counters of a [BreakerClient].
+#[derive(Debug, Default)]
+struct Circuit {
+    state: CircuitState,
+    calls: usize,
+    failures: usize,
+    changed_at: Option<Instant>,
+}
+
+/// A client that stops sending consumption calls to a struggling database. Calls are
+/// counted in windows of `window` calls, and when the share of calls failing with
+/// [ConsumeError::DatabaseConnectionError] or [ConsumeError::DatabaseError] reaches
+/// `failure_rate`, the circuit opens and calls fail with [ConsumeError::CircuitOpen]
+/// without reaching the database. After `cooldown`, one call is let through to probe the
+/// database, closing the circuit if it succeeds and opening it again if it fails.
+/// Conversion errors do not count as failures. The wrapped client is available through
+/// `Deref`, so queries that do not consume rows are unaffected.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::client::BreakerClient;
+/// use std::time::Duration;
+/// use tokio_postgres::NoTls;
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = BreakerClient::new(v.0)
+///             .failure_rate(0.5)
+///             .window(20)
+///             .cooldown(Duration::from_secs(10));
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         match client.consume::<i32>("select 1;", &[]).await {
+///             Ok(v) => println!("Consumed {} rows", v.len()), // v is of type Vec<i32>
+///             Err(_) => eprintln!("An error occurred while querying database"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub struct BreakerClient {
+    client: Client,
+    failure_rate: f64,
+    window: usize,
+    cooldown: Duration,
+    circuit: Mutex<Circuit>,
+}
+
+impl BreakerClient {
+    /// Wraps `client`, opening the circuit when half of a window of 20 calls fail and
+    /// probing the database again after 30 seconds.
+    pub fn new(client: Client) -> Self {
+        BreakerClient {
+            client,
+            failure_rate: 0.5,
+            window: 20,
+            cooldown: Duration::from_secs(30),
+            circuit: Mutex::new(Circuit::default()),
+        }
+    }
+
+    /// Sets the share of failed calls in a window, from 0 to 1, that opens the circuit.
+    pub fn failure_rate(mut self, failure_rate: f64) -> Self {
+        self.failure_rate = failure_rate;
+        self
+    }
+
+    /// Sets the number of calls the failure rate is measured over.
+    pub fn window(mut self, window: usize) -> Self {
+        self.window = window.max(1);
+        self
+    }
+
+    /// Sets how long the circuit stays open before a call probes the database.
+    pub fn cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Returns the state of the circuit.
+    pub fn state(&self) -> CircuitState {
+        match self.circuit.lock() {
+            Ok(v) => v.state,
+            Err(v) => v.into_inner().state,
+        }
+    }
+
+    /// Returns the wrapped client.
+    pub fn into_inner(self) -> Client {
+        self.client
+    }
+
+    /// Returns whether a call may reach the database, letting a probe through once the
+    /// cooldown elapses. A probe that never finishes, e.g. because its future was
+    /// dropped, is replaced by another after a further cooldown.
+    fn allow(&self) -> bool {
+        let mut circuit = match self.circuit.lock() {
+            Ok(v) => v,
+            Err(v) => v.into_inner(),
+        };
+
+        let cooled = match circuit.changed_at {
+            Some(v) => v.elapsed() >= self.cooldown,
+            None => true,
+        };
+
+        match (circuit.state, cooled) {
+            (CircuitState::Closed, _) => true,
+            (_, true) => {
+                circuit.state = CircuitState::HalfOpen;
+                circuit.changed_at = Some(Instant::now());
+                true
+            }
+            (_, false) => false,
+        }
+    }
+
+    /// Counts the outcome of a call that reached the database. Calls finishing while the
+    /// circuit is open started before it opened, and are not counted.
+    fn record<T>(&self, result: &Result<T, ConsumeError>) {
+        let failed = matches!(
+            result,
+            Err(ConsumeError::DatabaseConnectionError | ConsumeError::DatabaseError { .. })
+        );
+
+        let mut circuit = match self.circuit.lock() {
+            Ok(v) => v,
+            Err(v) => v.into_inner(),
+        };
+
+        match (circuit.state, failed) {
+            (CircuitState::Closed, _) => {
+                circuit.calls += 1;
+                circuit.failures += failed as usize;
+
+                if circuit.calls >= self.window {
+                    if circuit.failures as f64 >= self.failure_rate * circuit.calls as f64 {
+                        circuit.state = CircuitState::Open;
+                        circuit.changed_at = Some(Instant::now());
+                    }
+
+                    circuit.calls = 0;
+                    circuit.failures = 0;
+                }
+            }
+            (CircuitState::HalfOpen, true) => {
+                circuit.state = CircuitState::Open;
+                circuit.changed_at = Some(Instant::now());
+            }
+            (CircuitState::HalfOpen, false) => *circuit = Circuit::default(),
+            (CircuitState::Open, _) => (),
+        }
+    }
+
+    /// Consumes row data with `RowConsumer::consume` unless the circuit is open.
+    pub async fn consume<T: RowConsumer>(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<T>, ConsumeError> {
+        if !self.allow() {
+            return Err(ConsumeError::CircuitOpen);
+        }
+
+        let result = T::consume(&self.client, query, params).await;
+        self.record(&result);
+        result
+    }
+
+    /// Consumes row data with `RowConsumer::consume_with_options` unless the circuit is
+    /// open.
+    pub async fn consume_with_options<T: RowConsumer + Send>(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+        options: &ConsumeOptions,
+    ) -> Result<Vec<T>, ConsumeError> {
+        if !self.allow() {
+            return Err(ConsumeError::CircuitOpen);
+        }
+
+        let result = T::consume_with_options(&self.client, query, params, options).await;
+        self.record(&result);
+        result
+    }
+
+    /// Executes a statement unless the circuit is open, returning the number of rows
+    /// modified.
+    pub async fn execute(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, ConsumeError> {
+        if !self.allow() {
+            return Err(ConsumeError::CircuitOpen);
+        }
+
+        let result = match self.client.execute(query, params).await {
+            Ok(v) => Ok(v),
+            Err(e) => Err(ConsumeError::from(e)),
+        };
+
+        self.record(&result);
+        result
+    }
+}
+
+impl Deref for BreakerClient {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        &self.client
+    }
+}
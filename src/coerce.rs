@@ -0,0 +1,78 @@
+//! Support for consuming booleans stored as integers or text, as legacy schemas often do.
+use std::error::Error;
+use tokio_postgres::types::{FromSql, Type};
+
+/// A boolean read from a `bool` column, an `int2`, `int4`, or `int8` column holding `0`
+/// or `1`, or a `text`, `varchar`, `char(n)`, or `"char"` column holding `t`, `f`,
+/// `true`, or `false`, ignoring case and surrounding whitespace.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CoercedBool(pub bool);
+
+/// Reads a boolean from its integer representation.
+fn bool_from_integer(value: i64) -> Result<CoercedBool, Box<dyn Error + Sync + Send>> {
+    match value {
+        0 => Ok(CoercedBool(false)),
+        1 => Ok(CoercedBool(true)),
+        _ => Err("integer is not a boolean".into()),
+    }
+}
+
+/// Reads a boolean from its text representation.
+fn bool_from_text(value: &str) -> Result<CoercedBool, Box<dyn Error + Sync + Send>> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "t" | "true" => Ok(CoercedBool(true)),
+        "f" | "false" => Ok(CoercedBool(false)),
+        _ => Err("text is not a boolean".into()),
+    }
+}
+
+impl<'a> FromSql<'a> for CoercedBool {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        match *ty {
+            Type::BOOL => Ok(CoercedBool(bool::from_sql(ty, raw)?)),
+            Type::INT2 => bool_from_integer(i16::from_sql(ty, raw)? as i64),
+            Type::INT4 => bool_from_integer(i32::from_sql(ty, raw)? as i64),
+            Type::INT8 => bool_from_integer(i64::from_sql(ty, raw)?),
+            Type::CHAR => bool_from_text(std::str::from_utf8(raw)?),
+            Type::TEXT | Type::VARCHAR | Type::BPCHAR => bool_from_text(<&str>::from_sql(ty, raw)?),
+            _ => Err("unsupported boolean type".into()),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(
+            *ty,
+            Type::BOOL
+                | Type::INT2
+                | Type::INT4
+                | Type::INT8
+                | Type::CHAR
+                | Type::TEXT
+                | Type::VARCHAR
+                | Type::BPCHAR
+        )
+    }
+}
+
+/// Conversion of coerced columns into the implementing type. Used by the
+/// `#[pgde(coerce)]` field attribute of the derive macro. Returns `None` when a value is
+/// NULL.
+pub trait CoerceColumn: Sized {
+    /// Converts a nullable coerced boolean into the implementing type.
+    fn from_coerced(value: Option<CoercedBool>) -> Option<Self>;
+}
+
+impl<T: CoerceColumn> CoerceColumn for Option<T> {
+    fn from_coerced(value: Option<CoercedBool>) -> Option<Self> {
+        match value {
+            Some(_) => T::from_coerced(value).map(Some),
+            None => Some(None),
+        }
+    }
+}
+
+impl CoerceColumn for bool {
+    fn from_coerced(value: Option<CoercedBool>) -> Option<Self> {
+        value.map(|v| v.0)
+    }
+}
@@ -0,0 +1,393 @@
+//! Consumption of `COPY ... TO STDOUT` output in the CSV and text formats, for
+//! connections where binary results are unavailable.
+use crate::ConsumeError;
+use futures_util::StreamExt;
+use std::future::Future;
+use tokio_postgres::Client;
+
+/// The format of `COPY` output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CopyFormat {
+    /// `WITH (FORMAT csv)`, where unquoted empty values are NULL.
+    #[default]
+    Csv,
+    /// `WITH (FORMAT text)`, the default format of `COPY`, where `\N` is NULL.
+    Text,
+}
+
+/// Describes the `COPY` output read by `consume_copy_csv`, which must match the options
+/// of the `COPY` statement.
+///
+/// ## Example
+/// ```
+/// use pgde::CopyOptions;
+///
+/// let options = CopyOptions::csv().header(true);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CopyOptions {
+    format: CopyFormat,
+    delimiter: u8,
+    header: bool,
+}
+
+impl CopyOptions {
+    /// Reads `FORMAT csv` output, delimited by commas.
+    pub fn csv() -> Self {
+        CopyOptions {
+            format: CopyFormat::Csv,
+            delimiter: b',',
+            header: false,
+        }
+    }
+
+    /// Reads `FORMAT text` output, delimited by tabs.
+    pub fn text() -> Self {
+        CopyOptions {
+            format: CopyFormat::Text,
+            delimiter: b'\t',
+            header: false,
+        }
+    }
+
+    /// Sets the single byte delimiter between values.
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Skips the first line, for output of `COPY` with `HEADER`.
+    pub fn header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+}
+
+impl Default for CopyOptions {
+    fn default() -> Self {
+        Self::csv()
+    }
+}
+
+/// The trait for consuming `COPY` output into structs, implemented by the derive macro
+/// for structs marked with `#[pgde(copy)]`. Values are parsed with `FromStr`, except for
+/// `bool` fields, which also accept PostgreSQL's `t` and `f`. The field attributes
+/// `trim`, `empty_as_none`, `as_text`, `null_as`, and `nan` apply as they do to rows,
+/// while the attributes that read binary values, such as `interval`, fail to compile.
+pub trait CopyConsumer {
+    /// Converts the values of one record, where `None` is NULL. Upon error, provides field
+    /// and class information in the form of Strings as well as partially converted data.
+    fn from_copy_fields(fields: &[Option<String>]) -> Result<Self, (Self, Vec<String>)>
+    where
+        Self: Sized;
+
+    /// Runs a `COPY ... TO STDOUT` query and consumes its CSV or text output.
+    ///
+    /// ## Example
+    /// ```
+    /// # tokio_test::block_on(async {
+    /// use pgde::{CopyConsumer, CopyOptions};
+    /// use pgde_derive::RowConsumer;
+    /// use tokio_postgres::{NoTls, Row};
+    ///
+    /// #[derive(RowConsumer)]
+    /// #[pgde(copy)]
+    /// struct Foo {
+    ///     Id: i32,
+    ///     Data: Option<String>,
+    /// }
+    ///
+    /// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+    ///     Ok(v) => {
+    ///         let client = v.0;
+    ///         let conn = v.1;
+    ///
+    ///         tokio::spawn(async move {
+    ///             if let Err(e) = conn.await {
+    ///                 eprintln!("connection error: {}", e);
+    ///             }
+    ///         });
+    ///
+    ///         let query = "copy (select * from public.\"Foo\") to stdout with (format csv);";
+    ///
+    ///         match Foo::consume_copy_csv(&client, query, &CopyOptions::csv()).await {
+    ///             Ok(v) => println!("Copied {} rows", v.len()), // v is of type Vec<Foo>
+    ///             Err(_) => eprintln!("An error occurred while copying data"),
+    ///         };
+    ///     },
+    ///     Err(_) => eprintln!("Could not connect to database"),
+    /// };
+    /// # })
+    /// ```
+    fn consume_copy_csv(
+        conn: &Client,
+        query: &str,
+        options: &CopyOptions,
+    ) -> impl Future<Output = Result<Vec<Self>, ConsumeError>> + Send
+    where
+        Self: Sized + Send,
+    {
+        consume_copy(conn, query, options)
+    }
+}
+
+/// Parses a `bool` field of `COPY` output. Used by the derive macro.
+#[doc(hidden)]
+pub fn parse_copy_bool(value: &str) -> Option<bool> {
+    match value {
+        "t" | "true" => Some(true),
+        "f" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// A record read from the start of a buffer, along with the number of bytes it took up.
+type Record = (Vec<Option<String>>, usize);
+
+/// Reads the first record of `buf`, returning `None` if `buf` does not hold a complete
+/// record yet. At the end of the output, the last record need not end with a newline.
+fn next_record(buf: &[u8], options: &CopyOptions, at_end: bool) -> Result<Option<Record>, ()> {
+    match options.format {
+        CopyFormat::Csv => next_csv_record(buf, options.delimiter, at_end),
+        CopyFormat::Text => next_text_record(buf, options.delimiter, at_end),
+    }
+}
+
+/// Converts the bytes of a value to a String.
+fn field(bytes: Vec<u8>) -> Result<Option<String>, ()> {
+    match String::from_utf8(bytes) {
+        Ok(v) => Ok(Some(v)),
+        Err(_) => Err(()),
+    }
+}
+
+/// Reads a CSV record, where values may be quoted and quotes are escaped by doubling.
+fn next_csv_record(buf: &[u8], delimiter: u8, at_end: bool) -> Result<Option<Record>, ()> {
+    let mut record = Vec::new();
+    let mut value = Vec::new();
+    let mut quoted = false;
+    let mut in_quotes = false;
+    let mut i = 0;
+
+    let finish = |value: &mut Vec<u8>, quoted: bool| match (quoted, value.is_empty()) {
+        (false, true) => Ok(None),
+        _ => field(std::mem::take(value)),
+    };
+
+    while i < buf.len() {
+        let b = buf[i];
+
+        if in_quotes {
+            match (b, buf.get(i + 1)) {
+                (b'"', Some(b'"')) => {
+                    value.push(b'"');
+                    i += 1;
+                }
+                (b'"', Some(_)) => in_quotes = false,
+                (b'"', None) if at_end => in_quotes = false,
+                (b'"', None) => return Ok(None),
+                _ => value.push(b),
+            }
+        } else if b == b'"' && value.is_empty() && !quoted {
+            in_quotes = true;
+            quoted = true;
+        } else if b == delimiter {
+            record.push(finish(&mut value, quoted)?);
+            quoted = false;
+        } else if b == b'\n' {
+            record.push(finish(&mut value, quoted)?);
+            return Ok(Some((record, i + 1)));
+        } else {
+            value.push(b);
+        }
+
+        i += 1;
+    }
+
+    match (at_end, in_quotes, buf.is_empty()) {
+        (false, _, _) | (true, _, true) => Ok(None),
+        (true, true, false) => Err(()),
+        (true, false, false) => {
+            record.push(finish(&mut value, quoted)?);
+            Ok(Some((record, buf.len())))
+        }
+    }
+}
+
+/// Reads a text format record, where `\N` is NULL and special characters are escaped
+/// with backslashes.
+fn next_text_record(buf: &[u8], delimiter: u8, at_end: bool) -> Result<Option<Record>, ()> {
+    let mut record = Vec::new();
+    let mut raw = Vec::new();
+    let mut i = 0;
+
+    while i < buf.len() {
+        let b = buf[i];
+
+        if b == b'\\' {
+            match buf.get(i + 1) {
+                Some(v) => {
+                    raw.push(b);
+                    raw.push(*v);
+                    i += 1;
+                }
+                None if at_end => return Err(()),
+                None => return Ok(None),
+            }
+        } else if b == delimiter {
+            record.push(unescape_text(&std::mem::take(&mut raw))?);
+        } else if b == b'\n' {
+            record.push(unescape_text(&std::mem::take(&mut raw))?);
+            return Ok(Some((record, i + 1)));
+        } else {
+            raw.push(b);
+        }
+
+        i += 1;
+    }
+
+    match (at_end, buf.is_empty()) {
+        (true, false) => {
+            record.push(unescape_text(&raw)?);
+            Ok(Some((record, buf.len())))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Decodes the backslash escapes of a text format value.
+fn unescape_text(raw: &[u8]) -> Result<Option<String>, ()> {
+    if raw == b"\\N" {
+        return Ok(None);
+    }
+
+    let mut value = Vec::with_capacity(raw.len());
+    let mut i = 0;
+
+    while i < raw.len() {
+        if raw[i] != b'\\' || i + 1 >= raw.len() {
+            value.push(raw[i]);
+            i += 1;
+            continue;
+        }
+
+        let escaped = raw[i + 1];
+        i += 2;
+
+        let digits = |radix: u32, start: usize, max: usize| {
+            let end = (start..(start + max).min(raw.len()))
+                .find(|v| !(raw[*v] as char).is_digit(radix))
+                .unwrap_or((start + max).min(raw.len()));
+
+            (
+                u8::from_str_radix(std::str::from_utf8(&raw[start..end]).unwrap_or(""), radix),
+                end,
+            )
+        };
+
+        match escaped {
+            b'b' => value.push(0x08),
+            b'f' => value.push(0x0c),
+            b'n' => value.push(b'\n'),
+            b'r' => value.push(b'\r'),
+            b't' => value.push(b'\t'),
+            b'v' => value.push(0x0b),
+            b'0'..=b'7' => match digits(8, i - 1, 3) {
+                (Ok(v), end) => {
+                    value.push(v);
+                    i = end;
+                }
+                (Err(_), _) => return Err(()),
+            },
+            b'x' => match digits(16, i, 2) {
+                (Ok(v), end) => {
+                    value.push(v);
+                    i = end;
+                }
+                (Err(_), _) => value.push(b'x'),
+            },
+            v => value.push(v),
+        }
+    }
+
+    field(value)
+}
+
+/// The implementation of `consume_copy_csv`, converting records as the output arrives.
+async fn consume_copy<T: CopyConsumer + Send>(
+    conn: &Client,
+    query: &str,
+    options: &CopyOptions,
+) -> Result<Vec<T>, ConsumeError> {
+    let stream = match conn.copy_out(query).await {
+        Ok(v) => v,
+        Err(e) => return Err(ConsumeError::from(e)),
+    };
+
+    let mut stream = std::pin::pin!(stream);
+    let mut buf: Vec<u8> = Vec::new();
+    let mut data = Vec::new();
+    let mut skip_header = options.header;
+    let mut at_end = false;
+
+    while !at_end {
+        match stream.next().await {
+            Some(Ok(v)) => buf.extend_from_slice(&v),
+            Some(Err(e)) => return Err(ConsumeError::from(e)),
+            None => at_end = true,
+        }
+
+        for record in take_records(&mut buf, options, at_end)? {
+            match (skip_header, T::from_copy_fields(&record)) {
+                (true, _) => skip_header = false,
+                (false, Ok(v)) => data.push(v),
+                (false, Err(_)) => return Err(ConsumeError::ConversionError),
+            }
+        }
+    }
+
+    Ok(data)
+}
+
+/// Removes the complete records from the start of `buf`, leaving any partial record
+/// for the next chunk of output.
+fn take_records(
+    buf: &mut Vec<u8>,
+    options: &CopyOptions,
+    at_end: bool,
+) -> Result<Vec<Vec<Option<String>>>, ConsumeError> {
+    let mut records = Vec::new();
+    let mut start = 0;
+
+    while let Some((record, length)) = match next_record(&buf[start..], options, at_end) {
+        Ok(v) => v,
+        Err(_) => return Err(ConsumeError::ConversionError),
+    } {
+        start += length;
+        records.push(record);
+    }
+
+    buf.drain(..start);
+    Ok(records)
+}
+
+/// Parses `COPY` output arriving in the given chunks the way `consume_copy_csv` does,
+/// returning the values of each record after any header. Used by the fuzz targets.
+#[doc(hidden)]
+pub fn parse_copy_output(
+    chunks: &[&[u8]],
+    options: &CopyOptions,
+) -> Result<Vec<Vec<Option<String>>>, ConsumeError> {
+    let mut buf = Vec::new();
+    let mut records = Vec::new();
+
+    for (i, chunk) in chunks.iter().enumerate() {
+        buf.extend_from_slice(chunk);
+        records.extend(take_records(&mut buf, options, i + 1 == chunks.len())?);
+    }
+
+    match options.header && !records.is_empty() {
+        true => Ok(records.split_off(1)),
+        false => Ok(records),
+    }
+}
@@ -0,0 +1,36 @@
+//! Removal of rows sharing a key, used by `consume_dedup_by_key`.
+use crate::KeyedBy;
+use std::collections::HashSet;
+use std::hash::Hash;
+
+/// Which of the rows sharing a key `consume_dedup_by_key` keeps.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeepDuplicate {
+    /// Keeps the first row of each key, at its position.
+    #[default]
+    First,
+    /// Keeps the last row of each key, at its position.
+    Last,
+}
+
+/// Removes the rows whose key was already seen, in the direction `keep` calls for.
+pub(crate) fn dedup_by_key<T, K>(rows: Vec<T>, keep: KeepDuplicate) -> Vec<T>
+where
+    T: KeyedBy<K>,
+    K: Eq + Hash,
+{
+    let mut seen = HashSet::with_capacity(rows.len());
+
+    match keep {
+        KeepDuplicate::First => rows.into_iter().filter(|v| seen.insert(v.key())).collect(),
+        KeepDuplicate::Last => {
+            let mut rows: Vec<T> = rows
+                .into_iter()
+                .rev()
+                .filter(|v| seen.insert(v.key()))
+                .collect();
+            rows.reverse();
+            rows
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! Comparison of the results of the same query on two databases, for verifying
+//! migrations and replicas.
+use crate::{ConsumeError, RowConsumer};
+use std::collections::HashMap;
+use std::hash::Hash;
+use tokio_postgres::types::ToSql;
+use tokio_postgres::Client;
+
+/// The trait for types that are identified by a key, used to match rows of two result
+/// sets. Keys are expected to be unique within a result set.
+///
+/// ## Example
+/// ```
+/// use pgde::KeyedBy;
+///
+/// struct Foo {
+///     Id: i32,
+///     Data: String,
+/// }
+///
+/// impl KeyedBy<i32> for Foo {
+///     fn key(&self) -> i32 {
+///         self.Id
+///     }
+/// }
+/// ```
+pub trait KeyedBy<K> {
+    /// Returns the key identifying this value.
+    fn key(&self) -> K;
+}
+
+/// The differences between two result sets, matched by key.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diff<T> {
+    /// Rows whose keys only appear in the second result set.
+    pub added: Vec<T>,
+    /// Rows whose keys only appear in the first result set.
+    pub removed: Vec<T>,
+    /// Rows whose keys appear in both result sets with different values, as pairs of
+    /// the first and second row.
+    pub changed: Vec<(T, T)>,
+}
+
+impl<T> Diff<T> {
+    /// Compares two result sets, matching rows by key. Added and changed rows are in the
+    /// order of `new`, and removed rows in the order of `old`.
+    pub fn between<K>(old: Vec<T>, new: Vec<T>) -> Self
+    where
+        T: KeyedBy<K> + PartialEq,
+        K: Eq + Hash,
+    {
+        let mut index: HashMap<K, usize> =
+            old.iter().enumerate().map(|(i, v)| (v.key(), i)).collect();
+        let mut old: Vec<Option<T>> = old.into_iter().map(Some).collect();
+
+        let mut added = Vec::new();
+        let mut changed = Vec::new();
+
+        for row in new.into_iter() {
+            match index.remove(&row.key()).and_then(|i| old[i].take()) {
+                Some(v) if v == row => (),
+                Some(v) => changed.push((v, row)),
+                None => added.push(row),
+            }
+        }
+
+        let removed = old.into_iter().flatten().collect();
+
+        Diff {
+            added,
+            removed,
+            changed,
+        }
+    }
+
+    /// Whether the result sets were identical, up to order.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Consumes the same query on two databases and reports the rows added, removed, and
+/// changed in the second database relative to the first, matched by key.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::{diff, KeyedBy};
+/// use pgde_derive::RowConsumer;
+/// use tokio_postgres::{NoTls, Row};
+///
+/// #[derive(RowConsumer, PartialEq)]
+/// struct Foo {
+///     Id: i32,
+///     Data: String,
+/// }
+///
+/// impl KeyedBy<i32> for Foo {
+///     fn key(&self) -> i32 {
+///         self.Id
+///     }
+/// }
+///
+/// let source = tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await;
+/// let target = tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await;
+///
+/// match (source, target) {
+///     (Ok(source), Ok(target)) => {
+///         tokio::spawn(source.1);
+///         tokio::spawn(target.1);
+///
+///         let query = "select * from public.\"Foo\";";
+///
+///         match diff::<Foo, i32>(&source.0, &target.0, query, &[]).await {
+///             Ok(v) => println!("{} added, {} removed, {} changed", v.added.len(), v.removed.len(), v.changed.len()),
+///             Err(_) => eprintln!("An error occurred while querying databases"),
+///         };
+///     },
+///     _ => eprintln!("Could not connect to databases"),
+/// };
+/// # })
+/// ```
+pub async fn diff<T, K>(
+    client_a: &Client,
+    client_b: &Client,
+    query: &str,
+    params: &[&(dyn ToSql + Sync)],
+) -> Result<Diff<T>, ConsumeError>
+where
+    T: RowConsumer + PartialEq + KeyedBy<K>,
+    K: Eq + Hash,
+{
+    match (
+        T::consume(client_a, query, params).await,
+        T::consume(client_b, query, params).await,
+    ) {
+        (Ok(a), Ok(b)) => Ok(Diff::between(a, b)),
+        (Err(e), _) | (_, Err(e)) => Err(e),
+    }
+}
@@ -0,0 +1,309 @@
+//! Detection of drift between consuming types and the queries they consume, e.g. after a
+//! migration changes a table.
+#[cfg(feature = "watcher")]
+use crate::ErrorClass;
+use crate::{check_columns, ConsumeError, RowConsumer};
+#[cfg(feature = "watcher")]
+use futures_util::future::BoxFuture;
+#[cfg(feature = "watcher")]
+use std::sync::Arc;
+#[cfg(feature = "watcher")]
+use std::time::Duration;
+#[cfg(feature = "watcher")]
+use tokio::task::JoinHandle;
+use tokio_postgres::types::private::BytesMut;
+use tokio_postgres::types::ToSql;
+use tokio_postgres::Client;
+
+/// Prepares `query` without running it and checks its columns against `T`, failing as
+/// consuming it would: with [ConsumeError::ColumnCountMismatch] or
+/// [ConsumeError::AmbiguousColumns] if `T` cannot read its columns, with
+/// [ConsumeError::ColumnTypeMismatch] if `T` cannot read their types, and with
+/// [ConsumeError::DatabaseError] if the query no longer prepares, e.g. because a column
+/// it selects was dropped.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::verify_query;
+/// use pgde_derive::RowConsumer;
+/// use tokio_postgres::{NoTls, Row};
+///
+/// #[derive(RowConsumer)]
+/// struct Setting {
+///     name: String,
+///     setting: String,
+/// }
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         match verify_query::<Setting>(&client, "select name, setting from pg_settings;").await {
+///             Ok(_) => println!("Setting matches its query"),
+///             Err(_) => eprintln!("Setting has drifted from its query"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub async fn verify_query<T: RowConsumer>(
+    client: &Client,
+    query: &str,
+) -> Result<(), ConsumeError> {
+    let statement = match client.prepare(query).await {
+        Ok(v) => v,
+        Err(e) => return Err(ConsumeError::from(e)),
+    };
+
+    check_columns::<T>(statement.columns())?;
+
+    let errors = T::check_column_types(statement.columns());
+
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(ConsumeError::ColumnTypeMismatch(errors)),
+    }
+}
+
+/// A bound parameter that cannot be sent as the type its query expects, found by
+/// [check_params].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParamMismatch {
+    /// The zero based position of the parameter, e.g. `0` for `$1`.
+    pub index: usize,
+    /// The name of the PostgreSQL type the query expects, e.g. `int8`.
+    pub expected: String,
+    /// Why the parameter cannot be sent, naming its Rust type when the types differ.
+    pub message: String,
+}
+
+/// Prepares `query` without running it and checks every parameter in `params` against
+/// the type the query expects at its position, returning a [ParamMismatch] for every
+/// parameter that cannot be sent, e.g. a `&str` bound where an `int8` is expected, so that
+/// mixed up parameters are caught before a job fails deep into its run. Fails with
+/// [ConsumeError::InvalidQuery] if the query expects a different number of parameters,
+/// and with [ConsumeError::DatabaseError] if it does not prepare.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::check_params;
+/// use tokio_postgres::NoTls;
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         match check_params(&client, "select $1::bigint;", &[&"1"]).await {
+///             Ok(v) => {
+///                 for mismatch in v {
+///                     eprintln!("Parameter ${} is not a {}", mismatch.index + 1, mismatch.expected);
+///                 }
+///             }
+///             Err(_) => eprintln!("Could not prepare query"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub async fn check_params(
+    client: &Client,
+    query: &str,
+    params: &[&(dyn ToSql + Sync)],
+) -> Result<Vec<ParamMismatch>, ConsumeError> {
+    let statement = match client.prepare(query).await {
+        Ok(v) => v,
+        Err(e) => return Err(ConsumeError::from(e)),
+    };
+
+    if statement.params().len() != params.len() {
+        return Err(ConsumeError::InvalidQuery(format!(
+            "Expected {} parameters but received {}",
+            statement.params().len(),
+            params.len()
+        )));
+    }
+
+    let mut buf = BytesMut::new();
+    let mut mismatches = Vec::new();
+
+    for (index, (ty, param)) in statement.params().iter().zip(params).enumerate() {
+        buf.clear();
+
+        if let Err(e) = param.to_sql_checked(ty, &mut buf) {
+            mismatches.push(ParamMismatch {
+                index,
+                expected: String::from(ty.name()),
+                message: e.to_string(),
+            });
+        }
+    }
+
+    Ok(mismatches)
+}
+
+/// A check of a registered type and query.
+#[cfg(feature = "watcher")]
+type DriftCheck = for<'a> fn(&'a Client, &'a str) -> BoxFuture<'a, Result<(), ConsumeError>>;
+
+/// The drift callback of a watchdog.
+#[cfg(feature = "watcher")]
+type DriftHook = Box<dyn Fn(&Drift) + Send + Sync>;
+
+/// A registered type and query found to have drifted by a [DriftWatchdog].
+#[cfg(feature = "watcher")]
+pub struct Drift {
+    /// The name of the consuming type.
+    pub type_name: &'static str,
+    /// The query consumed into the type.
+    pub query: String,
+    /// The error `verify_query` failed with.
+    pub error: ConsumeError,
+}
+
+/// Re-validates registered pairs of consuming types and queries with [verify_query],
+/// once with `check` or on an interval with `poll`, and passes every pair found to have
+/// drifted to a callback, so that long-running services learn of breaking migrations
+/// before the queries fail for their users. Checks that fail because the connection
+/// failed are not reported as drift.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::DriftWatchdog;
+/// use pgde_derive::RowConsumer;
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use tokio_postgres::{NoTls, Row};
+///
+/// #[derive(RowConsumer)]
+/// struct Setting {
+///     name: String,
+///     setting: String,
+/// }
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         let watchdog = DriftWatchdog::new(Arc::new(client))
+///             .register::<Setting>("select name, setting from pg_settings;")
+///             .on_drift(|drift| eprintln!("{} has drifted from {}", drift.type_name, drift.query));
+///
+///         let watchdog = Arc::new(watchdog);
+///         let handle = watchdog.poll(Duration::from_secs(300));
+///
+///         println!("Found {} drifted queries", watchdog.check().await.len());
+///         handle.abort();
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+#[cfg(feature = "watcher")]
+pub struct DriftWatchdog {
+    client: Arc<Client>,
+    checks: Vec<(&'static str, String, DriftCheck)>,
+    on_drift: Option<DriftHook>,
+}
+
+#[cfg(feature = "watcher")]
+impl DriftWatchdog {
+    /// Creates a watchdog without registered queries.
+    pub fn new(client: Arc<Client>) -> Self {
+        DriftWatchdog {
+            client,
+            checks: Vec::new(),
+            on_drift: None,
+        }
+    }
+
+    /// Registers `query` as consumed into `T`.
+    pub fn register<T: RowConsumer + 'static>(mut self, query: impl Into<String>) -> Self {
+        self.checks
+            .push((std::any::type_name::<T>(), query.into(), verify_boxed::<T>));
+        self
+    }
+
+    /// Sets a callback given every registered pair found to have drifted, on every check
+    /// that finds it.
+    pub fn on_drift(mut self, hook: impl Fn(&Drift) + Send + Sync + 'static) -> Self {
+        self.on_drift = Some(Box::new(hook));
+        self
+    }
+
+    /// Verifies every registered pair, returning those that have drifted after passing
+    /// them to the drift callback.
+    pub async fn check(&self) -> Vec<Drift> {
+        let mut drifts = Vec::new();
+
+        for (type_name, query, check) in &self.checks {
+            match check(&self.client, query).await {
+                Err(e) if e.classification() != ErrorClass::ConnectionLost => {
+                    let drift = Drift {
+                        type_name,
+                        query: query.clone(),
+                        error: e,
+                    };
+
+                    if let Some(hook) = &self.on_drift {
+                        hook(&drift);
+                    }
+
+                    drifts.push(drift);
+                }
+                _ => (),
+            };
+        }
+
+        drifts
+    }
+
+    /// Spawns a task that checks the registered pairs every `interval`, starting
+    /// immediately. The task runs until its handle is aborted.
+    pub fn poll(self: &Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        let watchdog = self.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+
+            loop {
+                interval.tick().await;
+                let _ = watchdog.check().await;
+            }
+        })
+    }
+}
+
+/// Boxes `verify_query` for `T`, so that checks of different types can be stored alike.
+#[cfg(feature = "watcher")]
+fn verify_boxed<'a, T: RowConsumer + 'static>(
+    client: &'a Client,
+    query: &'a str,
+) -> BoxFuture<'a, Result<(), ConsumeError>> {
+    Box::pin(verify_query::<T>(client, query))
+}
@@ -0,0 +1,116 @@
+//! Export of query results as SQL `INSERT` statements, for seed files and lightweight
+//! backups.
+use crate::ident::quote_ident;
+use crate::ConsumeError;
+use std::io::Write;
+use tokio_postgres::types::ToSql;
+use tokio_postgres::Client;
+
+/// Runs a query and writes its rows to `writer` as `INSERT INTO ... VALUES ...;`
+/// statements, one per row, returning the number of rows written. Every column of the
+/// query must come from the same table, which is the table inserted into. Values are
+/// rendered and escaped by PostgreSQL with `quote_nullable`, so the output may be
+/// replayed with any client.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::dump_inserts;
+/// use tokio_postgres::NoTls;
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         let mut seed = Vec::new();
+///         let query = "select * from pg_catalog.pg_am where amname = $1;";
+///
+///         match dump_inserts(&client, query, &[&"btree"], &mut seed).await {
+///             Ok(v) => println!("Dumped {} rows", v),
+///             Err(_) => eprintln!("An error occurred while dumping rows"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub async fn dump_inserts<W: Write>(
+    conn: &Client,
+    query: &str,
+    params: &[&(dyn ToSql + Sync)],
+    writer: &mut W,
+) -> Result<usize, ConsumeError> {
+    let query = query.trim_end().trim_end_matches(';');
+
+    let statement = match conn.prepare(query).await {
+        Ok(v) => v,
+        Err(e) => return Err(ConsumeError::from(e)),
+    };
+
+    let columns = statement.columns();
+
+    let table_oid = match columns.first().and_then(|v| v.table_oid()) {
+        Some(v) if columns.iter().all(|c| c.table_oid() == Some(v)) => v,
+        _ => {
+            return Err(ConsumeError::InvalidQuery(String::from(
+                "Expected every column to come from the same table",
+            )))
+        }
+    };
+
+    let table: String = match conn
+        .query_one("select $1::oid::regclass::text", &[&table_oid])
+        .await
+    {
+        Ok(v) => match v.try_get(0) {
+            Ok(v) => v,
+            Err(_) => return Err(ConsumeError::ConversionError),
+        },
+        Err(e) => return Err(ConsumeError::from(e)),
+    };
+
+    let names: Vec<String> = columns.iter().map(|v| quote_ident(v.name())).collect();
+    let aliases: Vec<String> = (1..=columns.len()).map(|v| format!("\"{}\"", v)).collect();
+    let literals: Vec<String> = aliases
+        .iter()
+        .map(|v| format!("quote_nullable(q.{})", v))
+        .collect();
+
+    let query = format!(
+        "select {} from ({}) as q({})",
+        literals.join(", "),
+        query,
+        aliases.join(", ")
+    );
+
+    let rows = match conn.query(&query, params).await {
+        Ok(v) => v,
+        Err(e) => return Err(ConsumeError::from(e)),
+    };
+
+    let prefix = format!("INSERT INTO {} ({}) VALUES", table, names.join(", "));
+
+    for row in rows.iter() {
+        let mut values = Vec::with_capacity(row.len());
+
+        for i in 0..row.len() {
+            match row.try_get::<usize, String>(i) {
+                Ok(v) => values.push(v),
+                Err(_) => return Err(ConsumeError::ConversionError),
+            }
+        }
+
+        if let Err(e) = writeln!(writer, "{} ({});", prefix, values.join(", ")) {
+            return Err(ConsumeError::WriteError(e.to_string()));
+        }
+    }
+
+    Ok(rows.len())
+}
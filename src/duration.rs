@@ -0,0 +1,153 @@
+//! Support for consuming PostgreSQL `interval` values, and numbers of seconds, into
+//! duration types.
+use crate::numeric::numeric_to_f64;
+use std::error::Error;
+use std::time::Duration;
+use tokio_postgres::types::{FromSql, Type};
+
+const MICROSECONDS_PER_DAY: i128 = 86_400_000_000;
+const DAYS_PER_MONTH: i128 = 30;
+
+/// The raw components of a PostgreSQL `interval`. Months and days are kept apart from
+/// the time component, as they are in PostgreSQL, since their lengths vary.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Interval {
+    pub months: i32,
+    pub days: i32,
+    pub microseconds: i64,
+}
+
+impl Interval {
+    /// The total length of the interval in microseconds, treating a month as 30 days
+    /// and a day as 24 hours, as PostgreSQL's `justify_interval` does.
+    pub fn total_microseconds(&self) -> i128 {
+        (self.months as i128 * DAYS_PER_MONTH + self.days as i128) * MICROSECONDS_PER_DAY
+            + self.microseconds as i128
+    }
+}
+
+impl<'a> FromSql<'a> for Interval {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        match raw.len() {
+            16 => Ok(Interval {
+                microseconds: i64::from_be_bytes(raw[0..8].try_into()?),
+                days: i32::from_be_bytes(raw[8..12].try_into()?),
+                months: i32::from_be_bytes(raw[12..16].try_into()?),
+            }),
+            _ => Err("invalid interval length".into()),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::INTERVAL
+    }
+}
+
+/// A number of seconds read from a `float4`, `float8`, `int2`, `int4`, `int8`, or
+/// `numeric` column.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Seconds(pub f64);
+
+impl<'a> FromSql<'a> for Seconds {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        match *ty {
+            Type::FLOAT4 => Ok(Seconds(f32::from_sql(ty, raw)? as f64)),
+            Type::FLOAT8 => Ok(Seconds(f64::from_sql(ty, raw)?)),
+            Type::INT2 => Ok(Seconds(i16::from_sql(ty, raw)? as f64)),
+            Type::INT4 => Ok(Seconds(i32::from_sql(ty, raw)? as f64)),
+            Type::INT8 => Ok(Seconds(i64::from_sql(ty, raw)? as f64)),
+            Type::NUMERIC => Ok(Seconds(numeric_to_f64(raw)?)),
+            _ => Err("unsupported seconds type".into()),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(
+            *ty,
+            Type::FLOAT4 | Type::FLOAT8 | Type::INT2 | Type::INT4 | Type::INT8 | Type::NUMERIC
+        )
+    }
+}
+
+/// Converts a number of seconds into microseconds, returning `None` for values that are
+/// not finite or do not fit in an `i64`.
+#[cfg(any(feature = "chrono", feature = "time"))]
+fn seconds_to_microseconds(seconds: f64) -> Option<i64> {
+    let microseconds = (seconds * 1_000_000f64).round();
+
+    match microseconds.is_finite()
+        && microseconds >= i64::MIN as f64
+        && microseconds < i64::MAX as f64
+    {
+        true => Some(microseconds as i64),
+        false => None,
+    }
+}
+
+/// Conversion of `interval` and seconds columns into duration types. Used by the
+/// `#[pgde(interval)]` and `#[pgde(seconds)]` field attributes of the derive macro.
+/// Returns `None` when a value is NULL or cannot be represented by the target type,
+/// e.g. a negative interval consumed into `std::time::Duration`. Negative intervals are
+/// preserved by `chrono::Duration` and `time::Duration`, and a month is taken as 30 days.
+pub trait DurationColumn: Sized {
+    /// Converts a nullable `interval` into the implementing type.
+    fn from_interval(value: Option<Interval>) -> Option<Self>;
+
+    /// Converts a nullable number of seconds into the implementing type.
+    fn from_seconds(value: Option<Seconds>) -> Option<Self>;
+}
+
+impl<T: DurationColumn> DurationColumn for Option<T> {
+    fn from_interval(value: Option<Interval>) -> Option<Self> {
+        match value {
+            Some(_) => T::from_interval(value).map(Some),
+            None => Some(None),
+        }
+    }
+
+    fn from_seconds(value: Option<Seconds>) -> Option<Self> {
+        match value {
+            Some(_) => T::from_seconds(value).map(Some),
+            None => Some(None),
+        }
+    }
+}
+
+impl DurationColumn for Duration {
+    fn from_interval(value: Option<Interval>) -> Option<Self> {
+        let microseconds = u64::try_from(value?.total_microseconds()).ok()?;
+        Some(Duration::from_micros(microseconds))
+    }
+
+    fn from_seconds(value: Option<Seconds>) -> Option<Self> {
+        Duration::try_from_secs_f64(value?.0).ok()
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl DurationColumn for chrono::Duration {
+    fn from_interval(value: Option<Interval>) -> Option<Self> {
+        let microseconds = i64::try_from(value?.total_microseconds()).ok()?;
+        Some(chrono::Duration::microseconds(microseconds))
+    }
+
+    fn from_seconds(value: Option<Seconds>) -> Option<Self> {
+        Some(chrono::Duration::microseconds(seconds_to_microseconds(
+            value?.0,
+        )?))
+    }
+}
+
+#[cfg(feature = "time")]
+impl DurationColumn for time::Duration {
+    fn from_interval(value: Option<Interval>) -> Option<Self> {
+        let microseconds = i64::try_from(value?.total_microseconds()).ok()?;
+        Some(time::Duration::microseconds(microseconds))
+    }
+
+    fn from_seconds(value: Option<Seconds>) -> Option<Self> {
+        Some(time::Duration::microseconds(seconds_to_microseconds(
+            value?.0,
+        )?))
+    }
+}
@@ -0,0 +1,179 @@
+//! Leader election among sessions contending for a session-level advisory lock.
+use crate::ConsumeError;
+use futures_util::FutureExt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio_postgres::Client;
+
+/// The change callback of a leader, given whether it now leads.
+type ChangeHook = Box<dyn Fn(bool) + Send + Sync>;
+
+/// A contender for leadership of the advisory lock `key`, which leads for as long as its
+/// session holds the lock. Followers try to take the lock on every `heartbeat`, and
+/// leaders check that their session still holds it, so that a leader whose connection
+/// was lost, or whose lock was released, learns that it no longer leads. An optional
+/// callback is given the new state whenever it changes.
+///
+/// Heartbeats should be sent from a single task, such as the one spawned by `run`. The
+/// lock is released by `resign`, or when the leader is dropped.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::election::Leader;
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use tokio_postgres::NoTls;
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         match Leader::acquire(Arc::new(client), 42).await {
+///             Ok(leader) => {
+///                 let leader = Arc::new(leader.on_change(|leading| println!("Leading: {}", leading)));
+///                 let handle = leader.run(Duration::from_secs(5));
+///
+///                 if leader.is_leader() {
+///                     println!("Running scheduled jobs");
+///                 }
+///
+///                 handle.abort();
+///             },
+///             Err(_) => eprintln!("An error occurred while contending for leadership"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub struct Leader {
+    client: Arc<Client>,
+    key: i64,
+    leading: AtomicBool,
+    on_change: Option<ChangeHook>,
+}
+
+impl Leader {
+    /// Contends for leadership of `key`, trying to take the lock once without waiting.
+    pub async fn acquire(client: Arc<Client>, key: i64) -> Result<Self, ConsumeError> {
+        let leader = Leader {
+            client,
+            key,
+            leading: AtomicBool::new(false),
+            on_change: None,
+        };
+
+        leader.heartbeat().await?;
+
+        Ok(leader)
+    }
+
+    /// Sets a callback given whether the leader now leads, whenever a heartbeat or
+    /// `resign` changes it.
+    pub fn on_change(mut self, hook: impl Fn(bool) + Send + Sync + 'static) -> Self {
+        self.on_change = Some(Box::new(hook));
+        self
+    }
+
+    /// Returns the key of the lock.
+    pub fn key(&self) -> i64 {
+        self.key
+    }
+
+    /// Returns whether the leader led as of its last heartbeat.
+    pub fn is_leader(&self) -> bool {
+        self.leading.load(Ordering::SeqCst)
+    }
+
+    /// Checks that the session still holds the lock if leading, or tries to take it
+    /// otherwise, returning whether the leader now leads. A failed heartbeat loses
+    /// leadership, since a session whose connection failed no longer holds its locks.
+    /// Advisory locks are reentrant, so a session that still holds the lock after a
+    /// failed heartbeat leads again without taking it a second time.
+    pub async fn heartbeat(&self) -> Result<bool, ConsumeError> {
+        let query = match self.is_leader() {
+            true => format!("select {};", HELD),
+            false => format!(
+                "select case when {} then true else pg_try_advisory_lock($1) end;",
+                HELD
+            ),
+        };
+
+        let leading = match self.client.query_one(&query, &[&self.key]).await {
+            Ok(v) => match v.try_get::<usize, bool>(0) {
+                Ok(v) => Ok(v),
+                Err(e) => Err(ConsumeError::from(e)),
+            },
+            Err(e) => Err(ConsumeError::from(e)),
+        };
+
+        self.set_leading(*leading.as_ref().unwrap_or(&false));
+
+        leading
+    }
+
+    /// Releases the lock if the session holds it, so that another contender may take it.
+    /// The lock is released even if a failed heartbeat lost leadership while the session
+    /// kept it.
+    pub async fn resign(&self) -> Result<(), ConsumeError> {
+        self.set_leading(false);
+
+        match self.client.execute(&release_query(), &[&self.key]).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(ConsumeError::from(e)),
+        }
+    }
+
+    /// Spawns a task that sends a heartbeat every `interval`, starting after the first
+    /// interval has elapsed. The task runs until its handle is aborted.
+    pub fn run(self: &Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        let leader = self.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            interval.tick().await;
+
+            loop {
+                interval.tick().await;
+                let _ = leader.heartbeat().await;
+            }
+        })
+    }
+
+    fn set_leading(&self, leading: bool) {
+        if self.leading.swap(leading, Ordering::SeqCst) != leading {
+            if let Some(hook) = &self.on_change {
+                hook(leading);
+            }
+        }
+    }
+}
+
+impl Drop for Leader {
+    fn drop(&mut self) {
+        // Polling once is enough to queue the query on the connection.
+        let query = release_query().replace("$1", &self.key.to_string());
+        let _ = self.client.batch_execute(&query).now_or_never();
+    }
+}
+
+/// Whether the session holds the advisory lock `$1`.
+const HELD: &str = "exists (select 1 from pg_locks where locktype = 'advisory' and pid = pg_backend_pid() and granted and objsubid = 1 and ((classid::bigint << 32) | objid::bigint) = $1)";
+
+/// Releases the advisory lock `$1` if the session holds it.
+fn release_query() -> String {
+    format!(
+        "select case when {} then pg_advisory_unlock($1) else false end;",
+        HELD
+    )
+}
@@ -0,0 +1,184 @@
+//! Support for consuming `EXPLAIN (FORMAT JSON)` output into typed query plans.
+use crate::{ConsumeError, RawValue};
+use serde::Deserialize;
+use tokio_postgres::types::ToSql;
+use tokio_postgres::Client;
+
+/// Options for `consume_explain`, mapped onto the options of `EXPLAIN`.
+///
+/// ## Example
+/// ```
+/// use pgde::ExplainOptions;
+///
+/// let options = ExplainOptions::new().analyze(true).buffers(true);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExplainOptions {
+    analyze: bool,
+    buffers: bool,
+    costs: bool,
+    verbose: bool,
+}
+
+impl ExplainOptions {
+    /// Creates options that plan the query without running it.
+    pub fn new() -> Self {
+        ExplainOptions {
+            analyze: false,
+            buffers: false,
+            costs: true,
+            verbose: false,
+        }
+    }
+
+    /// Runs the query to report actual rows and timing. Note that the query's side
+    /// effects take place, so wrap data-modifying queries in a transaction that is rolled
+    /// back.
+    pub fn analyze(mut self, analyze: bool) -> Self {
+        self.analyze = analyze;
+        self
+    }
+
+    /// Reports buffer usage. Only has an effect along with `analyze`.
+    pub fn buffers(mut self, buffers: bool) -> Self {
+        self.buffers = buffers;
+        self
+    }
+
+    /// Reports estimated costs. Defaults to `true`.
+    pub fn costs(mut self, costs: bool) -> Self {
+        self.costs = costs;
+        self
+    }
+
+    /// Reports additional detail, such as the output columns of each node.
+    pub fn verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Returns the `EXPLAIN` statement for the query.
+    fn explain(&self, query: &str) -> String {
+        format!(
+            "explain (format json, analyze {}, buffers {}, costs {}, verbose {}) {}",
+            self.analyze,
+            self.buffers && self.analyze,
+            self.costs,
+            self.verbose,
+            query.trim_end().trim_end_matches(';')
+        )
+    }
+}
+
+impl Default for ExplainOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The plan of a query, as reported by `consume_explain`.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct ExplainPlan {
+    /// The root node of the plan.
+    #[serde(rename = "Plan")]
+    pub plan: PlanNode,
+    /// The time spent planning the query, in milliseconds, when analyzed.
+    #[serde(rename = "Planning Time")]
+    pub planning_time: Option<f64>,
+    /// The time spent running the query, in milliseconds, when analyzed.
+    #[serde(rename = "Execution Time")]
+    pub execution_time: Option<f64>,
+}
+
+/// A node of an [ExplainPlan]. Estimates are missing when `costs` is disabled, and
+/// actual values are missing unless `analyze` is enabled.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct PlanNode {
+    /// The kind of node, e.g. `Seq Scan` or `Hash Join`.
+    #[serde(rename = "Node Type")]
+    pub node_type: String,
+    /// The relation scanned by the node, if any.
+    #[serde(rename = "Relation Name")]
+    pub relation_name: Option<String>,
+    /// The estimated cost before the first row is returned.
+    #[serde(rename = "Startup Cost")]
+    pub startup_cost: Option<f64>,
+    /// The estimated cost to return all rows.
+    #[serde(rename = "Total Cost")]
+    pub total_cost: Option<f64>,
+    /// The estimated number of rows returned.
+    #[serde(rename = "Plan Rows")]
+    pub plan_rows: Option<f64>,
+    /// The estimated average width of returned rows, in bytes.
+    #[serde(rename = "Plan Width")]
+    pub plan_width: Option<i64>,
+    /// The time taken before the first row was returned, in milliseconds per loop.
+    #[serde(rename = "Actual Startup Time")]
+    pub actual_startup_time: Option<f64>,
+    /// The time taken to return all rows, in milliseconds per loop.
+    #[serde(rename = "Actual Total Time")]
+    pub actual_total_time: Option<f64>,
+    /// The number of rows returned, per loop.
+    #[serde(rename = "Actual Rows")]
+    pub actual_rows: Option<f64>,
+    /// The number of times the node was run.
+    #[serde(rename = "Actual Loops")]
+    pub actual_loops: Option<f64>,
+    /// The child nodes.
+    #[serde(rename = "Plans", default)]
+    pub plans: Vec<PlanNode>,
+}
+
+/// Runs `EXPLAIN (FORMAT JSON)` on the query and returns its plan. Parameters are passed
+/// through to the explained query.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::{consume_explain, ExplainOptions};
+/// use tokio_postgres::NoTls;
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         let query = "select * from generate_series(1, $1);";
+///         let options = ExplainOptions::new().analyze(true);
+///
+///         match consume_explain(&client, query, &[&100], &options).await {
+///             Ok(v) => println!("{} took {:?}ms", v.plan.node_type, v.execution_time),
+///             Err(_) => eprintln!("An error occurred while explaining query"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub async fn consume_explain(
+    conn: &Client,
+    query: &str,
+    params: &[&(dyn ToSql + Sync)],
+    options: &ExplainOptions,
+) -> Result<ExplainPlan, ConsumeError> {
+    let row = match conn.query_one(&options.explain(query), params).await {
+        Ok(v) => v,
+        Err(e) => return Err(ConsumeError::from(e)),
+    };
+
+    let bytes = match row.try_get::<usize, RawValue>(0) {
+        Ok(RawValue { bytes: Some(v), .. }) => v,
+        _ => return Err(ConsumeError::ConversionError),
+    };
+
+    match serde_json::from_slice::<Vec<ExplainPlan>>(&bytes) {
+        Ok(mut v) if v.len() == 1 => Ok(v.remove(0)),
+        _ => Err(ConsumeError::ConversionError),
+    }
+}
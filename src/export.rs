@@ -0,0 +1,136 @@
+//! Formatting helpers for exported rows, written as serde `serialize_with` functions so
+//! that timestamps and decimals are written the same way on every machine, whether the
+//! rows are exported with `consume_json_with` or another serde format such as CSV.
+//!
+//! ## Example
+//! ```
+//! use serde::Serialize;
+//! use std::time::SystemTime;
+//!
+//! #[derive(Serialize)]
+//! struct Invoice {
+//!     #[serde(serialize_with = "pgde::export::iso8601")]
+//!     issued_at: SystemTime,
+//!     #[serde(serialize_with = "pgde::export::fixed::<2, _, _>")]
+//!     total: f64,
+//! }
+//!
+//! let invoice = Invoice {
+//!     issued_at: SystemTime::UNIX_EPOCH,
+//!     total: 1234.5,
+//! };
+//!
+//! assert_eq!(
+//!     serde_json::to_string(&invoice).ok(),
+//!     Some(String::from(r#"{"issued_at":"1970-01-01T00:00:00Z","total":"1234.50"}"#))
+//! );
+//! ```
+use serde::Serializer;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MICROSECONDS_PER_DAY: i128 = 86_400_000_000;
+
+/// Writes a `SystemTime`, or a time convertible into one such as `chrono::DateTime` or
+/// `time::OffsetDateTime`, as an ISO 8601 timestamp in UTC, e.g. `2024-03-01T12:30:00Z`,
+/// with six digits of fractional seconds when it has any, truncated to microseconds as
+/// PostgreSQL stores them.
+pub fn iso8601<T: Clone + Into<SystemTime>, S: Serializer>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format_iso8601(value.clone().into()))
+}
+
+/// Writes an `Option` of a time like [iso8601], or `None` as the format's null value.
+pub fn iso8601_option<T: Clone + Into<SystemTime>, S: Serializer>(
+    value: &Option<T>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(v) => serializer.serialize_str(&format_iso8601(v.clone().into())),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Writes a number as text with exactly `PLACES` decimal places and no thousands
+/// separators, e.g. `1234.50` with two places, so that money and decimals keep their
+/// precision in formats whose readers would parse numbers as floats. `NaN` and the
+/// infinities are written as `NaN`, `Infinity`, and `-Infinity`, as PostgreSQL does.
+pub fn fixed<const PLACES: usize, T: Copy + Into<f64>, S: Serializer>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format_fixed::<PLACES>((*value).into()))
+}
+
+/// Writes an `Option` of a number like [fixed], or `None` as the format's null value.
+pub fn fixed_option<const PLACES: usize, T: Copy + Into<f64>, S: Serializer>(
+    value: &Option<T>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(v) => serializer.serialize_str(&format_fixed::<PLACES>((*v).into())),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Formats a number with `PLACES` decimal places.
+fn format_fixed<const PLACES: usize>(value: f64) -> String {
+    match value {
+        v if v.is_nan() => String::from("NaN"),
+        f64::INFINITY => String::from("Infinity"),
+        f64::NEG_INFINITY => String::from("-Infinity"),
+        v => format!("{:.*}", PLACES, v),
+    }
+}
+
+/// Formats a time as an ISO 8601 timestamp in UTC.
+fn format_iso8601(value: SystemTime) -> String {
+    let microseconds = match value.duration_since(UNIX_EPOCH) {
+        Ok(v) => v.as_micros() as i128,
+        Err(e) => -(e.duration().as_micros() as i128),
+    };
+
+    let days = microseconds.div_euclid(MICROSECONDS_PER_DAY);
+    let time = microseconds.rem_euclid(MICROSECONDS_PER_DAY);
+    let (year, month, day) = civil_from_days(days);
+
+    let seconds = time / 1_000_000;
+    let fraction = match time % 1_000_000 {
+        0 => String::new(),
+        v => format!(".{:06}", v),
+    };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        fraction
+    )
+}
+
+/// Converts days since `1970-01-01` into a year, month, and day of the proleptic
+/// Gregorian calendar.
+fn civil_from_days(days: i128) -> (i128, i128, i128) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = match month_index < 10 {
+        true => month_index + 3,
+        false => month_index - 9,
+    };
+
+    match month <= 2 {
+        true => (year_of_era + era * 400 + 1, month, day),
+        false => (year_of_era + era * 400, month, day),
+    }
+}
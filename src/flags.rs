@@ -0,0 +1,81 @@
+//! Support for consuming `bit(n)`, `varbit`, and integer columns into types generated by
+//! the `bitflags!` macro.
+use bitflags::Flags;
+use std::error::Error;
+use tokio_postgres::types::{FromSql, Type};
+
+/// The raw bits of a `bit(n)`, `varbit`, `int2`, `int4`, or `int8` column. The rightmost
+/// bit of a bit string is the least significant, as it is when PostgreSQL casts a bit
+/// string to an integer. Negative integers keep their two's complement bits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FlagBits(pub u64);
+
+impl FlagBits {
+    /// Converts the bits into a flags type, used by the `#[pgde(bitflags)]` field
+    /// attribute of the derive macro. Returns `None` if any bit does not belong to a
+    /// defined flag, unless `truncate` is set, in which case such bits are dropped.
+    pub fn to_flags<F>(self, truncate: bool) -> Option<F>
+    where
+        F: Flags,
+        F::Bits: TryFrom<u64> + Into<u64>,
+    {
+        match truncate {
+            true => {
+                let known: u64 = F::all().bits().into();
+                F::Bits::try_from(self.0 & known)
+                    .ok()
+                    .map(F::from_bits_truncate)
+            }
+            false => F::Bits::try_from(self.0).ok().and_then(F::from_bits),
+        }
+    }
+}
+
+/// Reads the bits of a `bit(n)` or `varbit` value, failing for bit strings with set
+/// bits beyond the 64th.
+fn bit_string_from_sql(raw: &[u8]) -> Result<u64, Box<dyn Error + Sync + Send>> {
+    let length = match raw.get(0..4) {
+        Some(v) => i32::from_be_bytes([v[0], v[1], v[2], v[3]]),
+        None => return Err("bit string is truncated".into()),
+    };
+
+    let length = usize::try_from(length)?;
+    let bytes = &raw[4..];
+
+    if bytes.len() != length.div_ceil(8) {
+        return Err("bit string is truncated".into());
+    }
+
+    let mut value = 0u64;
+
+    for i in 0..length {
+        let bit = bytes[i / 8] >> (7 - i % 8) & 1;
+
+        if value >> 63 != 0 {
+            return Err("bit string does not fit in 64 bits".into());
+        }
+
+        value = value << 1 | bit as u64;
+    }
+
+    Ok(value)
+}
+
+impl<'a> FromSql<'a> for FlagBits {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        match *ty {
+            Type::INT2 => Ok(FlagBits(i16::from_sql(ty, raw)? as u16 as u64)),
+            Type::INT4 => Ok(FlagBits(i32::from_sql(ty, raw)? as u32 as u64)),
+            Type::INT8 => Ok(FlagBits(i64::from_sql(ty, raw)? as u64)),
+            Type::BIT | Type::VARBIT => Ok(FlagBits(bit_string_from_sql(raw)?)),
+            _ => Err("unsupported flags type".into()),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(
+            *ty,
+            Type::INT2 | Type::INT4 | Type::INT8 | Type::BIT | Type::VARBIT
+        )
+    }
+}
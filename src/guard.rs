@@ -0,0 +1,134 @@
+//! Detection of queries repeated within one unit of work, such as `consume` called once
+//! per row of another query, during development.
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+/// A hook called with a query and the number of times it has run in one scope.
+type RepeatHook = Arc<dyn Fn(&str, usize) + Send + Sync>;
+
+/// The query counts of one scope of a [DevGuard].
+struct GuardScope {
+    max_repeats: usize,
+    on_repeat: RepeatHook,
+    counts: Mutex<HashMap<String, usize>>,
+}
+
+tokio::task_local! {
+    static GUARD_SCOPE: Arc<GuardScope>;
+}
+
+/// Counts the queries run by the crate's consumption methods within a scope, e.g. a
+/// request handler, and warns when the same parameterized query runs more than
+/// `max_repeats` times in it, which is typical of an accidental N+1 loop. Queries are
+/// identified by their text with whitespace collapsed, so calls differing only in their
+/// parameters count as repeats. The warning is written to stderr unless `on_repeat` is
+/// set, and is raised once per query and scope.
+///
+/// Queries are only counted in debug builds, so scopes may be left in place in release
+/// builds at no cost beyond a task local. Queries sent directly through a `Client` are
+/// not counted.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::{DevGuard, RowConsumer};
+/// use tokio_postgres::NoTls;
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         let guard = DevGuard::new(10);
+///
+///         guard
+///             .scope(async {
+///                 for id in 0..20 {
+///                     // Warns on the eleventh call
+///                     let _ = i32::consume(&client, "select $1::int;", &[&id]).await;
+///                 }
+///             })
+///             .await;
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+#[derive(Clone)]
+pub struct DevGuard {
+    max_repeats: usize,
+    on_repeat: RepeatHook,
+}
+
+impl DevGuard {
+    /// Creates a guard that warns when a query runs more than `max_repeats` times in one
+    /// scope.
+    pub fn new(max_repeats: usize) -> Self {
+        DevGuard {
+            max_repeats,
+            on_repeat: Arc::new(|query, count| {
+                eprintln!(
+                    "pgde: query ran {} times in one scope, possibly once per row of another query: {}",
+                    count, query
+                )
+            }),
+        }
+    }
+
+    /// Calls `hook` with the query and its count in place of writing to stderr, e.g. to
+    /// log through the application's logger or to fail a test.
+    pub fn on_repeat(mut self, hook: impl Fn(&str, usize) + Send + Sync + 'static) -> Self {
+        self.on_repeat = Arc::new(hook);
+        self
+    }
+
+    /// Runs `f`, counting the queries it runs. A scope nested in `f` counts its queries
+    /// separately.
+    pub async fn scope<F: Future>(&self, f: F) -> F::Output {
+        let scope = GuardScope {
+            max_repeats: self.max_repeats,
+            on_repeat: self.on_repeat.clone(),
+            counts: Mutex::new(HashMap::new()),
+        };
+
+        GUARD_SCOPE.scope(Arc::new(scope), f).await
+    }
+}
+
+impl std::fmt::Debug for DevGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DevGuard")
+            .field("max_repeats", &self.max_repeats)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Counts a query run by a consumption method in the current scope, if any. Does
+/// nothing in release builds.
+pub(crate) fn record(query: &str) {
+    if cfg!(debug_assertions) {
+        let _ = GUARD_SCOPE.try_with(|scope| {
+            let query = query.split_whitespace().collect::<Vec<&str>>().join(" ");
+
+            let count = match scope.counts.lock() {
+                Ok(mut v) => {
+                    let count = v.entry(query.clone()).or_insert(0);
+                    *count += 1;
+                    *count
+                }
+                Err(_) => return,
+            };
+
+            if count == scope.max_repeats + 1 {
+                (scope.on_repeat)(&query, count);
+            }
+        });
+    }
+}
@@ -0,0 +1,104 @@
+//! Lightweight health probes of a database connection.
+use crate::ConsumeError;
+use std::time::{Duration, Instant};
+use tokio_postgres::Client;
+
+/// The result of [check].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HealthReport {
+    /// The round trip time of `select 1`.
+    pub latency: Duration,
+    /// Whether the server is a standby in recovery.
+    pub in_recovery: bool,
+    /// How far replay on a standby lags behind the primary, measured from the last
+    /// replayed transaction. `None` on a primary, or on a standby that has not replayed
+    /// any transaction yet.
+    pub replication_lag: Option<Duration>,
+    /// The number of client connections to the server.
+    pub connections: i64,
+    /// The server's `max_connections` setting.
+    pub max_connections: i64,
+}
+
+impl HealthReport {
+    /// Returns the fraction of `max_connections` in use.
+    pub fn connection_usage(&self) -> f64 {
+        match self.max_connections {
+            0 => 0.0,
+            v => self.connections as f64 / v as f64,
+        }
+    }
+}
+
+/// Probes the connection and server behind `client`, returning
+/// [ConsumeError::DatabaseError] or [ConsumeError::DatabaseConnectionError] if any probe
+/// fails.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::health;
+/// use tokio_postgres::NoTls;
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         match health::check(&client).await {
+///             Ok(v) => println!("Database answered in {:?}", v.latency),
+///             Err(_) => eprintln!("Database is unhealthy"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub async fn check(client: &Client) -> Result<HealthReport, ConsumeError> {
+    let started = Instant::now();
+
+    if let Err(e) = client.simple_query("select 1;").await {
+        return Err(ConsumeError::from(e));
+    }
+
+    let latency = started.elapsed();
+
+    let row = match client
+        .query_one(
+            "select
+                pg_is_in_recovery(),
+                case when pg_is_in_recovery()
+                    then extract(epoch from now() - pg_last_xact_replay_timestamp())::float8
+                end,
+                (select count(*) from pg_stat_activity where backend_type = 'client backend'),
+                current_setting('max_connections')::int8;",
+            &[],
+        )
+        .await
+    {
+        Ok(v) => v,
+        Err(e) => return Err(ConsumeError::from(e)),
+    };
+
+    match (
+        row.try_get::<usize, bool>(0),
+        row.try_get::<usize, Option<f64>>(1),
+        row.try_get::<usize, i64>(2),
+        row.try_get::<usize, i64>(3),
+    ) {
+        (Ok(in_recovery), Ok(lag), Ok(connections), Ok(max_connections)) => Ok(HealthReport {
+            latency,
+            in_recovery,
+            replication_lag: lag.map(|v| Duration::try_from_secs_f64(v).unwrap_or_default()),
+            connections,
+            max_connections,
+        }),
+        _ => Err(ConsumeError::ConversionError),
+    }
+}
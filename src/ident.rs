@@ -0,0 +1,102 @@
+//! Validation and quoting of identifiers, for building queries with dynamic table and
+//! column names without risking SQL injection.
+//!
+//! ## Example
+//! ```
+//! use pgde::ident::QualifiedName;
+//!
+//! match QualifiedName::new(Some("public"), "Foo") {
+//!     Ok(v) => assert_eq!(format!("select * from {};", v), "select * from \"public\".\"Foo\";"),
+//!     Err(_) => eprintln!("Invalid table name"),
+//! };
+//! ```
+use crate::ConsumeError;
+use std::fmt;
+
+/// The most bytes PostgreSQL keeps of an identifier, `NAMEDATALEN - 1`. Longer
+/// identifiers are truncated by the server.
+pub const MAX_IDENT_LENGTH: usize = 63;
+
+/// Quotes an identifier for use in a query, doubling any embedded quotes. The result is
+/// always a single identifier, whatever the input holds.
+pub fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+/// A validated identifier, such as a table, schema, or column name, which is quoted when
+/// displayed.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Ident(String);
+
+impl Ident {
+    /// Validates an unquoted identifier. Identifiers may hold any characters other than
+    /// NUL and must be between 1 and 63 bytes long, so that the server does not truncate
+    /// them.
+    pub fn new(ident: &str) -> Result<Self, ConsumeError> {
+        match (ident.is_empty(), ident.contains('\0'), ident.len()) {
+            (true, _, _) => Err(ConsumeError::InvalidQuery(String::from(
+                "Identifier is empty",
+            ))),
+            (_, true, _) => Err(ConsumeError::InvalidQuery(format!(
+                "Identifier {} contains a NUL character",
+                quote_ident(&ident.replace('\0', ""))
+            ))),
+            (_, _, v) if v > MAX_IDENT_LENGTH => Err(ConsumeError::InvalidQuery(format!(
+                "Identifier {} is longer than {} bytes",
+                quote_ident(ident),
+                MAX_IDENT_LENGTH
+            ))),
+            _ => Ok(Ident(String::from(ident))),
+        }
+    }
+
+    /// Returns the unquoted identifier.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Ident {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", quote_ident(&self.0))
+    }
+}
+
+/// A validated, optionally schema-qualified name, which is quoted when displayed.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct QualifiedName {
+    schema: Option<Ident>,
+    name: Ident,
+}
+
+impl QualifiedName {
+    /// Validates an unquoted schema and name.
+    pub fn new(schema: Option<&str>, name: &str) -> Result<Self, ConsumeError> {
+        Ok(QualifiedName {
+            schema: match schema {
+                Some(v) => Some(Ident::new(v)?),
+                None => None,
+            },
+            name: Ident::new(name)?,
+        })
+    }
+
+    /// Returns the schema, if any.
+    pub fn schema(&self) -> Option<&Ident> {
+        self.schema.as_ref()
+    }
+
+    /// Returns the name.
+    pub fn name(&self) -> &Ident {
+        &self.name
+    }
+}
+
+impl fmt::Display for QualifiedName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.schema {
+            Some(v) => write!(f, "{}.{}", v, self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
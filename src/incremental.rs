@@ -0,0 +1,77 @@
+//! Incremental extraction of the rows past a cursor column's high-water mark, used by
+//! `consume_incremental`.
+use crate::ident::Ident;
+use crate::{check_columns, ConsumeError, RowConsumer};
+use tokio_postgres::types::{FromSql, ToSql};
+use tokio_postgres::Client;
+
+/// The rows of a query past the previous high-water mark of its cursor column, along
+/// with the new high-water mark to pass to the next call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Incremental<T, C> {
+    /// The rows whose cursor is past the previous mark, in cursor order.
+    pub items: Vec<T>,
+    /// The greatest cursor of the rows, or the previous mark when there are none.
+    pub cursor: Option<C>,
+}
+
+/// The implementation of `consume_incremental`, reading the rows past `last_cursor` in
+/// cursor order and taking the new mark from the last of them.
+pub(crate) async fn consume_incremental<T, C>(
+    conn: &Client,
+    query: &str,
+    params: &[&(dyn ToSql + Sync)],
+    cursor_column: &str,
+    last_cursor: Option<C>,
+) -> Result<Incremental<T, C>, ConsumeError>
+where
+    T: RowConsumer,
+    C: ToSql + for<'a> FromSql<'a> + Sync,
+{
+    let query = query.trim_end().trim_end_matches(';');
+    let column = Ident::new(cursor_column)?;
+    let mut params = params.to_vec();
+
+    let incremental_query = match &last_cursor {
+        Some(v) => {
+            params.push(v);
+
+            format!(
+                "select * from ({}) as pgde_incremental where {} > ${} order by {}",
+                query,
+                column,
+                params.len(),
+                column
+            )
+        }
+        None => format!(
+            "select * from ({}) as pgde_incremental where {} is not null order by {}",
+            query, column, column
+        ),
+    };
+
+    #[cfg(feature = "dev_guard")]
+    crate::guard::record(query);
+
+    let rows = match conn.query(&incremental_query, &params).await {
+        Ok(v) => v,
+        Err(e) => return Err(ConsumeError::from(e)),
+    };
+
+    if let Some(row) = rows.first() {
+        check_columns::<T>(row.columns())?;
+    }
+
+    let cursor = match rows.last() {
+        Some(row) => match row.try_get::<&str, C>(cursor_column) {
+            Ok(v) => Some(v),
+            Err(_) => return Err(ConsumeError::ConversionError),
+        },
+        None => last_cursor,
+    };
+
+    match T::from_rows(rows) {
+        Ok(items) => Ok(Incremental { items, cursor }),
+        Err(_) => Err(ConsumeError::ConversionError),
+    }
+}
@@ -0,0 +1,152 @@
+//! Support for consuming the `infinity` and `-infinity` values of `timestamp`,
+//! `timestamptz`, and `date` columns, which date time types cannot represent.
+use std::error::Error;
+use tokio_postgres::types::{FromSql, Type};
+
+/// A `timestamp`, `timestamptz`, or `date` value that may be `infinity` or `-infinity`,
+/// read into a date time type that reads the column otherwise. Infinite values, such as
+/// the open bounds of validity periods in archival tables, would otherwise be
+/// conversion errors.
+///
+/// ## Example
+/// ```
+/// use pgde::TimestampSpecial;
+/// use std::time::SystemTime;
+///
+/// let value = TimestampSpecial::<SystemTime>::Infinity;
+///
+/// assert_eq!(value.finite(), None);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TimestampSpecial<T> {
+    /// `-infinity`, earlier than every other value.
+    NegativeInfinity,
+    /// A finite value.
+    Finite(T),
+    /// `infinity`, later than every other value.
+    Infinity,
+}
+
+impl<T> TimestampSpecial<T> {
+    /// Returns the value if it is finite.
+    pub fn finite(self) -> Option<T> {
+        match self {
+            TimestampSpecial::Finite(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the value, mapping `-infinity` and `infinity` to the earliest and latest
+    /// values of the type.
+    pub fn clamped(self) -> T
+    where
+        T: TimestampBounds,
+    {
+        match self {
+            TimestampSpecial::NegativeInfinity => T::min_value(),
+            TimestampSpecial::Finite(v) => v,
+            TimestampSpecial::Infinity => T::max_value(),
+        }
+    }
+}
+
+impl<T: Default> Default for TimestampSpecial<T> {
+    fn default() -> Self {
+        TimestampSpecial::Finite(T::default())
+    }
+}
+
+impl<'a, T: FromSql<'a>> FromSql<'a> for TimestampSpecial<T> {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        let (negative_infinity, infinity) = match *ty {
+            Type::DATE => (&i32::MIN.to_be_bytes()[..], &i32::MAX.to_be_bytes()[..]),
+            _ => (&i64::MIN.to_be_bytes()[..], &i64::MAX.to_be_bytes()[..]),
+        };
+
+        match raw {
+            v if v == negative_infinity => Ok(TimestampSpecial::NegativeInfinity),
+            v if v == infinity => Ok(TimestampSpecial::Infinity),
+            _ => Ok(TimestampSpecial::Finite(T::from_sql(ty, raw)?)),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(*ty, Type::TIMESTAMP | Type::TIMESTAMPTZ | Type::DATE) && T::accepts(ty)
+    }
+}
+
+/// The earliest and latest values of a date time type, which `-infinity` and `infinity`
+/// are mapped to by [TimestampSpecial::clamped].
+pub trait TimestampBounds {
+    /// The earliest value of the type.
+    fn min_value() -> Self;
+
+    /// The latest value of the type.
+    fn max_value() -> Self;
+}
+
+#[cfg(feature = "chrono")]
+impl TimestampBounds for chrono::NaiveDateTime {
+    fn min_value() -> Self {
+        chrono::NaiveDateTime::MIN
+    }
+
+    fn max_value() -> Self {
+        chrono::NaiveDateTime::MAX
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TimestampBounds for chrono::DateTime<chrono::Utc> {
+    fn min_value() -> Self {
+        chrono::DateTime::<chrono::Utc>::MIN_UTC
+    }
+
+    fn max_value() -> Self {
+        chrono::DateTime::<chrono::Utc>::MAX_UTC
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TimestampBounds for chrono::NaiveDate {
+    fn min_value() -> Self {
+        chrono::NaiveDate::MIN
+    }
+
+    fn max_value() -> Self {
+        chrono::NaiveDate::MAX
+    }
+}
+
+#[cfg(feature = "time")]
+impl TimestampBounds for time::PrimitiveDateTime {
+    fn min_value() -> Self {
+        time::PrimitiveDateTime::MIN
+    }
+
+    fn max_value() -> Self {
+        time::PrimitiveDateTime::MAX
+    }
+}
+
+#[cfg(feature = "time")]
+impl TimestampBounds for time::OffsetDateTime {
+    fn min_value() -> Self {
+        time::PrimitiveDateTime::MIN.assume_utc()
+    }
+
+    fn max_value() -> Self {
+        time::PrimitiveDateTime::MAX.assume_utc()
+    }
+}
+
+#[cfg(feature = "time")]
+impl TimestampBounds for time::Date {
+    fn min_value() -> Self {
+        time::Date::MIN
+    }
+
+    fn max_value() -> Self {
+        time::Date::MAX
+    }
+}
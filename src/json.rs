@@ -0,0 +1,209 @@
+//! Formatting options for the JSON output of `consume_json_with`.
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::sync::Arc;
+
+/// A naming convention for the keys of rows serialized by `consume_json_with`, like
+/// serde's `rename_all`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenameRule {
+    /// `lowercase`
+    Lower,
+    /// `UPPERCASE`
+    Upper,
+    /// `PascalCase`
+    Pascal,
+    /// `camelCase`
+    Camel,
+    /// `snake_case`
+    Snake,
+    /// `SCREAMING_SNAKE_CASE`
+    ScreamingSnake,
+    /// `kebab-case`
+    Kebab,
+}
+
+impl RenameRule {
+    /// Renames a field name, splitting it into words at underscores, hyphens, and the
+    /// start of capitalized words.
+    pub fn apply(&self, name: &str) -> String {
+        let words = words(name);
+
+        let capitalize = |v: &String| {
+            let mut chars = v.chars();
+
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        };
+
+        match self {
+            RenameRule::Lower => words.concat(),
+            RenameRule::Upper => words.concat().to_uppercase(),
+            RenameRule::Pascal => words.iter().map(capitalize).collect(),
+            RenameRule::Camel => words
+                .iter()
+                .enumerate()
+                .map(|(i, v)| match i {
+                    0 => v.clone(),
+                    _ => capitalize(v),
+                })
+                .collect(),
+            RenameRule::Snake => words.join("_"),
+            RenameRule::ScreamingSnake => words.join("_").to_uppercase(),
+            RenameRule::Kebab => words.join("-"),
+        }
+    }
+}
+
+/// Splits a field name into lowercase words.
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut previous: Option<char> = None;
+
+    for c in name.chars() {
+        let boundary = match previous {
+            Some(p) => c.is_uppercase() && (p.is_lowercase() || p.is_numeric()),
+            None => false,
+        };
+
+        if (c == '_' || c == '-' || boundary) && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+
+        if c != '_' && c != '-' {
+            word.extend(c.to_lowercase());
+        }
+
+        previous = Some(c);
+    }
+
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    words
+}
+
+/// A callback replacing the value of a field before it is written, set with
+/// `JsonOptions::transform`.
+#[derive(Clone)]
+pub struct FieldTransform {
+    field: String,
+    hook: Arc<dyn Fn(Value) -> Value + Send + Sync>,
+}
+
+impl std::fmt::Debug for FieldTransform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FieldTransform")
+            .field("field", &self.field)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for FieldTransform {
+    fn eq(&self, other: &Self) -> bool {
+        self.field == other.field && Arc::ptr_eq(&self.hook, &other.hook)
+    }
+}
+
+impl Eq for FieldTransform {}
+
+/// Options for the JSON output of `consume_json_with`. The default matches
+/// `consume_json`. Rows whose fields are renamed, transformed, or left out are written
+/// with their fields in alphabetical order.
+///
+/// ## Example
+/// ```
+/// use pgde::{JsonOptions, RenameRule};
+/// use serde_json::Value;
+///
+/// let options = JsonOptions {
+///     pretty: true,
+///     rename_all: Some(RenameRule::Camel),
+///     ..Default::default()
+/// }
+/// .transform("email", |_| Value::from("[masked]"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JsonOptions {
+    /// Indents the output.
+    pub pretty: bool,
+    /// Writes fields without a value, e.g. `None`, as `null`. When `false`, such fields are
+    /// left out of their row.
+    pub null_for_missing: bool,
+    /// Renames the fields of every row.
+    pub rename_all: Option<RenameRule>,
+    /// Replaces the values of fields, added with `transform`.
+    pub transforms: Vec<FieldTransform>,
+}
+
+impl JsonOptions {
+    /// Replaces the value of `field` in every row with the result of `transform`, e.g. to
+    /// mask production data exported to a staging environment. `field` is the name of
+    /// the field before `rename_all` applies, and a transform returning `null` leaves the
+    /// field out when `null_for_missing` is `false`. Transforms of the same field apply in
+    /// the order they were added.
+    pub fn transform(
+        mut self,
+        field: &str,
+        transform: impl Fn(Value) -> Value + Send + Sync + 'static,
+    ) -> Self {
+        self.transforms.push(FieldTransform {
+            field: String::from(field),
+            hook: Arc::new(transform),
+        });
+        self
+    }
+}
+
+impl Default for JsonOptions {
+    fn default() -> Self {
+        JsonOptions {
+            pretty: false,
+            null_for_missing: true,
+            rename_all: None,
+            transforms: Vec::new(),
+        }
+    }
+}
+
+/// Serializes consumed rows according to `options`.
+pub(crate) fn to_json<T: Serialize>(rows: &[T], options: &JsonOptions) -> Option<String> {
+    if options.null_for_missing && options.rename_all.is_none() && options.transforms.is_empty() {
+        return match options.pretty {
+            true => serde_json::to_string_pretty(rows).ok(),
+            false => serde_json::to_string(rows).ok(),
+        };
+    }
+
+    let mut value = serde_json::to_value(rows).ok()?;
+
+    if let Value::Array(rows) = &mut value {
+        for row in rows.iter_mut() {
+            if let Value::Object(fields) = row {
+                for transform in options.transforms.iter() {
+                    if let Some(v) = fields.get_mut(&transform.field) {
+                        *v = (transform.hook)(v.take());
+                    }
+                }
+
+                *fields = std::mem::take(fields)
+                    .into_iter()
+                    .filter(|(_, v)| options.null_for_missing || !v.is_null())
+                    .map(|(k, v)| match options.rename_all {
+                        Some(rule) => (rule.apply(&k), v),
+                        None => (k, v),
+                    })
+                    .collect::<Map<String, Value>>();
+            }
+        }
+    }
+
+    match options.pretty {
+        true => serde_json::to_string_pretty(&value).ok(),
+        false => serde_json::to_string(&value).ok(),
+    }
+}
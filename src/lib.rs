@@ -4,7 +4,18 @@
 //! - `from_row`
 //! - `from_rows`
 //! - `consume`
-//! - `consume_json` if feature `consume_json` is enabled
+//! - `consume_with_options`
+//! - `consume_filtered`
+//! - `consume_where_in`
+//! - `consume_where_any`
+//! - `consume_page`
+//! - `consume_incremental`
+//! - `consume_map_by_key`
+//! - `consume_dedup_by_key` on classes with `#[pgde(key)]` fields
+//! - `consume_changed_since` on classes marked `#[pgde(row_hash)]`
+//! - `consume_index_map_by_key` and `consume_index_set_by_key` if feature `indexmap` is enabled
+//! - `consume_json` and `consume_json_with` if feature `consume_json` is enabled
+//! - `from_row_ref` and `from_rows_ref` of `RowRefConsumer`, which read rows by reference, on classes that derive `RowConsumer` and on the types implemented by this crate
 //!
 //! The latter implementations are built from `from_row`.
 //!
@@ -13,14 +24,31 @@
 //!
 //! | Feature | Description | Extra dependencies | Default |
 //! | ------- | ----------- | ------------------ | ------- |
+//! | `arrayvec` | Implements crate on `arrayvec::ArrayVec` | arrayvec | No |
 //! | `bit` | Implements crate on `bit_vec::BitVec` | bit-vec | No |
+//! | `bitflags` | Implements crate on types generated by `bitflags!` through the `bitflags` field attribute | bitflags | No |
 //! | `chrono` | Implements crate on types supplied by chrono | chrono | No |
-//! | `consume_json` | Implements `consume_json` on classes that derive the `RowConsumer` trait | serde, serde_json | No |
+//! | `consume_json` | Implements `consume_json` and `consume_json_with` on classes that derive the `RowConsumer` trait, and the `export` formatting helpers | serde, serde_json | No |
+//! | `dev_guard` | Implements `DevGuard`, which warns when a query repeats within a scope in debug builds | tokio | No |
+//! | `election` | Implements `election::Leader`, which elects a leader among sessions contending for an advisory lock | tokio | No |
+//! | `explain` | Implements `consume_explain` for consuming query plans | serde, serde_json | No |
+//! | `fake` | Implements `FakeRow` on structs that derive it, for generating deterministic rows of fake values | None | No |
 //! | `geo` | Implements crate on `geo_types::Point<f64>`, `geo_types::Rect<f64>`, and `geo_types::LineString<f64>` | geo-types | No |
+//! | `indexmap` | Implements `consume_index_map_by_key` and `consume_index_set_by_key`, which keep the order of a query's rows | indexmap | No |
 //! | `mac` | Implements crate on `eui48::MacAddress` | eui48 | No |
 //! | `json` | Implements crate on `serde_json::Value` | serde_json | No |
+//! | `queue` | Implements `queue::Worker`, which consumes jobs claimed with `FOR UPDATE SKIP LOCKED` | tokio | No |
+//! | `retry` | Implements `transactions::with_retry`, which retries transactions failing with serialization failures or deadlocks | tokio | No |
+//! | `seed` | Implements `seed::load`, which copies records parsed from JSON or CSV fixture files into a table | csv, serde, serde_json | No |
+//! | `smallvec` | Implements crate on `smallvec::SmallVec` | smallvec | No |
+//! | `summary` | Implements `Summary` on structs marked `#[pgde(summary)]`, for logging rows with sensitive fields redacted | None | No |
+//! | `supervisor` | Implements `client::SupervisedClient`, which reconnects when its connection ends | tokio | No |
+//! | `testing` | Implements `testing::assert_consumes_n` and `testing::assert_roundtrip`, which assert the results of queries in tests, and `testing::TestDb`, which isolates the tables of a test in a schema | None | No |
+//! | `throttle` | Implements `client::ThrottledClient`, which limits concurrent and per-second calls | tokio | No |
 //! | `time` | Implements crate on types supplied by time | time | No |
 //! | `uuid` | Implements crate on `uuid::Uuid` | uuid | No |
+//! | `watcher` | Implements `Watcher` and `RefCache`, which keep the latest results of a query, and `DriftWatchdog`, which re-validates queries in the background | arc-swap, tokio | No |
+//! | `web` | Implements `web::PgdeJson`, an axum response that runs `consume_json`. Enables `consume_json` | axum-core, http, serde, serde_json | No |
 //!
 //! ## Examples
 //! You may use `consume` to consume PostgreSQL row data into a struct like so.
@@ -61,6 +89,8 @@
 //!             Err(v) => match v {
 //!                 ConsumeError::ConversionError => eprintln!("Could not convert data"),
 //!                 ConsumeError::DatabaseConnectionError => eprintln!("Database errored on processing the query"),
+//!                 ConsumeError::DatabaseError { code, message } => eprintln!("Database rejected the query with {}: {}", code, message),
+//!                 _ => eprintln!("Could not consume data"),
 //!             },
 //!         };
 //!     },
@@ -71,6 +101,18 @@
 //!
 //! Types of `Vec<T>` and `Option<T>`, where `T` implements `FromSql`, are also supported on structs, or as standalone consuming types, that derive `RowConsumer`. When querying nullable fields, it is best to wrap field types in an `Option<>`. See the `RowConsumer` trait for use examples of `from_row` and `from_rows`.
 //!
+//! ## Overview
+//! Beyond consuming rows, the crate provides the following, each documented on its items.
+//! - Deriving: `RowConsumer` on structs and enums, the `row_consumer` attribute macro, `TextEnum` for enums stored as text, `QueryParams` for search filters, `FakeRow` for fixtures, and `Summary` and `RowHash` for structs marked `#[pgde(summary)]` and `#[pgde(row_hash)]`
+//! - Queries: `Table` and its `SelectBuilder`, `consume_page`, `consume_scalar`, `consume_column`, `query_exists`, `execute`, and `ConsumeOptions` for per-call error policies, limits, labels, and hooks
+//! - Errors: `ConsumeError`, whose `classification` returns an `ErrorClass`, and the `ConversionReport` of `ErrorPolicy::Report`
+//! - Schema checks: `verify_query`, `check_params`, `MappingSignature`, and, with feature `watcher`, `DriftWatchdog`
+//! - Bulk data: `CopyConsumer`, `dump_inserts`, `mirror_table`, `TempTable`, `diff`, `summarize`, and the `export` and `seed` modules
+//! - Clients and sessions: the `client` and `health` modules, `SessionSettings`, `with_schema`, and `deallocate_all`
+//! - Coordination: the `transactions`, `locks`, `election`, `migrate`, `outbox`, and `queue` modules
+//! - Caching: `Watcher` and `RefCache`, with feature `watcher`
+//! - Development: `DevGuard`, `consume_explain`, and the `testing`, `ident`, and `pg` modules
+//!
 //! This crate also provides implementations on a variety of data types, some provided by enabling features.
 //!
 //! | Type | Feature |
@@ -87,6 +129,8 @@
 //! | `String` | `default` |
 //! | `SystemTime` | `default` |
 //! | `IpAddr` | `default` |
+//! | `RawValue` | `default` |
+//! | `Duration` | `default` |
 //! | `bit_vec::BitVec` | `bit` |
 //! | `chrono::NaiveDateTime` | `chrono` |
 //! | `chrono::DateTime<Utc>` | `chrono` |
@@ -94,6 +138,7 @@
 //! | `chrono::DateTime<FixedOffset>` | `chrono` |
 //! | `chrono::NaiveDate` | `chrono` |
 //! | `chrono::NaiveTime` | `chrono` |
+//! | `chrono::Duration` | `chrono` |
 //! | `geo_types::Point<f64>` | `geo` |
 //! | `geo_types::Rect<f64>` | `geo` |
 //! | `geo_types::LineString<f64>` | `geo` |
@@ -103,7 +148,33 @@
 //! | `time::OffsetDateTime` | `time` |
 //! | `time::Date` | `time` |
 //! | `time::Time` | `time` |
+//! | `time::Duration` | `time` |
 //! | `uuid::Uuid` | `uuid` |
+//! | `arrayvec::ArrayVec<T, N>` | `arrayvec` |
+//! | `smallvec::SmallVec<[T; N]>` | `smallvec` |
+//!
+//! ## Field attributes
+//! Fields of structs that derive `RowConsumer` may be annotated with `#[pgde(...)]` to change how their column is read.
+//!
+//! | Attribute | Description |
+//! | --------- | ----------- |
+//! | `interval` | Reads an `interval` column into a duration type, or an `Option` of one |
+//! | `seconds` | Reads a `float4`, `float8`, `int2`, `int4`, `int8`, or `numeric` number of seconds into a duration type, or an `Option` of one |
+//! | `array` | Reads a one dimensional array column into a collection implementing `ArrayCollection`, such as `SmallVec` or `ArrayVec` |
+//! | `bitflags` | Reads a `bit(n)`, `varbit`, `int2`, `int4`, or `int8` column into a type generated by `bitflags!`, failing on unknown bits. Use `bitflags(truncate)` to drop unknown bits instead. Requires feature `bitflags` |
+//! | `narrow` | Reads an `int2`, `int4`, or `int8` column into a narrower integer, or a `float8` column into `f32`, failing for values out of range |
+//! | `widen` | Reads an `int2` or `int4` column into a wider integer, or an integer or `float4` column into `f64` |
+//! | `numeric` | Reads a `numeric` column into `f32` or `f64`, or an `Option` of one, failing for values the float cannot hold exactly, such as `0.1`. Use `numeric(round)` to convert to the nearest float, or `numeric(truncate)` to convert toward zero. `NaN` and the infinities are kept |
+//! | `coerce` | Reads a `bool` field from a `bool` column, an integer column holding `0` or `1`, or a text column holding `t`, `f`, `true`, or `false`, for schemas that store booleans as `smallint` or `char(1)` |
+//! | `trim` | Reads a text column into a `String`, or an `Option` of one, trimming surrounding whitespace such as the padding of `char(n)` values. May be combined with `empty_as_none` |
+//! | `empty_as_none` | Reads a text column into an `Option<String>`, treating empty strings as NULL. On a `String` field, empty strings are conversion errors |
+//! | `assume_utc` | Reads a `timestamp` column, taken to hold UTC, into `chrono::DateTime<Utc>` or `time::OffsetDateTime`, or an `Option` of one. Requires feature `chrono` or `time` |
+//! | `to_utc` | Reads a `timestamptz` column, normalized to UTC, into `chrono::NaiveDateTime` or `time::PrimitiveDateTime`, or an `Option` of one. Requires feature `chrono` or `time` |
+//! | `as_text` | Reads the column as text and parses it into the field's type, or the type within an `Option`, with `FromStr`. Text types, integers, floats, `numeric`, `money`, `bool`, and `uuid` are read as text, and other types may be cast to `text` in the query |
+//! | `nan(policy)` | Decides how a `NaN` read into an `f32` or `f64` field, or an `Option` of one, is consumed: `nan(accept)` keeps it, as fields without the attribute do, `nan(none)` reads it as `None`, and `nan(error)` fails. With `nan(none)`, a `NaN` read into a field that is not an `Option` fails. May be combined with any other attribute |
+//! | `null_as = "expr"` | Evaluates to `expr` when the column is NULL, e.g. `null_as = "-1"` or `null_as = "String::new()"`, rather than recording a conversion error. Not supported on `Option` fields, and may be combined with any other attribute |
+//! | `sensitive` | Writes the field as `[redacted]` in the summaries of structs marked `#[pgde(summary)]`, so that logged rows do not leak personal data. Does not change how the column is read |
+//! | `key` | Implements `KeyedBy` on the struct, keyed by the field, or by a tuple of every field marked `key` in order, for `consume_dedup_by_key` and `Diff::between`. Does not change how the column is read |
 //!
 //! ## Testing
 //! Testing requires access to a PostgreSQL database with no tables. Setting the following environment variables will allow you to test.
@@ -114,6 +185,154 @@
 //! | `POSTGRES_USER` | The user credential to provide. |
 //! | `POSTGRES_PASSWORD` | The password to provide. |
 //! | `POSTGRES_DB` | The name of the database to use for testing. |
+//!
+//! The parsers of column values and `COPY` output may also be fuzzed with [cargo-fuzz](https://github.com/rust-fuzz/cargo-fuzz) from a nightly toolchain, e.g. `cargo +nightly fuzz run decoders` or `cargo +nightly fuzz run copy_output`. No input should cause a panic.
+mod array;
+mod borrowed;
+#[cfg(feature = "watcher")]
+mod cache;
+pub mod client;
+mod coerce;
+mod copy;
+mod dedup;
+mod diff;
+mod drift;
+mod dump;
+mod duration;
+#[cfg(feature = "election")]
+pub mod election;
+#[cfg(feature = "explain")]
+mod explain;
+#[cfg(feature = "consume_json")]
+pub mod export;
+#[cfg(feature = "fake")]
+mod fake;
+#[cfg(feature = "bitflags")]
+mod flags;
+#[cfg(feature = "dev_guard")]
+mod guard;
+pub mod health;
+pub mod ident;
+mod incremental;
+mod infinity;
+#[cfg(feature = "consume_json")]
+mod json;
+pub mod locks;
+pub mod migrate;
+mod mirror;
+mod nan;
+mod number;
+mod numeric;
+mod options;
+pub mod outbox;
+mod page;
+mod params;
+pub mod pg;
+#[cfg(feature = "queue")]
+pub mod queue;
+mod raw;
+mod remote;
+mod report;
+mod row_hash;
+mod scalar;
+#[cfg(feature = "seed")]
+pub mod seed;
+mod select;
+mod session;
+mod signature;
+mod sqlstate;
+mod stats;
+#[cfg(feature = "summary")]
+mod summary;
+#[cfg(feature = "supervisor")]
+mod supervised;
+mod table;
+mod temp;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod text;
+mod text_enum;
+#[cfg(feature = "throttle")]
+mod throttled;
+pub mod transactions;
+mod utc;
+#[cfg(feature = "watcher")]
+mod watcher;
+#[cfg(feature = "web")]
+pub mod web;
+mod where_in;
+
+pub use array::{ArrayCollection, ArrayColumn};
+pub use borrowed::BorrowedRowConsumer;
+#[cfg(feature = "watcher")]
+pub use cache::RefCache;
+pub use coerce::{CoerceColumn, CoercedBool};
+pub use copy::{CopyConsumer, CopyFormat, CopyOptions};
+pub use dedup::KeepDuplicate;
+pub use diff::{diff, Diff, KeyedBy};
+pub use drift::{check_params, verify_query, ParamMismatch};
+#[cfg(feature = "watcher")]
+pub use drift::{Drift, DriftWatchdog};
+pub use dump::dump_inserts;
+pub use duration::{DurationColumn, Interval, Seconds};
+#[cfg(feature = "explain")]
+pub use explain::{consume_explain, ExplainOptions, ExplainPlan, PlanNode};
+#[cfg(feature = "fake")]
+pub use fake::{FakeField, FakeRng, FakeRow, FakeValue};
+#[cfg(feature = "bitflags")]
+pub use flags::FlagBits;
+#[cfg(feature = "dev_guard")]
+pub use guard::DevGuard;
+pub use incremental::Incremental;
+pub use infinity::{TimestampBounds, TimestampSpecial};
+#[cfg(feature = "consume_json")]
+pub use json::{FieldTransform, JsonOptions, RenameRule};
+pub use mirror::{mirror_table, MirrorOptions};
+pub use nan::{NanColumn, NanPolicy};
+pub use number::{Number, NumericColumn};
+pub use numeric::{FloatColumn, Numeric, PrecisionPolicy};
+pub use options::{BadRowPolicy, ConsumeOptions, ErrorPolicy, FailedQuery, FilteredRows};
+pub use page::{Page, PagedResult};
+pub use params::{Filter, QueryParams};
+pub use raw::RawValue;
+pub use remote::RemoteColumn;
+pub use report::{ConversionReport, FieldFailure, FieldFailures};
+pub use row_hash::RowHash;
+pub use scalar::{consume_column, consume_scalar, execute, query_exists};
+pub use select::SelectBuilder;
+pub use session::{deallocate_all, with_schema, SessionSettings};
+pub use signature::MappingSignature;
+pub use sqlstate::ErrorClass;
+pub use stats::{summarize, ColumnStats, ResultSummary, StatValue};
+#[cfg(feature = "summary")]
+pub use summary::Summary;
+pub use table::{NoColumns, SortOrder, Table, TableColumn};
+pub use temp::TempTable;
+pub use text::{TextColumn, TextValue};
+pub use text_enum::TextEnum;
+pub use utc::{UtcColumn, UtcTimestamp};
+#[cfg(feature = "watcher")]
+pub use watcher::Watcher;
+
+/// Items used by code generated by the pgde_derive crate and by the fuzz targets. Not
+/// part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::check_first_column;
+    pub use crate::copy::{parse_copy_bool, parse_copy_output};
+    pub use crate::raw::NullCheck;
+    pub use crate::report::classify_failure;
+    pub use crate::row_hash::RowHasher;
+    #[cfg(feature = "summary")]
+    pub use crate::summary::{format_summary, summary_value};
+    pub use crate::text_enum::{text_enum_accepts, text_enum_from_sql};
+    pub use tokio_postgres::types::private::BytesMut;
+    pub use tokio_postgres::types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
+    pub use tokio_postgres::Column;
+}
+
+#[cfg(feature = "arrayvec")]
+use arrayvec::ArrayVec;
 #[cfg(feature = "bit")]
 use bit_vec::BitVec;
 #[cfg(feature = "chrono")]
@@ -130,28 +349,204 @@ use geo_types::LineString;
 use geo_types::Point;
 #[cfg(feature = "geo")]
 use geo_types::Rect;
+#[cfg(feature = "indexmap")]
+use indexmap::{IndexMap, IndexSet};
 #[cfg(feature = "consume_json")]
 use serde::Serialize;
+#[cfg(feature = "smallvec")]
+use smallvec::SmallVec;
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
+use std::hash::Hash;
 use std::net::IpAddr;
 use std::net::Ipv4Addr;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 #[cfg(feature = "time")]
 use time::{Date, OffsetDateTime, PrimitiveDateTime, Time};
 use tokio_postgres::row::Row;
-use tokio_postgres::types::ToSql;
-use tokio_postgres::Client;
+use tokio_postgres::types::{FromSql, ToSql};
+use tokio_postgres::{Client, Column};
 #[cfg(feature = "uuid")]
 use uuid::Uuid;
 
-/// Errors that may occur during row consumption.
+/// Errors that may occur during row consumption. New variants may be added in minor
+/// releases, so matches should end with a wildcard arm.
+#[non_exhaustive]
 pub enum ConsumeError {
     ConversionError,
+    /// The connection to the database failed, without the database reporting an error.
     DatabaseConnectionError,
+    /// The database rejected a query, reporting the given SQLSTATE code and message.
+    /// See `classification` to branch on common codes.
+    DatabaseError {
+        code: String,
+        message: String,
+    },
+    /// The conversion errors of every row that failed conversion, reported when
+    /// consuming with [ErrorPolicy::CollectAll].
+    ConversionErrors(Vec<String>),
+    /// The conversion errors of every row that failed conversion, along with the number
+    /// of failures of every field, reported when consuming with [ErrorPolicy::Report].
+    ConversionReport(ConversionReport),
+    /// A query could not be built from the provided input, e.g. an unknown column.
+    InvalidQuery(String),
+    /// A query expected to return exactly one row returned the given number of rows.
+    UnexpectedRowCount(usize),
+    /// Output could not be written, e.g. by `dump_inserts`.
+    WriteError(String),
+    /// Input could not be read, e.g. a fixture file by `seed::load`.
+    ReadError(String),
+    /// Rows held a different number of columns than the consuming type reads, e.g. after
+    /// a column was added to or dropped from a table queried with `select *`.
+    ColumnCountMismatch {
+        expected: usize,
+        actual: usize,
+    },
+    /// Rows held more than one column with each of the given names, which the consuming
+    /// type reads by name, e.g. the `id` columns of a join.
+    AmbiguousColumns(Vec<String>),
+    /// The columns of a query could not be read as the types of the consuming type, found
+    /// before any row is converted when consuming with `ConsumeOptions::check_types`.
+    ColumnTypeMismatch(Vec<String>),
+    /// The mapping signature of the named type differs from the one stored in the
+    /// database, or none is stored, found by `MappingSignature::verify`.
+    SignatureMismatch {
+        name: String,
+        stored: Option<u64>,
+        actual: u64,
+    },
+    /// The circuit of a `client::BreakerClient` is open after too many calls failed, so
+    /// the call was not sent to the database.
+    CircuitOpen,
+}
+
+impl ConsumeError {
+    /// Returns the category of the error, derived from the SQLSTATE of a
+    /// [ConsumeError::DatabaseError]. [ConsumeError::DatabaseConnectionError] is
+    /// [ErrorClass::ConnectionLost], and errors raised by the crate rather than the
+    /// database are [ErrorClass::Other].
+    pub fn classification(&self) -> ErrorClass {
+        match self {
+            ConsumeError::DatabaseError { code, .. } => ErrorClass::from_code(code),
+            ConsumeError::DatabaseConnectionError => ErrorClass::ConnectionLost,
+            _ => ErrorClass::Other,
+        }
+    }
+}
+
+impl From<tokio_postgres::Error> for ConsumeError {
+    fn from(value: tokio_postgres::Error) -> Self {
+        match value.as_db_error() {
+            Some(v) => ConsumeError::DatabaseError {
+                code: String::from(v.code().code()),
+                message: String::from(v.message()),
+            },
+            None => ConsumeError::DatabaseConnectionError,
+        }
+    }
+}
+
+/// Checks that the first column of a query can be read as `T`, returning a message if it
+/// cannot. Used by the macros implementing `RowConsumer` on types read from one column.
+#[doc(hidden)]
+pub fn check_first_column<'a, T: FromSql<'a>>(columns: &[Column], class: &str) -> Vec<String> {
+    match columns.first() {
+        Some(v) if !T::accepts(v.type_()) => vec![format!(
+            "Column 0 \"{}\" of type {} cannot be read into class \"{}\"",
+            v.name(),
+            v.type_(),
+            class
+        )],
+        _ => Vec::new(),
+    }
+}
+
+/// Checks that the columns of a row hold the number of columns that `T` reads, if `T`
+/// declares one, and that every column `T` reads by name is unambiguous.
+pub(crate) fn check_columns<T: RowConsumer>(columns: &[Column]) -> Result<(), ConsumeError> {
+    if let Some(expected) = T::COLUMN_COUNT {
+        if expected != columns.len() {
+            return Err(ConsumeError::ColumnCountMismatch {
+                expected,
+                actual: columns.len(),
+            });
+        }
+    }
+
+    if let Some(names) = T::NAMED_COLUMNS {
+        let ambiguous: Vec<String> = names
+            .iter()
+            .filter(|v| columns.iter().filter(|c| c.name() == **v).count() > 1)
+            .map(|v| String::from(*v))
+            .collect();
+
+        if !ambiguous.is_empty() {
+            return Err(ConsumeError::AmbiguousColumns(ambiguous));
+        }
+    }
+
+    Ok(())
+}
+
+/// The error of the `TryFrom<Row>` implementation derived alongside `RowConsumer`,
+/// holding the field and class information of every field that failed conversion. A
+/// blanket implementation over every `RowConsumer` is not possible, since both `TryFrom`
+/// and `Row` are defined outside of this crate. Structs that implement `TryFrom<Row>`
+/// themselves may skip the derived implementations with `#[pgde(skip_try_from)]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RowError(pub Vec<String>);
+
+impl std::fmt::Display for RowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.join("; "))
+    }
 }
 
+impl std::error::Error for RowError {}
+
 /// The derivable trait for implementing PostgreSQL row consumption.
 pub trait RowConsumer {
+    /// The number of columns read by position from every row, declared by the derive
+    /// macro for structs. Consuming a query whose rows hold a different number of columns
+    /// fails with [ConsumeError::ColumnCountMismatch] rather than misreading them. The
+    /// derived `from_row` only records an error for rows with too few columns, so
+    /// that callers may read additional columns themselves.
+    const COLUMN_COUNT: Option<usize> = None;
+
+    /// The columns read by name, declared by the derive macro for tagged enums and by
+    /// `row_consumer(partial)` structs. Consuming a query that returns more than one
+    /// column with any of these names fails with [ConsumeError::AmbiguousColumns] rather
+    /// than reading whichever comes first.
+    const NAMED_COLUMNS: Option<&'static [&'static str]> = None;
+
+    /// Checks the types of a query's columns against the types they are read as,
+    /// returning a message for every column that cannot be read. Implemented by the
+    /// derive macro for structs and used once per query, rather than once per row, when
+    /// consuming with `ConsumeOptions::check_types`.
+    fn check_column_types(columns: &[Column]) -> Vec<String> {
+        let _ = columns;
+        Vec::new()
+    }
+
+    /// Classifies why the fields of a row that failed conversion could not be read,
+    /// returning the name and [FieldFailure] of every such field. Implemented by the
+    /// derive macro for structs and used for rows that fail conversion when consuming
+    /// with [ErrorPolicy::Report]. Other types report no fields.
+    fn field_failures(row: &Row) -> Vec<(&'static str, FieldFailure)> {
+        let _ = row;
+        Vec::new()
+    }
+
+    /// The signature of the mapping between the implementing type and the columns it
+    /// reads, implemented by the derive macro for structs, whose signature covers the
+    /// name, type, and order of every field. Other types have a signature without fields.
+    fn mapping_signature() -> MappingSignature
+    where
+        Self: Sized,
+    {
+        MappingSignature::new(std::any::type_name::<Self>(), &[])
+    }
+
     /// The unit row consumer implemented by the pgde_derive crate that consumes row
     /// data into another struct. Upon error, provides field and class information for the
     /// first encountered error in the form of a String as well as partially converted
@@ -219,51 +614,653 @@ pub trait RowConsumer {
     ///                 Ok(v) => Ok(v),
     ///                 Err(_) => Err(ConsumeError::ConversionError),
     ///             },
-    ///             Err(_) => Err(ConsumeError::DatabaseConnectionError),
+    ///             Err(e) => Err(ConsumeError::from(e)),
     ///         }
     ///     }
     /// }
     /// ```
-    fn from_rows(rows: Vec<Row>) -> Result<Vec<Self>, Vec<Self>>
+    fn from_rows(rows: Vec<Row>) -> Result<Vec<Self>, Vec<Self>>
+    where
+        Self: Sized,
+    {
+        let mut has_issue = false;
+        let mut data = Vec::with_capacity(rows.len());
+
+        for row in rows.into_iter() {
+            match Self::from_row(row) {
+                Ok(v) => data.push(v),
+                Err((v, _)) => {
+                    has_issue = true;
+                    data.push(v);
+                }
+            }
+        }
+
+        match has_issue {
+            false => Ok(data),
+            true => Err(data),
+        }
+    }
+
+    /// Consumes row data from provided connection, query, and parameters. Provides no
+    /// data on error, instead provides a [ConsumeError] enum.
+    ///
+    /// ## Example
+    /// You may use `consume` to consume PostgreSQL row data into a struct like so.
+    ///
+    /// ```
+    /// # tokio_test::block_on(async {
+    /// use pgde::ConsumeError;
+    /// use pgde::RowConsumer;
+    /// use pgde_derive::RowConsumer;
+    /// use tokio_postgres::{NoTls, Row};
+    ///
+    /// #[derive(RowConsumer)]
+    /// struct Foo {
+    ///     Id: i32,
+    ///     Data: String,
+    /// }
+    ///
+    /// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+    ///     Ok(v) => {
+    ///         let client = v.0;
+    ///         let conn = v.1;
+    ///
+    ///         tokio::spawn(async move {
+    ///             if let Err(e) = conn.await {
+    ///                 eprintln!("connection error: {}", e);
+    ///             }
+    ///         });
+    ///
+    ///         let query = "select * from public.\"Foo\";";
+    ///
+    ///         match Foo::consume(&client, query, &[]).await {
+    ///             Ok(v) => { // v is of type Vec<Foo>
+    ///                 match v.first() {
+    ///                     Some(v) => println!("Id {} has Data {}", v.Id, v.Data),
+    ///                     None => eprintln!("No data in table"),
+    ///                 }
+    ///             },
+    ///             Err(v) => match v {
+    ///                 ConsumeError::ConversionError => eprintln!("Could not convert data"),
+    ///                 ConsumeError::DatabaseConnectionError => eprintln!("Database errored on processing the query"),
+    ///                 ConsumeError::DatabaseError { code, message } => eprintln!("Database rejected the query with {}: {}", code, message),
+    ///                 _ => eprintln!("Could not consume data"),
+    ///             },
+    ///         };
+    ///     },
+    ///     Err(_) => eprintln!("Could not connect to database"),
+    /// };
+    /// # })
+    /// ```
+    ///
+    /// This crate also provides implementations on a variety of data types, some provided
+    /// by enabling features, that can be used by doing something like the following.
+    ///
+    /// ```
+    /// # tokio_test::block_on(async {
+    /// use pgde::ConsumeError;
+    /// use pgde::RowConsumer;
+    /// use tokio_postgres::{NoTls, Row};
+    ///
+    /// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+    ///     Ok(v) => {
+    ///         let client = v.0;
+    ///         let conn = v.1;
+    ///
+    ///         tokio::spawn(async move {
+    ///             if let Err(e) = conn.await {
+    ///                 eprintln!("connection error: {}", e);
+    ///             }
+    ///         });
+    ///
+    ///         let query = "select 1;";
+    ///
+    ///         match i32::consume(&client, query, &[]).await {
+    ///             Ok(v) => { // v is of type Vec<i32>
+    ///                 match v.first() {
+    ///                     Some(v) => println!("1 is {}", *v),
+    ///                     None => eprintln!("No data received"),
+    ///                 }
+    ///             },
+    ///             Err(v) => match v {
+    ///                 ConsumeError::ConversionError => eprintln!("Could not convert data"),
+    ///                 ConsumeError::DatabaseConnectionError => eprintln!("Database errored on processing the query"),
+    ///                 ConsumeError::DatabaseError { code, message } => eprintln!("Database rejected the query with {}: {}", code, message),
+    ///                 _ => eprintln!("Could not consume data"),
+    ///             },
+    ///         };
+    ///     },
+    ///     Err(_) => eprintln!("Could not connect to database"),
+    /// };
+    /// # })
+    /// ```
+    fn consume(
+        conn: &Client,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> impl Future<Output = Result<Vec<Self>, ConsumeError>> + Send
+    where
+        Self: Sized,
+    {
+        async move {
+            #[cfg(feature = "dev_guard")]
+            guard::record(query);
+
+            match conn.query(query, params).await {
+                Ok(v) => {
+                    if let Some(row) = v.first() {
+                        check_columns::<Self>(row.columns())?;
+                    }
+
+                    match Self::from_rows(v) {
+                        Ok(v) => Ok(v),
+                        Err(_) => Err(ConsumeError::ConversionError),
+                    }
+                }
+                Err(e) => Err(ConsumeError::from(e)),
+            }
+        }
+    }
+
+    /// Consumes row data like `consume`, with behavior configured by [ConsumeOptions]:
+    /// how conversion errors are reported, whether rows that fail conversion are kept,
+    /// dropped, or fail the call, how many rows are read, and how the query is labeled.
+    ///
+    /// ## Example
+    /// ```
+    /// # tokio_test::block_on(async {
+    /// use pgde::{BadRowPolicy, ConsumeOptions, RowConsumer};
+    /// use tokio_postgres::NoTls;
+    ///
+    /// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+    ///     Ok(v) => {
+    ///         let client = v.0;
+    ///         let conn = v.1;
+    ///
+    ///         tokio::spawn(async move {
+    ///             if let Err(e) = conn.await {
+    ///                 eprintln!("connection error: {}", e);
+    ///             }
+    ///         });
+    ///
+    ///         let query = "select generate_series(1, 10);";
+    ///         let options = ConsumeOptions::new()
+    ///             .bad_rows(BadRowPolicy::Skip)
+    ///             .max_rows(5)
+    ///             .trace_tag("example");
+    ///
+    ///         match i32::consume_with_options(&client, query, &[], &options).await {
+    ///             Ok(v) => println!("Received {} rows", v.len()), // v is of type Vec<i32>
+    ///             Err(_) => eprintln!("An error occurred while querying database"),
+    ///         };
+    ///     },
+    ///     Err(_) => eprintln!("Could not connect to database"),
+    /// };
+    /// # })
+    /// ```
+    fn consume_with_options(
+        conn: &Client,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+        options: &ConsumeOptions,
+    ) -> impl Future<Output = Result<Vec<Self>, ConsumeError>> + Send
+    where
+        Self: Sized + Send,
+    {
+        async move {
+            options::consume_rows(conn, query, params, options)
+                .await
+                .map(|v| v.rows)
+        }
+    }
+
+    /// Consumes row data like `consume`, but drops rows that fail conversion instead of
+    /// failing the call. Returns the remaining rows along with the number of rows that
+    /// were dropped, for best-effort consumers that prefer most rows over none.
+    ///
+    /// ## Example
+    /// ```
+    /// # tokio_test::block_on(async {
+    /// use pgde::RowConsumer;
+    /// use tokio_postgres::NoTls;
+    ///
+    /// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+    ///     Ok(v) => {
+    ///         let client = v.0;
+    ///         let conn = v.1;
+    ///
+    ///         tokio::spawn(async move {
+    ///             if let Err(e) = conn.await {
+    ///                 eprintln!("connection error: {}", e);
+    ///             }
+    ///         });
+    ///
+    ///         let query = "select v from (values (1), (null), (3)) t(v);";
+    ///
+    ///         match i32::consume_filtered(&client, query, &[]).await {
+    ///             Ok(v) => println!("Kept {} rows, skipped {}", v.rows.len(), v.skipped),
+    ///             Err(_) => eprintln!("An error occurred while querying database"),
+    ///         };
+    ///     },
+    ///     Err(_) => eprintln!("Could not connect to database"),
+    /// };
+    /// # })
+    /// ```
+    fn consume_filtered(
+        conn: &Client,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> impl Future<Output = Result<FilteredRows<Self>, ConsumeError>> + Send
+    where
+        Self: Sized + Send,
+    {
+        async move {
+            let options = ConsumeOptions::new().bad_rows(BadRowPolicy::Skip);
+            options::consume_rows(conn, query, params, &options).await
+        }
+    }
+
+    /// Consumes the rows of `base_query` whose `column` is one of `values`. The values are
+    /// sent as parameters, split across several queries when there are more than
+    /// PostgreSQL allows in one statement, and the results are merged. `column` must be
+    /// a column returned by `base_query`.
+    ///
+    /// ## Example
+    /// ```
+    /// # tokio_test::block_on(async {
+    /// use pgde::RowConsumer;
+    /// use tokio_postgres::NoTls;
+    ///
+    /// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+    ///     Ok(v) => {
+    ///         let client = v.0;
+    ///         let conn = v.1;
+    ///
+    ///         tokio::spawn(async move {
+    ///             if let Err(e) = conn.await {
+    ///                 eprintln!("connection error: {}", e);
+    ///             }
+    ///         });
+    ///
+    ///         let query = "select generate_series(1, 10) as id";
+    ///
+    ///         match i32::consume_where_in(&client, query, "id", &[2, 4, 6]).await {
+    ///             Ok(v) => println!("Found {} ids", v.len()), // v is of type Vec<i32>
+    ///             Err(_) => eprintln!("An error occurred while querying database"),
+    ///         };
+    ///     },
+    ///     Err(_) => eprintln!("Could not connect to database"),
+    /// };
+    /// # })
+    /// ```
+    fn consume_where_in<V: ToSql + Sync>(
+        conn: &Client,
+        base_query: &str,
+        column: &str,
+        values: &[V],
+    ) -> impl Future<Output = Result<Vec<Self>, ConsumeError>> + Send
+    where
+        Self: Sized + Send,
+    {
+        where_in::consume_where_in(conn, base_query, column, values)
+    }
+
+    /// Consumes the rows of `base_query` whose `column` is an element of `values`, which
+    /// are sent as a single array parameter to `= any($1)`. Unlike `consume_where_in`,
+    /// the query is the same for every number of values, so its plan may be reused, and
+    /// no values are split across queries. `V` may be any element type supported by
+    /// tokio-postgres or the enabled features, e.g. `i64`, `String`, `&str`,
+    /// `uuid::Uuid`, or the date time types of chrono and time, and must match the type
+    /// of `column`. `column` must be a column returned by `base_query`.
+    ///
+    /// Slices and `Vec`s of these types may likewise be passed as the parameter of any
+    /// query, e.g. `&[&ids]` for `where id = any($1)`.
+    ///
+    /// ## Example
+    /// ```
+    /// # tokio_test::block_on(async {
+    /// use pgde::RowConsumer;
+    /// use tokio_postgres::NoTls;
+    ///
+    /// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+    ///     Ok(v) => {
+    ///         let client = v.0;
+    ///         let conn = v.1;
+    ///
+    ///         tokio::spawn(async move {
+    ///             if let Err(e) = conn.await {
+    ///                 eprintln!("connection error: {}", e);
+    ///             }
+    ///         });
+    ///
+    ///         let query = "select generate_series(1, 10) as id";
+    ///
+    ///         match i32::consume_where_any(&client, query, "id", &[2, 4, 6]).await {
+    ///             Ok(v) => println!("Found {} ids", v.len()), // v is of type Vec<i32>
+    ///             Err(_) => eprintln!("An error occurred while querying database"),
+    ///         };
+    ///     },
+    ///     Err(_) => eprintln!("Could not connect to database"),
+    /// };
+    /// # })
+    /// ```
+    fn consume_where_any<V: ToSql + Sync>(
+        conn: &Client,
+        base_query: &str,
+        column: &str,
+        values: &[V],
+    ) -> impl Future<Output = Result<Vec<Self>, ConsumeError>> + Send
+    where
+        Self: Sized + Send,
+    {
+        where_in::consume_where_any(conn, base_query, column, values)
+    }
+
+    /// Consumes one page of the rows of `query`, which is run as a subquery with `LIMIT`
+    /// and `OFFSET` appended. With `Page::with_count`, a companion query also counts every
+    /// row of `query`, filling `PagedResult::total`. Queries should be ordered so that
+    /// pages are stable.
+    ///
+    /// ## Example
+    /// ```
+    /// # tokio_test::block_on(async {
+    /// use pgde::{Page, RowConsumer};
+    /// use tokio_postgres::NoTls;
+    ///
+    /// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+    ///     Ok(v) => {
+    ///         let client = v.0;
+    ///         let conn = v.1;
+    ///
+    ///         tokio::spawn(async move {
+    ///             if let Err(e) = conn.await {
+    ///                 eprintln!("connection error: {}", e);
+    ///             }
+    ///         });
+    ///
+    ///         let query = "select generate_series(1, 95) order by 1;";
+    ///         let page = Page::new(2, 20).with_count();
+    ///
+    ///         match i32::consume_page(&client, query, &[], page).await {
+    ///             Ok(v) => println!("Page 2 of {:?} holds {} rows", v.pages(), v.items.len()), // v is of type PagedResult<i32>
+    ///             Err(_) => eprintln!("An error occurred while querying database"),
+    ///         };
+    ///     },
+    ///     Err(_) => eprintln!("Could not connect to database"),
+    /// };
+    /// # })
+    /// ```
+    fn consume_page(
+        conn: &Client,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+        page: Page,
+    ) -> impl Future<Output = Result<PagedResult<Self>, ConsumeError>> + Send
+    where
+        Self: Sized + Send,
+    {
+        page::consume_page(conn, query, params, page)
+    }
+
+    /// Consumes the rows of a query whose `cursor_column`, such as an `updated_at`
+    /// timestamp, is past `last_cursor`, the high-water mark returned by the previous
+    /// call, in cursor order. `None` reads every row with a cursor. Returns the rows along
+    /// with the new mark, which is the cursor of the last row, or `last_cursor` when no
+    /// rows are past it. Every row past the mark is read at once, so rows sharing a cursor
+    /// value are never split between calls, and none are read twice. Rows written later
+    /// with a cursor at or before the mark are not read, so the cursor column should only
+    /// move forward. The query may not end in `ORDER BY` or `LIMIT` clauses of its own,
+    /// which would apply before the filter.
+    ///
+    /// ## Example
+    /// ```
+    /// # tokio_test::block_on(async {
+    /// use pgde::RowConsumer;
+    /// use pgde_derive::RowConsumer;
+    /// use std::time::SystemTime;
+    /// use tokio_postgres::{NoTls, Row};
+    ///
+    /// #[derive(RowConsumer)]
+    /// struct Order {
+    ///     id: i32,
+    ///     updated_at: Option<SystemTime>,
+    /// }
+    ///
+    /// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+    ///     Ok(v) => {
+    ///         let client = v.0;
+    ///         let conn = v.1;
+    ///
+    ///         tokio::spawn(async move {
+    ///             if let Err(e) = conn.await {
+    ///                 eprintln!("connection error: {}", e);
+    ///             }
+    ///         });
+    ///
+    ///         let query = "select 1 as id, now()::timestamp as updated_at";
+    ///         let last_cursor: Option<SystemTime> = None;
+    ///
+    ///         match Order::consume_incremental(&client, query, &[], "updated_at", last_cursor).await {
+    ///             Ok(v) => println!("{} orders changed, resume after {:?}", v.items.len(), v.cursor),
+    ///             Err(_) => eprintln!("An error occurred while querying database"),
+    ///         };
+    ///     },
+    ///     Err(_) => eprintln!("Could not connect to database"),
+    /// };
+    /// # })
+    /// ```
+    fn consume_incremental<C>(
+        conn: &Client,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+        cursor_column: &str,
+        last_cursor: Option<C>,
+    ) -> impl Future<Output = Result<Incremental<Self, C>, ConsumeError>> + Send
+    where
+        Self: Sized + Send,
+        C: ToSql + for<'a> FromSql<'a> + Sync + Send,
+    {
+        incremental::consume_incremental(conn, query, params, cursor_column, last_cursor)
+    }
+
+    /// Consumes row data like `consume` into a `HashMap` keyed by the value `key` returns
+    /// for each row, for loading a table into a lookup map. When several rows share a
+    /// key, the last one is kept.
+    ///
+    /// ## Example
+    /// ```
+    /// # tokio_test::block_on(async {
+    /// use pgde::RowConsumer;
+    /// use pgde_derive::RowConsumer;
+    /// use tokio_postgres::{NoTls, Row};
+    ///
+    /// #[derive(RowConsumer)]
+    /// struct Country {
+    ///     code: String,
+    ///     name: String,
+    /// }
+    ///
+    /// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+    ///     Ok(v) => {
+    ///         let client = v.0;
+    ///         let conn = v.1;
+    ///
+    ///         tokio::spawn(async move {
+    ///             if let Err(e) = conn.await {
+    ///                 eprintln!("connection error: {}", e);
+    ///             }
+    ///         });
+    ///
+    ///         let query = "select 'nz', 'New Zealand' union all select 'pe', 'Peru';";
+    ///
+    ///         match Country::consume_map_by_key(&client, query, &[], |v| v.code.clone()).await {
+    ///             Ok(v) => match v.get("pe") { // v is of type HashMap<String, Country>
+    ///                 Some(v) => println!("pe is {}", v.name),
+    ///                 None => eprintln!("No country with code pe"),
+    ///             },
+    ///             Err(_) => eprintln!("An error occurred while querying database"),
+    ///         };
+    ///     },
+    ///     Err(_) => eprintln!("Could not connect to database"),
+    /// };
+    /// # })
+    /// ```
+    fn consume_map_by_key<K, F>(
+        conn: &Client,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+        key: F,
+    ) -> impl Future<Output = Result<HashMap<K, Self>, ConsumeError>> + Send
+    where
+        Self: Sized + Send,
+        K: Eq + Hash + Send,
+        F: Fn(&Self) -> K + Send,
+    {
+        async move {
+            match Self::consume(conn, query, params).await {
+                Ok(v) => Ok(v.into_iter().map(|v| (key(&v), v)).collect()),
+                Err(v) => Err(v),
+            }
+        }
+    }
+
+    /// Consumes row data like `consume`, keeping one row of every key, e.g. of queries
+    /// joining many-to-many tables that repeat rows. Keys are declared by marking fields
+    /// `#[pgde(key)]`, or by implementing `KeyedBy` by hand, and `keep` chooses whether
+    /// the first or last row of a key is kept.
+    ///
+    /// ## Example
+    /// ```
+    /// # tokio_test::block_on(async {
+    /// use pgde::{KeepDuplicate, RowConsumer};
+    /// use pgde_derive::RowConsumer;
+    /// use tokio_postgres::{NoTls, Row};
+    ///
+    /// #[derive(RowConsumer)]
+    /// struct Author {
+    ///     #[pgde(key)]
+    ///     id: i32,
+    ///     name: String,
+    /// }
+    ///
+    /// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+    ///     Ok(v) => {
+    ///         let client = v.0;
+    ///         let conn = v.1;
+    ///
+    ///         tokio::spawn(async move {
+    ///             if let Err(e) = conn.await {
+    ///                 eprintln!("connection error: {}", e);
+    ///             }
+    ///         });
+    ///
+    ///         let query = "select 1, 'Ann' union all select 1, 'Ann' union all select 2, 'Bo';";
+    ///
+    ///         match Author::consume_dedup_by_key(&client, query, &[], KeepDuplicate::First).await {
+    ///             Ok(v) => println!("{} authors", v.len()),
+    ///             Err(_) => eprintln!("An error occurred while querying database"),
+    ///         };
+    ///     },
+    ///     Err(_) => eprintln!("Could not connect to database"),
+    /// };
+    /// # })
+    /// ```
+    fn consume_dedup_by_key<K>(
+        conn: &Client,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+        keep: KeepDuplicate,
+    ) -> impl Future<Output = Result<Vec<Self>, ConsumeError>> + Send
+    where
+        Self: KeyedBy<K> + Sized + Send,
+        K: Eq + Hash,
+    {
+        async move {
+            match Self::consume(conn, query, params).await {
+                Ok(v) => Ok(dedup::dedup_by_key(v, keep)),
+                Err(v) => Err(v),
+            }
+        }
+    }
+
+    /// Consumes row data like `consume`, leaving out the rows whose `RowHash` is among
+    /// `prior_hashes`, so that sync jobs that keep the hashes of the rows they have
+    /// written may skip the rows that have not changed since.
+    ///
+    /// ## Example
+    /// ```
+    /// # tokio_test::block_on(async {
+    /// use pgde::{RowConsumer, RowHash};
+    /// use pgde_derive::RowConsumer;
+    /// use std::collections::HashSet;
+    /// use tokio_postgres::{NoTls, Row};
+    ///
+    /// #[derive(RowConsumer)]
+    /// #[pgde(row_hash)]
+    /// struct Product {
+    ///     id: i32,
+    ///     price: f64,
+    /// }
+    ///
+    /// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+    ///     Ok(v) => {
+    ///         let client = v.0;
+    ///         let conn = v.1;
+    ///
+    ///         tokio::spawn(async move {
+    ///             if let Err(e) = conn.await {
+    ///                 eprintln!("connection error: {}", e);
+    ///             }
+    ///         });
+    ///
+    ///         let query = "select 1, 9.5::float8 union all select 2, 4.0::float8;";
+    ///         let prior_hashes = HashSet::from([Product { id: 1, price: 9.5 }.row_hash()]);
+    ///
+    ///         match Product::consume_changed_since(&client, query, &[], &prior_hashes).await {
+    ///             Ok(v) => println!("{} products changed", v.len()),
+    ///             Err(_) => eprintln!("An error occurred while querying database"),
+    ///         };
+    ///     },
+    ///     Err(_) => eprintln!("Could not connect to database"),
+    /// };
+    /// # })
+    /// ```
+    fn consume_changed_since(
+        conn: &Client,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+        prior_hashes: &HashSet<u64>,
+    ) -> impl Future<Output = Result<Vec<Self>, ConsumeError>> + Send
     where
-        Self: Sized,
+        Self: RowHash + Sized + Send,
     {
-        let mut has_issue = false;
-        let mut data = Vec::with_capacity(rows.len());
-
-        for row in rows.into_iter() {
-            match Self::from_row(row) {
-                Ok(v) => data.push(v),
-                Err((v, _)) => {
-                    has_issue = true;
-                    data.push(v);
-                }
+        async move {
+            match Self::consume(conn, query, params).await {
+                Ok(v) => Ok(v
+                    .into_iter()
+                    .filter(|v| !prior_hashes.contains(&v.row_hash()))
+                    .collect()),
+                Err(v) => Err(v),
             }
         }
-
-        match has_issue {
-            false => Ok(data),
-            true => Err(data),
-        }
     }
 
-    /// Consumes row data from provided connection, query, and parameters. Provides no
-    /// data on error, instead provides a [ConsumeError] enum.
+    /// Consumes row data like `consume_map_by_key` into an `IndexMap`, which keeps the
+    /// order of the query's rows, for consumers that need both order and lookup by key.
+    /// When several rows share a key, the last one is kept at the position of the first.
     ///
     /// ## Example
-    /// You may use `consume` to consume PostgreSQL row data into a struct like so.
-    ///
     /// ```
     /// # tokio_test::block_on(async {
-    /// use pgde::ConsumeError;
     /// use pgde::RowConsumer;
     /// use pgde_derive::RowConsumer;
     /// use tokio_postgres::{NoTls, Row};
     ///
     /// #[derive(RowConsumer)]
-    /// struct Foo {
-    ///     Id: i32,
-    ///     Data: String,
+    /// struct MenuItem {
+    ///     slug: String,
+    ///     label: String,
     /// }
     ///
     /// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
@@ -277,34 +1274,50 @@ pub trait RowConsumer {
     ///             }
     ///         });
     ///
-    ///         let query = "select * from public.\"Foo\";";
+    ///         let query = "select 'home', 'Home' union all select 'about', 'About';";
     ///
-    ///         match Foo::consume(&client, query, &[]).await {
-    ///             Ok(v) => { // v is of type Vec<Foo>
-    ///                 match v.first() {
-    ///                     Some(v) => println!("Id {} has Data {}", v.Id, v.Data),
-    ///                     None => eprintln!("No data in table"),
+    ///         match MenuItem::consume_index_map_by_key(&client, query, &[], |v| v.slug.clone()).await {
+    ///             Ok(v) => { // v is of type IndexMap<String, MenuItem>
+    ///                 for (slug, item) in v.iter() {
+    ///                     println!("{} links to /{}", item.label, slug);
     ///                 }
     ///             },
-    ///             Err(v) => match v {
-    ///                 ConsumeError::ConversionError => eprintln!("Could not convert data"),
-    ///                 ConsumeError::DatabaseConnectionError => eprintln!("Database errored on processing the query"),
-    ///             },
+    ///             Err(_) => eprintln!("An error occurred while querying database"),
     ///         };
     ///     },
     ///     Err(_) => eprintln!("Could not connect to database"),
     /// };
     /// # })
     /// ```
+    #[cfg(feature = "indexmap")]
+    fn consume_index_map_by_key<K, F>(
+        conn: &Client,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+        key: F,
+    ) -> impl Future<Output = Result<IndexMap<K, Self>, ConsumeError>> + Send
+    where
+        Self: Sized + Send,
+        K: Eq + Hash + Send,
+        F: Fn(&Self) -> K + Send,
+    {
+        async move {
+            match Self::consume(conn, query, params).await {
+                Ok(v) => Ok(v.into_iter().map(|v| (key(&v), v)).collect()),
+                Err(v) => Err(v),
+            }
+        }
+    }
+
+    /// Consumes row data like `consume` into an `IndexSet` of the values `key` returns
+    /// for each row, in the order of the query's rows, keeping the first position of
+    /// duplicated keys.
     ///
-    /// This crate also provides implementations on a variety of data types, some provided
-    /// by enabling features, that can be used by doing something like the following.
-    ///
+    /// ## Example
     /// ```
     /// # tokio_test::block_on(async {
-    /// use pgde::ConsumeError;
     /// use pgde::RowConsumer;
-    /// use tokio_postgres::{NoTls, Row};
+    /// use tokio_postgres::NoTls;
     ///
     /// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
     ///     Ok(v) => {
@@ -317,40 +1330,33 @@ pub trait RowConsumer {
     ///             }
     ///         });
     ///
-    ///         let query = "select 1;";
+    ///         let query = "select unnest(array[3, 1, 3, 2]);";
     ///
-    ///         match i32::consume(&client, query, &[]).await {
-    ///             Ok(v) => { // v is of type Vec<i32>
-    ///                 match v.first() {
-    ///                     Some(v) => println!("1 is {}", *v),
-    ///                     None => eprintln!("No data received"),
-    ///                 }
-    ///             },
-    ///             Err(v) => match v {
-    ///                 ConsumeError::ConversionError => eprintln!("Could not convert data"),
-    ///                 ConsumeError::DatabaseConnectionError => eprintln!("Database errored on processing the query"),
-    ///             },
+    ///         match i32::consume_index_set_by_key(&client, query, &[], |v| *v).await {
+    ///             Ok(v) => println!("Stages in order: {:?}", v), // v is of type IndexSet<i32>
+    ///             Err(_) => eprintln!("An error occurred while querying database"),
     ///         };
     ///     },
     ///     Err(_) => eprintln!("Could not connect to database"),
     /// };
     /// # })
     /// ```
-    fn consume(
+    #[cfg(feature = "indexmap")]
+    fn consume_index_set_by_key<K, F>(
         conn: &Client,
         query: &str,
         params: &[&(dyn ToSql + Sync)],
-    ) -> impl Future<Output = Result<Vec<Self>, ConsumeError>> + Send
+        key: F,
+    ) -> impl Future<Output = Result<IndexSet<K>, ConsumeError>> + Send
     where
-        Self: Sized,
+        Self: Sized + Send,
+        K: Eq + Hash + Send,
+        F: Fn(&Self) -> K + Send,
     {
         async move {
-            match conn.query(query, params).await {
-                Ok(v) => match Self::from_rows(v) {
-                    Ok(v) => Ok(v),
-                    Err(_) => Err(ConsumeError::ConversionError),
-                },
-                Err(_) => Err(ConsumeError::DatabaseConnectionError),
+            match Self::consume(conn, query, params).await {
+                Ok(v) => Ok(v.iter().map(key).collect()),
+                Err(v) => Err(v),
             }
         }
     }
@@ -418,17 +1424,150 @@ pub trait RowConsumer {
             }
         }
     }
+
+    /// Converts the results of `consume` into JSON like `consume_json`, formatted
+    /// according to [JsonOptions]: whether the output is indented, whether fields without
+    /// a value are written as `null`, how fields are named, and how their values are
+    /// transformed. On error returns `serde_json::Value::Null`.
+    ///
+    /// ## Example
+    /// ```
+    /// # tokio_test::block_on(async {
+    /// use pgde::{JsonOptions, RenameRule, RowConsumer};
+    /// use pgde_derive::RowConsumer;
+    /// use serde::Serialize;
+    /// use tokio_postgres::{NoTls, Row};
+    ///
+    /// #[derive(Serialize, RowConsumer)]
+    /// struct Foo {
+    ///     Id: i32,
+    ///     Data: Option<String>,
+    /// }
+    ///
+    /// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+    ///     Ok(v) => {
+    ///         let client = v.0;
+    ///         let conn = v.1;
+    ///
+    ///         tokio::spawn(async move {
+    ///             if let Err(e) = conn.await {
+    ///                 eprintln!("connection error: {}", e);
+    ///             }
+    ///         });
+    ///
+    ///         let query = "select * from public.\"Foo\";";
+    ///         let options = JsonOptions {
+    ///             pretty: true,
+    ///             null_for_missing: false,
+    ///             rename_all: Some(RenameRule::Camel),
+    ///             ..Default::default()
+    ///         };
+    ///
+    ///         match Foo::consume_json_with(&client, query, &[], &options).await {
+    ///             Ok(v) => println!("Received json data...\n{}", v),
+    ///             Err(_) => eprintln!("An error occurred while querying database"),
+    ///         };
+    ///     },
+    ///     Err(_) => eprintln!("Could not connect to database"),
+    /// };
+    /// # })
+    /// ```
+    #[cfg(feature = "consume_json")]
+    fn consume_json_with(
+        conn: &Client,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+        options: &JsonOptions,
+    ) -> impl Future<Output = Result<String, String>> + Send
+    where
+        Self: Serialize + Sized,
+    {
+        async move {
+            match &Self::consume(conn, query, params).await {
+                Ok(v) => match json::to_json(v, options) {
+                    Some(v) => Ok(v),
+                    None => Err(serde_json::Value::default().to_string()),
+                },
+                Err(_) => Err(serde_json::Value::default().to_string()),
+            }
+        }
+    }
+}
+
+/// Row consumption by reference, for callers who keep their rows, e.g. to read additional
+/// columns dynamically. Implemented by the derive macro alongside `RowConsumer`, whose
+/// derived `from_row` is built from `from_row_ref`, and by the types implemented by this
+/// crate.
+///
+/// ## Example
+/// ```
+/// use pgde::RowRefConsumer;
+/// use tokio_postgres::Row;
+///
+/// fn ids(rows: &[Row]) -> Vec<i32> {
+///     match i32::from_rows_ref(rows) {
+///         Ok(v) => v,
+///         Err(v) => v,
+///     }
+/// }
+/// ```
+pub trait RowRefConsumer: RowConsumer {
+    /// The unit row consumer that reads a row by reference. Upon error, provides field
+    /// and class information for the first encountered error in the form of a String as
+    /// well as partially converted data.
+    fn from_row_ref(row: &Row) -> Result<Self, (Self, Vec<String>)>
+    where
+        Self: Sized;
+
+    /// The n-row consumer built off of `from_row_ref`, leaving the rows with the caller.
+    /// Returns successfully converted data on error, but provides no breakdown of the
+    /// errors that occurred.
+    fn from_rows_ref(rows: &[Row]) -> Result<Vec<Self>, Vec<Self>>
+    where
+        Self: Sized,
+    {
+        let mut has_issue = false;
+        let mut data = Vec::with_capacity(rows.len());
+
+        for row in rows.iter() {
+            match Self::from_row_ref(row) {
+                Ok(v) => data.push(v),
+                Err((v, _)) => {
+                    has_issue = true;
+                    data.push(v);
+                }
+            }
+        }
+
+        match has_issue {
+            false => Ok(data),
+            true => Err(data),
+        }
+    }
 }
 
-/// A macro for implementing `from_row` on primitive types or types outside of this crate
-/// that implement `FromSql`. Used internally to implement `from_row` on `bool`, `i32`,
-/// `String`, etc.
+/// A macro for implementing `from_row` and `from_row_ref` on primitive types or types
+/// outside of this crate that implement `FromSql`. Used internally to implement them on
+/// `bool`, `i32`, `String`, etc.
 #[macro_export]
 macro_rules! pg_type_implementation {
     ( $( $x:ty ),* ) => {
         $(
             impl RowConsumer for $x {
+                fn check_column_types(columns: &[$crate::__private::Column]) -> Vec<String> {
+                    $crate::__private::check_first_column::<$x>(columns, stringify!($x))
+                }
+
                 fn from_row(row: Row) -> Result<Self, (Self, Vec<String>)>
+                where
+                    Self: Sized,
+                {
+                    <Self as $crate::RowRefConsumer>::from_row_ref(&row)
+                }
+            }
+
+            impl $crate::RowRefConsumer for $x {
+                fn from_row_ref(row: &Row) -> Result<Self, (Self, Vec<String>)>
                 where
                     Self: Sized,
                 {
@@ -452,15 +1591,28 @@ macro_rules! pg_type_implementation {
     };
 }
 
-/// A macro for implementing `from_row` on primitive types or types outside of this crate
-/// that implement `FromSql`. Used internally to implement `from_row` on `SystemTime` and
-/// `IpAddr`.
+/// A macro for implementing `from_row` and `from_row_ref` on primitive types or types
+/// outside of this crate that implement `FromSql`. Used internally to implement them on
+/// `SystemTime` and `IpAddr`.
 #[macro_export]
 macro_rules! pg_type_expr_implementation {
     ( $( $x:ty, $y:expr ),* ) => {
         $(
             impl RowConsumer for $x {
+                fn check_column_types(columns: &[$crate::__private::Column]) -> Vec<String> {
+                    $crate::__private::check_first_column::<$x>(columns, stringify!($x))
+                }
+
                 fn from_row(row: Row) -> Result<Self, (Self, Vec<String>)>
+                where
+                    Self: Sized,
+                {
+                    <Self as $crate::RowRefConsumer>::from_row_ref(&row)
+                }
+            }
+
+            impl $crate::RowRefConsumer for $x {
+                fn from_row_ref(row: &Row) -> Result<Self, (Self, Vec<String>)>
                 where
                     Self: Sized,
                 {
@@ -479,6 +1631,94 @@ macro_rules! pg_type_expr_implementation {
     };
 }
 
+/// A macro for implementing `from_row` and `from_row_ref` on duration types that
+/// implement [DurationColumn], reading the first column as an `interval`. Used internally
+/// to implement them on `std::time::Duration`, `chrono::Duration`, and `time::Duration`.
+macro_rules! pg_duration_implementation {
+    ( $( $x:ty ),* ) => {
+        $(
+            impl RowConsumer for $x {
+                fn check_column_types(columns: &[Column]) -> Vec<String> {
+                    check_first_column::<Option<Interval>>(columns, stringify!($x))
+                }
+
+                fn from_row(row: Row) -> Result<Self, (Self, Vec<String>)>
+                where
+                    Self: Sized,
+                {
+                    <Self as RowRefConsumer>::from_row_ref(&row)
+                }
+            }
+
+            impl RowRefConsumer for $x {
+                fn from_row_ref(row: &Row) -> Result<Self, (Self, Vec<String>)>
+                where
+                    Self: Sized,
+                {
+                    match row.try_get::<usize, Option<Interval>>(0).ok().and_then(<$x>::from_interval) {
+                        Some(v) => Ok(v),
+                        None => Err((<$x>::default(), vec![format!("Conversion error occurred for class \"{}\"", stringify!($x))])),
+                    }
+                }
+            }
+
+            impl RowConsumer for Option<$x> {
+                fn check_column_types(columns: &[Column]) -> Vec<String> {
+                    check_first_column::<Option<Interval>>(columns, stringify!(Option<$x>))
+                }
+
+                fn from_row(row: Row) -> Result<Self, (Self, Vec<String>)>
+                where
+                    Self: Sized,
+                {
+                    <Self as RowRefConsumer>::from_row_ref(&row)
+                }
+            }
+
+            impl RowRefConsumer for Option<$x> {
+                fn from_row_ref(row: &Row) -> Result<Self, (Self, Vec<String>)>
+                where
+                    Self: Sized,
+                {
+                    match row.try_get::<usize, Option<Interval>>(0).ok().and_then(Option::<$x>::from_interval) {
+                        Some(v) => Ok(v),
+                        None => Err((None, vec![format!("Conversion error occurred for class \"{}\"", stringify!(Option<$x>))])),
+                    }
+                }
+            }
+
+            impl RowConsumer for Vec<$x> {
+                fn check_column_types(columns: &[Column]) -> Vec<String> {
+                    check_first_column::<Vec<Interval>>(columns, stringify!(Vec<$x>))
+                }
+
+                fn from_row(row: Row) -> Result<Self, (Self, Vec<String>)>
+                where
+                    Self: Sized,
+                {
+                    <Self as RowRefConsumer>::from_row_ref(&row)
+                }
+            }
+
+            impl RowRefConsumer for Vec<$x> {
+                fn from_row_ref(row: &Row) -> Result<Self, (Self, Vec<String>)>
+                where
+                    Self: Sized,
+                {
+                    match row
+                        .try_get::<usize, Vec<Interval>>(0)
+                        .ok()
+                        .and_then(|v| v.into_iter().map(|v| <$x>::from_interval(Some(v))).collect())
+                    {
+                        Some(v) => Ok(v),
+                        None => Err((Vec::new(), vec![format!("Conversion error occurred for class \"{}\"", stringify!(Vec<$x>))])),
+                    }
+                }
+            }
+        )*
+    };
+}
+
 pg_type_implementation![
     bool,
     i8,
@@ -509,7 +1749,9 @@ pg_type_implementation![
     Option<f32>,
     Option<f64>,
     Option<String>,
-    Option<Vec<u8>>
+    Option<Vec<u8>>,
+    RawValue,
+    Vec<RawValue>
 ];
 
 pg_type_expr_implementation![
@@ -527,6 +1769,8 @@ pg_type_expr_implementation![
     Some(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)))
 ];
 
+pg_duration_implementation![Duration];
+
 #[cfg(feature = "bit")]
 pg_type_implementation![BitVec, Vec<BitVec>, Option<BitVec>];
 
@@ -552,6 +1796,9 @@ pg_type_implementation![
     Option<NaiveTime>
 ];
 
+#[cfg(feature = "chrono")]
+pg_duration_implementation![chrono::Duration];
+
 #[cfg(feature = "geo")]
 pg_type_implementation![Point<f64>, Vec<Point<f64>>, Option<Point<f64>>];
 
@@ -611,6 +1858,9 @@ pg_type_expr_implementation![
     Some(Time::MIDNIGHT)
 ];
 
+#[cfg(feature = "time")]
+pg_duration_implementation![time::Duration];
+
 #[cfg(feature = "uuid")]
 pg_type_implementation![Uuid, Vec<Uuid>, Option<Uuid>];
 
@@ -620,3 +1870,83 @@ pg_type_implementation![
     Vec<serde_json::Value>,
     Option<serde_json::Value>
 ];
+
+#[cfg(feature = "smallvec")]
+impl<A> RowConsumer for SmallVec<A>
+where
+    A: smallvec::Array,
+    for<'a> A::Item: FromSql<'a>,
+{
+    fn check_column_types(columns: &[Column]) -> Vec<String> {
+        check_first_column::<ArrayColumn<Self>>(columns, std::any::type_name::<Self>())
+    }
+
+    fn from_row(row: Row) -> Result<Self, (Self, Vec<String>)>
+    where
+        Self: Sized,
+    {
+        <Self as RowRefConsumer>::from_row_ref(&row)
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<A> RowRefConsumer for SmallVec<A>
+where
+    A: smallvec::Array,
+    for<'a> A::Item: FromSql<'a>,
+{
+    fn from_row_ref(row: &Row) -> Result<Self, (Self, Vec<String>)>
+    where
+        Self: Sized,
+    {
+        match row.try_get::<usize, ArrayColumn<Self>>(0) {
+            Ok(v) => Ok(v.0),
+            Err(_) => Err((
+                SmallVec::new(),
+                vec![format!(
+                    "Conversion error occurred for class \"{}\"",
+                    std::any::type_name::<Self>()
+                )],
+            )),
+        }
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+impl<T, const N: usize> RowConsumer for ArrayVec<T, N>
+where
+    for<'a> T: FromSql<'a>,
+{
+    fn check_column_types(columns: &[Column]) -> Vec<String> {
+        check_first_column::<ArrayColumn<Self>>(columns, std::any::type_name::<Self>())
+    }
+
+    fn from_row(row: Row) -> Result<Self, (Self, Vec<String>)>
+    where
+        Self: Sized,
+    {
+        <Self as RowRefConsumer>::from_row_ref(&row)
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+impl<T, const N: usize> RowRefConsumer for ArrayVec<T, N>
+where
+    for<'a> T: FromSql<'a>,
+{
+    fn from_row_ref(row: &Row) -> Result<Self, (Self, Vec<String>)>
+    where
+        Self: Sized,
+    {
+        match row.try_get::<usize, ArrayColumn<Self>>(0) {
+            Ok(v) => Ok(v.0),
+            Err(_) => Err((
+                ArrayVec::new(),
+                vec![format!(
+                    "Conversion error occurred for class \"{}\"",
+                    std::any::type_name::<Self>()
+                )],
+            )),
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! Session-level advisory locks, held for as long as a [LockGuard] is alive.
+use crate::ConsumeError;
+use futures_util::FutureExt;
+use std::time::Duration;
+use tokio_postgres::error::SqlState;
+use tokio_postgres::Client;
+
+/// A session-level advisory lock taken with `pg_advisory_lock`. The lock is released by
+/// `unlock`, or when the guard is dropped.
+///
+/// Dropping the guard sends `pg_advisory_unlock` on the connection without waiting for
+/// the result, since `Drop` cannot be asynchronous. Call `unlock` to know that the lock
+/// was released before continuing.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::locks::advisory_lock;
+/// use tokio_postgres::NoTls;
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         match advisory_lock(&client, 42).await {
+///             Ok(guard) => {
+///                 println!("Running job {}", guard.key());
+///
+///                 if guard.unlock().await.is_err() {
+///                     eprintln!("Could not release lock");
+///                 }
+///             },
+///             Err(_) => eprintln!("Could not take lock"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub struct LockGuard<'a> {
+    client: &'a Client,
+    key: i64,
+    released: bool,
+}
+
+impl LockGuard<'_> {
+    /// Returns the key of the lock.
+    pub fn key(&self) -> i64 {
+        self.key
+    }
+
+    /// Releases the lock, waiting for the database to confirm that it was held. Fails with
+    /// [ConsumeError::InvalidQuery] if the session no longer held the lock, e.g. because
+    /// it was released with `pg_advisory_unlock` directly.
+    pub async fn unlock(mut self) -> Result<(), ConsumeError> {
+        self.released = true;
+
+        match self
+            .client
+            .query_one("select pg_advisory_unlock($1);", &[&self.key])
+            .await
+        {
+            Ok(v) => match v.try_get::<usize, bool>(0) {
+                Ok(true) => Ok(()),
+                Ok(false) => Err(ConsumeError::InvalidQuery(String::from(
+                    "advisory lock was not held",
+                ))),
+                Err(_) => Err(ConsumeError::ConversionError),
+            },
+            Err(e) => Err(ConsumeError::from(e)),
+        }
+    }
+}
+
+impl Drop for LockGuard<'_> {
+    fn drop(&mut self) {
+        if !self.released {
+            // Polling once is enough to queue the query on the connection.
+            let query = format!("select pg_advisory_unlock({});", self.key);
+            let _ = self.client.batch_execute(&query).now_or_never();
+        }
+    }
+}
+
+/// Takes the advisory lock `key`, waiting for as long as another session holds it.
+pub async fn advisory_lock(client: &Client, key: i64) -> Result<LockGuard<'_>, ConsumeError> {
+    match client
+        .execute("select pg_advisory_lock($1);", &[&key])
+        .await
+    {
+        Ok(_) => Ok(LockGuard {
+            client,
+            key,
+            released: false,
+        }),
+        Err(e) => Err(ConsumeError::from(e)),
+    }
+}
+
+/// Takes the advisory lock `key` if it is available, returning `None` without waiting
+/// if another session holds it.
+pub async fn try_advisory_lock(
+    client: &Client,
+    key: i64,
+) -> Result<Option<LockGuard<'_>>, ConsumeError> {
+    match client
+        .query_one("select pg_try_advisory_lock($1);", &[&key])
+        .await
+    {
+        Ok(v) => match v.try_get::<usize, bool>(0) {
+            Ok(true) => Ok(Some(LockGuard {
+                client,
+                key,
+                released: false,
+            })),
+            Ok(false) => Ok(None),
+            Err(e) => Err(ConsumeError::from(e)),
+        },
+        Err(e) => Err(ConsumeError::from(e)),
+    }
+}
+
+/// Takes the advisory lock `key`, returning `None` if it could not be taken within
+/// `timeout`. The wait is bounded by the session's `lock_timeout`, which is restored
+/// afterwards.
+pub async fn advisory_lock_timeout(
+    client: &Client,
+    key: i64,
+    timeout: Duration,
+) -> Result<Option<LockGuard<'_>>, ConsumeError> {
+    let previous = match client
+        .query_one("select current_setting('lock_timeout');", &[])
+        .await
+    {
+        Ok(v) => match v.try_get::<usize, String>(0) {
+            Ok(v) => v,
+            Err(e) => return Err(ConsumeError::from(e)),
+        },
+        Err(e) => return Err(ConsumeError::from(e)),
+    };
+
+    // A lock_timeout of zero disables the timeout, so wait at least a millisecond.
+    let milliseconds = format!("{}ms", timeout.as_millis().max(1));
+
+    if let Err(e) = client
+        .execute(
+            "select set_config('lock_timeout', $1, false);",
+            &[&milliseconds],
+        )
+        .await
+    {
+        return Err(ConsumeError::from(e));
+    }
+
+    // Built before restoring lock_timeout, so that a failure to restore it releases the
+    // lock when the guard is dropped.
+    let locked = match client
+        .execute("select pg_advisory_lock($1);", &[&key])
+        .await
+    {
+        Ok(_) => Ok(Some(LockGuard {
+            client,
+            key,
+            released: false,
+        })),
+        Err(v) if v.code() == Some(&SqlState::LOCK_NOT_AVAILABLE) => Ok(None),
+        Err(e) => Err(ConsumeError::from(e)),
+    };
+
+    match client
+        .execute(
+            "select set_config('lock_timeout', $1, false);",
+            &[&previous],
+        )
+        .await
+    {
+        Ok(_) => locked,
+        Err(e) => Err(ConsumeError::from(e)),
+    }
+}
@@ -0,0 +1,245 @@
+//! A minimal migrations runner, recording applied versions in the `pgde_schema_version`
+//! table.
+use crate::locks::advisory_lock;
+use crate::{ConsumeError, RowConsumer};
+use std::collections::HashSet;
+use std::time::SystemTime;
+use tokio_postgres::error::SqlState;
+use tokio_postgres::{Client, Row};
+
+/// The advisory lock held while migrations run, so that concurrent runners apply each
+/// migration once.
+const MIGRATION_LOCK: i64 = 0x7067_6465_6d69_6772;
+
+/// A migration, whose SQL is applied in a transaction of its own, in ascending order of
+/// version.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Migration<'a> {
+    /// The version of the schema the migration produces.
+    pub version: i64,
+    /// A description of the migration, recorded with its version.
+    pub name: &'a str,
+    /// The statements of the migration, which must not begin or end transactions.
+    pub sql: &'a str,
+}
+
+/// A migration recorded in the `pgde_schema_version` table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AppliedMigration {
+    /// The version of the migration.
+    pub version: i64,
+    /// The name of the migration when it was applied.
+    pub name: String,
+    /// When the migration was applied.
+    pub applied_at: SystemTime,
+}
+
+impl RowConsumer for AppliedMigration {
+    const NAMED_COLUMNS: Option<&'static [&'static str]> = Some(&["version", "name", "applied_at"]);
+
+    fn from_row(row: Row) -> Result<Self, (Self, Vec<String>)>
+    where
+        Self: Sized,
+    {
+        let mut errors = Vec::new();
+
+        let mut migration = AppliedMigration {
+            version: 0,
+            name: String::new(),
+            applied_at: SystemTime::UNIX_EPOCH,
+        };
+
+        match row.try_get("version") {
+            Ok(v) => migration.version = v,
+            Err(_) => errors.push(String::from("AppliedMigration.version")),
+        };
+
+        match row.try_get("name") {
+            Ok(v) => migration.name = v,
+            Err(_) => errors.push(String::from("AppliedMigration.name")),
+        };
+
+        match row.try_get("applied_at") {
+            Ok(v) => migration.applied_at = v,
+            Err(_) => errors.push(String::from("AppliedMigration.applied_at")),
+        };
+
+        match errors.len() {
+            0 => Ok(migration),
+            _ => Err((migration, errors)),
+        }
+    }
+}
+
+/// The migrations already applied to a database, and those still to be applied.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MigrationPlan<'a> {
+    /// The migrations recorded as applied, in ascending order of version.
+    pub applied: Vec<AppliedMigration>,
+    /// The migrations not recorded as applied, in ascending order of version.
+    pub pending: Vec<Migration<'a>>,
+}
+
+impl MigrationPlan<'_> {
+    /// Returns whether no migrations are pending.
+    pub fn is_current(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+/// Returns the plan of applying `migrations`, without changing the database. Returns
+/// [ConsumeError::InvalidQuery] if two migrations share a version.
+pub async fn plan<'a>(
+    client: &Client,
+    migrations: &[Migration<'a>],
+) -> Result<MigrationPlan<'a>, ConsumeError> {
+    let mut seen = HashSet::new();
+
+    for migration in migrations {
+        if !seen.insert(migration.version) {
+            return Err(ConsumeError::InvalidQuery(format!(
+                "Migration version {} is used more than once",
+                migration.version
+            )));
+        }
+    }
+
+    let applied = match AppliedMigration::consume(
+        client,
+        "select version, name, applied_at from pgde_schema_version order by version;",
+        &[],
+    )
+    .await
+    {
+        Ok(v) => v,
+        Err(ConsumeError::DatabaseError { code, .. })
+            if code == SqlState::UNDEFINED_TABLE.code() =>
+        {
+            Vec::new()
+        }
+        Err(e) => return Err(e),
+    };
+
+    let versions = applied.iter().map(|v| v.version).collect::<HashSet<i64>>();
+
+    let mut pending = migrations
+        .iter()
+        .filter(|v| !versions.contains(&v.version))
+        .copied()
+        .collect::<Vec<Migration<'a>>>();
+
+    pending.sort_by_key(|v| v.version);
+
+    Ok(MigrationPlan { applied, pending })
+}
+
+/// Applies the pending `migrations`, each in a transaction that also records its version
+/// in the `pgde_schema_version` table, which is created if it does not exist. The
+/// returned plan lists the migrations applied by the run as pending. Runners are
+/// serialized by an advisory lock, so that concurrent runners apply each migration once.
+///
+/// A failed migration is rolled back and its error returned, leaving the migrations
+/// before it applied.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::migrate::{self, Migration};
+/// use tokio_postgres::NoTls;
+///
+/// const MIGRATIONS: &[Migration] = &[
+///     Migration {
+///         version: 1,
+///         name: "create accounts",
+///         sql: "create table if not exists accounts (id bigint primary key, name text not null);",
+///     },
+///     Migration {
+///         version: 2,
+///         name: "add account email",
+///         sql: "alter table accounts add column if not exists email text;",
+///     },
+/// ];
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         match migrate::run(&client, MIGRATIONS).await {
+///             Ok(v) => println!("Applied {} migrations", v.pending.len()),
+///             Err(_) => eprintln!("An error occurred while migrating database"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub async fn run<'a>(
+    client: &Client,
+    migrations: &[Migration<'a>],
+) -> Result<MigrationPlan<'a>, ConsumeError> {
+    let lock = advisory_lock(client, MIGRATION_LOCK).await?;
+
+    let result = apply(client, migrations).await;
+
+    match lock.unlock().await {
+        Ok(_) => result,
+        Err(e) => Err(e),
+    }
+}
+
+/// Plans and applies `migrations` while `run` holds the migration lock.
+async fn apply<'a>(
+    client: &Client,
+    migrations: &[Migration<'a>],
+) -> Result<MigrationPlan<'a>, ConsumeError> {
+    if let Err(e) = client
+        .batch_execute(
+            "create table if not exists pgde_schema_version (version bigint primary key, name text not null, applied_at timestamptz not null default now());",
+        )
+        .await
+    {
+        return Err(ConsumeError::from(e));
+    }
+
+    let plan = plan(client, migrations).await?;
+
+    for migration in &plan.pending {
+        if let Err(e) = client.batch_execute("begin;").await {
+            return Err(ConsumeError::from(e));
+        }
+
+        let result = match client.batch_execute(migration.sql).await {
+            Ok(_) => match client
+                .execute(
+                    "insert into pgde_schema_version (version, name) values ($1, $2);",
+                    &[&migration.version, &migration.name],
+                )
+                .await
+            {
+                Ok(_) => Ok(()),
+                Err(e) => Err(ConsumeError::from(e)),
+            },
+            Err(e) => Err(ConsumeError::from(e)),
+        };
+
+        let end = match result {
+            Ok(_) => "commit;",
+            Err(_) => "rollback;",
+        };
+
+        if let Err(e) = client.batch_execute(end).await {
+            return Err(ConsumeError::from(e));
+        }
+
+        result?;
+    }
+
+    Ok(plan)
+}
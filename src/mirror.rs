@@ -0,0 +1,195 @@
+//! Copying the rows of a table between databases with binary `COPY`, for fixture
+//! refreshes and moving data across environments.
+use crate::ident::quote_ident;
+use crate::{ConsumeError, Table};
+use futures_util::{SinkExt, StreamExt};
+
+/// Describes how `mirror_table` copies a table.
+///
+/// ## Example
+/// ```
+/// use pgde::MirrorOptions;
+///
+/// let options = MirrorOptions::new()
+///     .truncate(true)
+///     .filter("\"CreatedAt\" > now() - interval '7 days'");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MirrorOptions {
+    truncate: bool,
+    filter: Option<String>,
+    destination: Option<String>,
+}
+
+impl MirrorOptions {
+    /// Copies every row, appending to the destination table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Empties the destination table before copying, in the same transaction, so that
+    /// it holds exactly the source's rows once the copy commits.
+    pub fn truncate(mut self, truncate: bool) -> Self {
+        self.truncate = truncate;
+        self
+    }
+
+    /// Copies only the rows matching `filter`, a where clause on the source table.
+    pub fn filter(mut self, filter: &str) -> Self {
+        self.filter = Some(String::from(filter));
+        self
+    }
+
+    /// Copies into `table` in place of the source table's name, as written in a query.
+    pub fn destination(mut self, table: &str) -> Self {
+        self.destination = Some(String::from(table));
+        self
+    }
+}
+
+/// Copies the rows of `table` in the database of `src` into the table of the same name
+/// in the database of `dst`, returning the number of rows copied. `table` is the name as
+/// written in a query, e.g. from `Table::qualified_name`. Only the columns of `T` are
+/// copied, and they must have the same types in both tables.
+///
+/// Rows are streamed in PostgreSQL's binary `COPY` format from one connection to the
+/// other without being decoded, and are written in a single transaction of `dst`, which
+/// is rolled back if the copy fails. `dst` must not already be in a transaction.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::{mirror_table, MirrorOptions, Table};
+/// use pgde_derive::RowConsumer;
+/// use tokio_postgres::{NoTls, Row};
+///
+/// #[derive(RowConsumer)]
+/// struct Foo {
+///     Id: i32,
+///     Data: String,
+/// }
+///
+/// let conn_string = "host=localhost user=postgres password=password dbname=postgres";
+///
+/// match (tokio_postgres::connect(conn_string, NoTls).await, tokio_postgres::connect(conn_string, NoTls).await) {
+///     (Ok(src), Ok(dst)) => {
+///         let (src, src_conn) = src;
+///         let (dst, dst_conn) = dst;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = src_conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = dst_conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         let options = MirrorOptions::new().truncate(true).destination("public.\"FooFixture\"");
+///
+///         match mirror_table::<Foo>(&src, &dst, &Foo::qualified_name(), options).await {
+///             Ok(v) => println!("Mirrored {} rows", v),
+///             Err(_) => eprintln!("An error occurred while mirroring table"),
+///         };
+///     },
+///     _ => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub async fn mirror_table<T: Table>(
+    src: &tokio_postgres::Client,
+    dst: &tokio_postgres::Client,
+    table: &str,
+    options: MirrorOptions,
+) -> Result<u64, ConsumeError> {
+    let columns = T::COLUMNS
+        .iter()
+        .map(|v| quote_ident(v))
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    let copy_out = match &options.filter {
+        Some(v) => format!(
+            "copy (select {} from {} where {}) to stdout with (format binary)",
+            columns, table, v
+        ),
+        None => format!(
+            "copy {} ({}) to stdout with (format binary)",
+            table, columns
+        ),
+    };
+
+    let destination = options.destination.as_deref().unwrap_or(table);
+
+    if let Err(e) = dst.batch_execute("begin;").await {
+        return Err(ConsumeError::from(e));
+    }
+
+    let result = copy_rows(src, dst, &copy_out, destination, &columns, options.truncate).await;
+
+    let end = match result {
+        Ok(_) => "commit;",
+        Err(_) => "rollback;",
+    };
+
+    match dst.batch_execute(end).await {
+        Ok(_) => result,
+        Err(e) => Err(ConsumeError::from(e)),
+    }
+}
+
+/// Streams the output of `copy_out` on `src` into `destination` on `dst`, inside the
+/// transaction opened by `mirror_table`.
+async fn copy_rows(
+    src: &tokio_postgres::Client,
+    dst: &tokio_postgres::Client,
+    copy_out: &str,
+    destination: &str,
+    columns: &str,
+    truncate: bool,
+) -> Result<u64, ConsumeError> {
+    if truncate {
+        let query = format!("truncate {};", destination);
+
+        if let Err(e) = dst.batch_execute(&query).await {
+            return Err(ConsumeError::from(e));
+        }
+    }
+
+    let rows = match src.copy_out(copy_out).await {
+        Ok(v) => v,
+        Err(e) => return Err(ConsumeError::from(e)),
+    };
+
+    let copy_in = format!(
+        "copy {} ({}) from stdin with (format binary)",
+        destination, columns
+    );
+
+    let sink = match dst.copy_in(&copy_in).await {
+        Ok(v) => v,
+        Err(e) => return Err(ConsumeError::from(e)),
+    };
+
+    let mut rows = std::pin::pin!(rows);
+    let mut sink = std::pin::pin!(sink);
+
+    while let Some(chunk) = rows.next().await {
+        let chunk = match chunk {
+            Ok(v) => v,
+            Err(e) => return Err(ConsumeError::from(e)),
+        };
+
+        if let Err(e) = sink.send(chunk).await {
+            return Err(ConsumeError::from(e));
+        }
+    }
+
+    match sink.as_mut().finish().await {
+        Ok(v) => Ok(v),
+        Err(e) => Err(ConsumeError::from(e)),
+    }
+}
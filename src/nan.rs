@@ -0,0 +1,48 @@
+//! Support for deciding how the `NaN` values of floating point columns are consumed.
+
+/// How a `NaN` read from a `float4`, `float8`, or `numeric` column is consumed. Used by
+/// the `#[pgde(nan(...))]` field attribute of the derive macro.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NanPolicy {
+    /// Keeps `NaN` as the value of the field.
+    #[default]
+    Accept,
+    /// Reads `NaN` as `None` into an `Option` field, and fails for other fields.
+    AsNone,
+    /// Fails to convert `NaN`.
+    Error,
+}
+
+/// Application of a [NanPolicy] to a value read into the implementing type. Returns
+/// `None` when the value cannot be consumed under the policy.
+pub trait NanColumn: Sized {
+    /// Applies `policy` to `value`, returning it unchanged unless it is `NaN`.
+    fn from_nan(value: Self, policy: NanPolicy) -> Option<Self>;
+}
+
+impl<T: NanColumn> NanColumn for Option<T> {
+    fn from_nan(value: Self, policy: NanPolicy) -> Option<Self> {
+        match (value, policy) {
+            (Some(v), NanPolicy::AsNone) => Some(T::from_nan(v, NanPolicy::Error)),
+            (Some(v), _) => T::from_nan(v, policy).map(Some),
+            (None, _) => Some(None),
+        }
+    }
+}
+
+/// Implements `NanColumn` for a floating point type.
+macro_rules! nan_column {
+    ($t:ty) => {
+        impl NanColumn for $t {
+            fn from_nan(value: Self, policy: NanPolicy) -> Option<Self> {
+                match (value.is_nan(), policy) {
+                    (false, _) | (true, NanPolicy::Accept) => Some(value),
+                    (true, _) => None,
+                }
+            }
+        }
+    };
+}
+
+nan_column!(f32);
+nan_column!(f64);
@@ -0,0 +1,131 @@
+//! Support for consuming integer and floating point columns into numeric types of a
+//! different width than the column's.
+use std::error::Error;
+use tokio_postgres::types::{FromSql, Type};
+
+/// The value of an `int2`, `int4`, `int8`, `float4`, or `float8` column, keeping the
+/// width of the column it was read from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Number {
+    Int2(i16),
+    Int4(i32),
+    Int8(i64),
+    Float4(f32),
+    Float8(f64),
+}
+
+impl<'a> FromSql<'a> for Number {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        match *ty {
+            Type::INT2 => Ok(Number::Int2(i16::from_sql(ty, raw)?)),
+            Type::INT4 => Ok(Number::Int4(i32::from_sql(ty, raw)?)),
+            Type::INT8 => Ok(Number::Int8(i64::from_sql(ty, raw)?)),
+            Type::FLOAT4 => Ok(Number::Float4(f32::from_sql(ty, raw)?)),
+            Type::FLOAT8 => Ok(Number::Float8(f64::from_sql(ty, raw)?)),
+            _ => Err("unsupported number type".into()),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(
+            *ty,
+            Type::INT2 | Type::INT4 | Type::INT8 | Type::FLOAT4 | Type::FLOAT8
+        )
+    }
+}
+
+/// Conversion of numeric columns into a numeric type of a different width. Used by the
+/// `#[pgde(narrow)]` and `#[pgde(widen)]` field attributes of the derive macro. Returns
+/// `None` when a value is NULL or cannot be converted.
+pub trait NumericColumn: Sized {
+    /// Converts a nullable number from a column at least as wide as the implementing
+    /// type, failing for values out of its range. Integers are only narrowed into
+    /// integers, and floating point numbers into floating point numbers.
+    fn from_narrowed(value: Option<Number>) -> Option<Self>;
+
+    /// Converts a nullable number from a column no wider than the implementing type.
+    /// Integers may be widened into floating point numbers, in which case `int8` values
+    /// beyond 2^53 are rounded.
+    fn from_widened(value: Option<Number>) -> Option<Self>;
+}
+
+impl<T: NumericColumn> NumericColumn for Option<T> {
+    fn from_narrowed(value: Option<Number>) -> Option<Self> {
+        match value {
+            Some(_) => T::from_narrowed(value).map(Some),
+            None => Some(None),
+        }
+    }
+
+    fn from_widened(value: Option<Number>) -> Option<Self> {
+        match value {
+            Some(_) => T::from_widened(value).map(Some),
+            None => Some(None),
+        }
+    }
+}
+
+/// Implements `NumericColumn` for an integer type, given the columns it widens from.
+macro_rules! integer_column {
+    ($t:ty, widen = [$($variant:ident),*]) => {
+        impl NumericColumn for $t {
+            fn from_narrowed(value: Option<Number>) -> Option<Self> {
+                match value? {
+                    Number::Int2(v) => <$t>::try_from(v).ok(),
+                    Number::Int4(v) => <$t>::try_from(v).ok(),
+                    Number::Int8(v) => <$t>::try_from(v).ok(),
+                    _ => None,
+                }
+            }
+
+            fn from_widened(value: Option<Number>) -> Option<Self> {
+                match value? {
+                    $(Number::$variant(v) => Some(<$t>::from(v)),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+integer_column!(i16, widen = [Int2]);
+integer_column!(i32, widen = [Int2, Int4]);
+integer_column!(i64, widen = [Int2, Int4, Int8]);
+
+impl NumericColumn for f32 {
+    fn from_narrowed(value: Option<Number>) -> Option<Self> {
+        match value? {
+            Number::Float4(v) => Some(v),
+            Number::Float8(v) if !v.is_finite() || v.abs() <= f32::MAX as f64 => Some(v as f32),
+            _ => None,
+        }
+    }
+
+    fn from_widened(value: Option<Number>) -> Option<Self> {
+        match value? {
+            Number::Int2(v) => Some(f32::from(v)),
+            Number::Float4(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl NumericColumn for f64 {
+    fn from_narrowed(value: Option<Number>) -> Option<Self> {
+        match value? {
+            Number::Float4(v) => Some(f64::from(v)),
+            Number::Float8(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    fn from_widened(value: Option<Number>) -> Option<Self> {
+        match value? {
+            Number::Int2(v) => Some(f64::from(v)),
+            Number::Int4(v) => Some(f64::from(v)),
+            Number::Int8(v) => Some(v as f64),
+            Number::Float4(v) => Some(f64::from(v)),
+            Number::Float8(v) => Some(v),
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! Decoding helpers for PostgreSQL's binary `numeric` representation, which
+//! tokio_postgres does not provide a `FromSql` implementation for, and support for
+//! consuming `numeric` columns into floating point numbers with an explicit policy for
+//! values a float cannot hold exactly.
+use std::cmp::Ordering;
+use std::error::Error;
+use tokio_postgres::types::{FromSql, Type};
+
+const NUMERIC_NEG: u16 = 0x4000;
+const NUMERIC_NAN: u16 = 0xC000;
+const NUMERIC_PINF: u16 = 0xD000;
+const NUMERIC_NINF: u16 = 0xF000;
+
+/// Reads a big endian `u16` at the provided offset.
+fn read_u16(raw: &[u8], offset: usize) -> Result<u16, Box<dyn Error + Sync + Send>> {
+    match raw.get(offset..offset + 2) {
+        Some(v) => Ok(u16::from_be_bytes([v[0], v[1]])),
+        None => Err("numeric value is truncated".into()),
+    }
+}
+
+/// Converts the binary representation of a `numeric` into its exact decimal text, e.g.
+/// `-12.3400`, `NaN`, or `Infinity`.
+pub(crate) fn numeric_to_string(raw: &[u8]) -> Result<String, Box<dyn Error + Sync + Send>> {
+    let ndigits = read_u16(raw, 0)? as i32;
+    let weight = read_u16(raw, 2)? as i16 as i32;
+    let sign = read_u16(raw, 4)?;
+    let dscale = read_u16(raw, 6)? as usize;
+
+    match sign {
+        NUMERIC_NAN => return Ok(String::from("NaN")),
+        NUMERIC_PINF => return Ok(String::from("Infinity")),
+        NUMERIC_NINF => return Ok(String::from("-Infinity")),
+        _ => (),
+    }
+
+    let mut digits = Vec::with_capacity(ndigits as usize);
+
+    for i in 0..ndigits {
+        digits.push(read_u16(raw, 8 + i as usize * 2)?);
+    }
+
+    let digit_at = |i: i32| match i >= 0 && i < ndigits {
+        true => digits[i as usize],
+        false => 0,
+    };
+
+    let mut value = String::new();
+
+    if sign == NUMERIC_NEG {
+        value.push('-');
+    }
+
+    match weight < 0 {
+        true => value.push('0'),
+        false => {
+            value.push_str(&digit_at(0).to_string());
+
+            for i in 1..=weight {
+                value.push_str(&format!("{:04}", digit_at(i)));
+            }
+        }
+    }
+
+    if dscale > 0 {
+        let mut fraction = String::with_capacity(dscale + 4);
+        let mut i = weight + 1;
+
+        while fraction.len() < dscale {
+            fraction.push_str(&format!("{:04}", digit_at(i)));
+            i += 1;
+        }
+
+        fraction.truncate(dscale);
+        value.push('.');
+        value.push_str(&fraction);
+    }
+
+    Ok(value)
+}
+
+/// Converts the binary representation of a `numeric` into the nearest `f64`. Values
+/// outside of the `f64` range become infinite, as PostgreSQL does when casting to
+/// `float8`.
+pub(crate) fn numeric_to_f64(raw: &[u8]) -> Result<f64, Box<dyn Error + Sync + Send>> {
+    Ok(numeric_to_string(raw)?.parse::<f64>()?)
+}
+
+/// The exact decimal text of a `numeric` column, e.g. `-12.3400`, `NaN`, or `Infinity`.
+/// Used by the `#[pgde(numeric)]` field attribute of the derive macro.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Numeric(pub String);
+
+impl<'a> FromSql<'a> for Numeric {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Ok(Numeric(numeric_to_string(raw)?))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::NUMERIC
+    }
+}
+
+/// How a `numeric` value that a floating point number cannot hold exactly, such as
+/// `0.1`, is consumed. Values beyond the range of the float fail with every policy
+/// other than `Truncate`, and `NaN` and the infinities are always kept.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PrecisionPolicy {
+    /// Fails to convert the value.
+    #[default]
+    Error,
+    /// Converts the value to the nearest float.
+    Round,
+    /// Converts the value to the nearest float toward zero, or to the largest finite
+    /// float of its sign for values beyond the range of the float.
+    Truncate,
+}
+
+/// Conversion of `numeric` columns into floating point numbers. Used by the
+/// `#[pgde(numeric)]` field attribute of the derive macro. Returns `None` when a value is
+/// NULL or cannot be converted under the given policy.
+pub trait FloatColumn: Sized {
+    /// Converts a nullable `numeric` into the implementing type, applying `policy` when
+    /// the value cannot be held exactly.
+    fn from_numeric(value: Option<Numeric>, policy: PrecisionPolicy) -> Option<Self>;
+}
+
+impl<T: FloatColumn> FloatColumn for Option<T> {
+    fn from_numeric(value: Option<Numeric>, policy: PrecisionPolicy) -> Option<Self> {
+        match value {
+            Some(_) => T::from_numeric(value, policy).map(Some),
+            None => Some(None),
+        }
+    }
+}
+
+/// Removes the sign, the trailing zeros of the fraction, and a trailing decimal point
+/// from decimal text, returning whether it was negative and its magnitude, e.g.
+/// `(true, "12.34")` for `-12.3400`.
+fn decimal_magnitude(text: &str) -> (bool, &str) {
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(v) => (true, v),
+        None => (false, text),
+    };
+
+    let text = match text.contains('.') {
+        true => text.trim_end_matches('0').trim_end_matches('.'),
+        false => text,
+    };
+
+    (negative && text != "0", text)
+}
+
+/// Compares the magnitudes of two decimals, as returned by [decimal_magnitude].
+fn compare_magnitudes(a: &str, b: &str) -> Ordering {
+    let (a_whole, a_fraction) = a.split_once('.').unwrap_or((a, ""));
+    let (b_whole, b_fraction) = b.split_once('.').unwrap_or((b, ""));
+
+    a_whole
+        .len()
+        .cmp(&b_whole.len())
+        .then_with(|| a_whole.cmp(b_whole))
+        .then_with(|| a_fraction.cmp(b_fraction))
+}
+
+/// Implements `FloatColumn` for a floating point type, given the number of fraction
+/// digits needed to write any of its values exactly.
+macro_rules! float_column {
+    ($t:ty, $digits:expr) => {
+        impl FloatColumn for $t {
+            fn from_numeric(value: Option<Numeric>, policy: PrecisionPolicy) -> Option<Self> {
+                let value = value?.0;
+                let nearest = value.parse::<$t>().ok()?;
+
+                if !nearest.is_finite() {
+                    return match (value.as_str(), policy) {
+                        ("NaN" | "Infinity" | "-Infinity", _) => Some(nearest),
+                        (_, PrecisionPolicy::Truncate) => Some(<$t>::MAX.copysign(nearest)),
+                        _ => None,
+                    };
+                }
+
+                let exact = format!("{:.*}", $digits, nearest);
+                let (_, nearest_magnitude) = decimal_magnitude(&exact);
+                let (_, magnitude) = decimal_magnitude(&value);
+
+                match (compare_magnitudes(nearest_magnitude, magnitude), policy) {
+                    (Ordering::Equal, _) | (_, PrecisionPolicy::Round) => Some(nearest),
+                    (Ordering::Greater, PrecisionPolicy::Truncate) => {
+                        Some(<$t>::from_bits(nearest.to_bits() - 1))
+                    }
+                    (Ordering::Less, PrecisionPolicy::Truncate) => Some(nearest),
+                    (_, PrecisionPolicy::Error) => None,
+                }
+            }
+        }
+    };
+}
+
+float_column!(f32, 150);
+float_column!(f64, 1075);
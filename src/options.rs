@@ -0,0 +1,387 @@
+//! Per-call configuration of row consumption, used by `consume_with_options` and
+//! `consume_filtered`.
+use crate::{check_columns, ConsumeError, ConversionReport, RowConsumer};
+use futures_util::StreamExt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio_postgres::types::ToSql;
+use tokio_postgres::Client;
+
+/// How errors are reported when a row fails conversion and [BadRowPolicy::Fail] is in
+/// effect.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Stops at the first row that fails conversion and returns
+    /// [ConsumeError::ConversionError].
+    #[default]
+    FailFast,
+    /// Converts every row and returns [ConsumeError::ConversionErrors] with the errors of
+    /// every row that failed conversion.
+    CollectAll,
+    /// Converts every row and returns [ConsumeError::ConversionReport] with the errors of
+    /// every row that failed conversion and the number of failures of every field, split
+    /// into NULLs read into non-`Option` fields, mistyped columns, and rejected values.
+    /// Every row is read twice, once to classify the fields that fail conversion.
+    Report,
+}
+
+/// What happens to rows that fail conversion.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BadRowPolicy {
+    /// Fails the whole call, as `consume` does.
+    #[default]
+    Fail,
+    /// Keeps the partially converted row, whose failed fields hold default values.
+    KeepDefaults,
+    /// Drops the row from the result.
+    Skip,
+}
+
+/// A callback given the number of rows read so far and the time elapsed since the query
+/// was sent, set with `ConsumeOptions::on_progress`.
+#[derive(Clone)]
+pub(crate) struct ProgressHook {
+    every: usize,
+    hook: Arc<dyn Fn(usize, Duration) + Send + Sync>,
+}
+
+impl ProgressHook {
+    /// Calls the hook.
+    fn report(&self, rows_done: usize, started: Instant) {
+        (self.hook)(rows_done, started.elapsed());
+    }
+}
+
+impl std::fmt::Debug for ProgressHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProgressHook")
+            .field("every", &self.every)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for ProgressHook {
+    fn eq(&self, other: &Self) -> bool {
+        self.every == other.every && Arc::ptr_eq(&self.hook, &other.hook)
+    }
+}
+
+impl Eq for ProgressHook {}
+
+/// A query that failed in the database, passed to the hook set with
+/// `ConsumeOptions::on_error`, so that failures may be reproduced without guessing the
+/// values that were bound.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FailedQuery<'a> {
+    /// The text of the query, as sent.
+    pub query: &'a str,
+    /// The bound parameters in order, rendered with their `Debug` implementation and cut
+    /// to 64 characters, or `[redacted]` for parameters marked with
+    /// `ConsumeOptions::redact_params`.
+    pub params: &'a [String],
+    /// The error reported by the database or connection.
+    pub message: &'a str,
+}
+
+/// A callback given a failed query, set with `ConsumeOptions::on_error`.
+#[derive(Clone)]
+pub(crate) struct ErrorHook {
+    hook: Arc<dyn Fn(&FailedQuery<'_>) + Send + Sync>,
+}
+
+impl ErrorHook {
+    /// Renders the parameters of a failed query and calls the hook.
+    fn report(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+        redacted: &[usize],
+        error: &tokio_postgres::Error,
+    ) {
+        let params = params
+            .iter()
+            .enumerate()
+            .map(|(i, v)| match redacted.contains(&i) {
+                true => String::from("[redacted]"),
+                false => render_param(*v),
+            })
+            .collect::<Vec<String>>();
+
+        (self.hook)(&FailedQuery {
+            query,
+            params: &params,
+            message: &match error.as_db_error() {
+                Some(v) => String::from(v.message()),
+                None => error.to_string(),
+            },
+        });
+    }
+}
+
+impl std::fmt::Debug for ErrorHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ErrorHook").finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for ErrorHook {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.hook, &other.hook)
+    }
+}
+
+impl Eq for ErrorHook {}
+
+/// Renders a parameter with its `Debug` implementation, cut to 64 characters.
+fn render_param(value: &(dyn ToSql + Sync)) -> String {
+    let rendered = format!("{:?}", value);
+
+    match rendered.char_indices().nth(64) {
+        Some((i, _)) => format!("{}...", &rendered[..i]),
+        None => rendered,
+    }
+}
+
+/// Options for a single call to `consume_with_options`, built up from
+/// `ConsumeOptions::new()`.
+///
+/// ## Example
+/// ```
+/// use pgde::{BadRowPolicy, ConsumeOptions};
+///
+/// let options = ConsumeOptions::new()
+///     .bad_rows(BadRowPolicy::Skip)
+///     .max_rows(100)
+///     .trace_tag("nightly-report");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConsumeOptions {
+    pub(crate) error_policy: ErrorPolicy,
+    pub(crate) bad_rows: BadRowPolicy,
+    pub(crate) max_rows: Option<usize>,
+    pub(crate) label: Option<String>,
+    pub(crate) trace_tag: Option<String>,
+    pub(crate) progress: Option<ProgressHook>,
+    pub(crate) check_types: bool,
+    pub(crate) on_error: Option<ErrorHook>,
+    pub(crate) redacted_params: Vec<usize>,
+}
+
+impl ConsumeOptions {
+    /// Creates options that behave like `consume`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how conversion errors are reported. Defaults to [ErrorPolicy::FailFast].
+    pub fn error_policy(mut self, error_policy: ErrorPolicy) -> Self {
+        self.error_policy = error_policy;
+        self
+    }
+
+    /// Sets what happens to rows that fail conversion. Defaults to [BadRowPolicy::Fail].
+    pub fn bad_rows(mut self, bad_rows: BadRowPolicy) -> Self {
+        self.bad_rows = bad_rows;
+        self
+    }
+
+    /// Stops reading rows once `max_rows` rows have been consumed.
+    pub fn max_rows(mut self, max_rows: usize) -> Self {
+        self.max_rows = Some(max_rows);
+        self
+    }
+
+    /// Labels the statement. The label is sent ahead of the query in a comment, so that
+    /// it appears in server logs and `pg_stat_activity`. It does not name the prepared
+    /// statement, whose server side name is chosen by tokio-postgres.
+    pub fn label(mut self, label: &str) -> Self {
+        self.label = Some(String::from(label));
+        self
+    }
+
+    /// Tags the call for tracing. The tag is sent ahead of the query in a comment, so
+    /// that it appears in server logs and `pg_stat_activity`.
+    pub fn trace_tag(mut self, trace_tag: &str) -> Self {
+        self.trace_tag = Some(String::from(trace_tag));
+        self
+    }
+
+    /// Calls `hook` with the number of rows read so far and the time elapsed since the
+    /// query was sent, after every `every` rows and once more when reading finishes, so
+    /// that long-running exports can drive progress bars and heartbeat logs.
+    pub fn on_progress(
+        mut self,
+        every: usize,
+        hook: impl Fn(usize, Duration) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(ProgressHook {
+            every: every.max(1),
+            hook: Arc::new(hook),
+        });
+        self
+    }
+
+    /// Checks the types of the query's columns against the consuming type once, before
+    /// converting any row, failing with [ConsumeError::ColumnTypeMismatch] if a column
+    /// cannot be read rather than with a conversion error for every row.
+    pub fn check_types(mut self, check_types: bool) -> Self {
+        self.check_types = check_types;
+        self
+    }
+
+    /// Calls `hook` with the query, its rendered parameters, and the database's error
+    /// message when the query fails in the database, e.g. to log what was bound to a
+    /// query that returned [ConsumeError::DatabaseError]. Conversion errors are not
+    /// reported.
+    pub fn on_error(mut self, hook: impl Fn(&FailedQuery<'_>) + Send + Sync + 'static) -> Self {
+        self.on_error = Some(ErrorHook {
+            hook: Arc::new(hook),
+        });
+        self
+    }
+
+    /// Renders the parameters at the zero based positions `indices` as `[redacted]` in
+    /// the queries passed to `on_error`, e.g. passwords and tokens.
+    pub fn redact_params(mut self, indices: &[usize]) -> Self {
+        self.redacted_params = indices.to_vec();
+        self
+    }
+
+    /// Returns the query with the label and trace tag prepended as a comment, breaking up
+    /// `*/` and `/*` so that they can neither close the comment nor open a nested one.
+    pub(crate) fn annotate(&self, query: &str) -> String {
+        let labels = [
+            self.label.as_ref().map(|v| format!("label={}", v)),
+            self.trace_tag.as_ref().map(|v| format!("tag={}", v)),
+        ];
+
+        let labels: Vec<String> = labels.into_iter().flatten().collect();
+
+        match labels.len() {
+            0 => String::from(query),
+            _ => format!(
+                "/* {} */ {}",
+                labels.join(" ").replace("*/", "* /").replace("/*", "/ *"),
+                query
+            ),
+        }
+    }
+}
+
+/// The rows kept by `consume_filtered`, along with the number of rows that were dropped
+/// because they failed conversion.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FilteredRows<T> {
+    pub rows: Vec<T>,
+    pub skipped: usize,
+}
+
+/// The implementation of `consume_with_options` and `consume_filtered`, returning the
+/// consumed rows and the number of rows skipped under [BadRowPolicy::Skip].
+pub(crate) async fn consume_rows<T: RowConsumer + Send>(
+    conn: &Client,
+    query: &str,
+    params: &[&(dyn ToSql + Sync)],
+    options: &ConsumeOptions,
+) -> Result<FilteredRows<T>, ConsumeError> {
+    let query = options.annotate(query);
+    let started = Instant::now();
+
+    #[cfg(feature = "dev_guard")]
+    crate::guard::record(&query);
+
+    let stream = match conn.query_raw(&query, params.iter().copied()).await {
+        Ok(v) => v,
+        Err(e) => {
+            if let Some(hook) = options.on_error.as_ref() {
+                hook.report(&query, params, &options.redacted_params, &e);
+            }
+
+            return Err(ConsumeError::from(e));
+        }
+    };
+
+    let mut stream = std::pin::pin!(stream);
+    let mut data = Vec::new();
+    let mut errors = Vec::new();
+    let mut report = ConversionReport::default();
+    let mut skipped = 0;
+    let mut row_index = 0;
+
+    while options.max_rows.is_none_or(|v| data.len() < v) {
+        let row = match stream.next().await {
+            Some(Ok(v)) => v,
+            Some(Err(e)) => {
+                if let Some(hook) = options.on_error.as_ref() {
+                    hook.report(&query, params, &options.redacted_params, &e);
+                }
+
+                return Err(ConsumeError::from(e));
+            }
+            None => break,
+        };
+
+        if row_index == 0 {
+            check_columns::<T>(row.columns())?;
+
+            if options.check_types {
+                let errors = T::check_column_types(row.columns());
+
+                if !errors.is_empty() {
+                    return Err(ConsumeError::ColumnTypeMismatch(errors));
+                }
+            }
+        }
+
+        // Fields are classified before conversion, which consumes the row.
+        let failures = match (options.bad_rows, options.error_policy) {
+            (BadRowPolicy::Fail, ErrorPolicy::Report) => T::field_failures(&row),
+            _ => Vec::new(),
+        };
+
+        match (T::from_row(row), options.bad_rows, options.error_policy) {
+            (Ok(v), _, _) => data.push(v),
+            (Err((v, _)), BadRowPolicy::KeepDefaults, _) => data.push(v),
+            (Err(_), BadRowPolicy::Skip, _) => skipped += 1,
+            (Err(_), BadRowPolicy::Fail, ErrorPolicy::FailFast) => {
+                return Err(ConsumeError::ConversionError)
+            }
+            (Err((_, v)), BadRowPolicy::Fail, ErrorPolicy::CollectAll) => {
+                errors.extend(v.into_iter().map(|v| format!("Row {}: {}", row_index, v)))
+            }
+            (Err((_, v)), BadRowPolicy::Fail, ErrorPolicy::Report) => {
+                for (field, failure) in failures {
+                    report.record(field, failure);
+                }
+
+                errors.extend(v.into_iter().map(|v| format!("Row {}: {}", row_index, v)))
+            }
+        }
+
+        row_index += 1;
+
+        if let Some(progress) = options.progress.as_ref() {
+            if row_index % progress.every == 0 {
+                progress.report(row_index, started);
+            }
+        }
+    }
+
+    if let Some(progress) = options.progress.as_ref() {
+        if row_index == 0 || row_index % progress.every != 0 {
+            progress.report(row_index, started);
+        }
+    }
+
+    match (errors.len(), options.error_policy) {
+        (0, _) => Ok(FilteredRows {
+            rows: data,
+            skipped,
+        }),
+        (_, ErrorPolicy::Report) => Err(ConsumeError::ConversionReport(ConversionReport {
+            errors,
+            ..report
+        })),
+        _ => Err(ConsumeError::ConversionErrors(errors)),
+    }
+}
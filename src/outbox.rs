@@ -0,0 +1,251 @@
+//! The transactional outbox pattern: messages written to a table in the same transaction
+//! as the changes they describe, and relayed by workers that claim them in batches.
+use crate::ident::quote_ident;
+use crate::{ConsumeError, RowConsumer};
+use futures_util::future::BoxFuture;
+use std::time::SystemTime;
+use tokio_postgres::{Client, Row, Transaction};
+
+/// A message of an [Outbox] table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutboxMessage {
+    /// The order the message was written in.
+    pub id: i64,
+    /// Where the message is relayed to, e.g. a topic or queue name.
+    pub topic: String,
+    /// The body of the message, e.g. serialized JSON.
+    pub payload: String,
+    /// When the message was written.
+    pub created_at: SystemTime,
+}
+
+impl RowConsumer for OutboxMessage {
+    const NAMED_COLUMNS: Option<&'static [&'static str]> =
+        Some(&["id", "topic", "payload", "created_at"]);
+
+    fn from_row(row: Row) -> Result<Self, (Self, Vec<String>)>
+    where
+        Self: Sized,
+    {
+        let mut errors = Vec::new();
+
+        let mut message = OutboxMessage {
+            id: 0,
+            topic: String::new(),
+            payload: String::new(),
+            created_at: SystemTime::UNIX_EPOCH,
+        };
+
+        match row.try_get("id") {
+            Ok(v) => message.id = v,
+            Err(_) => errors.push(String::from("OutboxMessage.id")),
+        };
+
+        match row.try_get("topic") {
+            Ok(v) => message.topic = v,
+            Err(_) => errors.push(String::from("OutboxMessage.topic")),
+        };
+
+        match row.try_get("payload") {
+            Ok(v) => message.payload = v,
+            Err(_) => errors.push(String::from("OutboxMessage.payload")),
+        };
+
+        match row.try_get("created_at") {
+            Ok(v) => message.created_at = v,
+            Err(_) => errors.push(String::from("OutboxMessage.created_at")),
+        };
+
+        match errors.len() {
+            0 => Ok(message),
+            _ => Err((message, errors)),
+        }
+    }
+}
+
+/// An outbox table, holding messages that are written in the transaction of the changes
+/// they describe with `enqueue`, so that a message is relayed if and only if its changes
+/// commit. Workers claim unprocessed messages in batches with `process`, which locks them
+/// with `FOR UPDATE SKIP LOCKED` so that concurrent workers take different messages, and
+/// marks them processed in the same transaction once relayed.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::outbox::Outbox;
+/// use pgde::ConsumeError;
+/// use tokio_postgres::NoTls;
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let mut client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         let outbox = Outbox::new("pgde_outbox");
+///
+///         if outbox.create_table(&client).await.is_err() {
+///             eprintln!("Could not create outbox");
+///         }
+///
+///         let result = outbox
+///             .process(&mut client, 100, |_, messages| {
+///                 Box::pin(async move {
+///                     for message in messages {
+///                         println!("Relaying {} to {}", message.id, message.topic);
+///                     }
+///
+///                     Ok::<(), ConsumeError>(())
+///                 })
+///             })
+///             .await;
+///
+///         match result {
+///             Ok(v) => println!("Relayed {} messages", v),
+///             Err(_) => eprintln!("An error occurred while relaying messages"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Outbox {
+    schema: Option<String>,
+    name: String,
+    table: String,
+}
+
+impl Outbox {
+    /// Uses the outbox table `table`, in the schemas of the `search_path`.
+    pub fn new(table: &str) -> Self {
+        Outbox {
+            schema: None,
+            name: String::from(table),
+            table: quote_ident(table),
+        }
+    }
+
+    /// Uses the outbox table `table` in `schema`.
+    pub fn with_schema(schema: &str, table: &str) -> Self {
+        Outbox {
+            schema: Some(String::from(schema)),
+            name: String::from(table),
+            table: format!("{}.{}", quote_ident(schema), quote_ident(table)),
+        }
+    }
+
+    /// Returns the schema of the table, if set.
+    pub fn schema(&self) -> Option<&str> {
+        self.schema.as_deref()
+    }
+
+    /// Returns the quoted, schema-qualified name of the table.
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    /// Creates the table and an index on its unprocessed messages, if they do not exist.
+    pub async fn create_table(&self, client: &Client) -> Result<(), ConsumeError> {
+        let index = quote_ident(&format!("{}_unprocessed", self.name));
+
+        let query = format!(
+            "create table if not exists {table} (
+                id bigint generated always as identity primary key,
+                topic text not null,
+                payload text not null,
+                created_at timestamptz not null default now(),
+                processed_at timestamptz
+            );
+            create index if not exists {index} on {table} (id) where processed_at is null;",
+            table = self.table,
+            index = index
+        );
+
+        match client.batch_execute(&query).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(ConsumeError::from(e)),
+        }
+    }
+
+    /// Writes a message, returning its id. Pass `Transaction::client` to write it in the
+    /// transaction of the changes it describes.
+    pub async fn enqueue(
+        &self,
+        client: &Client,
+        topic: &str,
+        payload: &str,
+    ) -> Result<i64, ConsumeError> {
+        let query = format!(
+            "insert into {} (topic, payload) values ($1, $2) returning id;",
+            self.table
+        );
+
+        crate::consume_scalar(client, &query, &[&topic, &payload]).await
+    }
+
+    /// Returns the query claiming up to `$1` unprocessed messages, oldest first, skipping
+    /// messages locked by other workers.
+    pub fn poll_query(&self) -> String {
+        format!(
+            "select id, topic, payload, created_at from {} where processed_at is null order by id limit $1 for update skip locked;",
+            self.table
+        )
+    }
+
+    /// Marks messages as processed, e.g. within the transaction that claimed them with
+    /// `poll_query`.
+    pub async fn mark_processed(&self, client: &Client, ids: &[i64]) -> Result<u64, ConsumeError> {
+        let query = format!(
+            "update {} set processed_at = now() where id = any($1);",
+            self.table
+        );
+
+        crate::execute(client, &query, &[&ids]).await
+    }
+
+    /// Claims up to `batch_size` unprocessed messages in a new transaction of `client` and
+    /// passes them to `f`. When `f` succeeds, the messages are marked processed and the
+    /// transaction is committed, returning the number of messages processed. When it
+    /// fails, the transaction is rolled back, leaving the messages to be claimed again.
+    /// `f` is not called when no messages are pending.
+    pub async fn process<E, F>(
+        &self,
+        client: &mut Client,
+        batch_size: i64,
+        f: F,
+    ) -> Result<usize, E>
+    where
+        E: From<ConsumeError>,
+        F: for<'t> FnOnce(&'t Transaction<'_>, &'t [OutboxMessage]) -> BoxFuture<'t, Result<(), E>>,
+    {
+        let txn = match client.transaction().await {
+            Ok(v) => v,
+            Err(e) => return Err(E::from(ConsumeError::from(e))),
+        };
+
+        let messages =
+            OutboxMessage::consume(txn.client(), &self.poll_query(), &[&batch_size]).await?;
+
+        if !messages.is_empty() {
+            if let Err(e) = f(&txn, &messages).await {
+                let _ = txn.rollback().await;
+                return Err(e);
+            }
+
+            let ids = messages.iter().map(|v| v.id).collect::<Vec<i64>>();
+
+            self.mark_processed(txn.client(), &ids).await?;
+        }
+
+        match txn.commit().await {
+            Ok(_) => Ok(messages.len()),
+            Err(e) => Err(E::from(ConsumeError::from(e))),
+        }
+    }
+}
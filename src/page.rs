@@ -0,0 +1,96 @@
+//! `LIMIT`/`OFFSET` pagination, used by `consume_page`.
+use crate::{ConsumeError, RowConsumer};
+use tokio_postgres::types::ToSql;
+use tokio_postgres::Client;
+
+/// A page of a query's rows, numbered from 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Page {
+    /// The page number, starting at 1. Page 0 is read as page 1.
+    pub number: u64,
+    /// The most rows a page holds.
+    pub size: u64,
+    /// Whether to run a companion query counting every row of the query.
+    pub count: bool,
+}
+
+impl Page {
+    /// Creates a page that does not count the query's rows.
+    pub fn new(number: u64, size: u64) -> Self {
+        Page {
+            number,
+            size,
+            count: false,
+        }
+    }
+
+    /// Also counts every row of the query, filling `PagedResult::total`.
+    pub fn with_count(mut self) -> Self {
+        self.count = true;
+        self
+    }
+
+    /// The number of rows before the page.
+    pub fn offset(&self) -> u64 {
+        self.number
+            .max(1)
+            .saturating_sub(1)
+            .saturating_mul(self.size)
+    }
+}
+
+/// The rows of a page, along with the page and, if it was counted, the number of rows of
+/// the whole query.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PagedResult<T> {
+    pub items: Vec<T>,
+    pub page: Page,
+    pub total: Option<u64>,
+}
+
+impl<T> PagedResult<T> {
+    /// The number of pages of the whole query, if it was counted.
+    pub fn pages(&self) -> Option<u64> {
+        match self.page.size {
+            0 => self.total.map(|_| 0),
+            v => self.total.map(|t| t.div_ceil(v)),
+        }
+    }
+}
+
+/// The implementation of `consume_page`, reading the page's rows and then, if asked,
+/// counting the query's rows.
+pub(crate) async fn consume_page<T: RowConsumer + Send>(
+    conn: &Client,
+    query: &str,
+    params: &[&(dyn ToSql + Sync)],
+    page: Page,
+) -> Result<PagedResult<T>, ConsumeError> {
+    let query = query.trim_end().trim_end_matches(';');
+
+    let paged_query = format!(
+        "select * from ({}) as pgde_page limit {} offset {}",
+        query,
+        page.size,
+        page.offset()
+    );
+
+    let items = T::consume(conn, &paged_query, params).await?;
+
+    let total = match page.count {
+        true => {
+            let count_query = format!("select count(*) from ({}) as pgde_page", query);
+
+            match conn.query_one(&count_query, params).await {
+                Ok(v) => match v.try_get::<usize, i64>(0) {
+                    Ok(v) => Some(v as u64),
+                    Err(_) => return Err(ConsumeError::ConversionError),
+                },
+                Err(e) => return Err(ConsumeError::from(e)),
+            }
+        }
+        false => None,
+    };
+
+    Ok(PagedResult { items, page, total })
+}
@@ -0,0 +1,119 @@
+//! Search filters collected from the fields of a struct that derives `QueryParams`, for
+//! endpoints with many optional filters.
+use crate::ident::quote_ident;
+use crate::{ConsumeError, RowConsumer};
+use std::future::Future;
+use tokio_postgres::types::ToSql;
+use tokio_postgres::Client;
+
+/// A filter of a `where` clause, comparing a column with a value sent as a parameter.
+pub struct Filter<'a> {
+    /// The unquoted name of the column.
+    pub column: &'static str,
+    /// The comparison, one of `=`, `<>`, `<`, `<=`, `>`, `>=`, `like`, `ilike`, or `any`,
+    /// which matches rows whose column equals any element of an array value.
+    pub operator: &'static str,
+    /// The value the column is compared with.
+    pub value: &'a (dyn ToSql + Sync),
+}
+
+/// The derivable trait for structs whose fields are search filters. Deriving it adds a
+/// filter for every field that is not an `Option`, and for every `Option` field that is
+/// `Some`, comparing the column of the same name, or the one given with
+/// `#[pgde(column = "...")]`, by equality, or by the comparison given with
+/// `#[pgde(op = "...")]`.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::QueryParams;
+/// use pgde_derive::{QueryParams, RowConsumer};
+/// use tokio_postgres::{NoTls, Row};
+///
+/// #[derive(RowConsumer)]
+/// struct Product {
+///     id: i32,
+///     name: String,
+///     price: i32,
+/// }
+///
+/// #[derive(Default, QueryParams)]
+/// struct ProductSearch {
+///     name: Option<String>,
+///     #[pgde(column = "price", op = ">=")]
+///     min_price: Option<i32>,
+///     #[pgde(column = "price", op = "<=")]
+///     max_price: Option<i32>,
+/// }
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         let search = ProductSearch {
+///             max_price: Some(20),
+///             ..Default::default()
+///         };
+///         let query = "select * from (values (1, 'pen', 2), (2, 'lamp', 40)) as p(id, name, price)";
+///
+///         match search.consume_matching::<Product>(&client, query).await {
+///             Ok(v) => println!("Found {} products", v.len()), // v is of type Vec<Product>
+///             Err(_) => eprintln!("An error occurred while querying database"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub trait QueryParams {
+    /// Returns the filters that are set, in field order.
+    fn filters(&self) -> Vec<Filter<'_>>;
+
+    /// Returns the `where` clause of the filters that are set, joined by `and`, along
+    /// with its parameters, numbered from `$1`. The clause is empty when no filter is set.
+    fn where_clause(&self) -> (String, Vec<&(dyn ToSql + Sync)>) {
+        let filters = self.filters();
+        let mut conditions = Vec::with_capacity(filters.len());
+        let mut params = Vec::with_capacity(filters.len());
+
+        for (i, filter) in filters.iter().enumerate() {
+            conditions.push(match filter.operator {
+                "any" => format!("{} = any(${})", quote_ident(filter.column), i + 1),
+                v => format!("{} {} ${}", quote_ident(filter.column), v, i + 1),
+            });
+            params.push(filter.value);
+        }
+
+        match conditions.len() {
+            0 => (String::new(), params),
+            _ => (format!("where {}", conditions.join(" and ")), params),
+        }
+    }
+
+    /// Consumes the rows of `base_query`, which must not take parameters, that match the
+    /// filters that are set. Columns are those returned by `base_query`, which is run as a
+    /// subquery.
+    fn consume_matching<T: RowConsumer + Send>(
+        &self,
+        conn: &Client,
+        base_query: &str,
+    ) -> impl Future<Output = Result<Vec<T>, ConsumeError>> + Send
+    where
+        Self: Sync,
+    {
+        async move {
+            let (clause, params) = self.where_clause();
+            let base_query = base_query.trim_end().trim_end_matches(';');
+            let query = format!("select * from ({}) as q {}", base_query, clause);
+
+            T::consume(conn, &query, &params).await
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! Re-exports of tokio_postgres and of the crates enabled by features, at the versions
+//! this crate is compiled against. Using these in place of direct dependencies avoids
+//! mismatches between, e.g., the `Uuid` a downstream crate names and the `Uuid` that
+//! `RowConsumer` is implemented on.
+//!
+//! ## Example
+//! ```
+//! # tokio_test::block_on(async {
+//! use pgde::pg::{Client, NoTls};
+//! use pgde::RowConsumer;
+//!
+//! async fn count(client: &Client) -> usize {
+//!     match i32::consume(client, "select 1;", &[]).await {
+//!         Ok(v) => v.len(),
+//!         Err(_) => 0,
+//!     }
+//! }
+//!
+//! match pgde::pg::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+//!     Ok(v) => {
+//!         let client = v.0;
+//!         let conn = v.1;
+//!
+//!         tokio::spawn(async move {
+//!             if let Err(e) = conn.await {
+//!                 eprintln!("connection error: {}", e);
+//!             }
+//!         });
+//!
+//!         println!("Counted {} rows", count(&client).await);
+//!     },
+//!     Err(_) => eprintln!("Could not connect to database"),
+//! };
+//! # })
+//! ```
+pub use tokio_postgres;
+pub use tokio_postgres::types;
+pub use tokio_postgres::{
+    connect, Client, Column, Config, Error, NoTls, Row, RowStream, Statement, Transaction,
+};
+
+#[cfg(feature = "arrayvec")]
+pub use arrayvec::{self, ArrayVec};
+#[cfg(feature = "bit")]
+pub use bit_vec::{self, BitVec};
+#[cfg(feature = "chrono")]
+pub use chrono;
+#[cfg(feature = "mac")]
+pub use eui48::{self, MacAddress};
+#[cfg(feature = "geo")]
+pub use geo_types::{self, LineString, Point, Rect};
+#[cfg(feature = "indexmap")]
+pub use indexmap::{self, IndexMap, IndexSet};
+#[cfg(feature = "json")]
+pub use serde_json::{self, Value};
+#[cfg(feature = "smallvec")]
+pub use smallvec::{self, SmallVec};
+#[cfg(feature = "time")]
+pub use time;
+#[cfg(feature = "uuid")]
+pub use uuid::{self, Uuid};
@@ -0,0 +1,250 @@
+//! A job queue worker that claims rows of a table with `FOR UPDATE SKIP LOCKED`, so that
+//! any number of workers may share one queue.
+use crate::ident::quote_ident;
+use crate::{ConsumeError, Table};
+use std::future::Future;
+use std::marker::PhantomData;
+use std::time::Duration;
+use tokio_postgres::Client;
+
+/// The outcome of one batch of jobs run by `Worker::run_once`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BatchReport {
+    /// Jobs whose handler succeeded, now marked `done`.
+    pub completed: usize,
+    /// Jobs whose handler failed with attempts remaining, left `pending` to be retried.
+    pub retried: usize,
+    /// Jobs whose handler failed on their last attempt, now marked `failed`.
+    pub failed: usize,
+}
+
+impl BatchReport {
+    /// Returns the number of jobs claimed.
+    pub fn len(&self) -> usize {
+        self.completed + self.retried + self.failed
+    }
+
+    /// Returns whether no jobs were pending.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A worker consuming the jobs of `T`'s table, which holds a job per row along with an
+/// integer id column, a text status column, and an integer attempts column, named `id`,
+/// `status`, and `attempts` unless set otherwise. Jobs with status `pending` are claimed
+/// in batches, oldest id first, with `FOR UPDATE SKIP LOCKED`, consumed into `T`, and
+/// passed to a handler one at a time. Jobs whose handler succeeds are marked `done`, and
+/// jobs whose handler fails have their attempts counted and are marked `failed` once
+/// they reach `max_attempts`, or left `pending` to be retried otherwise.
+///
+/// The claimed jobs stay locked in one transaction until the batch is finished, so a
+/// worker that dies mid-batch leaves its jobs pending for other workers.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::queue::Worker;
+/// use pgde_derive::RowConsumer;
+/// use tokio_postgres::{NoTls, Row};
+///
+/// #[derive(RowConsumer)]
+/// #[pgde(table = "email_jobs")]
+/// struct EmailJob {
+///     recipient: String,
+///     subject: String,
+/// }
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let mut client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         let worker = Worker::<EmailJob>::new().batch_size(50).max_attempts(5);
+///
+///         let result = worker
+///             .run_once(&mut client, |job| async move {
+///                 println!("Sending {} to {}", job.subject, job.recipient);
+///                 Ok::<(), String>(())
+///             })
+///             .await;
+///
+///         match result {
+///             Ok(v) => println!("Completed {} jobs", v.completed),
+///             Err(_) => eprintln!("An error occurred while running jobs"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+#[derive(Clone, Debug)]
+pub struct Worker<T> {
+    id_column: String,
+    status_column: String,
+    attempts_column: String,
+    batch_size: i64,
+    max_attempts: i32,
+    poll_interval: Duration,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Table> Worker<T> {
+    /// Creates a worker claiming 10 jobs at a time, attempting each up to 3 times, and
+    /// polling every second when no jobs are pending.
+    pub fn new() -> Self {
+        Worker {
+            id_column: String::from("id"),
+            status_column: String::from("status"),
+            attempts_column: String::from("attempts"),
+            batch_size: 10,
+            max_attempts: 3,
+            poll_interval: Duration::from_secs(1),
+            marker: PhantomData,
+        }
+    }
+
+    /// Sets the column identifying jobs, which orders them.
+    pub fn id_column(mut self, id_column: &str) -> Self {
+        self.id_column = String::from(id_column);
+        self
+    }
+
+    /// Sets the text column holding `pending`, `done`, or `failed`.
+    pub fn status_column(mut self, status_column: &str) -> Self {
+        self.status_column = String::from(status_column);
+        self
+    }
+
+    /// Sets the integer column counting the failed attempts of jobs.
+    pub fn attempts_column(mut self, attempts_column: &str) -> Self {
+        self.attempts_column = String::from(attempts_column);
+        self
+    }
+
+    /// Sets the number of jobs claimed at a time.
+    pub fn batch_size(mut self, batch_size: i64) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Sets the number of times a job is attempted before it is marked `failed`.
+    pub fn max_attempts(mut self, max_attempts: i32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Sets how long `run` waits after finding no pending jobs.
+    pub fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Claims a batch of pending jobs in a new transaction of `client`, passes each to
+    /// `handler`, records their outcomes, and commits.
+    pub async fn run_once<F, Fut, E>(
+        &self,
+        client: &mut Client,
+        handler: F,
+    ) -> Result<BatchReport, ConsumeError>
+    where
+        F: Fn(T) -> Fut,
+        Fut: Future<Output = Result<(), E>>,
+    {
+        let id = quote_ident(&self.id_column);
+        let status = quote_ident(&self.status_column);
+        let attempts = quote_ident(&self.attempts_column);
+
+        let claim = format!(
+            "select {}, {}::bigint as pgde_job_id from {} where {} = 'pending' order by {} limit $1 for update skip locked;",
+            T::COLUMNS
+                .iter()
+                .map(|v| quote_ident(v))
+                .collect::<Vec<String>>()
+                .join(", "),
+            id,
+            T::qualified_name(),
+            status,
+            id
+        );
+
+        let done = format!(
+            "update {} set {} = 'done' where {} = $1::bigint;",
+            T::qualified_name(),
+            status,
+            id
+        );
+
+        let failed = format!(
+            "update {table} set {attempts} = {attempts} + 1, {status} = case when {attempts} + 1 >= $2 then 'failed' else 'pending' end where {id} = $1::bigint returning {status};",
+            table = T::qualified_name(),
+            attempts = attempts,
+            status = status,
+            id = id
+        );
+
+        let txn = client.transaction().await?;
+        let rows = txn.query(&claim, &[&self.batch_size]).await?;
+        let mut report = BatchReport::default();
+
+        for row in rows {
+            let job_id: i64 = match row.try_get("pgde_job_id") {
+                Ok(v) => v,
+                Err(_) => return Err(ConsumeError::ConversionError),
+            };
+
+            let job = match T::from_row(row) {
+                Ok(v) => v,
+                Err(_) => return Err(ConsumeError::ConversionError),
+            };
+
+            match handler(job).await {
+                Ok(_) => {
+                    txn.execute(&done, &[&job_id]).await?;
+                    report.completed += 1;
+                }
+                Err(_) => {
+                    let row = txn
+                        .query_one(&failed, &[&job_id, &self.max_attempts])
+                        .await?;
+
+                    match row.try_get::<usize, &str>(0) {
+                        Ok("failed") => report.failed += 1,
+                        _ => report.retried += 1,
+                    };
+                }
+            };
+        }
+
+        txn.commit().await?;
+
+        Ok(report)
+    }
+
+    /// Runs batches with `run_once` until an error occurs, waiting `poll_interval` after
+    /// a batch that found no pending jobs. Stop the worker by dropping the future, e.g.
+    /// by aborting the task running it; jobs of an unfinished batch are left pending.
+    pub async fn run<F, Fut, E>(&self, client: &mut Client, handler: F) -> Result<(), ConsumeError>
+    where
+        F: Fn(T) -> Fut,
+        Fut: Future<Output = Result<(), E>>,
+    {
+        loop {
+            if self.run_once(client, &handler).await?.is_empty() {
+                tokio::time::sleep(self.poll_interval).await;
+            }
+        }
+    }
+}
+
+impl<T: Table> Default for Worker<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
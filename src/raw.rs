@@ -0,0 +1,53 @@
+//! Support for capturing the raw binary representation of columns of any type.
+use std::error::Error;
+use tokio_postgres::types::{FromSql, Type};
+
+/// The raw binary representation of a column of any type, as sent by PostgreSQL,
+/// along with the OID of the column's type. `bytes` is `None` for NULL values. Useful
+/// for logging or forwarding values of extension types that cannot otherwise be
+/// consumed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RawValue {
+    pub oid: u32,
+    pub bytes: Option<Vec<u8>>,
+}
+
+impl<'a> FromSql<'a> for RawValue {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Ok(RawValue {
+            oid: ty.oid(),
+            bytes: Some(raw.to_vec()),
+        })
+    }
+
+    fn from_sql_null(ty: &Type) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Ok(RawValue {
+            oid: ty.oid(),
+            bytes: None,
+        })
+    }
+
+    fn accepts(_: &Type) -> bool {
+        true
+    }
+}
+
+/// Whether a column of any type is NULL, read without decoding or copying its value.
+/// Used by the `#[pgde(null_as = "...")]` field attribute of the derive macro.
+#[doc(hidden)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NullCheck(pub bool);
+
+impl<'a> FromSql<'a> for NullCheck {
+    fn from_sql(_: &Type, _: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Ok(NullCheck(false))
+    }
+
+    fn from_sql_null(_: &Type) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Ok(NullCheck(true))
+    }
+
+    fn accepts(_: &Type) -> bool {
+        true
+    }
+}
@@ -0,0 +1,100 @@
+//! Support for consuming columns into types defined outside of the crate that derives
+//! `RowConsumer`, in the style of serde's remote derive.
+use tokio_postgres::types::FromSql;
+
+/// Reads a column into `T`, a type that neither this crate nor the calling crate may
+/// implement `RowConsumer` or `FromSql` on, by reading it as `Via` and converting it.
+/// Implemented on a definition type declared with `impl_row_consumer!`, which fields name
+/// with `#[pgde(remote = "...")]`.
+pub trait RemoteColumn<T> {
+    /// The type the column is read as.
+    type Via: for<'a> FromSql<'a>;
+
+    /// Converts the column's value, returning `None` if it cannot be converted.
+    fn convert(value: Self::Via) -> Option<T>;
+
+    /// The value of a field that could not be read, in place of `Default::default()`.
+    fn fallback() -> T;
+}
+
+/// Declares a definition type that reads columns into a type defined outside of the
+/// calling crate, by reading them as an intermediate type that implements `FromSql` and
+/// converting them with a function returning an `Option`. Fields of that type, or of an
+/// `Option` of it, are read through the definition with `#[pgde(remote = "...")]`.
+///
+/// The orphan rule prevents implementing `RowConsumer` or `FromSql` directly on a type
+/// that neither the calling crate nor this crate defines, so the definition type stands
+/// in for it, as with serde's `#[serde(remote = "...")]`. Fields that fail conversion
+/// hold `Default::default()`, or the value given with `default = ...` for types that do
+/// not implement `Default`.
+///
+/// ## Example
+/// ```
+/// use pgde::impl_row_consumer;
+/// use pgde_derive::RowConsumer;
+/// use std::net::{Ipv4Addr, SocketAddr};
+/// use tokio_postgres::Row;
+///
+/// fn parse_socket_addr(value: String) -> Option<SocketAddr> {
+///     value.parse().ok()
+/// }
+///
+/// impl_row_consumer!(
+///     SocketAddrDef for SocketAddr,
+///     via = String,
+///     with = parse_socket_addr,
+///     default = SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0))
+/// );
+///
+/// #[derive(RowConsumer)]
+/// struct Peer {
+///     Name: String,
+///     #[pgde(remote = "SocketAddrDef")]
+///     Address: SocketAddr,
+///     #[pgde(remote = "SocketAddrDef")]
+///     Backup: Option<SocketAddr>,
+/// }
+/// ```
+#[macro_export]
+macro_rules! impl_row_consumer {
+    ( $def:ident for $foreign:ty, via = $via:ty, with = $with:expr ) => {
+        $crate::impl_row_consumer!(
+            $def for $foreign,
+            via = $via,
+            with = $with,
+            default = <$foreign>::default()
+        );
+    };
+    ( $def:ident for $foreign:ty, via = $via:ty, with = $with:expr, default = $default:expr ) => {
+        /// A definition type reading columns into a type defined outside of this crate,
+        /// declared with `pgde::impl_row_consumer!`.
+        pub struct $def;
+
+        impl $crate::RemoteColumn<$foreign> for $def {
+            type Via = $via;
+
+            fn convert(value: $via) -> Option<$foreign> {
+                ($with)(value)
+            }
+
+            fn fallback() -> $foreign {
+                $default
+            }
+        }
+
+        impl $crate::RemoteColumn<Option<$foreign>> for $def {
+            type Via = Option<$via>;
+
+            fn convert(value: Option<$via>) -> Option<Option<$foreign>> {
+                match value {
+                    Some(v) => ($with)(v).map(Some),
+                    None => Some(None),
+                }
+            }
+
+            fn fallback() -> Option<$foreign> {
+                None
+            }
+        }
+    };
+}
@@ -0,0 +1,89 @@
+//! Per-field breakdowns of conversion errors, reported when consuming with
+//! `ErrorPolicy::Report`.
+use crate::raw::NullCheck;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::Display;
+use tokio_postgres::row::RowIndex;
+use tokio_postgres::types::{FromSql, WrongType};
+use tokio_postgres::Row;
+
+/// Why a field of a row failed conversion.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FieldFailure {
+    /// The column was NULL and the field is not an `Option`.
+    NullIntoNonOption,
+    /// The column's type cannot be read into the field's type.
+    TypeMismatch,
+    /// The column was read, but its value was rejected, e.g. by a reading attribute such
+    /// as `#[pgde(narrow)]`, or the column is missing.
+    InvalidValue,
+}
+
+/// The number of rows whose field failed conversion, by [FieldFailure].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FieldFailures {
+    pub null_into_non_option: usize,
+    pub type_mismatch: usize,
+    pub invalid_value: usize,
+}
+
+impl FieldFailures {
+    /// Returns the number of rows whose field failed conversion for any reason.
+    pub fn total(&self) -> usize {
+        self.null_into_non_option + self.type_mismatch + self.invalid_value
+    }
+}
+
+/// The conversion errors of every row that failed conversion, along with the number of
+/// failures of every field, so that the columns causing a large import to fail can be
+/// found without reading every error. Fields are counted for structs that derive
+/// `RowConsumer`.
+///
+/// ## Example
+/// ```
+/// use pgde::{ConversionReport, FieldFailure};
+///
+/// let mut report = ConversionReport::default();
+/// report.record("email", FieldFailure::NullIntoNonOption);
+/// report.record("email", FieldFailure::NullIntoNonOption);
+///
+/// assert_eq!(report.fields["email"].null_into_non_option, 2);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConversionReport {
+    /// The conversion errors, as reported under `ErrorPolicy::CollectAll`.
+    pub errors: Vec<String>,
+    /// The failures of every field that failed conversion, by field name.
+    pub fields: BTreeMap<String, FieldFailures>,
+}
+
+impl ConversionReport {
+    /// Counts a failure of the named field.
+    pub fn record(&mut self, field: &str, failure: FieldFailure) {
+        let failures = self.fields.entry(String::from(field)).or_default();
+
+        match failure {
+            FieldFailure::NullIntoNonOption => failures.null_into_non_option += 1,
+            FieldFailure::TypeMismatch => failures.type_mismatch += 1,
+            FieldFailure::InvalidValue => failures.invalid_value += 1,
+        }
+    }
+}
+
+/// Classifies the failure of a field whose column, read as `T`, failed conversion. Used
+/// by the derive macro.
+#[doc(hidden)]
+pub fn classify_failure<'a, T, I>(row: &'a Row, index: I) -> FieldFailure
+where
+    T: FromSql<'a>,
+    I: RowIndex + Display + Copy,
+{
+    match row.try_get::<I, T>(index) {
+        Err(e) if e.source().is_some_and(|v| v.is::<WrongType>()) => FieldFailure::TypeMismatch,
+        _ => match row.try_get::<I, NullCheck>(index) {
+            Ok(NullCheck(true)) => FieldFailure::NullIntoNonOption,
+            _ => FieldFailure::InvalidValue,
+        },
+    }
+}
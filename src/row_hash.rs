@@ -0,0 +1,81 @@
+//! Hashes of consumed rows, for detecting which rows changed since a previous run.
+use std::fmt::{Debug, Write};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// A hash of the values of a consumed row, for sync jobs that skip rows they have already
+/// written downstream. Implemented by the derive macro for structs marked
+/// `#[pgde(row_hash)]`, from the name of every field and its value written with `Debug`,
+/// using 64-bit FNV-1a. Unlike `std::hash::Hash` with the standard library's hashers, the
+/// hash of a row is the same in every process and on every run, as long as the `Debug`
+/// output of its field types does not change.
+///
+/// ## Example
+/// ```
+/// use pgde::RowHash;
+/// use pgde_derive::RowConsumer;
+/// use tokio_postgres::Row;
+///
+/// #[derive(RowConsumer)]
+/// #[pgde(row_hash)]
+/// struct Product {
+///     id: i32,
+///     price: f64,
+/// }
+///
+/// let product = Product { id: 1, price: 9.5 };
+/// let repriced = Product { id: 1, price: 8.5 };
+///
+/// assert_ne!(product.row_hash(), repriced.row_hash());
+/// ```
+pub trait RowHash {
+    /// Returns the hash of the row.
+    fn row_hash(&self) -> u64;
+}
+
+/// Hashes the fields of a row for [RowHash]. Used by the derive macro.
+#[doc(hidden)]
+pub struct RowHasher(u64);
+
+impl RowHasher {
+    /// Starts a hash with no fields.
+    pub fn new() -> Self {
+        RowHasher(FNV_OFFSET_BASIS)
+    }
+
+    /// Adds a field's name and value to the hash, each followed by a byte that cannot
+    /// appear in UTF-8 text, so that the boundaries between fields are part of the hash.
+    pub fn field(&mut self, name: &str, value: &dyn Debug) {
+        self.bytes(name.as_bytes());
+        self.bytes(&[0xff]);
+        let _ = write!(self, "{:?}", value);
+        self.bytes(&[0xff]);
+    }
+
+    /// Returns the hash of the fields added so far.
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+
+    /// Adds bytes to the hash.
+    fn bytes(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= u64::from(*b);
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+}
+
+impl Default for RowHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Write for RowHasher {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.bytes(s.as_bytes());
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+//! Consumption of single values and single columns, for queries such as `count(*)` and
+//! id lists that do not need a struct, and execution of statements that return no rows.
+use crate::ConsumeError;
+use tokio_postgres::types::{FromSql, ToSql};
+use tokio_postgres::{Client, Row};
+
+/// Reads the only column of a row, failing for rows with more than one column.
+fn only_column<T: for<'a> FromSql<'a>>(row: &Row) -> Result<T, ConsumeError> {
+    match row.len() {
+        1 => match row.try_get::<usize, T>(0) {
+            Ok(v) => Ok(v),
+            Err(_) => Err(ConsumeError::ConversionError),
+        },
+        v => Err(ConsumeError::InvalidQuery(format!(
+            "Expected a single column, found {}",
+            v
+        ))),
+    }
+}
+
+/// Consumes the single value of a query that returns one row and one column, such as
+/// `select count(*) ...`. Returns [ConsumeError::UnexpectedRowCount] if the query
+/// returns no rows or more than one row.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::consume_scalar;
+/// use tokio_postgres::NoTls;
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         match consume_scalar::<i64>(&client, "select count(*) from pg_class;", &[]).await {
+///             Ok(v) => println!("Found {} relations", v),
+///             Err(_) => eprintln!("An error occurred while querying database"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub async fn consume_scalar<T: for<'a> FromSql<'a>>(
+    conn: &Client,
+    query: &str,
+    params: &[&(dyn ToSql + Sync)],
+) -> Result<T, ConsumeError> {
+    #[cfg(feature = "dev_guard")]
+    crate::guard::record(query);
+
+    match conn.query(query, params).await {
+        Ok(v) => match v.len() {
+            1 => only_column(&v[0]),
+            n => Err(ConsumeError::UnexpectedRowCount(n)),
+        },
+        Err(e) => Err(ConsumeError::from(e)),
+    }
+}
+
+/// Consumes the single column of every row of a query, such as a list of ids.
+pub async fn consume_column<T: for<'a> FromSql<'a>>(
+    conn: &Client,
+    query: &str,
+    params: &[&(dyn ToSql + Sync)],
+) -> Result<Vec<T>, ConsumeError> {
+    #[cfg(feature = "dev_guard")]
+    crate::guard::record(query);
+
+    match conn.query(query, params).await {
+        Ok(v) => v.iter().map(only_column).collect(),
+        Err(e) => Err(ConsumeError::from(e)),
+    }
+}
+
+/// Returns whether a query returns any rows, by running `select exists(...)` over it.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::query_exists;
+/// use tokio_postgres::NoTls;
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         let query = "select * from pg_class where relname = $1";
+///
+///         match query_exists(&client, query, &[&"pg_type"]).await {
+///             Ok(v) => println!("pg_type exists: {}", v),
+///             Err(_) => eprintln!("An error occurred while querying database"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub async fn query_exists(
+    conn: &Client,
+    query: &str,
+    params: &[&(dyn ToSql + Sync)],
+) -> Result<bool, ConsumeError> {
+    let query = format!("select exists({})", query.trim_end().trim_end_matches(';'));
+
+    consume_scalar(conn, &query, params).await
+}
+
+/// Executes a statement, such as an `insert`, `update`, or `delete`, returning the number
+/// of rows modified, so that writes report errors as a [ConsumeError] like consumption.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::execute;
+/// use tokio_postgres::NoTls;
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         let query = "update public.\"Foo\" set \"Data\" = $1 where \"Id\" = $2;";
+///
+///         match execute(&client, query, &[&"bar", &10]).await {
+///             Ok(v) => println!("Updated {} rows", v),
+///             Err(_) => eprintln!("An error occurred while updating database"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub async fn execute(
+    conn: &Client,
+    query: &str,
+    params: &[&(dyn ToSql + Sync)],
+) -> Result<u64, ConsumeError> {
+    #[cfg(feature = "dev_guard")]
+    crate::guard::record(query);
+
+    match conn.execute(query, params).await {
+        Ok(v) => Ok(v),
+        Err(e) => Err(ConsumeError::from(e)),
+    }
+}
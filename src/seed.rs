@@ -0,0 +1,210 @@
+//! Seeding tables from fixture files, validated against the types they hold.
+use crate::ident::quote_ident;
+use crate::ConsumeError;
+use futures_util::SinkExt;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::io::Cursor;
+use std::path::Path;
+use tokio_postgres::Client;
+
+/// The format of a seed file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format<'a> {
+    /// A JSON array of objects, one per row.
+    Json(&'a Path),
+    /// CSV with a header row naming the fields of each record.
+    Csv(&'a Path),
+}
+
+/// Parses the records of a seed file into `T`, validating every record before any row
+/// is written, and copies them into `table` with `COPY`, returning the number of rows
+/// copied. The columns copied are the fields `T` serializes, and values are written in
+/// `COPY`'s text format, so that e.g. strings are parsed as the dates or enums of their
+/// columns, and nested arrays and objects are written as JSON. `table` is interpolated
+/// into the query as is.
+///
+/// Returns [ConsumeError::ReadError] if the file cannot be read or parsed, and
+/// [ConsumeError::ConversionErrors] with the error of every record that does not match
+/// `T`. The file is read synchronously, as befits fixtures.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::seed::{self, Format};
+/// use serde::{Deserialize, Serialize};
+/// use std::path::Path;
+/// use tokio_postgres::NoTls;
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Account {
+///     id: i64,
+///     email: String,
+/// }
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         match seed::load::<Account>(&client, "accounts", Format::Json(Path::new("fixtures/accounts.json"))).await {
+///             Ok(v) => println!("Seeded {} accounts", v),
+///             Err(_) => eprintln!("An error occurred while seeding accounts"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub async fn load<T>(client: &Client, table: &str, format: Format<'_>) -> Result<u64, ConsumeError>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let records = match format {
+        Format::Json(path) => read_json::<T>(path)?,
+        Format::Csv(path) => read_csv::<T>(path)?,
+    };
+
+    let mut rows = Vec::with_capacity(records.len());
+
+    for record in &records {
+        match serde_json::to_value(record) {
+            Ok(Value::Object(v)) => rows.push(v),
+            _ => {
+                return Err(ConsumeError::InvalidQuery(String::from(
+                    "Seed records must serialize as objects",
+                )))
+            }
+        };
+    }
+
+    let columns = match rows.first() {
+        Some(v) => v.keys().cloned().collect::<Vec<String>>(),
+        None => return Ok(0),
+    };
+
+    let query = format!(
+        "copy {} ({}) from stdin",
+        table,
+        columns
+            .iter()
+            .map(|v| quote_ident(v))
+            .collect::<Vec<String>>()
+            .join(", ")
+    );
+
+    let sink = match client.copy_in(&query).await {
+        Ok(v) => v,
+        Err(e) => return Err(ConsumeError::from(e)),
+    };
+
+    let mut sink = std::pin::pin!(sink);
+
+    for row in rows {
+        let line = columns
+            .iter()
+            .map(|v| copy_text(row.get(v).unwrap_or(&Value::Null)))
+            .collect::<Vec<String>>()
+            .join("\t")
+            + "\n";
+
+        if let Err(e) = sink.send(Cursor::new(line.into_bytes())).await {
+            return Err(ConsumeError::from(e));
+        }
+    }
+
+    match sink.as_mut().finish().await {
+        Ok(v) => Ok(v),
+        Err(e) => Err(ConsumeError::from(e)),
+    }
+}
+
+/// Reads a JSON array of records, collecting the error of every record that is not a `T`.
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>, ConsumeError> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(v) => v,
+        Err(e) => {
+            return Err(ConsumeError::ReadError(format!(
+                "{}: {}",
+                path.display(),
+                e
+            )))
+        }
+    };
+
+    let values = match serde_json::from_str::<Vec<Value>>(&text) {
+        Ok(v) => v,
+        Err(e) => {
+            return Err(ConsumeError::ReadError(format!(
+                "{}: {}",
+                path.display(),
+                e
+            )))
+        }
+    };
+
+    let mut records = Vec::with_capacity(values.len());
+    let mut errors = Vec::new();
+
+    for (i, value) in values.into_iter().enumerate() {
+        match serde_json::from_value(value) {
+            Ok(v) => records.push(v),
+            Err(e) => errors.push(format!("Record {}: {}", i + 1, e)),
+        };
+    }
+
+    match errors.len() {
+        0 => Ok(records),
+        _ => Err(ConsumeError::ConversionErrors(errors)),
+    }
+}
+
+/// Reads CSV records, collecting the error of every record that is not a `T`.
+fn read_csv<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>, ConsumeError> {
+    let mut reader = match csv::Reader::from_path(path) {
+        Ok(v) => v,
+        Err(e) => {
+            return Err(ConsumeError::ReadError(format!(
+                "{}: {}",
+                path.display(),
+                e
+            )))
+        }
+    };
+
+    let mut records = Vec::new();
+    let mut errors = Vec::new();
+
+    for (i, record) in reader.deserialize().enumerate() {
+        match record {
+            Ok(v) => records.push(v),
+            Err(e) => errors.push(format!("Record {}: {}", i + 1, e)),
+        };
+    }
+
+    match errors.len() {
+        0 => Ok(records),
+        _ => Err(ConsumeError::ConversionErrors(errors)),
+    }
+}
+
+/// Renders a value as a field of `COPY`'s text format.
+fn copy_text(value: &Value) -> String {
+    let text = match value {
+        Value::Null => return String::from("\\N"),
+        Value::String(v) => v.clone(),
+        v => v.to_string(),
+    };
+
+    text.replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t")
+}
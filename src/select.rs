@@ -0,0 +1,176 @@
+//! A minimal builder for parameterized `select` queries over a [Table].
+use crate::ident::quote_ident;
+use crate::table::Table;
+use crate::ConsumeError;
+use std::marker::PhantomData;
+use tokio_postgres::types::ToSql;
+use tokio_postgres::Client;
+
+/// A builder for simple filtered lookups, started with `T::select()`. Columns are given
+/// by name, which is checked against `T::COLUMNS`, or as a variant of `T::Columns`, and
+/// values are always sent as parameters.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::Table;
+/// use pgde_derive::RowConsumer;
+/// use tokio_postgres::{NoTls, Row};
+///
+/// #[derive(RowConsumer)]
+/// #[pgde(table = "Foo", schema = "public")]
+/// struct Foo {
+///     Id: i32,
+///     Data: String,
+/// }
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         let data = String::from("bar");
+///
+///         match Foo::select().where_eq("Data", &data).order_by(FooColumns::Id).limit(50).consume(&client).await {
+///             Ok(v) => println!("Found {} rows", v.len()), // v is of type Vec<Foo>
+///             Err(_) => eprintln!("An error occurred while querying database"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub struct SelectBuilder<'a, T> {
+    filters: Vec<(&'a str, &'a (dyn ToSql + Sync))>,
+    order: Vec<(&'a str, bool)>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+    include_deleted: bool,
+    table: PhantomData<T>,
+}
+
+impl<'a, T: Table> SelectBuilder<'a, T> {
+    /// Creates a builder that selects every row of the table.
+    pub fn new() -> Self {
+        SelectBuilder {
+            filters: Vec::new(),
+            order: Vec::new(),
+            limit: None,
+            offset: None,
+            include_deleted: false,
+            table: PhantomData,
+        }
+    }
+
+    /// Only selects rows where `column` equals `value`.
+    pub fn where_eq(mut self, column: impl Into<&'a str>, value: &'a (dyn ToSql + Sync)) -> Self {
+        self.filters.push((column.into(), value));
+        self
+    }
+
+    /// Orders rows by `column`, ascending.
+    pub fn order_by(mut self, column: impl Into<&'a str>) -> Self {
+        self.order.push((column.into(), false));
+        self
+    }
+
+    /// Orders rows by `column`, descending.
+    pub fn order_by_desc(mut self, column: impl Into<&'a str>) -> Self {
+        self.order.push((column.into(), true));
+        self
+    }
+
+    /// Selects at most `limit` rows.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Skips the first `offset` rows.
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Also selects rows marked as deleted by the table's `Table::SOFT_DELETE` column,
+    /// which are otherwise left out.
+    pub fn include_deleted(mut self) -> Self {
+        self.include_deleted = true;
+        self
+    }
+
+    /// Quotes a column after checking that it is one of `T::COLUMNS`.
+    fn column(column: &str) -> Result<String, ConsumeError> {
+        match T::COLUMNS.contains(&column) {
+            true => Ok(quote_ident(column)),
+            false => Err(ConsumeError::InvalidQuery(format!(
+                "Unknown column \"{}\" for table {}",
+                column,
+                T::qualified_name()
+            ))),
+        }
+    }
+
+    /// Returns the generated query, whose parameters are the `where_eq` values in order.
+    pub fn sql(&self) -> Result<String, ConsumeError> {
+        let columns: Vec<String> = T::COLUMNS.iter().map(|v| quote_ident(v)).collect();
+        let mut query = format!("select {} from {}", columns.join(", "), T::qualified_name());
+        let mut conditions = Vec::with_capacity(self.filters.len() + 1);
+
+        if let (Some(v), false) = (T::SOFT_DELETE, self.include_deleted) {
+            conditions.push(format!("{} is null", quote_ident(v)));
+        }
+
+        for (i, (column, _)) in self.filters.iter().enumerate() {
+            conditions.push(format!("{} = ${}", Self::column(column)?, i + 1));
+        }
+
+        if !conditions.is_empty() {
+            query.push_str(&format!(" where {}", conditions.join(" and ")));
+        }
+
+        let mut order = Vec::with_capacity(self.order.len());
+
+        for (column, descending) in self.order.iter() {
+            let direction = match descending {
+                true => "desc",
+                false => "asc",
+            };
+
+            order.push(format!("{} {}", Self::column(column)?, direction));
+        }
+
+        if !order.is_empty() {
+            query.push_str(&format!(" order by {}", order.join(", ")));
+        }
+
+        if let Some(v) = self.limit {
+            query.push_str(&format!(" limit {}", v));
+        }
+
+        if let Some(v) = self.offset {
+            query.push_str(&format!(" offset {}", v));
+        }
+
+        Ok(query)
+    }
+
+    /// Runs the query and consumes the selected rows.
+    pub async fn consume(self, conn: &Client) -> Result<Vec<T>, ConsumeError> {
+        let query = self.sql()?;
+        let params: Vec<&(dyn ToSql + Sync)> = self.filters.iter().map(|v| v.1).collect();
+        T::consume(conn, &query, &params).await
+    }
+}
+
+impl<T: Table> Default for SelectBuilder<'_, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,243 @@
+//! Session settings applied to a connection when it is established, and scoped changes
+//! to the schemas a session searches.
+use crate::ident::quote_ident;
+use crate::ConsumeError;
+use futures_util::future::BoxFuture;
+use futures_util::FutureExt;
+use std::time::Duration;
+use tokio_postgres::types::ToSql;
+use tokio_postgres::Client;
+
+/// Settings of a database session, such as `statement_timeout` and `search_path`, built
+/// up from `SessionSettings::new()`. Settings that are not set keep the server's default.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::SessionSettings;
+/// use std::time::Duration;
+/// use tokio_postgres::NoTls;
+///
+/// let settings = SessionSettings::new()
+///     .statement_timeout(Duration::from_secs(30))
+///     .application_name("nightly-report")
+///     .search_path(&["reporting", "public"])
+///     .time_zone("UTC");
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         if settings.apply(&client).await.is_err() {
+///             eprintln!("Could not apply session settings");
+///         }
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SessionSettings {
+    settings: Vec<(&'static str, String)>,
+}
+
+impl SessionSettings {
+    /// Creates settings that change nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the value of `name`, or adds it.
+    fn set(mut self, name: &'static str, value: String) -> Self {
+        self.settings.retain(|v| v.0 != name);
+        self.settings.push((name, value));
+        self
+    }
+
+    /// Aborts statements that run longer than `timeout`, rounded down to milliseconds.
+    /// A timeout of zero disables the limit.
+    pub fn statement_timeout(self, timeout: Duration) -> Self {
+        self.set("statement_timeout", format!("{}ms", timeout.as_millis()))
+    }
+
+    /// Sets the application name reported in `pg_stat_activity` and server logs.
+    pub fn application_name(self, application_name: &str) -> Self {
+        self.set("application_name", String::from(application_name))
+    }
+
+    /// Sets the schemas searched for unqualified names, in order.
+    pub fn search_path(self, schemas: &[&str]) -> Self {
+        let schemas: Vec<String> = schemas.iter().map(|v| quote_ident(v)).collect();
+        self.set("search_path", schemas.join(", "))
+    }
+
+    /// Sets the time zone used to display and interpret timestamps.
+    pub fn time_zone(self, time_zone: &str) -> Self {
+        self.set("TimeZone", String::from(time_zone))
+    }
+
+    /// Returns whether no settings are set.
+    pub fn is_empty(&self) -> bool {
+        self.settings.is_empty()
+    }
+
+    /// Applies the settings to the session of `client`.
+    pub async fn apply(&self, client: &Client) -> Result<(), ConsumeError> {
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        let calls: Vec<String> = (0..self.settings.len())
+            .map(|i| format!("set_config(${}, ${}, false)", i * 2 + 1, i * 2 + 2))
+            .collect();
+
+        let mut params: Vec<&(dyn ToSql + Sync)> = Vec::with_capacity(self.settings.len() * 2);
+
+        for (name, value) in self.settings.iter() {
+            params.push(name);
+            params.push(value);
+        }
+
+        match client
+            .execute(&format!("select {};", calls.join(", ")), &params)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => Err(ConsumeError::from(e)),
+        }
+    }
+}
+
+/// Sets the `search_path` of the session of `client` to `schema` alone while `f` runs,
+/// then restores the previous `search_path`, whether `f` succeeds or fails. Unqualified
+/// names in the queries of `f`, including those of the derived `Table` helpers, then
+/// refer to the tables of `schema`, so apps keeping a schema per tenant may reuse one set
+/// of consumer structs for every tenant.
+///
+/// The `search_path` applies to the whole session, so the client should not run queries
+/// for other tenants concurrently, such as when it is shared between tasks. If the
+/// returned future is dropped before it completes, such as when it is cancelled or `f`
+/// panics, the restore is queued on the connection without waiting for the result, since
+/// `Drop` cannot be asynchronous. Errors reading, setting, or restoring the `search_path`
+/// are returned as [ConsumeError::DatabaseError] or
+/// [ConsumeError::DatabaseConnectionError], converted into the error type of `f`.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::{with_schema, ConsumeError, RowConsumer};
+/// use tokio_postgres::NoTls;
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         let result = with_schema(&client, "pg_catalog", |scoped| {
+///             Box::pin(async move { String::consume(scoped, "select current_schema();", &[]).await })
+///         })
+///         .await;
+///
+///         match result {
+///             Ok(v) => println!("Consumed {} rows in schema", v.len()), // v is of type Vec<String>
+///             Err(_) => eprintln!("An error occurred while querying database"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub async fn with_schema<R, E, F>(client: &Client, schema: &str, f: F) -> Result<R, E>
+where
+    E: From<ConsumeError>,
+    F: for<'s> FnOnce(&'s Client) -> BoxFuture<'s, Result<R, E>>,
+{
+    let previous = match client
+        .query_one("select current_setting('search_path');", &[])
+        .await
+    {
+        Ok(v) => match v.try_get::<usize, String>(0) {
+            Ok(v) => v,
+            Err(e) => return Err(E::from(ConsumeError::from(e))),
+        },
+        Err(e) => return Err(E::from(ConsumeError::from(e))),
+    };
+
+    // Built before setting the search_path, so that a failure to set it, or a dropped
+    // future, restores it when the guard is dropped.
+    let mut guard = SchemaGuard {
+        client,
+        previous,
+        restored: false,
+    };
+
+    if let Err(e) = client
+        .execute(
+            "select set_config('search_path', $1, false);",
+            &[&quote_ident(schema)],
+        )
+        .await
+    {
+        return Err(E::from(ConsumeError::from(e)));
+    }
+
+    let result = f(client).await;
+
+    guard.restored = true;
+
+    match client.batch_execute(&guard.restore()).await {
+        Ok(_) => result,
+        Err(e) => Err(E::from(ConsumeError::from(e))),
+    }
+}
+
+/// Restores the `search_path` replaced by `with_schema` when dropped, unless it was
+/// already restored.
+struct SchemaGuard<'a> {
+    client: &'a Client,
+    previous: String,
+    restored: bool,
+}
+
+impl SchemaGuard<'_> {
+    /// Returns the query restoring the `search_path`.
+    fn restore(&self) -> String {
+        format!(
+            "select set_config('search_path', '{}', false);",
+            self.previous.replace('\'', "''")
+        )
+    }
+}
+
+impl Drop for SchemaGuard<'_> {
+    fn drop(&mut self) {
+        if !self.restored {
+            // Polling once is enough to queue the query on the connection.
+            let _ = self.client.batch_execute(&self.restore()).now_or_never();
+        }
+    }
+}
+
+/// Drops every prepared statement of the session of `client` with `DEALLOCATE ALL`, such
+/// as when a pooled connection is reset by PgBouncer or handed to another user of the
+/// pool. Statements prepared by `client` before the call, including those held by a
+/// statement cache, cannot be used afterwards and should be prepared again.
+pub async fn deallocate_all(client: &Client) -> Result<(), ConsumeError> {
+    match client.batch_execute("deallocate all;").await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(ConsumeError::from(e)),
+    }
+}
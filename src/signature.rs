@@ -0,0 +1,161 @@
+//! Signatures of the mapping between a struct's fields and the columns it reads, for
+//! detecting skew between deployed code and the schema it expects.
+use crate::ConsumeError;
+use tokio_postgres::Client;
+
+/// The table signatures are stored in by `MappingSignature::store`.
+const SIGNATURE_TABLE: &str = "pgde_mapping_signatures";
+
+/// The fields a type reads from rows, in order, as column name and field type pairs, and
+/// a hash of them. Returned by `RowConsumer::mapping_signature`, which the derive macro
+/// implements, so that a change to the name, type, or order of any field changes the
+/// hash.
+///
+/// A deployment may `store` the signature of each struct it reads with, and another
+/// deployment, e.g. the green half of a blue/green deployment, may `verify` its own
+/// signatures against the stored ones before serving traffic. Signatures are stored in
+/// the `pgde_mapping_signatures` table, which `store` creates if it does not exist.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::RowConsumer;
+/// use pgde_derive::RowConsumer;
+/// use tokio_postgres::{NoTls, Row};
+///
+/// #[derive(RowConsumer)]
+/// struct Foo {
+///     Id: i32,
+///     Data: String,
+/// }
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         let signature = Foo::mapping_signature();
+///
+///         match signature.verify(&client).await {
+///             Ok(_) => println!("Foo matches the stored mapping"),
+///             Err(_) => eprintln!("Foo does not match the stored mapping"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MappingSignature {
+    /// The name of the type.
+    pub name: &'static str,
+    /// The column name and type of every field, in order.
+    pub fields: &'static [(&'static str, &'static str)],
+    /// A hash of the fields, stable across builds and compiler versions.
+    pub hash: u64,
+}
+
+impl MappingSignature {
+    /// Creates the signature of the given fields, hashing them with 64-bit FNV-1a.
+    pub const fn new(name: &'static str, fields: &'static [(&'static str, &'static str)]) -> Self {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        let mut i = 0;
+
+        while i < fields.len() {
+            hash = fnv1a(
+                fnv1a(fnv1a(hash, fields[i].0.as_bytes()), b":"),
+                fields[i].1.as_bytes(),
+            );
+            hash = fnv1a(hash, b";");
+            i += 1;
+        }
+
+        MappingSignature { name, fields, hash }
+    }
+
+    /// Stores the signature under its type's name, replacing any stored signature.
+    pub async fn store(&self, client: &Client) -> Result<(), ConsumeError> {
+        let create = format!(
+            "create table if not exists {} (name text primary key, signature bigint not null);",
+            SIGNATURE_TABLE
+        );
+
+        if let Err(e) = client.batch_execute(&create).await {
+            return Err(ConsumeError::from(e));
+        }
+
+        let upsert = format!(
+            "insert into {} values ($1, $2) on conflict (name) do update set signature = excluded.signature;",
+            SIGNATURE_TABLE
+        );
+
+        match client
+            .execute(&upsert, &[&self.name, &(self.hash as i64)])
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => Err(ConsumeError::from(e)),
+        }
+    }
+
+    /// Returns the signature stored under its type's name, if any.
+    pub async fn stored(&self, client: &Client) -> Result<Option<u64>, ConsumeError> {
+        let query = format!("select signature from {} where name = $1;", SIGNATURE_TABLE);
+
+        let exists = match client
+            .query_one("select to_regclass($1) is not null;", &[&SIGNATURE_TABLE])
+            .await
+        {
+            Ok(v) => match v.try_get::<usize, bool>(0) {
+                Ok(v) => v,
+                Err(e) => return Err(ConsumeError::from(e)),
+            },
+            Err(e) => return Err(ConsumeError::from(e)),
+        };
+
+        if !exists {
+            return Ok(None);
+        }
+
+        match client.query_opt(&query, &[&self.name]).await {
+            Ok(Some(v)) => match v.try_get::<usize, i64>(0) {
+                Ok(v) => Ok(Some(v as u64)),
+                Err(_) => Err(ConsumeError::ConversionError),
+            },
+            Ok(None) => Ok(None),
+            Err(e) => Err(ConsumeError::from(e)),
+        }
+    }
+
+    /// Checks the signature against the one stored under its type's name, failing with
+    /// [ConsumeError::SignatureMismatch] if they differ or none is stored.
+    pub async fn verify(&self, client: &Client) -> Result<(), ConsumeError> {
+        match self.stored(client).await? {
+            Some(v) if v == self.hash => Ok(()),
+            stored => Err(ConsumeError::SignatureMismatch {
+                name: String::from(self.name),
+                stored,
+                actual: self.hash,
+            }),
+        }
+    }
+}
+
+/// Folds bytes into a 64-bit FNV-1a hash.
+const fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    let mut i = 0;
+
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+        i += 1;
+    }
+
+    hash
+}
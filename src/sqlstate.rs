@@ -0,0 +1,37 @@
+//! Classification of database errors by SQLSTATE, so that callers may branch on the
+//! kind of failure without parsing messages.
+
+/// The category of a failed call, returned by `ConsumeError::classification`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorClass {
+    /// A unique or primary key constraint was violated, SQLSTATE `23505`.
+    UniqueViolation,
+    /// A foreign key constraint was violated, SQLSTATE `23503`.
+    ForeignKeyViolation,
+    /// The transaction could not be serialized or was chosen as a deadlock victim,
+    /// SQLSTATE `40001` or `40P01`, and may succeed if retried.
+    SerializationFailure,
+    /// The connection was lost or refused, SQLSTATE class `08` or an administrator
+    /// shutdown, or failed without the database reporting an error.
+    ConnectionLost,
+    /// A statement, lock, or idle transaction timeout was reached, SQLSTATE `57014`,
+    /// `55P03`, or `25P03`.
+    Timeout,
+    /// Any other error.
+    Other,
+}
+
+impl ErrorClass {
+    /// Classifies a five character SQLSTATE code.
+    pub fn from_code(code: &str) -> Self {
+        match code {
+            "23505" => ErrorClass::UniqueViolation,
+            "23503" => ErrorClass::ForeignKeyViolation,
+            "40001" | "40P01" => ErrorClass::SerializationFailure,
+            "57P01" | "57P02" | "57P03" => ErrorClass::ConnectionLost,
+            v if v.starts_with("08") => ErrorClass::ConnectionLost,
+            "57014" | "55P03" | "25P03" => ErrorClass::Timeout,
+            _ => ErrorClass::Other,
+        }
+    }
+}
@@ -0,0 +1,232 @@
+//! Per-column statistics of a query's results, gathered while streaming its rows.
+use crate::numeric::numeric_to_f64;
+use crate::raw::NullCheck;
+use crate::{ConsumeError, RowConsumer, UtcTimestamp};
+use futures_util::StreamExt;
+use std::error::Error;
+use tokio_postgres::types::{FromSql, ToSql, Type};
+use tokio_postgres::Client;
+
+/// A value of a numeric or temporal column, as tracked for its minimum and maximum.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StatValue {
+    /// The value of an `int2`, `int4`, or `int8` column.
+    Integer(i64),
+    /// The value of a `float4`, `float8`, or `numeric` column.
+    Float(f64),
+    /// The value of a `timestamp` or `timestamptz` column.
+    Timestamp(UtcTimestamp),
+    /// The value of a `date` column, in days since `2000-01-01`.
+    Date(i32),
+}
+
+impl StatValue {
+    /// Returns whether the value orders before `other`, which is read from the same column.
+    fn is_less(&self, other: &StatValue) -> bool {
+        match (self, other) {
+            (StatValue::Integer(a), StatValue::Integer(b)) => a < b,
+            (StatValue::Float(a), StatValue::Float(b)) => a < b,
+            (StatValue::Timestamp(a), StatValue::Timestamp(b)) => a.0 < b.0,
+            (StatValue::Date(a), StatValue::Date(b)) => a < b,
+            _ => false,
+        }
+    }
+}
+
+impl<'a> FromSql<'a> for StatValue {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        match *ty {
+            Type::INT2 => Ok(StatValue::Integer(i16::from_sql(ty, raw)? as i64)),
+            Type::INT4 => Ok(StatValue::Integer(i32::from_sql(ty, raw)? as i64)),
+            Type::INT8 => Ok(StatValue::Integer(i64::from_sql(ty, raw)?)),
+            Type::FLOAT4 => Ok(StatValue::Float(f32::from_sql(ty, raw)? as f64)),
+            Type::FLOAT8 => Ok(StatValue::Float(f64::from_sql(ty, raw)?)),
+            Type::NUMERIC => Ok(StatValue::Float(numeric_to_f64(raw)?)),
+            Type::TIMESTAMP | Type::TIMESTAMPTZ => {
+                Ok(StatValue::Timestamp(UtcTimestamp::from_sql(ty, raw)?))
+            }
+            Type::DATE => Ok(StatValue::Date(i32::from_sql(&Type::INT4, raw)?)),
+            _ => Err("unsupported statistics type".into()),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(
+            *ty,
+            Type::INT2
+                | Type::INT4
+                | Type::INT8
+                | Type::FLOAT4
+                | Type::FLOAT8
+                | Type::NUMERIC
+                | Type::TIMESTAMP
+                | Type::TIMESTAMPTZ
+                | Type::DATE
+        )
+    }
+}
+
+/// The statistics of a column of a [ResultSummary].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnStats {
+    /// The name of the column.
+    pub name: String,
+    /// The number of values that are not NULL.
+    pub count: u64,
+    /// The number of NULL values.
+    pub nulls: u64,
+    /// The least value, for numeric and temporal columns with any values. `NaN` is
+    /// skipped.
+    pub min: Option<StatValue>,
+    /// The greatest value, for numeric and temporal columns with any values. `NaN` is
+    /// skipped.
+    pub max: Option<StatValue>,
+}
+
+/// The per-column statistics of a query's results, returned by [summarize].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResultSummary {
+    /// The number of rows.
+    pub rows: u64,
+    /// The number of rows that failed conversion into the consuming type.
+    pub invalid_rows: u64,
+    /// The statistics of every column, in the order of the query's columns.
+    pub columns: Vec<ColumnStats>,
+}
+
+/// Runs `query` and gathers the per-column statistics of its results for data quality
+/// checks, counting values and NULLs of every column, tracking the least and greatest
+/// values of numeric and temporal columns, and counting the rows that fail conversion
+/// into `T`. Rows are streamed and dropped once counted, so that summarizing uses
+/// constant memory however large the results are.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::summarize;
+/// use pgde_derive::RowConsumer;
+/// use tokio_postgres::{NoTls, Row};
+///
+/// #[derive(RowConsumer)]
+/// struct Setting {
+///     name: String,
+///     min_val: Option<String>,
+/// }
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         match summarize::<Setting>(&client, "select name, min_val from pg_settings;", &[]).await {
+///             Ok(v) => {
+///                 for column in v.columns {
+///                     println!("{} has {} NULLs in {} rows", column.name, column.nulls, v.rows);
+///                 }
+///             },
+///             Err(_) => eprintln!("An error occurred while querying database"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub async fn summarize<T: RowConsumer>(
+    client: &Client,
+    query: &str,
+    params: &[&(dyn ToSql + Sync)],
+) -> Result<ResultSummary, ConsumeError> {
+    let statement = match client.prepare(query).await {
+        Ok(v) => v,
+        Err(e) => return Err(ConsumeError::from(e)),
+    };
+
+    let mut summary = ResultSummary {
+        rows: 0,
+        invalid_rows: 0,
+        columns: statement
+            .columns()
+            .iter()
+            .map(|v| ColumnStats {
+                name: String::from(v.name()),
+                count: 0,
+                nulls: 0,
+                min: None,
+                max: None,
+            })
+            .collect(),
+    };
+
+    let tracked = statement
+        .columns()
+        .iter()
+        .map(|v| <StatValue as FromSql>::accepts(v.type_()))
+        .collect::<Vec<bool>>();
+
+    let stream = match client.query_raw(&statement, params.iter().copied()).await {
+        Ok(v) => v,
+        Err(e) => return Err(ConsumeError::from(e)),
+    };
+
+    let mut stream = std::pin::pin!(stream);
+
+    while let Some(row) = stream.next().await {
+        let row = match row {
+            Ok(v) => v,
+            Err(e) => return Err(ConsumeError::from(e)),
+        };
+
+        summary.rows += 1;
+
+        for (i, column) in summary.columns.iter_mut().enumerate() {
+            let value = match tracked[i] {
+                true => match row.try_get::<usize, Option<StatValue>>(i) {
+                    Ok(v) => v,
+                    Err(_) => return Err(ConsumeError::ConversionError),
+                },
+                false => match row.try_get::<usize, NullCheck>(i) {
+                    Ok(NullCheck(true)) => None,
+                    Ok(NullCheck(false)) => {
+                        column.count += 1;
+                        continue;
+                    }
+                    Err(_) => return Err(ConsumeError::ConversionError),
+                },
+            };
+
+            let value = match value {
+                Some(v) => v,
+                None => {
+                    column.nulls += 1;
+                    continue;
+                }
+            };
+
+            column.count += 1;
+
+            if matches!(value, StatValue::Float(v) if v.is_nan()) {
+                continue;
+            }
+
+            if column.min.is_none_or(|v| value.is_less(&v)) {
+                column.min = Some(value);
+            }
+
+            if column.max.is_none_or(|v| v.is_less(&value)) {
+                column.max = Some(value);
+            }
+        }
+
+        if T::from_row(row).is_err() {
+            summary.invalid_rows += 1;
+        }
+    }
+
+    Ok(summary)
+}
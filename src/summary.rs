@@ -0,0 +1,60 @@
+//! Summaries of consumed rows for structured logging, with sensitive fields redacted.
+use std::fmt::Debug;
+
+/// The longest a summarized value may be, in characters, before it is cut short.
+const MAX_VALUE_LENGTH: usize = 64;
+
+/// A one-line summary of a consumed row, naming every field alongside a representative
+/// value, for structured logging. Implemented by the derive macro for structs marked
+/// `#[pgde(summary)]`. Values are written with `Debug` and cut short past 64 characters,
+/// and fields marked `#[pgde(sensitive)]` are written as `[redacted]`, so that logging
+/// rows cannot leak the personal data they hold.
+///
+/// ## Example
+/// ```
+/// use pgde::Summary;
+/// use pgde_derive::RowConsumer;
+/// use tokio_postgres::Row;
+///
+/// #[derive(RowConsumer)]
+/// #[pgde(summary)]
+/// struct Customer {
+///     Id: i32,
+///     #[pgde(sensitive)]
+///     Email: String,
+/// }
+///
+/// let customer = Customer {
+///     Id: 7,
+///     Email: String::from("someone@example.com"),
+/// };
+///
+/// assert_eq!(customer.summary(), "Customer { Id: 7, Email: [redacted] }");
+/// ```
+pub trait Summary {
+    /// Returns the summary of the row.
+    fn summary(&self) -> String;
+}
+
+/// Writes a field's value for a summary, cutting it short past the maximum length.
+pub fn summary_value(value: &dyn Debug) -> String {
+    let value = format!("{:?}", value);
+
+    match value.char_indices().nth(MAX_VALUE_LENGTH) {
+        Some((i, _)) => format!("{}...", &value[..i]),
+        None => value,
+    }
+}
+
+/// Writes a summary from the values of a struct's fields, `None` for sensitive fields.
+pub fn format_summary(name: &str, fields: &[(&str, Option<String>)]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|(field, value)| match value {
+            Some(v) => format!("{}: {}", field, v),
+            None => format!("{}: [redacted]", field),
+        })
+        .collect();
+
+    format!("{} {{ {} }}", name, fields.join(", "))
+}
@@ -0,0 +1,204 @@
+//! A client that owns its connection and reconnects when the connection ends.
+use crate::{ConsumeError, ConsumeOptions, RowConsumer, SessionSettings};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio_postgres::tls::{MakeTlsConnect, TlsConnect};
+use tokio_postgres::types::ToSql;
+use tokio_postgres::{Client, Config, Socket};
+
+/// A client that spawns its own connection task onto the tokio runtime and, when the
+/// connection ends, reconnects with exponential backoff. Consumption calls that fail
+/// because the connection was lost are retried on the new connection, so queries passed
+/// to them should be safe to run more than once. [SessionSettings] given to
+/// `connect_with_settings` are applied to every connection, including reconnections.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::client::SupervisedClient;
+/// use tokio_postgres::{Config, NoTls};
+///
+/// match "host=localhost user=postgres password=password dbname=postgres".parse::<Config>() {
+///     Ok(config) => match SupervisedClient::connect(config, NoTls).await {
+///         Ok(client) => match client.consume::<i32>("select 1;", &[]).await {
+///             Ok(v) => println!("Consumed {} rows", v.len()), // v is of type Vec<i32>
+///             Err(_) => eprintln!("An error occurred while querying database"),
+///         },
+///         Err(_) => eprintln!("Could not connect to database"),
+///     },
+///     Err(_) => eprintln!("Invalid connection string"),
+/// };
+/// # })
+/// ```
+pub struct SupervisedClient<T> {
+    config: Config,
+    tls: T,
+    settings: SessionSettings,
+    client: Mutex<Arc<Client>>,
+    reconnecting: tokio::sync::Mutex<()>,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    max_retries: usize,
+}
+
+impl<T> SupervisedClient<T>
+where
+    T: MakeTlsConnect<Socket> + Clone + Send + Sync + 'static,
+    T::Stream: Send,
+    T::TlsConnect: Send,
+    <T::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    /// Connects to the database described by `config`. By default, reconnection waits
+    /// 100ms before its first attempt, doubling up to 10s, and a call is retried up to 3
+    /// times.
+    pub async fn connect(config: Config, tls: T) -> Result<Self, ConsumeError> {
+        Self::connect_with_settings(config, tls, SessionSettings::new()).await
+    }
+
+    /// Connects to the database described by `config` like `connect`, applying
+    /// `settings` to the session of the connection and of every reconnection.
+    pub async fn connect_with_settings(
+        config: Config,
+        tls: T,
+        settings: SessionSettings,
+    ) -> Result<Self, ConsumeError> {
+        let client = Self::open(&config, tls.clone(), &settings).await?;
+
+        Ok(SupervisedClient {
+            config,
+            tls,
+            settings,
+            client: Mutex::new(Arc::new(client)),
+            reconnecting: tokio::sync::Mutex::new(()),
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            max_retries: 3,
+        })
+    }
+
+    /// Sets the wait before the first reconnection attempt and the most that the wait,
+    /// doubled after every failed attempt, may grow to.
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Sets how many times a call is retried after the connection is lost, each retry
+    /// waiting for a reconnection.
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Opens a connection, spawns its task, and applies the session settings.
+    async fn open(
+        config: &Config,
+        tls: T,
+        settings: &SessionSettings,
+    ) -> Result<Client, ConsumeError> {
+        match config.connect(tls).await {
+            Ok((client, conn)) => {
+                tokio::spawn(async move {
+                    let _ = conn.await;
+                });
+
+                settings.apply(&client).await?;
+
+                Ok(client)
+            }
+            Err(e) => Err(ConsumeError::from(e)),
+        }
+    }
+
+    /// Returns the current client, which may be closed if the connection was lost and has
+    /// not been reestablished yet.
+    pub fn client(&self) -> Arc<Client> {
+        match self.client.lock() {
+            Ok(v) => v.clone(),
+            Err(v) => v.into_inner().clone(),
+        }
+    }
+
+    /// Reconnects if the current client is closed, waiting between failed attempts.
+    /// Concurrent callers share a single reconnection.
+    pub async fn reconnect(&self) -> Result<Arc<Client>, ConsumeError> {
+        let _reconnecting = self.reconnecting.lock().await;
+        let current = self.client();
+
+        if !current.is_closed() {
+            return Ok(current);
+        }
+
+        let mut backoff = self.initial_backoff;
+
+        for attempt in 0..=self.max_retries {
+            if attempt > 0 {
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(self.max_backoff);
+            }
+
+            if let Ok(v) = Self::open(&self.config, self.tls.clone(), &self.settings).await {
+                let client = Arc::new(v);
+
+                match self.client.lock() {
+                    Ok(mut v) => *v = client.clone(),
+                    Err(v) => *v.into_inner() = client.clone(),
+                };
+
+                return Ok(client);
+            }
+        }
+
+        Err(ConsumeError::DatabaseConnectionError)
+    }
+
+    /// Consumes row data with `RowConsumer::consume`, reconnecting and retrying if the
+    /// connection is lost.
+    pub async fn consume<R: RowConsumer>(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<R>, ConsumeError> {
+        let mut retries = 0;
+
+        loop {
+            let client = self.client();
+
+            match R::consume(&client, query, params).await {
+                Err(ConsumeError::DatabaseConnectionError)
+                    if client.is_closed() && retries < self.max_retries =>
+                {
+                    retries += 1;
+                    self.reconnect().await?;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Consumes row data with `RowConsumer::consume_with_options`, reconnecting and
+    /// retrying if the connection is lost.
+    pub async fn consume_with_options<R: RowConsumer + Send>(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+        options: &ConsumeOptions,
+    ) -> Result<Vec<R>, ConsumeError> {
+        let mut retries = 0;
+
+        loop {
+            let client = self.client();
+
+            match R::consume_with_options(&client, query, params, options).await {
+                Err(ConsumeError::DatabaseConnectionError)
+                    if client.is_closed() && retries < self.max_retries =>
+                {
+                    retries += 1;
+                    self.reconnect().await?;
+                }
+                result => return result,
+            }
+        }
+    }
+}
@@ -0,0 +1,400 @@
+//! Table metadata generated by the derive macro, used to build queries from a struct's
+//! columns.
+use crate::ident::quote_ident;
+use crate::select::SelectBuilder;
+use crate::{ConsumeError, RowConsumer};
+use std::future::Future;
+use tokio_postgres::types::ToSql;
+use tokio_postgres::{Client, Row};
+
+/// A column of a [Table], generated by the derive macro as a `<Struct>Columns` enum with
+/// a variant for every field, named after the field in upper camel case. Query builders
+/// such as [SelectBuilder] accept columns in place of their names, so that a misspelled
+/// column fails to compile rather than at runtime. The enum may be renamed with
+/// `#[pgde(columns_enum = "...")]` on the struct, e.g. when the module already has a type
+/// of that name, or skipped with `#[pgde(skip_columns_enum)]`.
+pub trait TableColumn: Copy + 'static {
+    /// The name of the column.
+    fn as_str(&self) -> &'static str;
+
+    /// The position of the column in `Table::COLUMNS`, and of its field in the struct.
+    fn index(&self) -> usize;
+}
+
+/// The columns of a struct marked `#[pgde(skip_columns_enum)]`, for which no
+/// `<Struct>Columns` enum is generated. It has no variants, so such a struct can only be
+/// ordered by `Table::consume_ordered` with an empty order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NoColumns {}
+
+impl TableColumn for NoColumns {
+    fn as_str(&self) -> &'static str {
+        match *self {}
+    }
+
+    fn index(&self) -> usize {
+        match *self {}
+    }
+}
+
+/// The direction a column is sorted in by `Table::consume_ordered`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SortOrder {
+    /// Smallest values first.
+    #[default]
+    Asc,
+    /// Largest values first.
+    Desc,
+}
+
+/// Describes the table a struct that derives `RowConsumer` is read from. The derive
+/// macro implements this trait using the struct's name as the table name and its
+/// field names as the column names. The table and schema may be set with
+/// `#[pgde(table = "...", schema = "...")]` on the struct, and a soft delete column with
+/// `#[pgde(soft_delete = "...")]`.
+pub trait Table: RowConsumer {
+    /// The schema of the table, if any.
+    const SCHEMA: Option<&'static str>;
+
+    /// The name of the table.
+    const TABLE: &'static str;
+
+    /// The names of the columns read by `from_row`, in order.
+    const COLUMNS: &'static [&'static str];
+
+    /// The column marking rows as deleted when it is not null, set with
+    /// `#[pgde(soft_delete = "...")]` on the struct. The generated queries of
+    /// [SelectBuilder], `consume_selected`, `exists`, and `consume_count` leave out
+    /// deleted rows, unless `SelectBuilder::include_deleted` is called.
+    const SOFT_DELETE: Option<&'static str> = None;
+
+    /// The number of fields of the struct, and of columns read by `from_row`.
+    const FIELD_COUNT: usize = Self::COLUMNS.len();
+
+    /// The columns of the table, one for every field, as generated by the derive macro, or
+    /// [NoColumns] for a struct marked `#[pgde(skip_columns_enum)]`.
+    type Columns: TableColumn;
+
+    /// Consumes a row holding only the given columns, which are read by name, leaving the
+    /// remaining fields with default values. Implemented by the derive macro.
+    fn from_row_selected(row: &Row, columns: &[&str]) -> Result<Self, (Self, Vec<String>)>
+    where
+        Self: Sized;
+
+    /// Returns the quoted, schema-qualified name of the table.
+    fn qualified_name() -> String {
+        match Self::SCHEMA {
+            Some(v) => format!("{}.{}", quote_ident(v), quote_ident(Self::TABLE)),
+            None => quote_ident(Self::TABLE),
+        }
+    }
+
+    /// Starts a [SelectBuilder] that selects this struct's columns from its table.
+    fn select<'a>() -> SelectBuilder<'a, Self>
+    where
+        Self: Sized,
+    {
+        SelectBuilder::new()
+    }
+
+    /// Consumes only `columns` of the table's rows, leaving the remaining fields with
+    /// default values, e.g. to avoid reading wide rows when only a few fields are
+    /// requested. Rows are filtered by `filter`, a where clause using `params`, unless it
+    /// is empty.
+    ///
+    /// ## Example
+    /// ```
+    /// # tokio_test::block_on(async {
+    /// use pgde::Table;
+    /// use pgde_derive::RowConsumer;
+    /// use tokio_postgres::{NoTls, Row};
+    ///
+    /// #[derive(RowConsumer)]
+    /// struct Foo {
+    ///     Id: i32,
+    ///     Data: String,
+    ///     Blob: Option<Vec<u8>>,
+    /// }
+    ///
+    /// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+    ///     Ok(v) => {
+    ///         let client = v.0;
+    ///         let conn = v.1;
+    ///
+    ///         tokio::spawn(async move {
+    ///             if let Err(e) = conn.await {
+    ///                 eprintln!("connection error: {}", e);
+    ///             }
+    ///         });
+    ///
+    ///         match Foo::consume_selected(&client, &["Id", "Data"], "\"Id\" > $1", &[&10]).await {
+    ///             Ok(v) => println!("Found {} rows", v.len()), // v is of type Vec<Foo> with Blob set to None
+    ///             Err(_) => eprintln!("An error occurred while querying database"),
+    ///         };
+    ///     },
+    ///     Err(_) => eprintln!("Could not connect to database"),
+    /// };
+    /// # })
+    /// ```
+    fn consume_selected(
+        conn: &Client,
+        columns: &[&str],
+        filter: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> impl Future<Output = Result<Vec<Self>, ConsumeError>> + Send
+    where
+        Self: Sized,
+    {
+        async move {
+            let mut selected = Vec::with_capacity(columns.len());
+
+            for column in columns.iter() {
+                match Self::COLUMNS.contains(column) {
+                    true => selected.push(quote_ident(column)),
+                    false => {
+                        return Err(ConsumeError::InvalidQuery(format!(
+                            "Unknown column \"{}\" for table {}",
+                            column,
+                            Self::qualified_name()
+                        )))
+                    }
+                }
+            }
+
+            if selected.is_empty() {
+                return Err(ConsumeError::InvalidQuery(String::from(
+                    "No columns were selected",
+                )));
+            }
+
+            let query = with_filter(
+                format!(
+                    "select {} from {}",
+                    selected.join(", "),
+                    Self::qualified_name()
+                ),
+                Self::SOFT_DELETE,
+                filter,
+            );
+
+            #[cfg(feature = "dev_guard")]
+            crate::guard::record(&query);
+
+            let rows = match conn.query(&query, params).await {
+                Ok(v) => v,
+                Err(e) => return Err(ConsumeError::from(e)),
+            };
+
+            let mut data = Vec::with_capacity(rows.len());
+
+            for row in rows.iter() {
+                match Self::from_row_selected(row, columns) {
+                    Ok(v) => data.push(v),
+                    Err(_) => return Err(ConsumeError::ConversionError),
+                }
+            }
+
+            Ok(data)
+        }
+    }
+
+    /// Consumes the rows of `base_query` sorted by `order`, appending an `ORDER BY`
+    /// clause built from columns of the table rather than from text. Sort fields taken
+    /// from a request, e.g. a `sort=created_at:desc` parameter of an API, are parsed into
+    /// `Self::Columns` first, so that they cannot inject SQL. The base query is run as a
+    /// subquery, so it may itself be filtered or joined, and is consumed as is when
+    /// `order` is empty.
+    ///
+    /// ## Example
+    /// ```
+    /// # tokio_test::block_on(async {
+    /// use pgde::{SortOrder, Table};
+    /// use pgde_derive::RowConsumer;
+    /// use tokio_postgres::{NoTls, Row};
+    ///
+    /// #[derive(RowConsumer)]
+    /// struct Foo {
+    ///     Id: i32,
+    ///     Data: String,
+    /// }
+    ///
+    /// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+    ///     Ok(v) => {
+    ///         let client = v.0;
+    ///         let conn = v.1;
+    ///
+    ///         tokio::spawn(async move {
+    ///             if let Err(e) = conn.await {
+    ///                 eprintln!("connection error: {}", e);
+    ///             }
+    ///         });
+    ///
+    ///         let query = "select * from public.\"Foo\" where \"Id\" > $1";
+    ///         let order = [(FooColumns::Data, SortOrder::Asc), (FooColumns::Id, SortOrder::Desc)];
+    ///
+    ///         match Foo::consume_ordered(&client, query, &[&10], &order).await {
+    ///             Ok(v) => println!("Found {} rows", v.len()), // v is of type Vec<Foo>
+    ///             Err(_) => eprintln!("An error occurred while querying database"),
+    ///         };
+    ///     },
+    ///     Err(_) => eprintln!("Could not connect to database"),
+    /// };
+    /// # })
+    /// ```
+    fn consume_ordered(
+        conn: &Client,
+        base_query: &str,
+        params: &[&(dyn ToSql + Sync)],
+        order: &[(Self::Columns, SortOrder)],
+    ) -> impl Future<Output = Result<Vec<Self>, ConsumeError>> + Send
+    where
+        Self: Sized,
+    {
+        let base_query = base_query.trim_end().trim_end_matches(';');
+
+        let query = match order.is_empty() {
+            true => String::from(base_query),
+            false => {
+                let order: Vec<String> = order
+                    .iter()
+                    .map(|(column, direction)| match direction {
+                        SortOrder::Asc => format!("{} asc", quote_ident(column.as_str())),
+                        SortOrder::Desc => format!("{} desc", quote_ident(column.as_str())),
+                    })
+                    .collect();
+
+                format!(
+                    "select * from ({}) as pgde_ordered order by {}",
+                    base_query,
+                    order.join(", ")
+                )
+            }
+        };
+
+        async move { Self::consume(conn, &query, params).await }
+    }
+
+    /// Returns whether the table has any rows matching `filter`, a where clause using
+    /// `params`, or any rows at all if `filter` is empty.
+    ///
+    /// ## Example
+    /// ```
+    /// # tokio_test::block_on(async {
+    /// use pgde::Table;
+    /// use pgde_derive::RowConsumer;
+    /// use tokio_postgres::{NoTls, Row};
+    ///
+    /// #[derive(RowConsumer)]
+    /// struct Foo {
+    ///     Id: i32,
+    ///     Data: String,
+    /// }
+    ///
+    /// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+    ///     Ok(v) => {
+    ///         let client = v.0;
+    ///         let conn = v.1;
+    ///
+    ///         tokio::spawn(async move {
+    ///             if let Err(e) = conn.await {
+    ///                 eprintln!("connection error: {}", e);
+    ///             }
+    ///         });
+    ///
+    ///         match Foo::exists(&client, "\"Id\" = $1", &[&10]).await {
+    ///             Ok(v) => println!("Foo 10 exists: {}", v),
+    ///             Err(_) => eprintln!("An error occurred while querying database"),
+    ///         };
+    ///     },
+    ///     Err(_) => eprintln!("Could not connect to database"),
+    /// };
+    /// # })
+    /// ```
+    fn exists(
+        conn: &Client,
+        filter: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> impl Future<Output = Result<bool, ConsumeError>> + Send
+    where
+        Self: Sized,
+    {
+        async move {
+            let query = with_filter(
+                format!("select 1 from {}", Self::qualified_name()),
+                Self::SOFT_DELETE,
+                filter,
+            );
+            crate::query_exists(conn, &query, params).await
+        }
+    }
+
+    /// Counts the rows of the table matching `filter`, a where clause using `params`, or
+    /// every row if `filter` is empty, with `select count(*)`.
+    ///
+    /// ## Example
+    /// ```
+    /// # tokio_test::block_on(async {
+    /// use pgde::Table;
+    /// use pgde_derive::RowConsumer;
+    /// use tokio_postgres::{NoTls, Row};
+    ///
+    /// #[derive(RowConsumer)]
+    /// struct Foo {
+    ///     Id: i32,
+    ///     Data: String,
+    /// }
+    ///
+    /// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+    ///     Ok(v) => {
+    ///         let client = v.0;
+    ///         let conn = v.1;
+    ///
+    ///         tokio::spawn(async move {
+    ///             if let Err(e) = conn.await {
+    ///                 eprintln!("connection error: {}", e);
+    ///             }
+    ///         });
+    ///
+    ///         match Foo::consume_count(&client, "\"Id\" > $1", &[&10]).await {
+    ///             Ok(v) => println!("Found {} rows", v),
+    ///             Err(_) => eprintln!("An error occurred while querying database"),
+    ///         };
+    ///     },
+    ///     Err(_) => eprintln!("Could not connect to database"),
+    /// };
+    /// # })
+    /// ```
+    fn consume_count(
+        conn: &Client,
+        filter: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> impl Future<Output = Result<i64, ConsumeError>> + Send
+    where
+        Self: Sized,
+    {
+        async move {
+            let query = with_filter(
+                format!("select count(*) from {}", Self::qualified_name()),
+                Self::SOFT_DELETE,
+                filter,
+            );
+            crate::consume_scalar(conn, &query, params).await
+        }
+    }
+}
+
+/// Appends `filter` to `query` as a where clause, unless it is empty, along with a check
+/// that the `soft_delete` column is null, if any.
+pub(crate) fn with_filter(query: String, soft_delete: Option<&str>, filter: &str) -> String {
+    match (soft_delete, filter.trim().is_empty()) {
+        (None, true) => query,
+        (None, false) => format!("{} where {}", query, filter),
+        (Some(v), true) => format!("{} where {} is null", query, quote_ident(v)),
+        (Some(v), false) => format!(
+            "{} where {} is null and ({})",
+            query,
+            quote_ident(v),
+            filter
+        ),
+    }
+}
@@ -0,0 +1,185 @@
+//! Temporary tables shaped like a struct's table, for joining queries against large sets
+//! of keys or rows loaded by the client.
+use crate::ident::quote_ident;
+use crate::{ConsumeError, Table};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio_postgres::binary_copy::BinaryCopyInWriter;
+use tokio_postgres::types::ToSql;
+use tokio_postgres::Transaction;
+
+/// Distinguishes the temporary tables created by one process.
+static TEMP_TABLE_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// A temporary table holding the columns of `T`, created with the same types as in `T`'s
+/// table and dropped when the transaction it was created in ends. Rows are loaded with
+/// binary `COPY`, and the table's `name` may be interpolated into later queries of the
+/// transaction, e.g. to join against a large set of keys rather than passing them as
+/// parameters.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::{RowConsumer, TempTable};
+/// use pgde_derive::RowConsumer;
+/// use tokio_postgres::{NoTls, Row};
+///
+/// #[derive(RowConsumer)]
+/// struct Foo {
+///     Id: i32,
+///     Data: String,
+/// }
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let mut client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         match client.transaction().await {
+///             Ok(txn) => {
+///                 match TempTable::<Foo>::create(&txn).await {
+///                     Ok(temp) => {
+///                         let rows: [&[&(dyn tokio_postgres::types::ToSql + Sync)]; 2] =
+///                             [&[&1, &"a"], &[&2, &"b"]];
+///
+///                         let _ = temp.load(&txn, rows).await;
+///
+///                         let query = format!(
+///                             "select f.* from public.\"Foo\" as f join {} as t using (\"Id\");",
+///                             temp.name()
+///                         );
+///
+///                         match Foo::consume(txn.client(), &query, &[]).await {
+///                             Ok(v) => println!("Joined {} rows", v.len()), // v is of type Vec<Foo>
+///                             Err(_) => eprintln!("An error occurred while querying database"),
+///                         };
+///                     },
+///                     Err(_) => eprintln!("Could not create temporary table"),
+///                 };
+///
+///                 let _ = txn.commit().await;
+///             },
+///             Err(_) => eprintln!("Could not start transaction"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+#[derive(Debug)]
+pub struct TempTable<T> {
+    name: String,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Table> TempTable<T> {
+    /// Creates a temporary table with the columns of `T`, in order, copying their types
+    /// from `T`'s table, which must exist. The table is dropped when `txn` commits or
+    /// rolls back.
+    pub async fn create(txn: &Transaction<'_>) -> Result<Self, ConsumeError> {
+        let count = TEMP_TABLE_COUNT.fetch_add(1, Ordering::Relaxed);
+        let name = quote_ident(&format!("pgde_temp_{}_{}", T::TABLE, count));
+
+        let query = format!(
+            "create temporary table {} on commit drop as select {} from {} with no data;",
+            name,
+            columns::<T>(),
+            T::qualified_name()
+        );
+
+        match txn.batch_execute(&query).await {
+            Ok(_) => Ok(TempTable {
+                name,
+                marker: PhantomData,
+            }),
+            Err(e) => Err(ConsumeError::from(e)),
+        }
+    }
+
+    /// Returns the quoted name of the table, for use in queries of the transaction that
+    /// created it.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Loads `rows` into the table with binary `COPY`, returning the number of rows
+    /// loaded. Every row holds a value for each of `T`'s columns, in order, of a type
+    /// accepted by the column. Rows of another length are rejected with
+    /// [ConsumeError::ColumnCountMismatch] before any is loaded, leaving the transaction
+    /// usable.
+    pub async fn load<'a, I>(&self, txn: &Transaction<'_>, rows: I) -> Result<u64, ConsumeError>
+    where
+        I: IntoIterator<Item = &'a [&'a (dyn ToSql + Sync)]>,
+    {
+        let statement = match txn
+            .prepare(&format!("select {} from {};", columns::<T>(), self.name))
+            .await
+        {
+            Ok(v) => v,
+            Err(e) => return Err(ConsumeError::from(e)),
+        };
+
+        let types = statement
+            .columns()
+            .iter()
+            .map(|v| v.type_().clone())
+            .collect::<Vec<_>>();
+
+        let rows = rows.into_iter().collect::<Vec<_>>();
+
+        if let Some(row) = rows.iter().find(|v| v.len() != types.len()) {
+            return Err(ConsumeError::ColumnCountMismatch {
+                expected: types.len(),
+                actual: row.len(),
+            });
+        }
+
+        let sink = match txn
+            .copy_in(&format!(
+                "copy {} ({}) from stdin with (format binary)",
+                self.name,
+                columns::<T>()
+            ))
+            .await
+        {
+            Ok(v) => v,
+            Err(e) => return Err(ConsumeError::from(e)),
+        };
+
+        let writer = BinaryCopyInWriter::new(sink, &types);
+        let mut writer = std::pin::pin!(writer);
+
+        for row in rows {
+            if let Err(e) = writer.as_mut().write(row).await {
+                return Err(ConsumeError::from(e));
+            }
+        }
+
+        match writer.as_mut().finish().await {
+            Ok(v) => Ok(v),
+            Err(e) => Err(ConsumeError::from(e)),
+        }
+    }
+
+    /// Consumes the rows of the table.
+    pub async fn consume(&self, txn: &Transaction<'_>) -> Result<Vec<T>, ConsumeError> {
+        let query = format!("select {} from {};", columns::<T>(), self.name);
+
+        T::consume(txn.client(), &query, &[]).await
+    }
+}
+
+/// Returns the quoted columns of `T`, separated by commas.
+fn columns<T: Table>() -> String {
+    T::COLUMNS
+        .iter()
+        .map(|v| quote_ident(v))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
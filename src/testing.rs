@@ -0,0 +1,365 @@
+//! Assertions for tests of queries and consuming types, which panic with the details of
+//! a failure instead of returning errors to be matched.
+use crate::ident::quote_ident;
+use crate::{ConsumeError, QueryParams, RowConsumer, Table};
+use futures_util::future::BoxFuture;
+use futures_util::FutureExt;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::SystemTime;
+use tokio_postgres::types::ToSql;
+use tokio_postgres::Client;
+
+/// The number of test schemas created by the process, which makes their names unique.
+static SCHEMA_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Describes a [ConsumeError] for a panic message.
+fn describe(error: &ConsumeError) -> String {
+    match error {
+        ConsumeError::ConversionError => String::from("a row could not be converted"),
+        ConsumeError::DatabaseConnectionError => String::from("the connection failed"),
+        ConsumeError::DatabaseError { code, message } => {
+            format!("the database rejected the query with {}: {}", code, message)
+        }
+        ConsumeError::ConversionErrors(v) => {
+            format!("rows could not be converted: {}", v.join("; "))
+        }
+        ConsumeError::ConversionReport(v) => {
+            format!("rows could not be converted: {}", v.errors.join("; "))
+        }
+        ConsumeError::InvalidQuery(v) => format!("the query is invalid: {}", v),
+        ConsumeError::UnexpectedRowCount(v) => format!("{} rows were returned", v),
+        ConsumeError::WriteError(v) => format!("output could not be written: {}", v),
+        ConsumeError::ReadError(v) => format!("input could not be read: {}", v),
+        ConsumeError::ColumnCountMismatch { expected, actual } => format!(
+            "{} columns were returned where {} are read",
+            actual, expected
+        ),
+        ConsumeError::AmbiguousColumns(v) => {
+            format!("columns are ambiguous: {}", v.join(", "))
+        }
+        ConsumeError::ColumnTypeMismatch(v) => {
+            format!("columns have the wrong types: {}", v.join("; "))
+        }
+        ConsumeError::SignatureMismatch {
+            name,
+            stored,
+            actual,
+        } => format!(
+            "the signature {} of {} differs from the stored {:?}",
+            actual, name, stored
+        ),
+        ConsumeError::CircuitOpen => String::from("the circuit is open"),
+    }
+}
+
+/// Consumes `query` into `T`, asserting that it returns exactly `n` rows, and returns
+/// them. Panics with the error if the query fails, or with every row returned if their
+/// number differs.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::testing::assert_consumes_n;
+/// use tokio_postgres::NoTls;
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         let rows = assert_consumes_n::<i32>(&client, "select generate_series(1, $1);", &[&3i32], 3).await;
+///         assert_eq!(rows, vec![1, 2, 3]);
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub async fn assert_consumes_n<T: RowConsumer + Debug>(
+    client: &Client,
+    query: &str,
+    params: &[&(dyn ToSql + Sync)],
+    n: usize,
+) -> Vec<T> {
+    let rows = match T::consume(client, query, params).await {
+        Ok(v) => v,
+        Err(e) => panic!("Could not consume `{}`: {}", query, describe(&e)),
+    };
+
+    if rows.len() != n {
+        panic!(
+            "Expected `{}` to return {} rows, but it returned {}:\n{:#?}",
+            query,
+            n,
+            rows.len(),
+            rows
+        );
+    }
+
+    rows
+}
+
+/// Inserts `value` into `table` and reads it back, asserting that exactly one row
+/// matches it and that the row equals it. The columns written and matched are the
+/// filters of `value`'s `QueryParams`, so fields should be compared by equality, and
+/// `Option` fields that are `None` are left to their columns' defaults. The row is read
+/// back from `T`'s columns.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::testing::assert_roundtrip;
+/// use pgde_derive::{QueryParams, RowConsumer};
+/// use tokio_postgres::{NoTls, Row};
+///
+/// #[derive(Debug, PartialEq, QueryParams, RowConsumer)]
+/// struct Account {
+///     id: i64,
+///     email: String,
+/// }
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         let account = Account {
+///             id: 1,
+///             email: String::from("someone@example.com"),
+///         };
+///
+///         if client.batch_execute("create temporary table account (id bigint, email text);").await.is_ok() {
+///             assert_roundtrip(&client, "account", &account).await;
+///         }
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub async fn assert_roundtrip<T>(client: &Client, table: &str, value: &T)
+where
+    T: RowConsumer + Table + QueryParams + Debug + PartialEq,
+{
+    let filters = value.filters();
+
+    if filters.is_empty() {
+        panic!("Could not round trip {:?}: no fields are set", value);
+    }
+
+    let insert = format!(
+        "insert into {} ({}) values ({});",
+        table,
+        filters
+            .iter()
+            .map(|v| quote_ident(v.column))
+            .collect::<Vec<String>>()
+            .join(", "),
+        (1..=filters.len())
+            .map(|v| format!("${}", v))
+            .collect::<Vec<String>>()
+            .join(", ")
+    );
+
+    let params = filters.iter().map(|v| v.value).collect::<Vec<_>>();
+
+    if let Err(e) = client.execute(&insert, &params).await {
+        panic!(
+            "Could not insert {:?} into {}: {}",
+            value,
+            table,
+            describe(&ConsumeError::from(e))
+        );
+    }
+
+    let (clause, params) = value.where_clause();
+
+    let select = format!(
+        "select {} from {} {};",
+        T::COLUMNS
+            .iter()
+            .map(|v| quote_ident(v))
+            .collect::<Vec<String>>()
+            .join(", "),
+        table,
+        clause
+    );
+
+    let rows = assert_consumes_n::<T>(client, &select, &params, 1).await;
+
+    assert_eq!(
+        &rows[0], value,
+        "Could not round trip through {}: the row read back differs",
+        table
+    );
+}
+
+/// A uniquely named schema isolating the tables of a test, created by `create`, which
+/// puts it first on the session's `search_path` so that unqualified tables are created
+/// and read in it. `run` runs a test against the schema and drops it afterwards, along
+/// with everything in it, and restores the `search_path`, so that tests do not leak
+/// tables into a shared database or see those of other tests.
+///
+/// The `search_path` applies to the whole session, so each test should use a client of
+/// its own. If the guard is dropped without being run, such as when a test panics, the
+/// schema is dropped by sending the query on the connection without waiting for the
+/// result, since `Drop` cannot be asynchronous.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::testing::{assert_consumes_n, TestDb};
+/// use pgde::ConsumeError;
+/// use tokio_postgres::NoTls;
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         let result = match TestDb::create(&client).await {
+///             Ok(db) => {
+///                 db.run(|client| {
+///                     Box::pin(async move {
+///                         if let Err(e) = client.batch_execute("create table flags (on_off boolean); insert into flags values (true);").await {
+///                             return Err(ConsumeError::from(e));
+///                         }
+///
+///                         assert_consumes_n::<bool>(client, "select on_off from flags;", &[], 1).await;
+///                         Ok(())
+///                     })
+///                 })
+///                 .await
+///             },
+///             Err(e) => Err(e),
+///         };
+///
+///         if result.is_err() {
+///             eprintln!("An error occurred while running test");
+///         }
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub struct TestDb<'a> {
+    client: &'a Client,
+    schema: String,
+    previous: String,
+    dropped: bool,
+}
+
+impl<'a> TestDb<'a> {
+    /// Creates a uniquely named schema and puts it first on the `search_path` of the
+    /// session of `client`.
+    pub async fn create(client: &'a Client) -> Result<Self, ConsumeError> {
+        let micros = match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(v) => v.as_micros(),
+            Err(_) => 0,
+        };
+
+        let schema = format!(
+            "pgde_test_{}_{}_{}",
+            std::process::id(),
+            SCHEMA_COUNT.fetch_add(1, Ordering::SeqCst),
+            micros
+        );
+
+        let previous = match client
+            .query_one("select current_setting('search_path');", &[])
+            .await
+        {
+            Ok(v) => match v.try_get::<usize, String>(0) {
+                Ok(v) => v,
+                Err(e) => return Err(ConsumeError::from(e)),
+            },
+            Err(e) => return Err(ConsumeError::from(e)),
+        };
+
+        if let Err(e) = client
+            .batch_execute(&format!("create schema {};", quote_ident(&schema)))
+            .await
+        {
+            return Err(ConsumeError::from(e));
+        }
+
+        // Built before setting the search_path, so that a failure to set it drops the
+        // schema when the guard is dropped.
+        let db = TestDb {
+            client,
+            schema,
+            previous,
+            dropped: false,
+        };
+
+        let search_path = format!("{}, {}", quote_ident(&db.schema), db.previous);
+
+        match client
+            .execute(
+                "select set_config('search_path', $1, false);",
+                &[&search_path],
+            )
+            .await
+        {
+            Ok(_) => Ok(db),
+            Err(e) => Err(ConsumeError::from(e)),
+        }
+    }
+
+    /// Returns the name of the schema.
+    pub fn schema(&self) -> &str {
+        &self.schema
+    }
+
+    /// Runs `f` against the schema, then drops the schema and restores the
+    /// `search_path`, whether `f` succeeds or fails.
+    pub async fn run<R, E, F>(mut self, f: F) -> Result<R, E>
+    where
+        E: From<ConsumeError>,
+        F: for<'s> FnOnce(&'s Client) -> BoxFuture<'s, Result<R, E>>,
+    {
+        let result = f(self.client).await;
+
+        self.dropped = true;
+
+        match self.client.batch_execute(&self.cleanup()).await {
+            Ok(_) => result,
+            Err(e) => Err(E::from(ConsumeError::from(e))),
+        }
+    }
+
+    /// Returns the queries dropping the schema and restoring the `search_path`.
+    fn cleanup(&self) -> String {
+        format!(
+            "drop schema {} cascade; select set_config('search_path', '{}', false);",
+            quote_ident(&self.schema),
+            self.previous.replace('\'', "''")
+        )
+    }
+}
+
+impl Drop for TestDb<'_> {
+    fn drop(&mut self) {
+        if !self.dropped {
+            // Polling once is enough to queue the query on the connection.
+            let _ = self.client.batch_execute(&self.cleanup()).now_or_never();
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//! Support for cleaning up text columns while consuming them, such as the padding of
+//! `char(n)` values or empty strings that stand in for NULL, and for reading columns of
+//! other types as text.
+use crate::numeric::numeric_to_string;
+use std::error::Error;
+use tokio_postgres::types::{FromSql, Kind, Type};
+
+/// Conversion of text columns into the implementing type. Used by the `#[pgde(trim)]`
+/// and `#[pgde(empty_as_none)]` field attributes of the derive macro. Returns `None`
+/// when a value is NULL, or empty while `empty_as_none` is set.
+pub trait TextColumn: Sized {
+    /// Converts nullable text into the implementing type, first trimming surrounding
+    /// whitespace if `trim` is set, then treating an empty string as NULL if
+    /// `empty_as_none` is set.
+    fn from_text(value: Option<String>, trim: bool, empty_as_none: bool) -> Option<Self>;
+}
+
+impl TextColumn for String {
+    fn from_text(value: Option<String>, trim: bool, empty_as_none: bool) -> Option<Self> {
+        let value = match trim {
+            true => String::from(value?.trim()),
+            false => value?,
+        };
+
+        match empty_as_none && value.is_empty() {
+            true => None,
+            false => Some(value),
+        }
+    }
+}
+
+impl<T: TextColumn> TextColumn for Option<T> {
+    fn from_text(value: Option<String>, trim: bool, empty_as_none: bool) -> Option<Self> {
+        match value {
+            Some(_) => Some(T::from_text(value, trim, empty_as_none)),
+            None => Some(None),
+        }
+    }
+}
+
+/// The text representation of a column, read from text types, including `citext` and
+/// enums, or rendered from `int2`, `int4`, `int8`, `oid`, `float4`, `float8`,
+/// `numeric`, `money`, `bool`, and `uuid` values. The padding of `char(n)` values is
+/// removed, and `money` is rendered without a currency symbol, e.g. `-12.34`. Used by
+/// the `#[pgde(as_text)]` field attribute of the derive macro, which parses the text
+/// with `FromStr`. Columns of other types may be cast to `text` in the query.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TextValue(pub String);
+
+impl<'a> FromSql<'a> for TextValue {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        let text = match *ty {
+            Type::BPCHAR => String::from(<&str>::from_sql(ty, raw)?.trim_end_matches(' ')),
+            Type::INT2 => i16::from_sql(ty, raw)?.to_string(),
+            Type::INT4 => i32::from_sql(ty, raw)?.to_string(),
+            Type::INT8 => i64::from_sql(ty, raw)?.to_string(),
+            Type::OID => u32::from_sql(ty, raw)?.to_string(),
+            Type::FLOAT4 => f32::from_sql(ty, raw)?.to_string(),
+            Type::FLOAT8 => f64::from_sql(ty, raw)?.to_string(),
+            Type::NUMERIC => numeric_to_string(raw)?,
+            Type::MONEY => {
+                let cents = i64::from_sql(&Type::INT8, raw)?;
+                let sign = if cents < 0 { "-" } else { "" };
+                format!(
+                    "{}{}.{:02}",
+                    sign,
+                    cents.unsigned_abs() / 100,
+                    cents.unsigned_abs() % 100
+                )
+            }
+            Type::BOOL => bool::from_sql(ty, raw)?.to_string(),
+            Type::UUID => match <[u8; 16]>::try_from(raw) {
+                Ok(v) => {
+                    let hex: String = v.iter().map(|b| format!("{:02x}", b)).collect();
+                    format!(
+                        "{}-{}-{}-{}-{}",
+                        &hex[0..8],
+                        &hex[8..12],
+                        &hex[12..16],
+                        &hex[16..20],
+                        &hex[20..32]
+                    )
+                }
+                Err(_) => return Err("invalid uuid length".into()),
+            },
+            _ => match ty.kind() {
+                Kind::Enum(_) => String::from(std::str::from_utf8(raw)?),
+                _ => String::from(<&str>::from_sql(ty, raw)?),
+            },
+        };
+
+        Ok(TextValue(text))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        <&str as FromSql>::accepts(ty)
+            || matches!(ty.kind(), Kind::Enum(_))
+            || matches!(
+                *ty,
+                Type::INT2
+                    | Type::INT4
+                    | Type::INT8
+                    | Type::OID
+                    | Type::FLOAT4
+                    | Type::FLOAT8
+                    | Type::NUMERIC
+                    | Type::MONEY
+                    | Type::BOOL
+                    | Type::UUID
+            )
+    }
+}
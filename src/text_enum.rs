@@ -0,0 +1,67 @@
+//! Support for enums that are stored as text, implemented through the `TextEnum` derive
+//! macro of the pgde_derive crate.
+use std::error::Error;
+use tokio_postgres::types::{FromSql, Kind, Type};
+
+/// The derivable trait for mapping an enum to and from text column values. Deriving
+/// `TextEnum` also implements `FromSql` and `ToSql` for the enum, so it may be used as
+/// a field of a struct that derives `RowConsumer` or as a query parameter.
+///
+/// Each unit variant is represented by its name, or by the value provided with
+/// `#[pgde(rename = "...")]`. A single tuple variant holding a `String` may be marked
+/// with `#[pgde(other)]` to capture values that match no other variant.
+///
+/// ## Example
+/// ```
+/// use pgde::TextEnum;
+/// use pgde_derive::TextEnum;
+///
+/// #[derive(Debug, Default, PartialEq, TextEnum)]
+/// enum Status {
+///     #[default]
+///     #[pgde(rename = "active")]
+///     Active,
+///     #[pgde(rename = "inactive")]
+///     Inactive,
+///     #[pgde(other)]
+///     Other(String),
+/// }
+///
+/// assert_eq!(Status::from_text("active"), Some(Status::Active));
+/// assert_eq!(Status::from_text("pending"), Some(Status::Other(String::from("pending"))));
+/// assert_eq!(Status::Inactive.as_text(), "inactive");
+/// ```
+pub trait TextEnum: Sized {
+    /// Maps a text value to a variant, returning `None` when no variant matches.
+    fn from_text(value: &str) -> Option<Self>;
+
+    /// Returns the text value of the variant.
+    fn as_text(&self) -> &str;
+}
+
+/// Returns whether a column of the given type can be read as the text of a
+/// [TextEnum]. Used by the `TextEnum` derive macro.
+#[doc(hidden)]
+pub fn text_enum_accepts(ty: &Type) -> bool {
+    <&str as FromSql>::accepts(ty) || matches!(ty.kind(), Kind::Enum(_))
+}
+
+/// Reads the text of a [TextEnum] from a column, removing the padding of `char(n)`
+/// values. Used by the `TextEnum` derive macro.
+#[doc(hidden)]
+pub fn text_enum_from_sql<T: TextEnum>(
+    ty: &Type,
+    raw: &[u8],
+) -> Result<T, Box<dyn Error + Sync + Send>> {
+    let value = std::str::from_utf8(raw)?;
+
+    let value = match *ty {
+        Type::BPCHAR => value.trim_end_matches(' '),
+        _ => value,
+    };
+
+    match T::from_text(value) {
+        Some(v) => Ok(v),
+        None => Err(format!("unknown variant \"{}\"", value).into()),
+    }
+}
@@ -0,0 +1,186 @@
+//! A client that limits how many consumption calls run at once and how often they start.
+use crate::{ConsumeError, ConsumeOptions, RowConsumer};
+use std::ops::Deref;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tokio_postgres::types::ToSql;
+use tokio_postgres::Client;
+
+/// The lowest rate limit, of one call per hour, which keeps waits finite.
+const MIN_PER_SECOND: f64 = 1.0 / 3600.0;
+
+/// The tokens of a rate limit, refilled continuously up to one second's worth.
+#[derive(Debug)]
+struct TokenBucket {
+    per_second: f64,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    /// Takes a token, or returns how long to wait until one is available.
+    fn take(&mut self) -> Result<(), Duration> {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.per_second.max(1.0));
+        self.refilled_at = now;
+
+        match self.tokens >= 1.0 {
+            true => {
+                self.tokens -= 1.0;
+                Ok(())
+            }
+            false => Err(Duration::from_secs_f64(
+                (1.0 - self.tokens) / self.per_second,
+            )),
+        }
+    }
+}
+
+/// A client that caps the number of consumption calls running at once, and optionally
+/// the number started per second, protecting small databases from bursty batch jobs.
+/// Calls beyond the limits wait their turn rather than fail. The wrapped client is
+/// available through `Deref`, and queries sent through it are not limited.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::client::ThrottledClient;
+/// use tokio_postgres::NoTls;
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = ThrottledClient::new(v.0, 4).rate_limit(50.0);
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         match client.consume::<i32>("select 1;", &[]).await {
+///             Ok(v) => println!("Consumed {} rows", v.len()), // v is of type Vec<i32>
+///             Err(_) => eprintln!("An error occurred while querying database"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub struct ThrottledClient {
+    client: Client,
+    permits: Semaphore,
+    rate_limit: Option<Mutex<TokenBucket>>,
+}
+
+impl ThrottledClient {
+    /// Wraps `client`, running at most `max_concurrent` calls at once, and at least one.
+    pub fn new(client: Client, max_concurrent: usize) -> Self {
+        ThrottledClient {
+            client,
+            permits: Semaphore::new(max_concurrent.max(1)),
+            rate_limit: None,
+        }
+    }
+
+    /// Starts at most `per_second` calls per second, allowing bursts of up to a second's
+    /// worth of calls after a quiet period. Rates below one call per hour, including zero,
+    /// negative, and NaN rates, are raised to one call per hour, and an infinite rate
+    /// removes the limit.
+    pub fn rate_limit(mut self, per_second: f64) -> Self {
+        if per_second == f64::INFINITY {
+            self.rate_limit = None;
+            return self;
+        }
+
+        let per_second = per_second.max(MIN_PER_SECOND);
+
+        self.rate_limit = Some(Mutex::new(TokenBucket {
+            per_second,
+            tokens: per_second.max(1.0),
+            refilled_at: Instant::now(),
+        }));
+        self
+    }
+
+    /// Returns the number of calls that may start without waiting for a running call.
+    pub fn available(&self) -> usize {
+        self.permits.available_permits()
+    }
+
+    /// Returns the wrapped client.
+    pub fn into_inner(self) -> Client {
+        self.client
+    }
+
+    /// Waits for a call to be allowed to start, returning the permit it holds while it
+    /// runs.
+    async fn acquire(&self) -> Result<SemaphorePermit<'_>, ConsumeError> {
+        let permit = match self.permits.acquire().await {
+            Ok(v) => v,
+            Err(_) => return Err(ConsumeError::DatabaseConnectionError),
+        };
+
+        if let Some(rate_limit) = &self.rate_limit {
+            loop {
+                let taken = match rate_limit.lock() {
+                    Ok(mut v) => v.take(),
+                    Err(v) => v.into_inner().take(),
+                };
+
+                match taken {
+                    Ok(_) => break,
+                    Err(v) => tokio::time::sleep(v).await,
+                }
+            }
+        }
+
+        Ok(permit)
+    }
+
+    /// Consumes row data with `RowConsumer::consume` once the limits allow.
+    pub async fn consume<T: RowConsumer>(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<T>, ConsumeError> {
+        let _permit = self.acquire().await?;
+        T::consume(&self.client, query, params).await
+    }
+
+    /// Consumes row data with `RowConsumer::consume_with_options` once the limits allow.
+    pub async fn consume_with_options<T: RowConsumer + Send>(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+        options: &ConsumeOptions,
+    ) -> Result<Vec<T>, ConsumeError> {
+        let _permit = self.acquire().await?;
+        T::consume_with_options(&self.client, query, params, options).await
+    }
+
+    /// Executes a statement once the limits allow, returning the number of rows modified.
+    pub async fn execute(
+        &self,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, ConsumeError> {
+        let _permit = self.acquire().await?;
+
+        match self.client.execute(query, params).await {
+            Ok(v) => Ok(v),
+            Err(e) => Err(ConsumeError::from(e)),
+        }
+    }
+}
+
+impl Deref for ThrottledClient {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        &self.client
+    }
+}
@@ -0,0 +1,371 @@
+//! Helpers for structuring work inside transactions.
+use crate::ConsumeError;
+#[cfg(feature = "retry")]
+use crate::ErrorClass;
+use futures_util::future::BoxFuture;
+#[cfg(feature = "retry")]
+use std::time::Duration;
+use tokio_postgres::{Client, IsolationLevel, Transaction};
+
+/// How `with_transaction` and `with_retry` begin a transaction, mapped onto the options
+/// of `BEGIN`.
+///
+/// ## Example
+/// ```
+/// use pgde::transactions::TransactionOptions;
+/// use tokio_postgres::IsolationLevel;
+///
+/// let options = TransactionOptions::new()
+///     .isolation(IsolationLevel::Serializable)
+///     .read_only(true)
+///     .deferrable(true);
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TransactionOptions {
+    isolation: Option<IsolationLevel>,
+    read_only: bool,
+    deferrable: bool,
+}
+
+impl TransactionOptions {
+    /// Creates options that begin a read write transaction at the session's default
+    /// isolation level.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the isolation level of the transaction.
+    pub fn isolation(mut self, isolation: IsolationLevel) -> Self {
+        self.isolation = Some(isolation);
+        self
+    }
+
+    /// Begins the transaction as `READ ONLY`, so that writes fail.
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Begins the transaction as `DEFERRABLE`. Along with `read_only` and the
+    /// `Serializable` isolation level, the transaction waits for a snapshot that cannot
+    /// conflict with others, after which it runs without the overhead or risk of
+    /// serialization failures, as suits long-running analytical queries.
+    pub fn deferrable(mut self, deferrable: bool) -> Self {
+        self.deferrable = deferrable;
+        self
+    }
+
+    /// Begins a transaction of `client` with these options.
+    async fn begin<'a>(&self, client: &'a mut Client) -> Result<Transaction<'a>, ConsumeError> {
+        let mut builder = client
+            .build_transaction()
+            .read_only(self.read_only)
+            .deferrable(self.deferrable);
+
+        if let Some(v) = self.isolation {
+            builder = builder.isolation_level(v);
+        }
+
+        match builder.start().await {
+            Ok(v) => Ok(v),
+            Err(e) => Err(ConsumeError::from(e)),
+        }
+    }
+}
+
+/// Runs `f` inside a new transaction of `client`, begun with `options`. The transaction
+/// is committed when `f` succeeds and rolled back when it fails.
+///
+/// Errors beginning or committing the transaction are returned as a [ConsumeError],
+/// converted into the error type of `f`.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::transactions::{with_transaction, TransactionOptions};
+/// use pgde::{ConsumeError, RowConsumer};
+/// use tokio_postgres::{IsolationLevel, NoTls};
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let mut client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         let options = TransactionOptions::new()
+///             .isolation(IsolationLevel::Serializable)
+///             .read_only(true)
+///             .deferrable(true);
+///
+///         let result = with_transaction(&mut client, options, |txn| {
+///             Box::pin(async move { i32::consume(txn.client(), "select 1;", &[]).await })
+///         })
+///         .await;
+///
+///         match result {
+///             Ok(v) => println!("Consumed {} rows in transaction", v.len()), // v is of type Vec<i32>
+///             Err(_) => eprintln!("Transaction was rolled back"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub async fn with_transaction<R, E, F>(
+    client: &mut Client,
+    options: TransactionOptions,
+    f: F,
+) -> Result<R, E>
+where
+    E: From<ConsumeError>,
+    F: for<'t> FnOnce(&'t Transaction<'_>) -> BoxFuture<'t, Result<R, E>>,
+{
+    let txn = options.begin(client).await?;
+
+    match f(&txn).await {
+        Ok(v) => match txn.commit().await {
+            Ok(_) => Ok(v),
+            Err(e) => Err(E::from(ConsumeError::from(e))),
+        },
+        Err(e) => {
+            let _ = txn.rollback().await;
+            Err(e)
+        }
+    }
+}
+
+/// Runs `f` inside a new savepoint of `txn`. The savepoint is released when `f`
+/// succeeds and rolled back when it fails, leaving the rest of the transaction usable
+/// either way. Use `Transaction::client` to consume rows within the savepoint.
+///
+/// Errors creating, releasing, or rolling back the savepoint are returned as a
+/// [ConsumeError], converted into the error type of `f`.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::transactions::with_savepoint;
+/// use pgde::{ConsumeError, RowConsumer};
+/// use tokio_postgres::NoTls;
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let mut client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         match client.transaction().await {
+///             Ok(mut txn) => {
+///                 let result = with_savepoint(&mut txn, |sp| {
+///                     Box::pin(async move { i32::consume(sp.client(), "select 1;", &[]).await })
+///                 })
+///                 .await;
+///
+///                 match result {
+///                     Ok(v) => println!("Consumed {} rows in savepoint", v.len()), // v is of type Vec<i32>
+///                     Err(_) => eprintln!("Savepoint was rolled back"),
+///                 };
+///
+///                 let _ = txn.commit().await;
+///             },
+///             Err(_) => eprintln!("Could not start transaction"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub async fn with_savepoint<R, E, F>(txn: &mut Transaction<'_>, f: F) -> Result<R, E>
+where
+    E: From<ConsumeError>,
+    F: for<'s> FnOnce(&'s Transaction<'_>) -> BoxFuture<'s, Result<R, E>>,
+{
+    let savepoint = match txn.transaction().await {
+        Ok(v) => v,
+        Err(e) => return Err(E::from(ConsumeError::from(e))),
+    };
+
+    let result = f(&savepoint).await;
+
+    let finished = match result {
+        Ok(_) => savepoint.commit().await,
+        Err(_) => savepoint.rollback().await,
+    };
+
+    match finished {
+        Ok(_) => result,
+        Err(e) => Err(E::from(ConsumeError::from(e))),
+    }
+}
+
+/// Errors that may be resolved by running a transaction again, checked by `with_retry`.
+#[cfg(feature = "retry")]
+pub trait RetryableError {
+    /// Returns whether the transaction that failed with this error should be retried.
+    fn is_retryable(&self) -> bool;
+}
+
+/// Serialization failures and deadlocks, SQLSTATE `40001` and `40P01`, are retryable.
+#[cfg(feature = "retry")]
+impl RetryableError for ConsumeError {
+    fn is_retryable(&self) -> bool {
+        self.classification() == ErrorClass::SerializationFailure
+    }
+}
+
+/// How `with_retry` runs and retries a transaction.
+///
+/// ## Example
+/// ```
+/// use pgde::transactions::RetryPolicy;
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy::new(5).backoff(Duration::from_millis(20), Duration::from_secs(1));
+/// ```
+#[cfg(feature = "retry")]
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    transaction: TransactionOptions,
+}
+
+#[cfg(feature = "retry")]
+impl RetryPolicy {
+    /// Runs a `SERIALIZABLE` transaction up to `max_attempts` times, waiting 10ms before
+    /// the first retry and doubling the wait up to 500ms.
+    pub fn new(max_attempts: u32) -> Self {
+        RetryPolicy {
+            max_attempts: max_attempts.max(1),
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(500),
+            transaction: TransactionOptions::new().isolation(IsolationLevel::Serializable),
+        }
+    }
+
+    /// Sets the wait before the first retry, which doubles with every retry up to
+    /// `max_delay`.
+    pub fn backoff(mut self, base_delay: Duration, max_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self.max_delay = max_delay.max(base_delay);
+        self
+    }
+
+    /// Sets how the transaction is begun. Defaults to the
+    /// [IsolationLevel::Serializable] isolation level.
+    pub fn transaction(mut self, transaction: TransactionOptions) -> Self {
+        self.transaction = transaction;
+        self
+    }
+
+    /// Returns the wait before retrying after `attempt` failed attempts, between half and
+    /// all of the backoff, so that transactions that conflicted do not retry in lockstep.
+    fn delay(&self, attempt: u32) -> Duration {
+        use std::hash::{BuildHasher, Hasher};
+
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay);
+
+        let random = std::collections::hash_map::RandomState::new()
+            .build_hasher()
+            .finish();
+
+        backoff / 2 + backoff.mul_f64((random % 1024) as f64 / 2048.0)
+    }
+}
+
+#[cfg(feature = "retry")]
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(3)
+    }
+}
+
+/// Runs `f` inside a new transaction of `client`, begun with the `TransactionOptions` of
+/// `policy`, and commits it, running `f` again in a fresh transaction when it or the
+/// commit fails with a retryable error, up to the attempts of `policy`, after a jittered
+/// exponential backoff. This is the pattern PostgreSQL documents for `SERIALIZABLE`
+/// transactions, whose serialization failures and deadlocks are expected under
+/// contention. `f` may run more than once, so it should not have effects outside of the
+/// transaction.
+///
+/// Errors starting or committing the transaction are returned as a [ConsumeError],
+/// converted into the error type of `f`. The error of the last attempt is returned once
+/// the attempts run out.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::transactions::{with_retry, RetryPolicy};
+/// use pgde::{ConsumeError, RowConsumer};
+/// use tokio_postgres::NoTls;
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let mut client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         let result = with_retry(&mut client, RetryPolicy::new(5), |txn| {
+///             Box::pin(async move { i32::consume(txn.client(), "select 1;", &[]).await })
+///         })
+///         .await;
+///
+///         match result {
+///             Ok(v) => println!("Consumed {} rows in transaction", v.len()), // v is of type Vec<i32>
+///             Err(_) => eprintln!("Transaction failed"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+#[cfg(feature = "retry")]
+pub async fn with_retry<R, E, F>(client: &mut Client, policy: RetryPolicy, mut f: F) -> Result<R, E>
+where
+    E: From<ConsumeError> + RetryableError,
+    F: for<'t> FnMut(&'t Transaction<'_>) -> BoxFuture<'t, Result<R, E>>,
+{
+    let mut attempt = 1;
+
+    loop {
+        let txn = policy.transaction.begin(client).await?;
+
+        let result = match f(&txn).await {
+            Ok(v) => match txn.commit().await {
+                Ok(_) => Ok(v),
+                Err(e) => Err(E::from(ConsumeError::from(e))),
+            },
+            Err(e) => {
+                let _ = txn.rollback().await;
+                Err(e)
+            }
+        };
+
+        match result {
+            Err(e) if e.is_retryable() && attempt < policy.max_attempts => {
+                tokio::time::sleep(policy.delay(attempt)).await;
+                attempt += 1;
+            }
+            _ => return result,
+        }
+    }
+}
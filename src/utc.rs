@@ -0,0 +1,108 @@
+//! Support for consuming `timestamp` and `timestamptz` columns into date time types of
+//! the other kind, by declaring that naive timestamps hold UTC.
+use std::error::Error;
+use tokio_postgres::types::{FromSql, Type};
+
+/// The microseconds since `2000-01-01 00:00:00` UTC of a `timestamp` or `timestamptz`.
+/// A `timestamp` is taken to hold UTC, and a `timestamptz` is always sent in UTC.
+/// `infinity` and `-infinity` are kept as `i64::MAX` and `i64::MIN`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UtcTimestamp(pub i64);
+
+impl<'a> FromSql<'a> for UtcTimestamp {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Ok(UtcTimestamp(
+            i64::from_sql(&Type::INT8, raw).map_err(|_| format!("invalid {} length", ty.name()))?,
+        ))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(*ty, Type::TIMESTAMP | Type::TIMESTAMPTZ)
+    }
+}
+
+/// Conversion of UTC timestamps into date time types. Used by the `#[pgde(assume_utc)]`
+/// and `#[pgde(to_utc)]` field attributes of the derive macro, which read a `timestamp`
+/// into a time zone aware type, and a `timestamptz` into a naive type holding UTC,
+/// respectively. Returns `None` when a value is NULL, infinite, or out of the type's
+/// range.
+pub trait UtcColumn: Sized {
+    /// Converts a nullable UTC timestamp into the implementing type.
+    fn from_utc(value: Option<UtcTimestamp>) -> Option<Self>;
+
+    /// The value of a field that could not be read, in place of `Default::default()`,
+    /// which `time`'s date time types do not implement.
+    fn fallback() -> Self;
+}
+
+impl<T: UtcColumn> UtcColumn for Option<T> {
+    fn from_utc(value: Option<UtcTimestamp>) -> Option<Self> {
+        match value {
+            Some(_) => T::from_utc(value).map(Some),
+            None => Some(None),
+        }
+    }
+
+    fn fallback() -> Self {
+        None
+    }
+}
+
+/// Returns the microseconds of a finite timestamp.
+#[cfg(any(feature = "chrono", feature = "time"))]
+fn finite_microseconds(value: Option<UtcTimestamp>) -> Option<i64> {
+    match value? {
+        UtcTimestamp(i64::MAX) | UtcTimestamp(i64::MIN) => None,
+        UtcTimestamp(v) => Some(v),
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl UtcColumn for chrono::NaiveDateTime {
+    fn from_utc(value: Option<UtcTimestamp>) -> Option<Self> {
+        chrono::NaiveDate::from_ymd_opt(2000, 1, 1)?
+            .and_hms_opt(0, 0, 0)?
+            .checked_add_signed(chrono::Duration::microseconds(finite_microseconds(value)?))
+    }
+
+    fn fallback() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl UtcColumn for chrono::DateTime<chrono::Utc> {
+    fn from_utc(value: Option<UtcTimestamp>) -> Option<Self> {
+        chrono::NaiveDateTime::from_utc(value).map(|v| v.and_utc())
+    }
+
+    fn fallback() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(feature = "time")]
+impl UtcColumn for time::PrimitiveDateTime {
+    fn from_utc(value: Option<UtcTimestamp>) -> Option<Self> {
+        time::PrimitiveDateTime::new(
+            time::Date::from_calendar_date(2000, time::Month::January, 1).ok()?,
+            time::Time::MIDNIGHT,
+        )
+        .checked_add(time::Duration::microseconds(finite_microseconds(value)?))
+    }
+
+    fn fallback() -> Self {
+        time::OffsetDateTime::UNIX_EPOCH.date().midnight()
+    }
+}
+
+#[cfg(feature = "time")]
+impl UtcColumn for time::OffsetDateTime {
+    fn from_utc(value: Option<UtcTimestamp>) -> Option<Self> {
+        time::PrimitiveDateTime::from_utc(value).map(|v| v.assume_utc())
+    }
+
+    fn fallback() -> Self {
+        time::OffsetDateTime::UNIX_EPOCH
+    }
+}
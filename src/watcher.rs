@@ -0,0 +1,162 @@
+//! A polling or `NOTIFY` driven cache of a query's results, for hot-reloading
+//! configuration tables.
+use crate::{ConsumeError, Diff, KeyedBy, RowConsumer};
+use arc_swap::ArcSwap;
+use futures_util::{Stream, StreamExt};
+use std::hash::Hash;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio_postgres::{AsyncMessage, Client};
+
+/// The change callback of a watcher, given the previous and latest results.
+type ChangeHook<T> = Box<dyn Fn(&[T], &[T]) + Send + Sync>;
+
+/// Keeps the latest results of a query, re-running it on an interval with `poll` or
+/// whenever a notification arrives with `listen`. Readers get the latest results with
+/// `current` without waiting on the database, and an optional callback is given the
+/// computed [Diff] whenever the results change. Failed refreshes keep the previous
+/// results.
+///
+/// ## Example
+/// ```
+/// # tokio_test::block_on(async {
+/// use pgde::{KeyedBy, Watcher};
+/// use pgde_derive::RowConsumer;
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use tokio_postgres::{NoTls, Row};
+///
+/// #[derive(RowConsumer, Clone, PartialEq)]
+/// struct Setting {
+///     Name: String,
+///     Value: String,
+/// }
+///
+/// impl KeyedBy<String> for Setting {
+///     fn key(&self) -> String {
+///         self.Name.clone()
+///     }
+/// }
+///
+/// match tokio_postgres::connect("host=localhost user=postgres password=password dbname=postgres", NoTls).await {
+///     Ok(v) => {
+///         let client = v.0;
+///         let conn = v.1;
+///
+///         tokio::spawn(async move {
+///             if let Err(e) = conn.await {
+///                 eprintln!("connection error: {}", e);
+///             }
+///         });
+///
+///         let watcher = Watcher::<Setting>::new(Arc::new(client), "select name, setting from pg_settings;")
+///             .on_change(|diff| println!("{} settings changed", diff.changed.len()));
+///
+///         match watcher.refresh().await {
+///             Ok(_) => {
+///                 let watcher = Arc::new(watcher);
+///                 let handle = watcher.poll(Duration::from_secs(30));
+///
+///                 println!("Loaded {} settings", watcher.current().len());
+///                 handle.abort();
+///             },
+///             Err(_) => eprintln!("An error occurred while querying database"),
+///         };
+///     },
+///     Err(_) => eprintln!("Could not connect to database"),
+/// };
+/// # })
+/// ```
+pub struct Watcher<T> {
+    client: Arc<Client>,
+    query: String,
+    current: ArcSwap<Vec<T>>,
+    on_change: Option<ChangeHook<T>>,
+}
+
+impl<T> Watcher<T>
+where
+    T: RowConsumer + Send + Sync + 'static,
+{
+    /// Creates a watcher of `query`, whose results are empty until the first refresh.
+    pub fn new(client: Arc<Client>, query: impl Into<String>) -> Self {
+        Watcher {
+            client,
+            query: query.into(),
+            current: ArcSwap::from_pointee(Vec::new()),
+            on_change: None,
+        }
+    }
+
+    /// Sets a callback given the differences between the previous and latest results,
+    /// matched by key, whenever a refresh changes them.
+    pub fn on_change<K>(mut self, hook: impl Fn(&Diff<T>) + Send + Sync + 'static) -> Self
+    where
+        T: KeyedBy<K> + PartialEq + Clone,
+        K: Eq + Hash,
+    {
+        self.on_change = Some(Box::new(move |old, new| {
+            let diff = Diff::between(old.to_vec(), new.to_vec());
+
+            if !diff.is_empty() {
+                hook(&diff);
+            }
+        }));
+        self
+    }
+
+    /// Returns the latest results.
+    pub fn current(&self) -> Arc<Vec<T>> {
+        self.current.load_full()
+    }
+
+    /// Re-runs the query, replacing the latest results and calling the change callback.
+    pub async fn refresh(&self) -> Result<Arc<Vec<T>>, ConsumeError> {
+        let latest = Arc::new(T::consume(&self.client, &self.query, &[]).await?);
+        let previous = self.current.swap(latest.clone());
+
+        if let Some(hook) = &self.on_change {
+            hook(&previous, &latest);
+        }
+
+        Ok(latest)
+    }
+
+    /// Spawns a task that refreshes the results every `interval`, starting immediately.
+    /// The task runs until its handle is aborted.
+    pub fn poll(self: &Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        let watcher = self.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+
+            loop {
+                interval.tick().await;
+                let _ = watcher.refresh().await;
+            }
+        })
+    }
+
+    /// Spawns a task that refreshes the results whenever a notification arrives on
+    /// `messages`, such as the messages of a connection polled with
+    /// `Connection::poll_message` that ran `LISTEN`. The task runs until the stream ends
+    /// or its handle is aborted.
+    pub fn listen<S, E>(self: &Arc<Self>, messages: S) -> JoinHandle<()>
+    where
+        S: Stream<Item = Result<AsyncMessage, E>> + Send + 'static,
+        E: Send + 'static,
+    {
+        let watcher = self.clone();
+
+        tokio::spawn(async move {
+            let mut messages = std::pin::pin!(messages);
+
+            while let Some(message) = messages.next().await {
+                if let Ok(AsyncMessage::Notification(_)) = message {
+                    let _ = watcher.refresh().await;
+                }
+            }
+        })
+    }
+}
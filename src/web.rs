@@ -0,0 +1,54 @@
+//! Adapters for returning consumed rows from axum handlers.
+use crate::RowConsumer;
+use axum_core::response::{IntoResponse, Response};
+use http::header::CONTENT_TYPE;
+use http::StatusCode;
+use serde::Serialize;
+use tokio_postgres::types::ToSql;
+use tokio_postgres::Client;
+
+/// A response holding rows consumed with `consume_json`. Successfully consumed rows are
+/// sent as a JSON array with status 200, and failures as `null` with status 500, both
+/// with content type `application/json`.
+///
+/// ## Example
+/// ```
+/// use pgde::web::PgdeJson;
+/// use pgde_derive::RowConsumer;
+/// use serde::Serialize;
+/// use tokio_postgres::{Client, Row};
+///
+/// #[derive(RowConsumer, Serialize)]
+/// struct Foo {
+///     Id: i32,
+///     Data: String,
+/// }
+///
+/// async fn list_foos(client: &Client) -> PgdeJson {
+///     PgdeJson::consume::<Foo>(client, "select * from public.\"Foo\";", &[]).await
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PgdeJson(pub Result<String, String>);
+
+impl PgdeJson {
+    /// Consumes row data with `RowConsumer::consume_json` into a response.
+    pub async fn consume<T: RowConsumer + Serialize>(
+        conn: &Client,
+        query: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Self {
+        PgdeJson(T::consume_json(conn, query, params).await)
+    }
+}
+
+impl IntoResponse for PgdeJson {
+    fn into_response(self) -> Response {
+        let (status, body) = match self.0 {
+            Ok(v) => (StatusCode::OK, v),
+            Err(v) => (StatusCode::INTERNAL_SERVER_ERROR, v),
+        };
+
+        (status, [(CONTENT_TYPE, "application/json")], body).into_response()
+    }
+}
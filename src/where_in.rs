@@ -0,0 +1,66 @@
+//! Expansion of `in (...)` lists into query parameters, used by `consume_where_in`, and
+//! their array counterpart, used by `consume_where_any`.
+use crate::ident::quote_ident;
+use crate::{ConsumeError, RowConsumer};
+use tokio_postgres::types::ToSql;
+use tokio_postgres::Client;
+
+/// The largest number of parameters PostgreSQL accepts for a single statement.
+const MAX_PARAMETERS: usize = 65_535;
+
+/// Builds a query that filters the rows of `base_query` to those whose `column` is one of
+/// `count` parameters.
+fn where_in_query(base_query: &str, column: &str, count: usize) -> String {
+    let placeholders: Vec<String> = (1..=count).map(|v| format!("${}", v)).collect();
+
+    format!(
+        "select * from ({}) as pgde_where_in where {} in ({})",
+        base_query.trim_end().trim_end_matches(';'),
+        quote_ident(column),
+        placeholders.join(", ")
+    )
+}
+
+/// Builds a query that filters the rows of `base_query` to those whose `column` is an
+/// element of the array parameter `$1`.
+fn where_any_query(base_query: &str, column: &str) -> String {
+    format!(
+        "select * from ({}) as pgde_where_any where {} = any($1)",
+        base_query.trim_end().trim_end_matches(';'),
+        quote_ident(column)
+    )
+}
+
+/// The implementation of `consume_where_in`, running one query per chunk of values and
+/// merging the results.
+pub(crate) async fn consume_where_in<T: RowConsumer + Send, V: ToSql + Sync>(
+    conn: &Client,
+    base_query: &str,
+    column: &str,
+    values: &[V],
+) -> Result<Vec<T>, ConsumeError> {
+    let mut data = Vec::new();
+
+    for chunk in values.chunks(MAX_PARAMETERS) {
+        let query = where_in_query(base_query, column, chunk.len());
+        let params: Vec<&(dyn ToSql + Sync)> =
+            chunk.iter().map(|v| v as &(dyn ToSql + Sync)).collect();
+
+        data.append(&mut T::consume(conn, &query, &params).await?);
+    }
+
+    Ok(data)
+}
+
+/// The implementation of `consume_where_any`, sending the values as a single array
+/// parameter.
+pub(crate) async fn consume_where_any<T: RowConsumer + Send, V: ToSql + Sync>(
+    conn: &Client,
+    base_query: &str,
+    column: &str,
+    values: &[V],
+) -> Result<Vec<T>, ConsumeError> {
+    let query = where_any_query(base_query, column);
+
+    T::consume(conn, &query, &[&values]).await
+}
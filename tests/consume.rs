@@ -1,4 +1,6 @@
 //! Attempts to test a variety of `consume` scenarios for data types mentioned in the provided `FromSql` type implementations from postgres_types.
+#[cfg(feature = "arrayvec")]
+use arrayvec::ArrayVec;
 #[cfg(feature = "bit")]
 use bit_vec::BitVec;
 #[cfg(feature = "chrono")]
@@ -13,17 +15,40 @@ use geo_types::line_string;
 use geo_types::point;
 #[cfg(feature = "geo")]
 use geo_types::Rect;
+use pgde::client::{BreakerClient, CircuitState, Route, RoutedClient, TimedClient};
+use pgde::health;
+use pgde::locks::{advisory_lock, advisory_lock_timeout, try_advisory_lock};
+use pgde::transactions::with_savepoint;
+use pgde::RawValue;
 use pgde::RowConsumer;
+use pgde::RowRefConsumer;
+#[cfg(feature = "summary")]
+use pgde::Summary;
+use pgde::Table;
+use pgde::TableColumn;
+use pgde::TextEnum;
+use pgde::{
+    deallocate_all, with_schema, BadRowPolicy, BorrowedRowConsumer, ConsumeError, ConsumeOptions,
+    CopyConsumer, CopyOptions, ErrorClass, ErrorPolicy, FieldFailures, KeyedBy, Page, QueryParams,
+    SessionSettings, SortOrder,
+};
+use pgde_derive::row_consumer;
+use pgde_derive::QueryParams;
 use pgde_derive::RowConsumer;
+use pgde_derive::TextEnum;
 #[cfg(feature = "consume_json")]
 use serde::Serialize;
 #[cfg(feature = "json")]
 use serde_json::json;
+#[cfg(feature = "smallvec")]
+use smallvec::SmallVec;
 use std::net::IpAddr;
 use std::net::Ipv4Addr;
-use std::time::SystemTime;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
 #[cfg(feature = "time")]
 use time::{macros::*, Date, OffsetDateTime, PrimitiveDateTime, Time};
+use tokio_postgres::types::ToSql;
 use tokio_postgres::Row;
 use tokio_postgres::{Client, NoTls};
 #[cfg(feature = "uuid")]
@@ -157,8 +182,8 @@ async fn consume_boolean() -> Result<(), String> {
 
                                 match result.last() {
                                     Some(result_value) => {
-                                        assert_eq!(
-                                            *result_value, false,
+                                        assert!(
+                                            !*result_value,
                                             "Could not consume boolean into bool"
                                         );
 
@@ -1237,6 +1262,111 @@ async fn consume_json_impl() -> Result<(), String> {
     }
 }
 
+#[tokio::test]
+#[cfg(feature = "consume_json")]
+async fn consume_json_with_options() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(Serialize, RowConsumer)]
+    struct Foo {
+        foo_id: i32,
+        bar_value: Option<i32>,
+    }
+
+    let renamed = pgde::JsonOptions {
+        null_for_missing: false,
+        rename_all: Some(pgde::RenameRule::Camel),
+        ..Default::default()
+    };
+
+    let pretty = pgde::JsonOptions {
+        pretty: true,
+        ..Default::default()
+    };
+
+    let masked = pgde::JsonOptions {
+        null_for_missing: false,
+        rename_all: Some(pgde::RenameRule::Camel),
+        ..Default::default()
+    }
+    .transform("foo_id", |v| serde_json::Value::from(format!("id-{}", v)))
+    .transform("bar_value", |_| serde_json::Value::Null);
+
+    match connect_to_database().await {
+        Ok(v) => match (
+            Foo::consume_json_with(&v, "select 1, null::int;", &[], &renamed).await,
+            Foo::consume_json_with(&v, "select 1, 2;", &[], &pretty).await,
+            Foo::consume_json_with(&v, "select 1, 2;", &[], &masked).await,
+        ) {
+            (Ok(renamed), Ok(pretty), Ok(masked)) => {
+                assert_eq!(
+                    masked,
+                    String::from("[{\"fooId\":\"id-1\"}]"),
+                    "Could not transform fields"
+                );
+                assert_eq!(
+                    renamed,
+                    String::from("[{\"fooId\":1}]"),
+                    "Could not rename fields and leave out nulls"
+                );
+                assert_eq!(
+                    pretty,
+                    String::from("[\n  {\n    \"foo_id\": 1,\n    \"bar_value\": 2\n  }\n]"),
+                    "Could not pretty print json"
+                );
+
+                Ok(())
+            }
+            _ => Err(String::from("Could not consume_json_with into struct")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "consume_json")]
+#[tokio::test]
+async fn consume_json_export_formatting() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(Serialize, RowConsumer)]
+    struct Invoice {
+        #[serde(serialize_with = "pgde::export::iso8601_option")]
+        issued_at: Option<SystemTime>,
+        #[serde(serialize_with = "pgde::export::iso8601_option")]
+        paid_at: Option<SystemTime>,
+        #[serde(serialize_with = "pgde::export::fixed::<2, _, _>")]
+        total: f64,
+        #[serde(serialize_with = "pgde::export::fixed_option::<3, _, _>")]
+        rate: Option<f32>,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => match Invoice::consume_json(
+            &v,
+            "select '2024-02-29 23:59:59.25'::timestamp, '1969-12-31 23:59:59'::timestamp, 1234567.5::float8, null::float4
+            union all
+            select '0001-01-01 00:00:00'::timestamp, null, 'NaN'::float8, 0.5::float4;",
+            &[],
+        )
+        .await
+        {
+            Ok(result) => {
+                assert_eq!(
+                    result,
+                    String::from(concat!(
+                        "[{\"issued_at\":\"2024-02-29T23:59:59.250000Z\",\"paid_at\":\"1969-12-31T23:59:59Z\",\"total\":\"1234567.50\",\"rate\":null},",
+                        "{\"issued_at\":\"0001-01-01T00:00:00Z\",\"paid_at\":null,\"total\":\"NaN\",\"rate\":\"0.500\"}]"
+                    )),
+                    "Could not format exported fields"
+                );
+                Ok(())
+            }
+            Err(_) => Err(String::from("Could not consume_json with export formatting")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
 #[tokio::test]
 async fn consume_option() -> Result<(), String> {
     db_env_assertion!();
@@ -1292,6 +1422,85 @@ async fn consume_option_unit() -> Result<(), String> {
     }
 }
 
+#[tokio::test]
+async fn consume_timestamp_special() -> Result<(), String> {
+    db_env_assertion!();
+
+    use pgde::TimestampSpecial;
+
+    match connect_to_database().await {
+        Ok(v) => {
+            match pgde::consume_column::<TimestampSpecial<SystemTime>>(
+                &v,
+                "select v from (values ('-infinity'::timestamp), ('1970-01-01'::timestamp), ('infinity'::timestamp)) t(v);",
+                &[],
+            )
+            .await
+            {
+                Ok(result) => {
+                    assert_eq!(
+                        result,
+                        vec![
+                            TimestampSpecial::NegativeInfinity,
+                            TimestampSpecial::Finite(SystemTime::UNIX_EPOCH),
+                            TimestampSpecial::Infinity,
+                        ],
+                        "Could not consume infinite timestamps"
+                    );
+                    Ok(())
+                }
+                Err(_) => Err(String::from("Could not consume infinite timestamps")),
+            }
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn consume_timestamp_special_clamped() -> Result<(), String> {
+    db_env_assertion!();
+
+    use pgde::TimestampSpecial;
+
+    #[derive(RowConsumer)]
+    struct Validity {
+        valid_from: TimestampSpecial<NaiveDate>,
+        valid_to: TimestampSpecial<NaiveDateTime>,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => {
+            match Validity::consume(&v, "select '-infinity'::date, 'infinity'::timestamp;", &[])
+                .await
+            {
+                Ok(result) => match result.last() {
+                    Some(result_value) => {
+                        assert_eq!(
+                            result_value.valid_from.clamped(),
+                            NaiveDate::MIN,
+                            "Could not clamp -infinity date"
+                        );
+                        assert_eq!(
+                            result_value.valid_to.clamped(),
+                            NaiveDateTime::MAX,
+                            "Could not clamp infinity timestamp"
+                        );
+                        Ok(())
+                    }
+                    None => Err(String::from(
+                        "Could not consume infinite bounds into struct",
+                    )),
+                },
+                Err(_) => Err(String::from(
+                    "Could not consume infinite bounds into struct",
+                )),
+            }
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
 #[cfg(feature = "chrono")]
 #[tokio::test]
 async fn consume_chrono_naivedatetime() -> Result<(), String> {
@@ -1484,7 +1693,7 @@ async fn consume_chrono_datetime_fixedoffset() -> Result<(), String> {
             Ok(_) => {
                 let test_datetime = FixedOffset::east_opt(5)
                     .unwrap()
-                    .with_ymd_and_hms(2016, 11, 08, 0, 0, 0)
+                    .with_ymd_and_hms(2016, 11, 8, 0, 0, 0)
                     .unwrap();
 
                 match v
@@ -1863,3 +2072,5961 @@ async fn consume_time_time() -> Result<(), String> {
         Err(_) => Err(String::from("Could not connect to database")),
     }
 }
+
+#[tokio::test]
+async fn consume_interval() -> Result<(), String> {
+    db_env_assertion!();
+
+    match connect_to_database().await {
+        Ok(v) => match Duration::consume(&v, "select interval '1 day 2.5 seconds';", &[]).await {
+            Ok(result) => match result.last() {
+                Some(result_value) => {
+                    assert_eq!(
+                        *result_value,
+                        Duration::from_millis(86_402_500),
+                        "Could not consume interval into Duration"
+                    );
+
+                    match Duration::consume(&v, "select interval '-1 second';", &[]).await {
+                        Ok(_) => Err(String::from("Consumed negative interval into Duration")),
+                        Err(_) => Ok(()),
+                    }
+                }
+                None => Err(String::from("Could not consume interval into Duration")),
+            },
+            Err(_) => Err(String::from("Could not consume interval into Duration")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_duration_attributes() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    struct DurationConsumer {
+        #[pgde(interval)]
+        retention: Duration,
+        #[pgde(interval)]
+        nullable: Option<Duration>,
+        #[pgde(seconds)]
+        latency: Duration,
+        #[pgde(seconds)]
+        timeout: Duration,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => match DurationConsumer::consume(
+            &v,
+            "select interval '1 month', null::interval, 0.25::float8, 1.5::numeric;",
+            &[],
+        )
+        .await
+        {
+            Ok(result) => match result.last() {
+                Some(result_value) => {
+                    assert_eq!(
+                        result_value.retention,
+                        Duration::from_secs(30 * 86_400),
+                        "Could not consume interval into Duration"
+                    );
+                    assert_eq!(
+                        result_value.nullable, None,
+                        "Could not consume null into Option<Duration>"
+                    );
+                    assert_eq!(
+                        result_value.latency,
+                        Duration::from_millis(250),
+                        "Could not consume float8 seconds into Duration"
+                    );
+                    assert_eq!(
+                        result_value.timeout,
+                        Duration::from_millis(1500),
+                        "Could not consume numeric seconds into Duration"
+                    );
+                    Ok(())
+                }
+                None => Err(String::from("Could not consume durations into struct")),
+            },
+            Err(_) => Err(String::from("Could not consume durations into struct")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_narrow_and_widen() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    struct Measurement {
+        #[pgde(narrow)]
+        count: i32,
+        #[pgde(narrow)]
+        nullable: Option<i32>,
+        #[pgde(widen)]
+        total: i64,
+        #[pgde(widen)]
+        ratio: f64,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => {
+            match Measurement::consume(&v, "select 42::int8, null::int8, 7::int4, 3::int2;", &[])
+                .await
+            {
+                Ok(result) => match result.last() {
+                    Some(result_value) => {
+                        assert_eq!(result_value.count, 42, "Could not narrow int8 into i32");
+                        assert_eq!(
+                            result_value.nullable, None,
+                            "Could not narrow null into Option<i32>"
+                        );
+                        assert_eq!(result_value.total, 7, "Could not widen int4 into i64");
+                        assert_eq!(result_value.ratio, 3.0, "Could not widen int2 into f64");
+
+                        match Measurement::consume(
+                            &v,
+                            "select 4294967296::int8, null::int8, 7::int4, 3::int2;",
+                            &[],
+                        )
+                        .await
+                        {
+                            Ok(_) => Err(String::from("Narrowed out of range int8 into i32")),
+                            Err(_) => Ok(()),
+                        }
+                    }
+                    None => Err(String::from("Could not consume numbers into struct")),
+                },
+                Err(_) => Err(String::from("Could not consume numbers into struct")),
+            }
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_numeric_precision() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    struct Price {
+        #[pgde(numeric)]
+        exact: f64,
+        #[pgde(numeric)]
+        nullable: Option<f64>,
+        #[pgde(numeric(round))]
+        rounded: f64,
+        #[pgde(numeric(truncate))]
+        truncated: f32,
+        #[pgde(numeric(truncate))]
+        overflowed: f32,
+        #[pgde(numeric(error))]
+        special: f64,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => {
+            match Price::consume(
+                &v,
+                "select -12.3750::numeric, null::numeric, 0.1::numeric, 0.1::numeric, 1e40::numeric, 'NaN'::numeric;",
+                &[],
+            )
+            .await
+            {
+                Ok(result) => match result.last() {
+                    Some(result_value) => {
+                        assert_eq!(result_value.exact, -12.375, "Could not read exact numeric");
+                        assert_eq!(
+                            result_value.nullable, None,
+                            "Could not read null into Option<f64>"
+                        );
+                        assert_eq!(result_value.rounded, 0.1, "Could not round numeric");
+                        assert_eq!(
+                            result_value.truncated,
+                            f32::from_bits(0.1f32.to_bits() - 1),
+                            "Could not truncate numeric"
+                        );
+                        assert_eq!(
+                            result_value.overflowed,
+                            f32::MAX,
+                            "Could not truncate out of range numeric"
+                        );
+                        assert!(result_value.special.is_nan(), "Could not read NaN numeric");
+
+                        match Price::consume(
+                            &v,
+                            "select 0.1::numeric, null::numeric, 0.1::numeric, 0.1::numeric, 1::numeric, 1::numeric;",
+                            &[],
+                        )
+                        .await
+                        {
+                            Ok(_) => Err(String::from("Read inexact numeric without a policy")),
+                            Err(_) => Ok(()),
+                        }
+                    }
+                    None => Err(String::from("Could not consume numerics into struct")),
+                },
+                Err(_) => Err(String::from("Could not consume numerics into struct")),
+            }
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_nan_policy() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    struct Reading {
+        accepted: f64,
+        #[pgde(nan(none))]
+        missing: Option<f64>,
+        #[pgde(numeric(round), nan(none))]
+        missing_numeric: Option<f64>,
+        #[pgde(nan(error))]
+        checked: f32,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => {
+            match Reading::consume(
+                &v,
+                "select 'NaN'::float8, 'NaN'::float8, 'NaN'::numeric, 1.5::float4;",
+                &[],
+            )
+            .await
+            {
+                Ok(result) => match result.last() {
+                    Some(result_value) => {
+                        assert!(result_value.accepted.is_nan(), "Could not accept NaN");
+                        assert_eq!(result_value.missing, None, "Could not read NaN as None");
+                        assert_eq!(
+                            result_value.missing_numeric, None,
+                            "Could not read NaN numeric as None"
+                        );
+                        assert_eq!(result_value.checked, 1.5, "Could not read checked float");
+
+                        match Reading::consume(
+                            &v,
+                            "select 1::float8, 1::float8, 1::numeric, 'NaN'::float4;",
+                            &[],
+                        )
+                        .await
+                        {
+                            Ok(_) => Err(String::from("Read NaN with the error policy")),
+                            Err(_) => Ok(()),
+                        }
+                    }
+                    None => Err(String::from("Could not consume NaN into struct")),
+                },
+                Err(_) => Err(String::from("Could not consume NaN into struct")),
+            }
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_coerced_bool() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    struct Flags {
+        #[pgde(coerce)]
+        from_smallint: bool,
+        #[pgde(coerce)]
+        from_char: bool,
+        #[pgde(coerce)]
+        from_text: bool,
+        #[pgde(coerce)]
+        from_bool: bool,
+        #[pgde(coerce)]
+        nullable: Option<bool>,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => match Flags::consume(
+            &v,
+            "select 1::int2, 'f'::char(1), 'TRUE'::text, false, null::int2;",
+            &[],
+        )
+        .await
+        {
+            Ok(result) => match result.last() {
+                Some(result_value) => {
+                    assert!(
+                        result_value.from_smallint,
+                        "Could not coerce smallint into bool"
+                    );
+                    assert!(!result_value.from_char, "Could not coerce char into bool");
+                    assert!(result_value.from_text, "Could not coerce text into bool");
+                    assert!(!result_value.from_bool, "Could not coerce bool into bool");
+                    assert_eq!(
+                        result_value.nullable, None,
+                        "Could not coerce null into Option<bool>"
+                    );
+
+                    match Flags::consume(&v, "select 2::int2, 'f', 'yes', false, null;", &[]).await
+                    {
+                        Ok(_) => Err(String::from("Coerced invalid values into bool")),
+                        Err(_) => Ok(()),
+                    }
+                }
+                None => Err(String::from(
+                    "Could not consume coerced booleans into struct",
+                )),
+            },
+            Err(_) => Err(String::from(
+                "Could not consume coerced booleans into struct",
+            )),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_trim_and_empty_as_none() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    struct Contact {
+        #[pgde(trim)]
+        code: String,
+        #[pgde(empty_as_none)]
+        email: Option<String>,
+        #[pgde(trim, empty_as_none)]
+        phone: Option<String>,
+        #[pgde(empty_as_none)]
+        fax: Option<String>,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => match Contact::consume(
+            &v,
+            "select 'hello world'::char(15), '', '   '::char(3), 'none';",
+            &[],
+        )
+        .await
+        {
+            Ok(result) => match result.last() {
+                Some(result_value) => {
+                    assert_eq!(
+                        result_value.code, "hello world",
+                        "Could not trim char(n) into String"
+                    );
+                    assert_eq!(
+                        result_value.email, None,
+                        "Could not consume empty string as None"
+                    );
+                    assert_eq!(
+                        result_value.phone, None,
+                        "Could not consume blank char(n) as None"
+                    );
+                    assert_eq!(
+                        result_value.fax,
+                        Some(String::from("none")),
+                        "Could not consume text into Option<String>"
+                    );
+                    Ok(())
+                }
+                None => Err(String::from("Could not consume text into struct")),
+            },
+            Err(_) => Err(String::from("Could not consume text into struct")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "time")]
+#[tokio::test]
+async fn consume_utc_attributes() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    struct Event {
+        #[pgde(assume_utc)]
+        created: OffsetDateTime,
+        #[pgde(assume_utc)]
+        deleted: Option<OffsetDateTime>,
+        #[pgde(to_utc)]
+        updated: PrimitiveDateTime,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => match Event::consume(
+            &v,
+            "select '2024-03-01 12:30:00'::timestamp, null::timestamp, '2024-03-01 12:30:00+02'::timestamptz;",
+            &[],
+        )
+        .await
+        {
+            Ok(result) => match result.last() {
+                Some(result_value) => {
+                    assert_eq!(
+                        result_value.created,
+                        datetime!(2024-03-01 12:30:00 UTC),
+                        "Could not consume timestamp as UTC into OffsetDateTime"
+                    );
+                    assert_eq!(
+                        result_value.deleted, None,
+                        "Could not consume null into Option<OffsetDateTime>"
+                    );
+                    assert_eq!(
+                        result_value.updated,
+                        datetime!(2024-03-01 10:30:00),
+                        "Could not consume timestamptz as UTC into PrimitiveDateTime"
+                    );
+                    Ok(())
+                }
+                None => Err(String::from("Could not consume timestamps into struct")),
+            },
+            Err(_) => Err(String::from("Could not consume timestamps into struct")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_null_as() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    struct Account {
+        #[pgde(null_as = "-1")]
+        balance: i64,
+        #[pgde(null_as = "String::from(\"unknown\")")]
+        owner: String,
+        #[pgde(null_as = "-1", narrow)]
+        limit: i32,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => match Account::consume(
+            &v,
+            "select null::int8, null::text, 5::int8 union all select 10, 'alice', null;",
+            &[],
+        )
+        .await
+        {
+            Ok(result) => {
+                assert_eq!(result.len(), 2, "Could not consume nulls into struct");
+                assert_eq!(result[0].balance, -1, "Could not consume null as -1");
+                assert_eq!(
+                    result[0].owner, "unknown",
+                    "Could not consume null as a String sentinel"
+                );
+                assert_eq!(result[0].limit, 5, "Could not narrow int8 beside null_as");
+                assert_eq!(result[1].balance, 10, "Could not consume int8 into i64");
+                assert_eq!(
+                    result[1].owner, "alice",
+                    "Could not consume text into String"
+                );
+                assert_eq!(
+                    result[1].limit, -1,
+                    "Could not consume null as -1 when narrowing"
+                );
+                Ok(())
+            }
+            Err(_) => Err(String::from("Could not consume nulls into struct")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_as_text() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    struct Invoice {
+        #[pgde(as_text)]
+        total: f64,
+        #[pgde(as_text)]
+        fee: String,
+        #[pgde(as_text)]
+        code: u16,
+        #[pgde(as_text)]
+        address: Option<std::net::IpAddr>,
+        #[pgde(as_text)]
+        note: Option<u8>,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => match Invoice::consume(
+            &v,
+            "select 12.50::numeric, '-3.25'::money, '404'::char(5), '10.0.0.1'::varchar, null::int2;",
+            &[],
+        )
+        .await
+        {
+            Ok(result) => match result.last() {
+                Some(result_value) => {
+                    assert_eq!(result_value.total, 12.5, "Could not parse numeric into f64");
+                    assert_eq!(result_value.fee, "-3.25", "Could not read money as text");
+                    assert_eq!(result_value.code, 404, "Could not parse char(n) into u16");
+                    assert_eq!(
+                        result_value.address,
+                        Some(std::net::IpAddr::from([10, 0, 0, 1])),
+                        "Could not parse varchar into Option<IpAddr>"
+                    );
+                    assert_eq!(
+                        result_value.note, None,
+                        "Could not parse null into Option<u8>"
+                    );
+
+                    match Invoice::consume(&v, "select 'abc', 1, 2, null, 300;", &[]).await {
+                        Ok(_) => Err(String::from("Parsed invalid text into struct")),
+                        Err(_) => Ok(()),
+                    }
+                }
+                None => Err(String::from("Could not consume text into struct")),
+            },
+            Err(_) => Err(String::from("Could not consume text into struct")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn consume_chrono_duration() -> Result<(), String> {
+    db_env_assertion!();
+
+    match connect_to_database().await {
+        Ok(v) => match chrono::Duration::consume(&v, "select interval '-2 hours';", &[]).await {
+            Ok(result) => match result.last() {
+                Some(result_value) => {
+                    assert_eq!(
+                        *result_value,
+                        chrono::Duration::hours(-2),
+                        "Could not consume interval into chrono::Duration"
+                    );
+                    Ok(())
+                }
+                None => Err(String::from(
+                    "Could not consume interval into chrono::Duration",
+                )),
+            },
+            Err(_) => Err(String::from(
+                "Could not consume interval into chrono::Duration",
+            )),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "time")]
+#[tokio::test]
+async fn consume_time_duration() -> Result<(), String> {
+    db_env_assertion!();
+
+    match connect_to_database().await {
+        Ok(v) => match time::Duration::consume(&v, "select interval '-3 days';", &[]).await {
+            Ok(result) => match result.last() {
+                Some(result_value) => {
+                    assert_eq!(
+                        *result_value,
+                        time::Duration::days(-3),
+                        "Could not consume interval into time::Duration"
+                    );
+                    Ok(())
+                }
+                None => Err(String::from(
+                    "Could not consume interval into time::Duration",
+                )),
+            },
+            Err(_) => Err(String::from(
+                "Could not consume interval into time::Duration",
+            )),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "smallvec")]
+#[tokio::test]
+async fn consume_smallvec() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    struct Tagged {
+        id: i32,
+        #[pgde(array)]
+        tags: SmallVec<[String; 4]>,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => match Tagged::consume(&v, "select 1, array['a', 'b']::text[];", &[]).await {
+            Ok(result) => match result.last() {
+                Some(result_value) => {
+                    assert_eq!(result_value.id, 1, "Could not consume int into i32");
+                    assert_eq!(
+                        result_value.tags.as_slice(),
+                        &[String::from("a"), String::from("b")],
+                        "Could not consume text[] into SmallVec"
+                    );
+                    assert!(
+                        !result_value.tags.spilled(),
+                        "SmallVec spilled onto the heap"
+                    );
+
+                    match SmallVec::<[i32; 2]>::consume(&v, "select array[1, 2, 3];", &[]).await {
+                        Ok(result) => match result.last() {
+                            Some(result_value) => {
+                                assert_eq!(
+                                    result_value.as_slice(),
+                                    &[1, 2, 3],
+                                    "Could not consume int[] into SmallVec"
+                                );
+                                Ok(())
+                            }
+                            None => Err(String::from("Could not consume int[] into SmallVec")),
+                        },
+                        Err(_) => Err(String::from("Could not consume int[] into SmallVec")),
+                    }
+                }
+                None => Err(String::from("Could not consume text[] into SmallVec")),
+            },
+            Err(_) => Err(String::from("Could not consume text[] into SmallVec")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+#[tokio::test]
+async fn consume_arrayvec() -> Result<(), String> {
+    db_env_assertion!();
+
+    match connect_to_database().await {
+        Ok(v) => match ArrayVec::<i16, 3>::consume(&v, "select array[1, null, 3]::int2[];", &[])
+            .await
+        {
+            Ok(_) => Err(String::from("Consumed null element into ArrayVec<i16, 3>")),
+            Err(_) => match ArrayVec::<Option<i16>, 3>::consume(
+                &v,
+                "select array[1, null, 3]::int2[];",
+                &[],
+            )
+            .await
+            {
+                Ok(result) => match result.last() {
+                    Some(result_value) => {
+                        assert_eq!(
+                            result_value.as_slice(),
+                            &[Some(1), None, Some(3)],
+                            "Could not consume int2[] into ArrayVec"
+                        );
+
+                        match ArrayVec::<i16, 2>::consume(&v, "select array[1, 2, 3]::int2[];", &[])
+                            .await
+                        {
+                            Ok(_) => Err(String::from(
+                                "Consumed oversized array into ArrayVec<i16, 2>",
+                            )),
+                            Err(_) => Ok(()),
+                        }
+                    }
+                    None => Err(String::from("Could not consume int2[] into ArrayVec")),
+                },
+                Err(_) => Err(String::from("Could not consume int2[] into ArrayVec")),
+            },
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_text_enum() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(Debug, Default, PartialEq, TextEnum)]
+    enum Status {
+        #[default]
+        #[pgde(rename = "active")]
+        Active,
+        #[pgde(rename = "inactive")]
+        Inactive,
+        #[pgde(other)]
+        Other(String),
+    }
+
+    #[derive(Debug, Default, PartialEq, TextEnum)]
+    enum Strict {
+        #[default]
+        Yes,
+        No,
+    }
+
+    #[derive(RowConsumer)]
+    struct Account {
+        status: Status,
+        padded: Status,
+        unknown: Status,
+        strict: Strict,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => match Account::consume(
+            &v,
+            "select $1::varchar, 'inactive'::char(10), 'pending'::text, 'No'::text;",
+            &[&Status::Active],
+        )
+        .await
+        {
+            Ok(result) => match result.last() {
+                Some(result_value) => {
+                    assert_eq!(
+                        result_value.status,
+                        Status::Active,
+                        "Could not consume varchar into TextEnum"
+                    );
+                    assert_eq!(
+                        result_value.padded,
+                        Status::Inactive,
+                        "Could not consume char(n) into TextEnum"
+                    );
+                    assert_eq!(
+                        result_value.unknown,
+                        Status::Other(String::from("pending")),
+                        "Could not consume unknown text into TextEnum catch-all"
+                    );
+                    assert_eq!(
+                        result_value.unknown.as_text(),
+                        "pending",
+                        "Could not map TextEnum catch-all to text"
+                    );
+                    assert_eq!(
+                        result_value.strict,
+                        Strict::No,
+                        "Could not consume text into TextEnum"
+                    );
+
+                    match Account::consume(&v, "select 'active', 'active', 'active', 'maybe';", &[])
+                        .await
+                    {
+                        Ok(_) => Err(String::from(
+                            "Consumed unknown text into TextEnum without catch-all",
+                        )),
+                        Err(_) => Ok(()),
+                    }
+                }
+                None => Err(String::from("Could not consume text into TextEnum")),
+            },
+            Err(_) => Err(String::from("Could not consume text into TextEnum")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "bitflags")]
+#[tokio::test]
+async fn consume_bitflags() -> Result<(), String> {
+    db_env_assertion!();
+
+    bitflags::bitflags! {
+        #[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+        struct Permissions: u8 {
+            const READ = 0b001;
+            const WRITE = 0b010;
+            const EXECUTE = 0b100;
+        }
+    }
+
+    #[derive(RowConsumer)]
+    struct Grant {
+        #[pgde(bitflags)]
+        from_bits: Permissions,
+        #[pgde(bitflags)]
+        from_varbit: Permissions,
+        #[pgde(bitflags)]
+        from_int: Permissions,
+        #[pgde(bitflags(truncate))]
+        truncated: Permissions,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => {
+            match Grant::consume(&v, "select B'101', B'11'::varbit, 2::int2, 15;", &[]).await {
+                Ok(result) => match result.last() {
+                    Some(result_value) => {
+                        assert_eq!(
+                            result_value.from_bits,
+                            Permissions::READ | Permissions::EXECUTE,
+                            "Could not consume bit(n) into bitflags"
+                        );
+                        assert_eq!(
+                            result_value.from_varbit,
+                            Permissions::READ | Permissions::WRITE,
+                            "Could not consume varbit into bitflags"
+                        );
+                        assert_eq!(
+                            result_value.from_int,
+                            Permissions::WRITE,
+                            "Could not consume int2 into bitflags"
+                        );
+                        assert_eq!(
+                            result_value.truncated,
+                            Permissions::all(),
+                            "Could not truncate unknown bits into bitflags"
+                        );
+
+                        match Grant::consume(&v, "select B'1000', B'1'::varbit, 1::int2, 1;", &[])
+                            .await
+                        {
+                            Ok(_) => Err(String::from("Consumed unknown bits into bitflags")),
+                            Err(_) => Ok(()),
+                        }
+                    }
+                    None => Err(String::from("Could not consume bits into bitflags")),
+                },
+                Err(_) => Err(String::from("Could not consume bits into bitflags")),
+            }
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_raw_value() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    struct RawConsumer {
+        id: i32,
+        value: RawValue,
+        nullable: RawValue,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => match RawConsumer::consume(&v, "select 1, 42::int4, null::text;", &[]).await {
+            Ok(result) => match result.last() {
+                Some(result_value) => {
+                    assert_eq!(result_value.id, 1, "Could not consume int into i32");
+                    assert_eq!(
+                        result_value.value,
+                        RawValue {
+                            oid: 23,
+                            bytes: Some(vec![0, 0, 0, 42]),
+                        },
+                        "Could not consume int4 into RawValue"
+                    );
+                    assert_eq!(
+                        result_value.nullable,
+                        RawValue {
+                            oid: 25,
+                            bytes: None,
+                        },
+                        "Could not consume null into RawValue"
+                    );
+
+                    match RawValue::consume(&v, "select 'ab'::bytea;", &[]).await {
+                        Ok(result) => match result.last() {
+                            Some(result_value) => {
+                                assert_eq!(
+                                    result_value.bytes,
+                                    Some(vec![b'a', b'b']),
+                                    "Could not consume bytea into RawValue"
+                                );
+                                Ok(())
+                            }
+                            None => Err(String::from("Could not consume bytea into RawValue")),
+                        },
+                        Err(_) => Err(String::from("Could not consume bytea into RawValue")),
+                    }
+                }
+                None => Err(String::from("Could not consume columns into RawValue")),
+            },
+            Err(_) => Err(String::from("Could not consume columns into RawValue")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_with_options_on_error() -> Result<(), String> {
+    db_env_assertion!();
+
+    match connect_to_database().await {
+        Ok(v) => {
+            let failures = Arc::new(Mutex::new(Vec::new()));
+            let recorded = failures.clone();
+
+            let options = ConsumeOptions::new()
+                .redact_params(&[1])
+                .on_error(move |failed| {
+                    if let Ok(mut v) = recorded.lock() {
+                        v.push((
+                            String::from(failed.query),
+                            failed.params.to_vec(),
+                            failed.message.contains("division by zero"),
+                        ));
+                    }
+                });
+
+            match i32::consume_with_options(
+                &v,
+                "select $1::int / 0 where $2::text is not null;",
+                &[&7, &"hunter2"],
+                &options,
+            )
+            .await
+            {
+                Err(ConsumeError::DatabaseError { .. }) => (),
+                _ => return Err(String::from("Could not fail query")),
+            };
+
+            match i32::consume_with_options(&v, "select $1::int;", &[&7], &options).await {
+                Ok(v) => assert_eq!(v, vec![7], "Could not consume row"),
+                Err(_) => return Err(String::from("Could not consume row")),
+            };
+
+            match failures.lock() {
+                Ok(v) => assert_eq!(
+                    v.as_slice(),
+                    &[(
+                        String::from("select $1::int / 0 where $2::text is not null;"),
+                        vec![String::from("7"), String::from("[redacted]")],
+                        true,
+                    )],
+                    "Could not report failed query"
+                ),
+                Err(_) => return Err(String::from("Could not read failed queries")),
+            };
+
+            Ok(())
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_with_options() -> Result<(), String> {
+    db_env_assertion!();
+
+    let query = "select v from (values (1), (null), (3), (null), (5)) t(v);";
+
+    match connect_to_database().await {
+        Ok(v) => {
+            match i32::consume_with_options(&v, query, &[], &ConsumeOptions::new()).await {
+                Err(ConsumeError::ConversionError) => (),
+                _ => return Err(String::from("Consumed null into i32 with default options")),
+            }
+
+            match i32::consume_with_options(
+                &v,
+                query,
+                &[],
+                &ConsumeOptions::new().error_policy(ErrorPolicy::CollectAll),
+            )
+            .await
+            {
+                Err(ConsumeError::ConversionErrors(errors)) => {
+                    assert_eq!(errors.len(), 2, "Could not collect all conversion errors");
+                    assert!(
+                        errors[0].starts_with("Row 1: "),
+                        "Could not report row of conversion error"
+                    );
+                }
+                _ => return Err(String::from("Could not collect all conversion errors")),
+            }
+
+            match i32::consume_with_options(
+                &v,
+                query,
+                &[],
+                &ConsumeOptions::new().bad_rows(BadRowPolicy::KeepDefaults),
+            )
+            .await
+            {
+                Ok(result) => assert_eq!(
+                    result,
+                    vec![1, 0, 3, 0, 5],
+                    "Could not keep defaults for bad rows"
+                ),
+                Err(_) => return Err(String::from("Could not keep defaults for bad rows")),
+            }
+
+            match i32::consume_with_options(
+                &v,
+                query,
+                &[],
+                &ConsumeOptions::new()
+                    .bad_rows(BadRowPolicy::Skip)
+                    .max_rows(2)
+                    .label("consume_with_options")
+                    .trace_tag("test */ tag"),
+            )
+            .await
+            {
+                Ok(result) => {
+                    assert_eq!(result, vec![1, 3], "Could not skip bad rows up to max rows");
+                    Ok(())
+                }
+                Err(_) => Err(String::from("Could not skip bad rows up to max rows")),
+            }
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_with_options_nested_comment() -> Result<(), String> {
+    db_env_assertion!();
+
+    match connect_to_database().await {
+        Ok(v) => match i32::consume_with_options(
+            &v,
+            "select 1;",
+            &[],
+            &ConsumeOptions::new()
+                .label("nested /* name")
+                .trace_tag("tag /*/ with */ comments"),
+        )
+        .await
+        {
+            Ok(result) => {
+                assert_eq!(
+                    result,
+                    vec![1],
+                    "Could not consume with nested comment labels"
+                );
+                Ok(())
+            }
+            Err(_) => Err(String::from("Could not consume with nested comment labels")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_conversion_report() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    struct Import {
+        id: i32,
+        email: String,
+        total: i64,
+        #[pgde(narrow)]
+        small: i16,
+    }
+
+    let query = "select * from (values (1, 'a@example.com', '1'::text, 1::bigint), (2, null, '2'::text, 100000::bigint), (3, null, '3'::text, 2::bigint)) t(id, email, total, small);";
+
+    match connect_to_database().await {
+        Ok(v) => match Import::consume_with_options(
+            &v,
+            query,
+            &[],
+            &ConsumeOptions::new().error_policy(ErrorPolicy::Report),
+        )
+        .await
+        {
+            Err(ConsumeError::ConversionReport(report)) => {
+                assert_eq!(
+                    report.errors.len(),
+                    6,
+                    "Could not report every conversion error"
+                );
+                assert_eq!(
+                    report.fields.get("email"),
+                    Some(&FieldFailures {
+                        null_into_non_option: 2,
+                        type_mismatch: 0,
+                        invalid_value: 0,
+                    }),
+                    "Could not count nulls read into non-Option field"
+                );
+                assert_eq!(
+                    report.fields.get("total"),
+                    Some(&FieldFailures {
+                        null_into_non_option: 0,
+                        type_mismatch: 3,
+                        invalid_value: 0,
+                    }),
+                    "Could not count mistyped column"
+                );
+                assert_eq!(
+                    report.fields.get("small").map(|v| v.invalid_value),
+                    Some(1),
+                    "Could not count rejected value"
+                );
+                assert_eq!(
+                    report.fields.get("id"),
+                    None,
+                    "Counted field without failures"
+                );
+
+                match Import::consume(&v, "select 4, 'a@example.com', 5::bigint, 6::bigint;", &[])
+                    .await
+                {
+                    Ok(result) => {
+                        let row = &result[0];
+
+                        assert_eq!(
+                            (row.id, row.email.as_str(), row.total, row.small),
+                            (4, "a@example.com", 5, 6),
+                            "Could not consume fields without failures"
+                        );
+
+                        Ok(())
+                    }
+                    Err(_) => Err(String::from("Could not consume fields without failures")),
+                }
+            }
+            _ => Err(String::from("Could not report conversion errors by field")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_filtered() -> Result<(), String> {
+    db_env_assertion!();
+
+    match connect_to_database().await {
+        Ok(v) => match i32::consume_filtered(
+            &v,
+            "select v from (values (1), (null), ('3'::text::int), (null)) t(v);",
+            &[],
+        )
+        .await
+        {
+            Ok(result) => {
+                assert_eq!(
+                    result.rows,
+                    vec![1, 3],
+                    "Could not drop rows failing conversion"
+                );
+                assert_eq!(result.skipped, 2, "Could not count rows failing conversion");
+                Ok(())
+            }
+            Err(_) => Err(String::from("Could not drop rows failing conversion")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_select_builder() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    #[pgde(table = "consume_select_builder", schema = "public")]
+    struct Order {
+        id: i32,
+        status: String,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => match v
+            .batch_execute(
+                "drop table if exists consume_select_builder;
+                create table consume_select_builder (
+                    id int,
+                    status text
+                );
+                insert into consume_select_builder values (1, 'open'), (2, 'closed'), (3, 'open'), (4, 'open');",
+            )
+            .await
+        {
+            Ok(_) => {
+                let status = String::from("open");
+
+                match Order::select()
+                    .where_eq("status", &status)
+                    .order_by_desc("id")
+                    .limit(2)
+                    .consume(&v)
+                    .await
+                {
+                    Ok(result) => {
+                        let ids: Vec<i32> = result.iter().map(|v| v.id).collect();
+                        assert_eq!(ids, vec![4, 3], "Could not consume select builder query");
+                        assert!(
+                            result.iter().all(|v| v.status == status),
+                            "Could not filter select builder query"
+                        );
+
+                        match Order::select().where_eq("status; drop table x", &status).sql() {
+                            Err(ConsumeError::InvalidQuery(_)) => Ok(()),
+                            _ => Err(String::from("Built select query with unknown column")),
+                        }
+                    }
+                    Err(_) => Err(String::from("Could not consume select builder query")),
+                }
+            }
+            Err(v) => Err(v.to_string()),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_table_columns() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    #[pgde(table = "consume_table_columns")]
+    struct Order {
+        id: i32,
+        customer_id: i32,
+        r#type: String,
+    }
+
+    fn column_names<T: Table>(columns: &[T::Columns]) -> Vec<(usize, &'static str)> {
+        columns.iter().map(|v| (v.index(), v.as_str())).collect()
+    }
+
+    assert_eq!(Order::FIELD_COUNT, 3, "Could not count fields");
+    assert_eq!(
+        column_names::<Order>(&[
+            OrderColumns::Type,
+            OrderColumns::CustomerId,
+            OrderColumns::Id
+        ]),
+        vec![(2, "type"), (1, "customer_id"), (0, "id")],
+        "Could not name columns"
+    );
+
+    match connect_to_database().await {
+        Ok(v) => match v
+            .batch_execute(
+                "drop table if exists consume_table_columns;
+                create table consume_table_columns (
+                    id int,
+                    customer_id int,
+                    type text
+                );
+                insert into consume_table_columns values (1, 7, 'a'), (2, 7, 'b'), (3, 8, 'a');",
+            )
+            .await
+        {
+            Ok(_) => {
+                let customer_id = 7;
+
+                match Order::select()
+                    .where_eq(OrderColumns::CustomerId, &customer_id)
+                    .order_by_desc(OrderColumns::Id)
+                    .consume(&v)
+                    .await
+                {
+                    Ok(result) => {
+                        let rows: Vec<(i32, i32, &str)> = result
+                            .iter()
+                            .map(|v| (v.id, v.customer_id, v.r#type.as_str()))
+                            .collect();
+                        assert_eq!(
+                            rows,
+                            vec![(2, 7, "b"), (1, 7, "a")],
+                            "Could not build query from typed columns"
+                        );
+                        Ok(())
+                    }
+                    Err(_) => Err(String::from("Could not consume select builder query")),
+                }
+            }
+            Err(v) => Err(v.to_string()),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_table_columns_renamed() -> Result<(), String> {
+    db_env_assertion!();
+
+    struct InvoiceColumns;
+
+    #[derive(RowConsumer)]
+    #[pgde(columns_enum = "InvoiceField")]
+    struct Invoice {
+        id: i32,
+    }
+
+    #[derive(RowConsumer)]
+    #[pgde(skip_columns_enum)]
+    struct Membership {
+        user_id: i32,
+        _user_id: i32,
+        self_: i32,
+    }
+
+    let _ = InvoiceColumns;
+    assert_eq!(
+        InvoiceField::Id.as_str(),
+        "id",
+        "Could not rename columns enum"
+    );
+
+    match connect_to_database().await {
+        Ok(v) => match Invoice::consume(&v, "select 5 as id;", &[]).await {
+            Ok(invoices) => match Membership::consume(&v, "select 1, 2, 3;", &[]).await {
+                Ok(memberships) => {
+                    let rows: Vec<(i32, i32, i32, i32)> = invoices
+                        .iter()
+                        .zip(memberships.iter())
+                        .map(|(a, b)| (a.id, b.user_id, b._user_id, b.self_))
+                        .collect();
+                    assert_eq!(
+                        rows,
+                        vec![(5, 1, 2, 3)],
+                        "Could not consume structs without their default columns enum"
+                    );
+                    Ok(())
+                }
+                Err(_) => Err(String::from("Could not consume memberships")),
+            },
+            Err(_) => Err(String::from("Could not consume invoices")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_ordered() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    struct Post {
+        id: i32,
+        author: String,
+    }
+
+    let query = "select 1 as id, 'b' as author union all select 2, 'a' union all
+        select 3, 'b' union all select 4, 'a';";
+
+    match connect_to_database().await {
+        Ok(v) => {
+            let order = [
+                (PostColumns::Author, SortOrder::Asc),
+                (PostColumns::Id, SortOrder::Desc),
+            ];
+
+            match Post::consume_ordered(&v, query, &[], &order).await {
+                Ok(result) => {
+                    let rows: Vec<(i32, &str)> =
+                        result.iter().map(|v| (v.id, v.author.as_str())).collect();
+                    assert_eq!(
+                        rows,
+                        vec![(4, "a"), (2, "a"), (3, "b"), (1, "b")],
+                        "Could not order query by typed columns"
+                    );
+                }
+                Err(_) => return Err(String::from("Could not consume ordered query")),
+            };
+
+            match Post::consume_ordered(&v, "select 1, 'a';", &[], &[]).await {
+                Ok(result) => {
+                    assert_eq!(result.len(), 1, "Could not consume unordered query");
+                    Ok(())
+                }
+                Err(_) => Err(String::from("Could not consume unordered query")),
+            }
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_soft_delete() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    #[pgde(table = "consume_soft_delete", soft_delete = "deleted_at")]
+    struct Customer {
+        id: i32,
+        region: String,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => match v
+            .batch_execute(
+                "drop table if exists consume_soft_delete;
+                create table consume_soft_delete (
+                    id int,
+                    region text,
+                    deleted_at timestamptz
+                );
+                insert into consume_soft_delete values
+                    (1, 'north', null), (2, 'north', now()), (3, 'south', null);",
+            )
+            .await
+        {
+            Ok(_) => {
+                let region = String::from("north");
+
+                match (
+                    Customer::select()
+                        .where_eq("region", &region)
+                        .consume(&v)
+                        .await,
+                    Customer::select().include_deleted().consume(&v).await,
+                ) {
+                    (Ok(live), Ok(all)) => {
+                        let live: Vec<(i32, &str)> =
+                            live.iter().map(|v| (v.id, v.region.as_str())).collect();
+                        assert_eq!(live, vec![(1, "north")], "Could not leave out deleted rows");
+                        assert_eq!(all.len(), 3, "Could not include deleted rows");
+                    }
+                    _ => return Err(String::from("Could not consume select builder query")),
+                };
+
+                match (
+                    Customer::consume_count(&v, "", &[]).await,
+                    Customer::consume_count(&v, "region = $1", &[&region]).await,
+                    Customer::exists(&v, "id = $1", &[&2]).await,
+                ) {
+                    (Ok(count), Ok(filtered), Ok(exists)) => {
+                        assert_eq!(count, 2, "Could not count live rows");
+                        assert_eq!(filtered, 1, "Could not count filtered live rows");
+                        assert!(!exists, "Found deleted row");
+                        Ok(())
+                    }
+                    _ => Err(String::from("Could not query live rows")),
+                }
+            }
+            Err(v) => Err(v.to_string()),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_where_in() -> Result<(), String> {
+    db_env_assertion!();
+
+    let query = "select generate_series(1, 100000) as id;";
+    let values: Vec<i32> = (0..70_000).map(|v| v * 2).collect();
+
+    match connect_to_database().await {
+        Ok(v) => match i32::consume_where_in(&v, query, "id", &values).await {
+            Ok(mut result) => {
+                result.sort();
+                assert_eq!(result.len(), 50_000, "Could not consume chunked in-list");
+                assert_eq!(
+                    result.first(),
+                    Some(&2),
+                    "Could not consume chunked in-list"
+                );
+                assert_eq!(
+                    result.last(),
+                    Some(&100_000),
+                    "Could not consume chunked in-list"
+                );
+
+                match i32::consume_where_in::<i32>(&v, query, "id", &[]).await {
+                    Ok(result) => {
+                        assert!(result.is_empty(), "Consumed rows for empty in-list");
+                        Ok(())
+                    }
+                    Err(_) => Err(String::from("Could not consume empty in-list")),
+                }
+            }
+            Err(_) => Err(String::from("Could not consume chunked in-list")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_where_any() -> Result<(), String> {
+    db_env_assertion!();
+
+    let query = "select generate_series(1, 100000)::bigint as id;";
+    let values: Vec<i64> = (0..70_000).map(|v| v * 2).collect();
+
+    match connect_to_database().await {
+        Ok(v) => {
+            match i64::consume_where_any(&v, query, "id", &values).await {
+                Ok(mut result) => {
+                    result.sort();
+                    assert_eq!(result.len(), 50_000, "Could not consume array parameter");
+                    assert_eq!(
+                        result.first(),
+                        Some(&2),
+                        "Could not consume array parameter"
+                    );
+                }
+                Err(_) => return Err(String::from("Could not consume array parameter")),
+            }
+
+            match String::consume_where_any(
+                &v,
+                "select name from (values ('a'), ('b'), ('c')) t(name);",
+                "name",
+                &["a", "c", "d"],
+            )
+            .await
+            {
+                Ok(mut result) => {
+                    result.sort();
+                    assert_eq!(
+                        result,
+                        vec![String::from("a"), String::from("c")],
+                        "Could not consume text array parameter"
+                    );
+                }
+                Err(_) => return Err(String::from("Could not consume text array parameter")),
+            }
+
+            match i64::consume_where_any::<i64>(&v, query, "id", &[]).await {
+                Ok(result) => {
+                    assert!(result.is_empty(), "Consumed rows for empty array parameter");
+                    Ok(())
+                }
+                Err(_) => Err(String::from("Could not consume empty array parameter")),
+            }
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(all(feature = "uuid", feature = "chrono"))]
+#[tokio::test]
+async fn consume_where_any_feature_types() -> Result<(), String> {
+    db_env_assertion!();
+
+    let ids = vec![Uuid::from_u128(1), Uuid::from_u128(3)];
+    let days = vec![
+        NaiveDate::from_ymd_opt(2024, 1, 1).unwrap_or_default(),
+        NaiveDate::from_ymd_opt(2024, 1, 3).unwrap_or_default(),
+    ];
+
+    match connect_to_database().await {
+        Ok(v) => {
+            match Uuid::consume_where_any(
+                &v,
+                "select id from (values ('00000000-0000-0000-0000-000000000001'::uuid), ('00000000-0000-0000-0000-000000000002'::uuid)) t(id);",
+                "id",
+                &ids,
+            )
+            .await
+            {
+                Ok(result) => assert_eq!(
+                    result,
+                    vec![Uuid::from_u128(1)],
+                    "Could not consume uuid array parameter"
+                ),
+                Err(_) => return Err(String::from("Could not consume uuid array parameter")),
+            }
+
+            match NaiveDate::consume_where_any(
+                &v,
+                "select day::date from generate_series('2024-01-01'::date, '2024-01-02'::date, '1 day') t(day);",
+                "day",
+                &days,
+            )
+            .await
+            {
+                Ok(result) => {
+                    assert_eq!(
+                        result,
+                        vec![NaiveDate::from_ymd_opt(2024, 1, 1).unwrap_or_default()],
+                        "Could not consume date array parameter"
+                    );
+                    Ok(())
+                }
+                Err(_) => Err(String::from("Could not consume date array parameter")),
+            }
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "retry")]
+#[tokio::test]
+async fn consume_with_retry() -> Result<(), String> {
+    use pgde::transactions::{with_retry, RetryPolicy};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    db_env_assertion!();
+
+    match connect_to_database().await {
+        Ok(mut v) => match v
+            .batch_execute(
+                "drop table if exists consume_with_retry;
+                create table consume_with_retry (
+                    attempt int
+                );",
+            )
+            .await
+        {
+            Ok(_) => {
+                let attempts = Arc::new(AtomicUsize::new(0));
+                let policy =
+                    RetryPolicy::new(5).backoff(Duration::from_millis(1), Duration::from_millis(5));
+
+                let counted = attempts.clone();
+
+                let result = with_retry(&mut v, policy, move |txn| {
+                    let attempt = counted.fetch_add(1, Ordering::SeqCst) as i32 + 1;
+
+                    Box::pin(async move {
+                        pgde::execute(
+                            txn.client(),
+                            "insert into consume_with_retry values ($1);",
+                            &[&attempt],
+                        )
+                        .await?;
+
+                        if attempt < 3 {
+                            pgde::execute(
+                                txn.client(),
+                                "do $$ begin raise exception 'conflict' using errcode = '40001'; end $$;",
+                                &[],
+                            )
+                            .await?;
+                        }
+
+                        i32::consume(txn.client(), "select attempt from consume_with_retry;", &[])
+                            .await
+                    })
+                })
+                .await;
+
+                match result {
+                    Ok(v) => assert_eq!(v, vec![3], "Could not roll back failed attempts"),
+                    Err(_) => return Err(String::from("Could not retry transaction")),
+                };
+
+                assert_eq!(
+                    attempts.load(Ordering::SeqCst),
+                    3,
+                    "Could not count attempts"
+                );
+
+                let attempts = Arc::new(AtomicUsize::new(0));
+                let counted = attempts.clone();
+
+                let result: Result<u64, ConsumeError> =
+                    with_retry(&mut v, RetryPolicy::new(2), move |txn| {
+                        counted.fetch_add(1, Ordering::SeqCst);
+
+                        Box::pin(async move {
+                            pgde::execute(
+                                txn.client(),
+                                "do $$ begin raise exception 'deadlock' using errcode = '40P01'; end $$;",
+                                &[],
+                            )
+                            .await
+                        })
+                    })
+                    .await;
+
+                match result {
+                    Err(e) => assert_eq!(
+                        e.classification(),
+                        ErrorClass::SerializationFailure,
+                        "Could not return last error"
+                    ),
+                    Ok(_) => return Err(String::from("Could not fail transaction")),
+                };
+
+                assert_eq!(
+                    attempts.load(Ordering::SeqCst),
+                    2,
+                    "Could not limit attempts"
+                );
+
+                let attempts = Arc::new(AtomicUsize::new(0));
+                let counted = attempts.clone();
+
+                let result: Result<u64, ConsumeError> =
+                    with_retry(&mut v, RetryPolicy::new(5), move |txn| {
+                        counted.fetch_add(1, Ordering::SeqCst);
+
+                        Box::pin(async move {
+                            pgde::execute(
+                                txn.client(),
+                                "select * from consume_with_retry_missing;",
+                                &[],
+                            )
+                            .await
+                        })
+                    })
+                    .await;
+
+                assert!(result.is_err(), "Could not fail transaction");
+                assert_eq!(
+                    attempts.load(Ordering::SeqCst),
+                    1,
+                    "Could not skip retrying other errors"
+                );
+                Ok(())
+            }
+            Err(v) => Err(v.to_string()),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_with_transaction() -> Result<(), String> {
+    use pgde::transactions::{with_transaction, TransactionOptions};
+    use tokio_postgres::IsolationLevel;
+
+    db_env_assertion!();
+
+    match connect_to_database().await {
+        Ok(mut v) => match v
+            .batch_execute(
+                "drop table if exists consume_with_transaction;
+                create table consume_with_transaction (
+                    id int
+                );",
+            )
+            .await
+        {
+            Ok(_) => {
+                let options = TransactionOptions::new()
+                    .isolation(IsolationLevel::Serializable)
+                    .read_only(true)
+                    .deferrable(true);
+
+                let result = with_transaction(&mut v, options, |txn| {
+                    Box::pin(async move {
+                        String::consume(
+                            txn.client(),
+                            "select concat_ws(' ', current_setting('transaction_isolation'), current_setting('transaction_read_only'), current_setting('transaction_deferrable'));",
+                            &[],
+                        )
+                        .await
+                    })
+                })
+                .await;
+
+                match result {
+                    Ok(v) => assert_eq!(
+                        v,
+                        vec![String::from("serializable on on")],
+                        "Could not begin transaction with options"
+                    ),
+                    Err(_) => return Err(String::from("Could not run transaction")),
+                };
+
+                let result =
+                    with_transaction(&mut v, TransactionOptions::new().read_only(true), |txn| {
+                        Box::pin(async move {
+                            pgde::execute(
+                                txn.client(),
+                                "insert into consume_with_transaction values (1);",
+                                &[],
+                            )
+                            .await
+                        })
+                    })
+                    .await;
+
+                match result {
+                    Err(ConsumeError::DatabaseError { code, .. }) => {
+                        assert_eq!(
+                            code, "25006",
+                            "Could not reject write in read only transaction"
+                        )
+                    }
+                    _ => {
+                        return Err(String::from(
+                            "Could not reject write in read only transaction",
+                        ))
+                    }
+                };
+
+                let result = with_transaction(&mut v, TransactionOptions::new(), |txn| {
+                    Box::pin(async move {
+                        pgde::execute(
+                            txn.client(),
+                            "insert into consume_with_transaction values (2);",
+                            &[],
+                        )
+                        .await
+                    })
+                })
+                .await;
+
+                match result {
+                    Ok(v) => assert_eq!(v, 1, "Could not write in transaction"),
+                    Err(_) => return Err(String::from("Could not write in transaction")),
+                };
+
+                match i32::consume(&v, "select id from consume_with_transaction;", &[]).await {
+                    Ok(v) => assert_eq!(v, vec![2], "Could not commit transaction"),
+                    Err(_) => return Err(String::from("Could not read committed rows")),
+                };
+                Ok(())
+            }
+            Err(v) => Err(v.to_string()),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_with_savepoint() -> Result<(), String> {
+    db_env_assertion!();
+
+    match connect_to_database().await {
+        Ok(mut v) => match v
+            .batch_execute(
+                "drop table if exists consume_with_savepoint;
+                create table consume_with_savepoint (
+                    field1 int
+                );",
+            )
+            .await
+        {
+            Ok(_) => match v.transaction().await {
+                Ok(mut txn) => {
+                    let failed: Result<Vec<i32>, ConsumeError> = with_savepoint(&mut txn, |sp| {
+                        Box::pin(async move {
+                            let _ = sp
+                                .execute("insert into consume_with_savepoint values (1);", &[])
+                                .await;
+                            i32::consume(sp.client(), "select null::int;", &[]).await
+                        })
+                    })
+                    .await;
+
+                    assert!(failed.is_err(), "Consumed null into i32 in savepoint");
+
+                    let succeeded: Result<Vec<i32>, ConsumeError> =
+                        with_savepoint(&mut txn, |sp| {
+                            Box::pin(async move {
+                                let _ = sp
+                                    .execute("insert into consume_with_savepoint values (2);", &[])
+                                    .await;
+                                i32::consume(sp.client(), "select 2;", &[]).await
+                            })
+                        })
+                        .await;
+
+                    assert!(succeeded.is_ok(), "Could not consume in savepoint");
+
+                    match i32::consume(
+                        txn.client(),
+                        "select field1 from consume_with_savepoint;",
+                        &[],
+                    )
+                    .await
+                    {
+                        Ok(result) => {
+                            assert_eq!(result, vec![2], "Could not roll back failed savepoint");
+
+                            match txn.commit().await {
+                                Ok(_) => Ok(()),
+                                Err(v) => Err(v.to_string()),
+                            }
+                        }
+                        Err(_) => Err(String::from("Could not consume after savepoints")),
+                    }
+                }
+                Err(v) => Err(v.to_string()),
+            },
+            Err(v) => Err(v.to_string()),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_advisory_lock() -> Result<(), String> {
+    db_env_assertion!();
+
+    let key = 1136;
+
+    match (connect_to_database().await, connect_to_database().await) {
+        (Ok(first), Ok(second)) => match advisory_lock(&first, key).await {
+            Ok(guard) => {
+                match try_advisory_lock(&second, key).await {
+                    Ok(v) => assert!(v.is_none(), "Took lock held by another session"),
+                    Err(_) => return Err(String::from("Could not try lock")),
+                };
+
+                match advisory_lock_timeout(&second, key, Duration::from_millis(100)).await {
+                    Ok(v) => assert!(v.is_none(), "Took lock held by another session"),
+                    Err(_) => return Err(String::from("Could not wait for lock")),
+                };
+
+                drop(guard);
+
+                // Any later query on the same session runs after the unlock sent on drop.
+                if first.execute("select 1;", &[]).await.is_err() {
+                    return Err(String::from("Could not query after dropping lock"));
+                }
+
+                match try_advisory_lock(&second, key).await {
+                    Ok(Some(v)) => match v.unlock().await {
+                        Ok(_) => Ok(()),
+                        Err(_) => Err(String::from("Could not unlock")),
+                    },
+                    Ok(None) => Err(String::from("Could not take lock released on drop")),
+                    Err(_) => Err(String::from("Could not try lock")),
+                }
+            }
+            Err(_) => Err(String::from("Could not take lock")),
+        },
+        _ => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_advisory_unlock_not_held() -> Result<(), String> {
+    db_env_assertion!();
+
+    let key = 11361;
+
+    match connect_to_database().await {
+        Ok(v) => match advisory_lock(&v, key).await {
+            Ok(guard) => {
+                if v.execute("select pg_advisory_unlock($1);", &[&key])
+                    .await
+                    .is_err()
+                {
+                    return Err(String::from("Could not release lock directly"));
+                }
+
+                match guard.unlock().await {
+                    Err(ConsumeError::InvalidQuery(_)) => Ok(()),
+                    _ => Err(String::from("Could not report lock that was not held")),
+                }
+            }
+            Err(_) => Err(String::from("Could not take lock")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "explain")]
+#[tokio::test]
+async fn consume_explain() -> Result<(), String> {
+    db_env_assertion!();
+
+    match connect_to_database().await {
+        Ok(v) => {
+            let query = "select * from generate_series(1, $1);";
+            let options = pgde::ExplainOptions::new().analyze(true);
+
+            match pgde::consume_explain(&v, query, &[&10], &options).await {
+                Ok(result) => {
+                    assert_eq!(
+                        result.plan.node_type, "Function Scan",
+                        "Could not consume node type"
+                    );
+                    assert!(
+                        result.plan.total_cost.is_some(),
+                        "Could not consume total cost"
+                    );
+                    assert_eq!(
+                        result.plan.actual_rows,
+                        Some(10.0),
+                        "Could not consume actual rows"
+                    );
+                    assert!(
+                        result.execution_time.is_some(),
+                        "Could not consume execution time"
+                    );
+
+                    Ok(())
+                }
+                Err(_) => Err(String::from("Could not explain query")),
+            }
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_timed_client() -> Result<(), String> {
+    db_env_assertion!();
+
+    let slow_queries = Arc::new(Mutex::new(Vec::new()));
+    let recorded = slow_queries.clone();
+
+    match connect_to_database().await {
+        Ok(v) => {
+            let mut client = TimedClient::new(v, Duration::from_secs(60), move |slow| {
+                if let Ok(mut v) = recorded.lock() {
+                    v.push(String::from(slow.query));
+                }
+            });
+
+            match client.consume::<i32>("select 1;", &[]).await {
+                Ok(result) => assert_eq!(result, vec![1], "Could not consume with timed client"),
+                Err(_) => return Err(String::from("Could not consume with timed client")),
+            };
+
+            client.set_threshold(Duration::ZERO);
+
+            match client.consume::<i32>("select 2;", &[]).await {
+                Ok(result) => assert_eq!(result, vec![2], "Could not consume with timed client"),
+                Err(_) => return Err(String::from("Could not consume with timed client")),
+            };
+
+            match slow_queries.lock() {
+                Ok(v) => {
+                    assert_eq!(
+                        *v,
+                        vec![String::from("select 2;")],
+                        "Could not report slow query"
+                    );
+
+                    Ok(())
+                }
+                Err(_) => Err(String::from("Could not read slow queries")),
+            }
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_health_check() -> Result<(), String> {
+    db_env_assertion!();
+
+    match connect_to_database().await {
+        Ok(v) => match health::check(&v).await {
+            Ok(result) => {
+                assert!(!result.in_recovery, "Could not check recovery");
+                assert_eq!(result.replication_lag, None, "Could not check lag");
+                assert!(result.connections >= 1, "Could not count connections");
+                assert!(
+                    result.max_connections >= result.connections,
+                    "Could not read max connections"
+                );
+
+                Ok(())
+            }
+            Err(_) => Err(String::from("Could not check health")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_routed_client() -> Result<(), String> {
+    db_env_assertion!();
+
+    match (
+        connect_to_database().await,
+        connect_to_database().await,
+        connect_to_database().await,
+    ) {
+        (Ok(primary), Ok(first), Ok(second)) => {
+            let client = RoutedClient::new(primary, vec![first, second]);
+            let query = "select pg_backend_pid();";
+
+            let mut pids = Vec::new();
+
+            for route in [
+                Route::Replica,
+                Route::Replica,
+                Route::Replica,
+                Route::Primary,
+            ] {
+                match client.consume_on::<i32>(route, query, &[]).await {
+                    Ok(v) => pids.extend(v),
+                    Err(_) => return Err(String::from("Could not consume with routed client")),
+                };
+            }
+
+            assert_ne!(pids[0], pids[1], "Could not route reads round-robin");
+            assert_eq!(pids[0], pids[2], "Could not route reads round-robin");
+            assert_ne!(pids[3], pids[0], "Could not route to primary");
+            assert_ne!(pids[3], pids[1], "Could not route to primary");
+
+            match client.execute("select 1;", &[]).await {
+                Ok(v) => {
+                    assert_eq!(v, 1, "Could not execute on primary");
+                    Ok(())
+                }
+                Err(_) => Err(String::from("Could not execute on primary")),
+            }
+        }
+        _ => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_breaker_client() -> Result<(), String> {
+    db_env_assertion!();
+
+    match connect_to_database().await {
+        Ok(v) => {
+            let client = BreakerClient::new(v)
+                .failure_rate(0.5)
+                .window(2)
+                .cooldown(Duration::from_millis(50));
+            let failing = "select * from consume_breaker_client_missing;";
+
+            for _ in 0..2 {
+                match client.consume::<i32>(failing, &[]).await {
+                    Err(ConsumeError::DatabaseError { .. }) => (),
+                    _ => return Err(String::from("Consumed from missing table")),
+                };
+            }
+
+            assert_eq!(client.state(), CircuitState::Open, "Could not open circuit");
+
+            match client.consume::<i32>("select 1;", &[]).await {
+                Err(ConsumeError::CircuitOpen) => (),
+                _ => return Err(String::from("Could not short-circuit call")),
+            };
+
+            tokio::time::sleep(Duration::from_millis(60)).await;
+
+            match client.consume::<i32>(failing, &[]).await {
+                Err(ConsumeError::DatabaseError { .. }) => (),
+                _ => return Err(String::from("Could not probe database")),
+            };
+
+            assert_eq!(
+                client.state(),
+                CircuitState::Open,
+                "Could not reopen circuit"
+            );
+
+            tokio::time::sleep(Duration::from_millis(60)).await;
+
+            match client.consume::<i32>("select 1;", &[]).await {
+                Ok(result) => {
+                    assert_eq!(result, vec![1], "Could not consume through breaker");
+                    assert_eq!(
+                        client.state(),
+                        CircuitState::Closed,
+                        "Could not close circuit"
+                    );
+                    Ok(())
+                }
+                Err(_) => Err(String::from("Could not probe database")),
+            }
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "throttle")]
+#[tokio::test]
+async fn consume_throttled_client() -> Result<(), String> {
+    db_env_assertion!();
+
+    match (connect_to_database().await, connect_to_database().await) {
+        (Ok(v), Ok(w)) => {
+            let client = pgde::client::ThrottledClient::new(v, 1);
+            let query = "select 1 from pg_sleep(0.1);";
+            let started = std::time::Instant::now();
+
+            match tokio::join!(
+                client.consume::<i32>(query, &[]),
+                client.consume::<i32>(query, &[])
+            ) {
+                (Ok(first), Ok(second)) => {
+                    assert_eq!((first, second), (vec![1], vec![1]), "Could not consume");
+                    assert!(
+                        started.elapsed() >= Duration::from_millis(200),
+                        "Could not cap concurrent calls"
+                    );
+                    assert_eq!(client.available(), 1, "Could not release permits");
+                }
+                _ => return Err(String::from("Could not consume with throttled client")),
+            };
+
+            let client = pgde::client::ThrottledClient::new(w, 8).rate_limit(5.0);
+            let started = std::time::Instant::now();
+
+            for _ in 0..7 {
+                match client.execute("select 1;", &[]).await {
+                    Ok(_) => (),
+                    Err(_) => return Err(String::from("Could not execute with throttled client")),
+                };
+            }
+
+            assert!(
+                started.elapsed() >= Duration::from_millis(350),
+                "Could not limit calls per second"
+            );
+
+            // Rates that are not positive are raised to the lowest rate, so the second
+            // call waits rather than panics.
+            let mut inner = client.into_inner();
+
+            for per_second in [0.0, -1.0, f64::NAN] {
+                let limited = pgde::client::ThrottledClient::new(inner, 1).rate_limit(per_second);
+
+                if limited.execute("select 1;", &[]).await.is_err() {
+                    return Err(String::from("Could not execute with throttled client"));
+                }
+
+                assert!(
+                    tokio::time::timeout(
+                        Duration::from_millis(20),
+                        limited.execute("select 1;", &[])
+                    )
+                    .await
+                    .is_err(),
+                    "Could not limit calls at the lowest rate"
+                );
+
+                inner = limited.into_inner();
+            }
+
+            Ok(())
+        }
+        _ => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "supervisor")]
+#[tokio::test]
+async fn consume_supervised_client() -> Result<(), String> {
+    db_env_assertion!();
+
+    let conn_string = format!(
+        "host={} user={} password={} dbname={}",
+        DATABASE_HOST, DATABASE_USER, DATABASE_PASSWORD, DATABASE_NAME
+    );
+
+    let config = match conn_string.parse::<tokio_postgres::Config>() {
+        Ok(v) => v,
+        Err(v) => return Err(v.to_string()),
+    };
+
+    match (
+        pgde::client::SupervisedClient::connect(config, NoTls).await,
+        connect_to_database().await,
+    ) {
+        (Ok(client), Ok(admin)) => {
+            let client = client.backoff(Duration::from_millis(10), Duration::from_millis(100));
+            let query = "select pg_backend_pid();";
+
+            let first = match client.consume::<i32>(query, &[]).await {
+                Ok(v) => v,
+                Err(_) => return Err(String::from("Could not consume with supervised client")),
+            };
+
+            if admin
+                .execute("select pg_terminate_backend($1, 5000);", &[&first[0]])
+                .await
+                .is_err()
+            {
+                return Err(String::from("Could not terminate connection"));
+            }
+
+            match client.consume::<i32>(query, &[]).await {
+                Ok(second) => {
+                    assert_ne!(first, second, "Could not reconnect");
+                    Ok(())
+                }
+                Err(_) => Err(String::from("Could not consume after reconnecting")),
+            }
+        }
+        _ => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_session_settings() -> Result<(), String> {
+    db_env_assertion!();
+
+    let settings = SessionSettings::new()
+        .statement_timeout(Duration::from_secs(5))
+        .application_name("pgde-test")
+        .search_path(&["pg_catalog", "public"])
+        .time_zone("UTC");
+
+    match connect_to_database().await {
+        Ok(v) => match settings.apply(&v).await {
+            Ok(_) => match String::consume(
+                &v,
+                "select current_setting('statement_timeout') union all
+                select current_setting('application_name') union all
+                select current_setting('search_path') union all
+                select current_setting('TimeZone');",
+                &[],
+            )
+            .await
+            {
+                Ok(result) => {
+                    assert_eq!(
+                        result,
+                        vec!["5s", "pgde-test", "\"pg_catalog\", \"public\"", "UTC"],
+                        "Could not apply session settings"
+                    );
+
+                    Ok(())
+                }
+                Err(_) => Err(String::from("Could not consume session settings")),
+            },
+            Err(_) => Err(String::from("Could not apply session settings")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_with_schema() -> Result<(), String> {
+    db_env_assertion!();
+
+    match connect_to_database().await {
+        Ok(v) => match v
+            .batch_execute(
+                "drop schema if exists consume_with_schema_a cascade;
+                drop schema if exists consume_with_schema_b cascade;
+                create schema consume_with_schema_a;
+                create schema consume_with_schema_b;
+                create table consume_with_schema_a.item (name text);
+                create table consume_with_schema_b.item (name text);
+                insert into consume_with_schema_a.item values ('a');
+                insert into consume_with_schema_b.item values ('b');",
+            )
+            .await
+        {
+            Ok(_) => {
+                for tenant in ["a", "b"] {
+                    let schema = format!("consume_with_schema_{}", tenant);
+
+                    let result: Result<Vec<String>, ConsumeError> =
+                        with_schema(&v, &schema, |scoped| {
+                            Box::pin(async move {
+                                String::consume(scoped, "select name from item;", &[]).await
+                            })
+                        })
+                        .await;
+
+                    match result {
+                        Ok(result) => {
+                            assert_eq!(result, vec![tenant], "Could not scope query to schema")
+                        }
+                        Err(_) => return Err(String::from("Could not consume in schema")),
+                    };
+                }
+
+                let failed: Result<Vec<i32>, ConsumeError> =
+                    with_schema(&v, "consume_with_schema_a", |scoped| {
+                        Box::pin(
+                            async move { i32::consume(scoped, "select null::int;", &[]).await },
+                        )
+                    })
+                    .await;
+
+                assert!(failed.is_err(), "Consumed null into i32 in schema");
+
+                let cancelled = tokio::time::timeout(
+                    Duration::from_millis(20),
+                    with_schema(&v, "consume_with_schema_a", |_| {
+                        Box::pin(async move {
+                            tokio::time::sleep(Duration::from_secs(5)).await;
+                            Ok::<(), ConsumeError>(())
+                        })
+                    }),
+                )
+                .await;
+
+                assert!(cancelled.is_err(), "Could not cancel scoped closure");
+
+                match String::consume(&v, "select current_setting('search_path');", &[]).await {
+                    Ok(result) => {
+                        assert_eq!(
+                            result,
+                            vec!["\"$user\", public"],
+                            "Could not restore search_path"
+                        );
+                        Ok(())
+                    }
+                    Err(_) => Err(String::from("Could not consume search_path")),
+                }
+            }
+            Err(v) => Err(v.to_string()),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_deallocate_all() -> Result<(), String> {
+    db_env_assertion!();
+
+    match connect_to_database().await {
+        Ok(v) => match v.prepare("select 1;").await {
+            Ok(_) => {
+                let count = "select count(*) from pg_prepared_statements
+                    where statement not like '%pg_prepared_statements%';";
+
+                match i64::consume(&v, count, &[]).await {
+                    Ok(result) => assert!(result[0] > 0, "Could not prepare statement"),
+                    Err(_) => return Err(String::from("Could not count prepared statements")),
+                };
+
+                match deallocate_all(&v).await {
+                    Ok(_) => (),
+                    Err(_) => return Err(String::from("Could not deallocate statements")),
+                };
+
+                match i64::consume(&v, count, &[]).await {
+                    Ok(result) => {
+                        assert_eq!(result, vec![0], "Could not deallocate statements");
+                        Ok(())
+                    }
+                    Err(_) => Err(String::from("Could not count prepared statements")),
+                }
+            }
+            Err(v) => Err(v.to_string()),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_borrowed() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    struct Borrowed<'a> {
+        name: &'a str,
+        blob: &'a [u8],
+        missing: Option<&'a str>,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => match v
+            .query(
+                "select 'foo', '\\x0102'::bytea, null::text union all
+                select 'bar', null, 'baz';",
+                &[],
+            )
+            .await
+        {
+            Ok(rows) => {
+                match Borrowed::from_row_borrowed(&rows[0]) {
+                    Ok(result) => {
+                        assert_eq!(result.name, "foo", "Could not borrow text");
+                        assert_eq!(result.blob, &[1, 2], "Could not borrow bytea");
+                        assert_eq!(result.missing, None, "Could not borrow null");
+                    }
+                    Err(_) => return Err(String::from("Could not borrow row")),
+                };
+
+                match Borrowed::from_rows_borrowed(&rows) {
+                    Ok(_) => Err(String::from("Borrowed null into &[u8]")),
+                    Err(result) => {
+                        assert_eq!(result.len(), 2, "Could not borrow rows");
+                        assert_eq!(result[1].name, "bar", "Could not borrow text");
+                        assert_eq!(result[1].missing, Some("baz"), "Could not borrow text");
+
+                        match <&str>::from_row_borrowed(&rows[1]) {
+                            Ok(v) => {
+                                assert_eq!(v, "bar", "Could not borrow &str");
+                                Ok(())
+                            }
+                            Err(_) => Err(String::from("Could not borrow &str")),
+                        }
+                    }
+                }
+            }
+            Err(v) => Err(v.to_string()),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_from_rows_ref() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    struct Known {
+        id: i32,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => match v
+            .query("select 1 as id, 'a' as extra union all select 2, 'b';", &[])
+            .await
+        {
+            Ok(rows) => match Known::from_rows_ref(&rows) {
+                Ok(result) => {
+                    assert_eq!(result[0].id, 1, "Could not consume row by reference");
+                    assert_eq!(result[1].id, 2, "Could not consume row by reference");
+
+                    match rows[1].try_get::<&str, &str>("extra") {
+                        Ok(extra) => {
+                            assert_eq!(extra, "b", "Could not keep rows");
+                            Ok(())
+                        }
+                        Err(v) => Err(v.to_string()),
+                    }
+                }
+                Err(_) => Err(String::from("Could not consume rows by reference")),
+            },
+            Err(v) => Err(v.to_string()),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_column_count_mismatch() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    struct Pair {
+        first: i32,
+        second: i32,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => match Pair::consume(&v, "select 1, 2;", &[]).await {
+            Ok(result) => {
+                assert_eq!(result[0].first, 1, "Could not consume columns into struct");
+                assert_eq!(result[0].second, 2, "Could not consume columns into struct");
+
+                match Pair::consume(&v, "select 1;", &[]).await {
+                    Ok(_) => return Err(String::from("Consumed too few columns into struct")),
+                    Err(ConsumeError::ColumnCountMismatch { expected, actual }) => {
+                        assert_eq!(expected, 2, "Could not report expected column count");
+                        assert_eq!(actual, 1, "Could not report actual column count");
+                    }
+                    Err(_) => return Err(String::from("Could not detect column count mismatch")),
+                };
+
+                let options = ConsumeOptions::new();
+
+                match Pair::consume_with_options(&v, "select 1, 2, 3;", &[], &options).await {
+                    Ok(_) => return Err(String::from("Consumed too many columns into struct")),
+                    Err(ConsumeError::ColumnCountMismatch { .. }) => (),
+                    Err(_) => return Err(String::from("Could not detect column count mismatch")),
+                };
+
+                match v.query_one("select 1;", &[]).await {
+                    Ok(row) => match Pair::from_row(row) {
+                        Ok(_) => Err(String::from("Converted too few columns into struct")),
+                        Err(_) => Ok(()),
+                    },
+                    Err(v) => Err(v.to_string()),
+                }
+            }
+            Err(_) => Err(String::from("Could not consume columns into struct")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_check_types() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    struct Order {
+        id: i32,
+        #[pgde(widen)]
+        amount: i64,
+        note: Option<String>,
+    }
+
+    let options = ConsumeOptions::new().check_types(true);
+
+    match connect_to_database().await {
+        Ok(v) => {
+            match Order::consume_with_options(&v, "select 1, 2::int2, null::text;", &[], &options)
+                .await
+            {
+                Ok(result) => {
+                    assert_eq!(result[0].id, 1, "Could not consume checked columns");
+                    assert_eq!(result[0].amount, 2, "Could not consume checked columns");
+                    assert_eq!(result[0].note, None, "Could not consume checked columns");
+                }
+                Err(_) => return Err(String::from("Could not consume checked columns")),
+            };
+
+            let query = "select 'a', 2::int8, 3 from generate_series(1, 1000);";
+
+            match Order::consume_with_options(&v, query, &[], &options).await {
+                Err(ConsumeError::ColumnTypeMismatch(errors)) => {
+                    assert_eq!(errors.len(), 2, "Could not report every mistyped column");
+                    assert!(
+                        errors[0].contains("\"id\""),
+                        "Could not name the mistyped field"
+                    );
+                }
+                _ => return Err(String::from("Could not detect mistyped columns")),
+            };
+
+            match i32::consume_with_options(&v, "select 'a';", &[], &options).await {
+                Err(ConsumeError::ColumnTypeMismatch(_)) => (),
+                _ => return Err(String::from("Could not detect mistyped column")),
+            };
+
+            match SystemTime::consume_with_options(&v, "select 'a';", &[], &options).await {
+                Err(ConsumeError::ColumnTypeMismatch(_)) => (),
+                _ => return Err(String::from("Could not detect mistyped time column")),
+            };
+
+            match Duration::consume_with_options(&v, "select 'a';", &[], &options).await {
+                Err(ConsumeError::ColumnTypeMismatch(_)) => Ok(()),
+                _ => Err(String::from("Could not detect mistyped interval column")),
+            }
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_map_by_key() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    struct Country {
+        code: String,
+        name: String,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => match Country::consume_map_by_key(
+            &v,
+            "select 'nz', 'New Zealand' union all select 'pe', 'Peru' union all select 'nz', 'Aotearoa';",
+            &[],
+            |v| v.code.clone(),
+        )
+        .await
+        {
+            Ok(result) => {
+                assert_eq!(result.len(), 2, "Could not consume rows into map");
+                assert_eq!(
+                    result.get("pe").map(|v| v.name.as_str()),
+                    Some("Peru"),
+                    "Could not look up row by key"
+                );
+                assert_eq!(
+                    result.get("nz").map(|v| v.name.as_str()),
+                    Some("Aotearoa"),
+                    "Could not keep the last row of a duplicated key"
+                );
+                Ok(())
+            }
+            Err(_) => Err(String::from("Could not consume rows into map")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_dedup_by_key() -> Result<(), String> {
+    db_env_assertion!();
+
+    use pgde::KeepDuplicate;
+
+    #[derive(RowConsumer, Debug, PartialEq)]
+    struct Enrollment {
+        #[pgde(key)]
+        student: i32,
+        #[pgde(key)]
+        course: String,
+        grade: i32,
+    }
+
+    let query = "select 1, 'math', 70 union all select 2, 'math', 80 union all select 1, 'math', 90 union all select 1, 'art', 60;";
+
+    match connect_to_database().await {
+        Ok(v) => match (
+            Enrollment::consume_dedup_by_key(&v, query, &[], KeepDuplicate::First).await,
+            Enrollment::consume_dedup_by_key(&v, query, &[], KeepDuplicate::Last).await,
+        ) {
+            (Ok(first), Ok(last)) => {
+                assert_eq!(
+                    first.iter().map(|v| v.grade).collect::<Vec<i32>>(),
+                    vec![70, 80, 60],
+                    "Could not keep the first row of each key"
+                );
+                assert_eq!(
+                    last.iter().map(|v| v.grade).collect::<Vec<i32>>(),
+                    vec![80, 90, 60],
+                    "Could not keep the last row of each key"
+                );
+                Ok(())
+            }
+            _ => Err(String::from("Could not consume deduplicated rows")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_changed_since() -> Result<(), String> {
+    db_env_assertion!();
+
+    use pgde::RowHash;
+    use std::collections::HashSet;
+
+    #[derive(RowConsumer, Debug, PartialEq)]
+    #[pgde(row_hash)]
+    struct Product {
+        id: i32,
+        name: String,
+        price: Option<f64>,
+    }
+
+    let unchanged = Product {
+        id: 1,
+        name: String::from("lamp"),
+        price: Some(9.5),
+    };
+    let repriced = Product {
+        id: 2,
+        name: String::from("desk"),
+        price: Some(120.0),
+    };
+
+    assert_eq!(
+        unchanged.row_hash(),
+        3451360880824449374,
+        "Could not hash row the same way on every run"
+    );
+
+    let prior_hashes = HashSet::from([
+        unchanged.row_hash(),
+        Product {
+            price: Some(100.0),
+            ..repriced
+        }
+        .row_hash(),
+    ]);
+
+    match connect_to_database().await {
+        Ok(v) => match Product::consume_changed_since(
+            &v,
+            "select 1, 'lamp', 9.5::float8 union all select 2, 'desk', 120::float8 union all select 3, 'sofa', null;",
+            &[],
+            &prior_hashes,
+        )
+        .await
+        {
+            Ok(result) => {
+                assert_eq!(
+                    result,
+                    vec![
+                        Product {
+                            id: 2,
+                            name: String::from("desk"),
+                            price: Some(120.0),
+                        },
+                        Product {
+                            id: 3,
+                            name: String::from("sofa"),
+                            price: None,
+                        },
+                    ],
+                    "Could not leave out unchanged rows"
+                );
+                Ok(())
+            }
+            Err(_) => Err(String::from("Could not consume changed rows")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "indexmap")]
+#[tokio::test]
+async fn consume_index_map_by_key() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    struct MenuItem {
+        slug: String,
+        label: String,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => {
+            let query = "select 'home', 'Home' union all select 'about', 'About' union all select 'home', 'Start';";
+
+            match MenuItem::consume_index_map_by_key(&v, query, &[], |v| v.slug.clone()).await {
+                Ok(result) => {
+                    let labels: Vec<&str> = result.values().map(|v| v.label.as_str()).collect();
+                    assert_eq!(
+                        labels,
+                        vec!["Start", "About"],
+                        "Could not keep row order in IndexMap"
+                    );
+                }
+                Err(_) => return Err(String::from("Could not consume rows into IndexMap")),
+            };
+
+            match i32::consume_index_set_by_key(&v, "select unnest(array[3, 1, 3, 2]);", &[], |v| {
+                *v
+            })
+            .await
+            {
+                Ok(result) => {
+                    assert_eq!(
+                        result.into_iter().collect::<Vec<i32>>(),
+                        vec![3, 1, 2],
+                        "Could not keep row order in IndexSet"
+                    );
+                    Ok(())
+                }
+                Err(_) => Err(String::from("Could not consume rows into IndexSet")),
+            }
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_query_params() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    struct Product {
+        id: i32,
+        name: String,
+        price: i32,
+    }
+
+    #[derive(Default, QueryParams)]
+    struct ProductSearch {
+        #[pgde(op = "ilike")]
+        name: Option<String>,
+        #[pgde(column = "price", op = ">=")]
+        min_price: Option<i32>,
+        #[pgde(column = "id", op = "any")]
+        ids: Option<Vec<i32>>,
+    }
+
+    let query = "select * from (values (1, 'pen', 2), (2, 'lamp', 40), (3, 'Pencil', 3)) as p(id, name, price) order by id;";
+
+    match connect_to_database().await {
+        Ok(v) => {
+            let search = ProductSearch {
+                name: Some(String::from("pen%")),
+                min_price: Some(3),
+                ..Default::default()
+            };
+
+            assert_eq!(
+                search.where_clause().0,
+                "where \"name\" ilike $1 and \"price\" >= $2",
+                "Could not build where clause from set filters"
+            );
+
+            match search.consume_matching::<Product>(&v, query).await {
+                Ok(result) => {
+                    assert_eq!(result.len(), 1, "Could not filter rows");
+                    assert_eq!(result[0].id, 3, "Could not filter rows");
+                    assert_eq!(result[0].name, "Pencil", "Could not filter rows");
+                    assert_eq!(result[0].price, 3, "Could not filter rows");
+                }
+                Err(_) => return Err(String::from("Could not consume filtered rows")),
+            };
+
+            let search = ProductSearch {
+                ids: Some(vec![1, 2]),
+                ..Default::default()
+            };
+
+            match search.consume_matching::<Product>(&v, query).await {
+                Ok(result) => assert_eq!(result.len(), 2, "Could not filter rows by any"),
+                Err(_) => return Err(String::from("Could not consume filtered rows")),
+            };
+
+            match ProductSearch::default()
+                .consume_matching::<Product>(&v, query)
+                .await
+            {
+                Ok(result) => {
+                    assert_eq!(result.len(), 3, "Could not consume unfiltered rows");
+                    Ok(())
+                }
+                Err(_) => Err(String::from("Could not consume unfiltered rows")),
+            }
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_page() -> Result<(), String> {
+    db_env_assertion!();
+
+    match connect_to_database().await {
+        Ok(v) => {
+            let query = "select generate_series(1, $1) order by 1;";
+
+            match i32::consume_page(&v, query, &[&25i32], Page::new(3, 10).with_count()).await {
+                Ok(result) => {
+                    assert_eq!(
+                        result.items,
+                        vec![21, 22, 23, 24, 25],
+                        "Could not consume last page"
+                    );
+                    assert_eq!(result.total, Some(25), "Could not count rows of query");
+                    assert_eq!(result.pages(), Some(3), "Could not count pages of query");
+                }
+                Err(_) => return Err(String::from("Could not consume page")),
+            };
+
+            match i32::consume_page(&v, query, &[&25i32], Page::new(1, 2)).await {
+                Ok(result) => {
+                    assert_eq!(result.items, vec![1, 2], "Could not consume first page");
+                    assert_eq!(result.total, None, "Could not skip counting rows of query");
+                    Ok(())
+                }
+                Err(_) => Err(String::from("Could not consume page")),
+            }
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_incremental() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer, Debug, PartialEq)]
+    struct Order {
+        id: i32,
+        updated_at: Option<SystemTime>,
+    }
+
+    let at = |seconds: u64| SystemTime::UNIX_EPOCH + Duration::from_secs(seconds);
+
+    match connect_to_database().await {
+        Ok(v) => {
+            match v
+                .batch_execute(
+                    "drop table if exists consume_incremental;
+                    create table consume_incremental (id int4, updated_at timestamp);
+                    insert into consume_incremental values
+                        (1, to_timestamp(10) at time zone 'UTC'),
+                        (2, to_timestamp(20) at time zone 'UTC'),
+                        (3, to_timestamp(20) at time zone 'UTC'),
+                        (4, null);",
+                )
+                .await
+            {
+                Ok(_) => (),
+                Err(_) => return Err(String::from("Could not create table")),
+            };
+
+            let query = "select id, updated_at from consume_incremental where id > $1;";
+
+            let first =
+                match Order::consume_incremental(&v, query, &[&0i32], "updated_at", None).await {
+                    Ok(result) => result,
+                    Err(_) => return Err(String::from("Could not consume incremental rows")),
+                };
+
+            assert_eq!(
+                first.items.iter().map(|v| v.id).collect::<Vec<i32>>(),
+                vec![1, 2, 3],
+                "Could not consume rows with a cursor"
+            );
+            assert_eq!(
+                first.cursor,
+                Some(at(20)),
+                "Could not take the high-water mark"
+            );
+
+            match v
+                .batch_execute(
+                    "insert into consume_incremental values (5, to_timestamp(30) at time zone 'UTC');",
+                )
+                .await
+            {
+                Ok(_) => (),
+                Err(_) => return Err(String::from("Could not insert row")),
+            };
+
+            let second =
+                match Order::consume_incremental(&v, query, &[&0i32], "updated_at", first.cursor)
+                    .await
+                {
+                    Ok(result) => result,
+                    Err(_) => return Err(String::from("Could not consume incremental rows")),
+                };
+
+            assert_eq!(
+                second.items,
+                vec![Order {
+                    id: 5,
+                    updated_at: Some(at(30)),
+                }],
+                "Could not consume rows past the high-water mark"
+            );
+
+            match Order::consume_incremental(&v, query, &[&0i32], "updated_at", second.cursor).await
+            {
+                Ok(result) => {
+                    assert!(result.items.is_empty(), "Could not skip consumed rows");
+                    assert_eq!(
+                        result.cursor,
+                        Some(at(30)),
+                        "Could not keep the high-water mark"
+                    );
+                    Ok(())
+                }
+                Err(_) => Err(String::from("Could not consume incremental rows")),
+            }
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_mapping_signature() -> Result<(), String> {
+    db_env_assertion!();
+
+    mod blue {
+        use pgde_derive::RowConsumer;
+        use tokio_postgres::Row;
+
+        #[derive(RowConsumer)]
+        pub struct Account {
+            pub id: i32,
+            pub name: String,
+        }
+    }
+
+    mod green {
+        use pgde_derive::RowConsumer;
+        use tokio_postgres::Row;
+
+        #[derive(RowConsumer)]
+        pub struct Account {
+            pub id: i64,
+            pub name: String,
+        }
+    }
+
+    let blue = blue::Account::mapping_signature();
+    let green = green::Account::mapping_signature();
+
+    assert_eq!(blue.name, "Account", "Could not name signature");
+    assert_eq!(
+        blue.fields,
+        &[("id", "i32"), ("name", "String")],
+        "Could not list fields of signature"
+    );
+    assert_ne!(blue.hash, green.hash, "Could not tell field types apart");
+    assert_eq!(
+        blue,
+        blue::Account::mapping_signature(),
+        "Could not compute a stable signature"
+    );
+
+    match connect_to_database().await {
+        Ok(v) => {
+            match v
+                .batch_execute("delete from pgde_mapping_signatures where name = 'Account';")
+                .await
+            {
+                Ok(_) | Err(_) => (),
+            };
+
+            match (
+                blue::Account::consume(&v, "select 1, 'blue';", &[]).await,
+                green::Account::consume(&v, "select 1::int8, 'green';", &[]).await,
+            ) {
+                (Ok(b), Ok(g)) => {
+                    assert_eq!(
+                        (b[0].id, b[0].name.as_str()),
+                        (1, "blue"),
+                        "Could not consume"
+                    );
+                    assert_eq!(
+                        (g[0].id, g[0].name.as_str()),
+                        (1, "green"),
+                        "Could not consume"
+                    );
+                }
+                _ => return Err(String::from("Could not consume accounts")),
+            };
+
+            match blue.verify(&v).await {
+                Err(ConsumeError::SignatureMismatch { stored: None, .. }) => (),
+                _ => return Err(String::from("Verified signature that is not stored")),
+            };
+
+            match blue.store(&v).await {
+                Ok(_) => (),
+                Err(_) => return Err(String::from("Could not store signature")),
+            };
+
+            match blue.verify(&v).await {
+                Ok(_) => (),
+                Err(_) => return Err(String::from("Could not verify stored signature")),
+            };
+
+            match green.verify(&v).await {
+                Err(ConsumeError::SignatureMismatch {
+                    name,
+                    stored,
+                    actual,
+                }) => {
+                    assert_eq!(name, "Account", "Could not name mismatched signature");
+                    assert_eq!(stored, Some(blue.hash), "Could not read stored signature");
+                    assert_eq!(actual, green.hash, "Could not report actual signature");
+                    Ok(())
+                }
+                _ => Err(String::from("Verified mismatched signature")),
+            }
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "summary")]
+#[tokio::test]
+async fn consume_summary() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    #[pgde(summary)]
+    struct Customer {
+        id: i32,
+        #[pgde(sensitive)]
+        email: String,
+        notes: Option<String>,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => match Customer::consume(
+            &v,
+            "select 7, 'someone@example.com', repeat('a', 100) union all
+            select 8, 'someone.else@example.com', null;",
+            &[],
+        )
+        .await
+        {
+            Ok(result) => {
+                let summaries: Vec<String> = result.iter().map(|v| v.summary()).collect();
+                assert_eq!(
+                    summaries,
+                    vec![
+                        format!(
+                            "Customer {{ id: 7, email: [redacted], notes: Some(\"{}...",
+                            "a".repeat(58)
+                        ) + " }",
+                        String::from("Customer { id: 8, email: [redacted], notes: None }"),
+                    ],
+                    "Could not summarize rows"
+                );
+                assert_eq!(
+                    result[1].email, "someone.else@example.com",
+                    "Could not consume rows"
+                );
+                assert_eq!(result[1].notes, None, "Could not consume rows");
+                Ok(())
+            }
+            Err(_) => Err(String::from("Could not consume rows")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_mirror_table() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer, Debug, PartialEq)]
+    #[pgde(table = "consume_mirror_table_src")]
+    struct Fixture {
+        id: i32,
+        label: Option<String>,
+    }
+
+    match (connect_to_database().await, connect_to_database().await) {
+        (Ok(src), Ok(dst)) => match src
+            .batch_execute(
+                "drop table if exists consume_mirror_table_src;
+                drop table if exists consume_mirror_table_dst;
+                create table consume_mirror_table_src (
+                    id int,
+                    label text,
+                    ignored text
+                );
+                create table consume_mirror_table_dst (
+                    id int,
+                    label text
+                );
+                insert into consume_mirror_table_src values (1, 'a', 'x'), (2, null, 'y'), (3, 'c', 'z');
+                insert into consume_mirror_table_dst values (9, 'stale');",
+            )
+            .await
+        {
+            Ok(_) => {
+                let options = pgde::MirrorOptions::new()
+                    .truncate(true)
+                    .filter("id < 3")
+                    .destination("consume_mirror_table_dst");
+
+                match pgde::mirror_table::<Fixture>(
+                    &src,
+                    &dst,
+                    "consume_mirror_table_src",
+                    options,
+                )
+                .await
+                {
+                    Ok(v) => assert_eq!(v, 2, "Could not mirror rows"),
+                    Err(_) => return Err(String::from("Could not mirror table")),
+                };
+
+                match Fixture::consume(&dst, "select * from consume_mirror_table_dst order by id;", &[])
+                    .await
+                {
+                    Ok(v) => assert_eq!(
+                        v,
+                        vec![
+                            Fixture {
+                                id: 1,
+                                label: Some(String::from("a")),
+                            },
+                            Fixture { id: 2, label: None },
+                        ],
+                        "Could not read mirrored rows"
+                    ),
+                    Err(_) => return Err(String::from("Could not read mirrored rows")),
+                };
+
+                assert!(
+                    pgde::mirror_table::<Fixture>(
+                        &src,
+                        &dst,
+                        "consume_mirror_table_missing",
+                        pgde::MirrorOptions::new().destination("consume_mirror_table_dst"),
+                    )
+                    .await
+                    .is_err(),
+                    "Could not fail mirroring missing table"
+                );
+
+                match pgde::query_exists(&dst, "select 1 from consume_mirror_table_dst where id = 2;", &[])
+                    .await
+                {
+                    Ok(v) => assert!(v, "Could not keep rows after failed mirror"),
+                    Err(_) => return Err(String::from("Could not query mirrored rows")),
+                };
+                Ok(())
+            }
+            Err(v) => Err(v.to_string()),
+        },
+        _ => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_temp_table() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer, Debug, PartialEq)]
+    #[pgde(table = "consume_temp_table")]
+    struct Order {
+        id: i32,
+        customer: String,
+    }
+
+    match connect_to_database().await {
+        Ok(mut v) => match v
+            .batch_execute(
+                "drop table if exists consume_temp_table;
+                create table consume_temp_table (
+                    id int,
+                    customer text
+                );
+                insert into consume_temp_table values (1, 'a'), (2, 'b'), (3, 'c');",
+            )
+            .await
+        {
+            Ok(_) => {
+                let txn = match v.transaction().await {
+                    Ok(v) => v,
+                    Err(_) => return Err(String::from("Could not start transaction")),
+                };
+
+                let temp = match pgde::TempTable::<Order>::create(&txn).await {
+                    Ok(v) => v,
+                    Err(_) => return Err(String::from("Could not create temporary table")),
+                };
+
+                let rows: [&[&(dyn ToSql + Sync)]; 2] = [&[&1, &"x"], &[&3, &"y"]];
+
+                match temp.load(&txn, rows).await {
+                    Ok(v) => assert_eq!(v, 2, "Could not load temporary table"),
+                    Err(_) => return Err(String::from("Could not load temporary table")),
+                };
+
+                let bad_row: [&[&(dyn ToSql + Sync)]; 1] = [&[&4]];
+
+                match temp.load(&txn, bad_row).await {
+                    Err(ConsumeError::ColumnCountMismatch { expected, actual }) => {
+                        assert_eq!((expected, actual), (2, 1), "Could not check row length")
+                    }
+                    _ => return Err(String::from("Could not reject short row")),
+                };
+
+                match temp.consume(&txn).await {
+                    Ok(v) => assert_eq!(
+                        v,
+                        vec![
+                            Order {
+                                id: 1,
+                                customer: String::from("x"),
+                            },
+                            Order {
+                                id: 3,
+                                customer: String::from("y"),
+                            },
+                        ],
+                        "Could not consume temporary table"
+                    ),
+                    Err(_) => return Err(String::from("Could not consume temporary table")),
+                };
+
+                let query = format!(
+                    "select o.* from consume_temp_table as o join {} as t using (id) order by id;",
+                    temp.name()
+                );
+
+                match Order::consume(txn.client(), &query, &[]).await {
+                    Ok(v) => assert_eq!(
+                        v.iter().map(|v| v.customer.as_str()).collect::<Vec<_>>(),
+                        vec!["a", "c"],
+                        "Could not join temporary table"
+                    ),
+                    Err(_) => return Err(String::from("Could not join temporary table")),
+                };
+
+                match txn.commit().await {
+                    Ok(_) => Ok(()),
+                    Err(_) => Err(String::from("Could not commit transaction")),
+                }
+            }
+            Err(v) => Err(v.to_string()),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "dev_guard")]
+#[tokio::test]
+async fn consume_dev_guard() -> Result<(), String> {
+    db_env_assertion!();
+
+    match connect_to_database().await {
+        Ok(v) => {
+            let repeats = Arc::new(Mutex::new(Vec::new()));
+            let recorded = repeats.clone();
+
+            let guard = pgde::DevGuard::new(3).on_repeat(move |query, count| {
+                if let Ok(mut v) = recorded.lock() {
+                    v.push((String::from(query), count));
+                }
+            });
+
+            let result = guard
+                .scope(async {
+                    for id in 0..6 {
+                        if i32::consume(&v, "select $1::int;", &[&id]).await.is_err() {
+                            return Err(String::from("Could not consume row"));
+                        }
+                    }
+
+                    match pgde::consume_scalar::<i32>(&v, "select   1;", &[]).await {
+                        Ok(_) => Ok(()),
+                        Err(_) => Err(String::from("Could not consume scalar")),
+                    }
+                })
+                .await;
+
+            result?;
+
+            for id in 0..6 {
+                if i32::consume(&v, "select $1::int;", &[&id]).await.is_err() {
+                    return Err(String::from("Could not consume row"));
+                }
+            }
+
+            match repeats.lock() {
+                Ok(v) => assert_eq!(
+                    v.as_slice(),
+                    &[(String::from("select $1::int;"), 4)],
+                    "Could not detect repeated query"
+                ),
+                Err(_) => return Err(String::from("Could not read repeated queries")),
+            };
+
+            Ok(())
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_error_classification() -> Result<(), String> {
+    db_env_assertion!();
+
+    match connect_to_database().await {
+        Ok(v) => match v
+            .batch_execute(
+                "drop table if exists consume_error_classification_child;
+                drop table if exists consume_error_classification;
+                create table consume_error_classification (
+                    id int primary key
+                );
+                create table consume_error_classification_child (
+                    parent int references consume_error_classification (id)
+                );
+                insert into consume_error_classification values (1);",
+            )
+            .await
+        {
+            Ok(_) => {
+                let cases = [
+                    (
+                        "insert into consume_error_classification values (1);",
+                        ErrorClass::UniqueViolation,
+                    ),
+                    (
+                        "insert into consume_error_classification_child values (2);",
+                        ErrorClass::ForeignKeyViolation,
+                    ),
+                    (
+                        "set local statement_timeout = 1; select pg_sleep(1);",
+                        ErrorClass::Timeout,
+                    ),
+                    (
+                        "select * from consume_error_classification_missing;",
+                        ErrorClass::Other,
+                    ),
+                ];
+
+                for (query, class) in cases {
+                    let result = match query.starts_with("set local") {
+                        true => v.batch_execute(&format!("begin; {} commit;", query)).await,
+                        false => v.batch_execute(query).await,
+                    };
+
+                    let _ = v.batch_execute("rollback;").await;
+
+                    match result {
+                        Ok(_) => return Err(format!("Could not fail query: {}", query)),
+                        Err(e) => assert_eq!(
+                            ConsumeError::from(e).classification(),
+                            class,
+                            "Could not classify error of query: {}",
+                            query
+                        ),
+                    };
+                }
+
+                match pgde::execute(
+                    &v,
+                    "insert into consume_error_classification values (1);",
+                    &[],
+                )
+                .await
+                {
+                    Err(ConsumeError::DatabaseError { code, .. }) => {
+                        assert_eq!(code, "23505", "Could not report SQLSTATE")
+                    }
+                    _ => return Err(String::from("Could not fail insert")),
+                };
+
+                assert_eq!(
+                    ConsumeError::DatabaseConnectionError.classification(),
+                    ErrorClass::ConnectionLost,
+                    "Could not classify connection error"
+                );
+                assert_eq!(
+                    ConsumeError::ConversionError.classification(),
+                    ErrorClass::Other,
+                    "Could not classify conversion error"
+                );
+                assert_eq!(
+                    ErrorClass::from_code("40P01"),
+                    ErrorClass::SerializationFailure,
+                    "Could not classify deadlock"
+                );
+                Ok(())
+            }
+            Err(v) => Err(v.to_string()),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "queue")]
+#[tokio::test]
+async fn consume_queue() -> Result<(), String> {
+    use pgde::queue::{BatchReport, Worker};
+
+    #[derive(RowConsumer)]
+    #[pgde(table = "consume_queue")]
+    struct Job {
+        name: String,
+    }
+
+    db_env_assertion!();
+
+    match connect_to_database().await {
+        Ok(mut v) => match v
+            .batch_execute(
+                "drop table if exists consume_queue; create table consume_queue (id integer generated always as identity primary key, name text not null, status text not null default 'pending', attempts integer not null default 0); insert into consume_queue (name) values ('ok'), ('flaky'), ('broken');",
+            )
+            .await
+        {
+            Ok(_) => {
+                let worker = Worker::<Job>::new().batch_size(2).max_attempts(2);
+                let handled = Arc::new(Mutex::new(Vec::new()));
+
+                let handler = |job: Job| {
+                    let handled = handled.clone();
+
+                    async move {
+                        if let Ok(mut v) = handled.lock() {
+                            v.push(job.name.clone());
+                        }
+
+                        match job.name.as_str() {
+                            "ok" => Ok(()),
+                            _ => Err(String::from("failed")),
+                        }
+                    }
+                };
+
+                let expected = [
+                    BatchReport {
+                        completed: 1,
+                        retried: 1,
+                        failed: 0,
+                    },
+                    BatchReport {
+                        completed: 0,
+                        retried: 1,
+                        failed: 1,
+                    },
+                    BatchReport {
+                        completed: 0,
+                        retried: 0,
+                        failed: 1,
+                    },
+                    BatchReport::default(),
+                ];
+
+                for report in expected {
+                    match worker.run_once(&mut v, &handler).await {
+                        Ok(v) => assert_eq!(v, report, "Could not run batch of jobs"),
+                        Err(_) => return Err(String::from("Could not run batch of jobs")),
+                    };
+                }
+
+                match handled.lock() {
+                    Ok(v) => assert_eq!(
+                        v.as_slice(),
+                        &["ok", "flaky", "flaky", "broken", "broken"],
+                        "Could not hand jobs to handler in order"
+                    ),
+                    Err(_) => return Err(String::from("Could not read handled jobs")),
+                };
+
+                match v
+                    .query(
+                        "select status, attempts from consume_queue order by id;",
+                        &[],
+                    )
+                    .await
+                {
+                    Ok(rows) => assert_eq!(
+                        rows.iter()
+                            .map(|r| (r.get::<usize, String>(0), r.get::<usize, i32>(1)))
+                            .collect::<Vec<_>>(),
+                        vec![
+                            (String::from("done"), 0),
+                            (String::from("failed"), 2),
+                            (String::from("failed"), 2),
+                        ],
+                        "Could not record job outcomes"
+                    ),
+                    Err(_) => return Err(String::from("Could not query jobs")),
+                };
+
+                Ok(())
+            }
+            Err(_) => Err(String::from("Could not create test table")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "election")]
+#[tokio::test]
+async fn consume_election() -> Result<(), String> {
+    use pgde::election::Leader;
+
+    db_env_assertion!();
+
+    match (connect_to_database().await, connect_to_database().await) {
+        (Ok(v), Ok(other)) => {
+            // A negative key spans both halves of the lock's classid and objid.
+            let key = -4204i64;
+            let changes = Arc::new(Mutex::new(Vec::new()));
+            let recorded = changes.clone();
+
+            let first = match Leader::acquire(Arc::new(v), key).await {
+                Ok(v) => v,
+                Err(_) => return Err(String::from("Could not contend for leadership")),
+            };
+
+            let second = match Leader::acquire(Arc::new(other), key).await {
+                Ok(v) => v.on_change(move |leading| {
+                    if let Ok(mut v) = recorded.lock() {
+                        v.push(leading);
+                    }
+                }),
+                Err(_) => return Err(String::from("Could not contend for leadership")),
+            };
+
+            assert!(first.is_leader(), "Could not take leadership");
+            assert!(!second.is_leader(), "Could not exclude follower");
+
+            match (first.heartbeat().await, second.heartbeat().await) {
+                (Ok(a), Ok(b)) => assert_eq!((a, b), (true, false), "Could not keep leadership"),
+                _ => return Err(String::from("Could not send heartbeats")),
+            };
+
+            if first.resign().await.is_err() {
+                return Err(String::from("Could not resign leadership"));
+            }
+
+            match (second.heartbeat().await, second.heartbeat().await) {
+                (Ok(a), Ok(b)) => assert!(a && b, "Could not take over leadership"),
+                _ => return Err(String::from("Could not send heartbeats")),
+            };
+
+            match first.heartbeat().await {
+                Ok(v) => assert!(!v, "Could not exclude resigned leader"),
+                Err(_) => return Err(String::from("Could not send heartbeat")),
+            };
+
+            if second.resign().await.is_err() {
+                return Err(String::from("Could not resign leadership"));
+            }
+
+            match changes.lock() {
+                Ok(v) => assert_eq!(
+                    v.as_slice(),
+                    &[true, false],
+                    "Could not notify leadership changes"
+                ),
+                Err(_) => return Err(String::from("Could not read leadership changes")),
+            };
+
+            Ok(())
+        }
+        _ => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "election")]
+#[tokio::test]
+async fn consume_election_failed_heartbeat() -> Result<(), String> {
+    use pgde::election::Leader;
+
+    db_env_assertion!();
+
+    match (connect_to_database().await, connect_to_database().await) {
+        (Ok(v), Ok(other)) => {
+            let key = 4205i64;
+            let client = Arc::new(v);
+
+            let leader = match Leader::acquire(client.clone(), key).await {
+                Ok(v) => v,
+                Err(_) => return Err(String::from("Could not contend for leadership")),
+            };
+
+            assert!(leader.is_leader(), "Could not take leadership");
+
+            // An aborted transaction fails the heartbeat while the session keeps the lock.
+            if client.batch_execute("begin;").await.is_err() {
+                return Err(String::from("Could not begin transaction"));
+            }
+
+            if client.batch_execute("select 1 / 0;").await.is_ok() {
+                return Err(String::from("Could not abort transaction"));
+            }
+
+            assert!(
+                leader.heartbeat().await.is_err(),
+                "Could not fail heartbeat"
+            );
+            assert!(!leader.is_leader(), "Could not lose leadership");
+
+            if client.batch_execute("rollback;").await.is_err() {
+                return Err(String::from("Could not roll back transaction"));
+            }
+
+            match leader.heartbeat().await {
+                Ok(v) => assert!(v, "Could not regain leadership"),
+                Err(_) => return Err(String::from("Could not send heartbeat")),
+            };
+
+            if leader.resign().await.is_err() {
+                return Err(String::from("Could not resign leadership"));
+            }
+
+            match other
+                .query_one("select pg_try_advisory_lock($1);", &[&key])
+                .await
+            {
+                Ok(v) => match v.try_get::<usize, bool>(0) {
+                    Ok(v) => assert!(v, "Could not release lock on resign"),
+                    Err(_) => return Err(String::from("Could not read lock result")),
+                },
+                Err(_) => return Err(String::from("Could not take lock")),
+            };
+
+            Ok(())
+        }
+        _ => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_migrate() -> Result<(), String> {
+    use pgde::migrate::{self, Migration};
+
+    db_env_assertion!();
+
+    let migrations = [
+        Migration {
+            version: 2,
+            name: "seed",
+            sql: "insert into consume_migrate values (1);",
+        },
+        Migration {
+            version: 1,
+            name: "create",
+            sql: "create table consume_migrate (id bigint primary key);",
+        },
+    ];
+
+    let failing = [
+        migrations[0],
+        migrations[1],
+        Migration {
+            version: 3,
+            name: "broken",
+            sql: "insert into consume_migrate values (1);",
+        },
+        Migration {
+            version: 4,
+            name: "later",
+            sql: "insert into consume_migrate values (4);",
+        },
+    ];
+
+    match connect_to_database().await {
+        Ok(v) => match v
+            .batch_execute(
+                "drop table if exists pgde_schema_version; drop table if exists consume_migrate;",
+            )
+            .await
+        {
+            Ok(_) => {
+                match migrate::plan(&v, &migrations).await {
+                    Ok(plan) => {
+                        assert!(plan.applied.is_empty(), "Could not plan fresh database");
+                        assert_eq!(
+                            plan.pending.iter().map(|m| m.version).collect::<Vec<_>>(),
+                            vec![1, 2],
+                            "Could not order pending migrations"
+                        );
+                    }
+                    Err(_) => return Err(String::from("Could not plan migrations")),
+                };
+
+                match migrate::run(&v, &migrations).await {
+                    Ok(plan) => assert_eq!(plan.pending.len(), 2, "Could not apply migrations"),
+                    Err(_) => return Err(String::from("Could not run migrations")),
+                };
+
+                match migrate::run(&v, &migrations).await {
+                    Ok(plan) => {
+                        assert!(plan.is_current(), "Could not skip applied migrations");
+                        assert_eq!(
+                            plan.applied
+                                .iter()
+                                .map(|m| (m.version, m.name.as_str()))
+                                .collect::<Vec<_>>(),
+                            vec![(1, "create"), (2, "seed")],
+                            "Could not record applied migrations"
+                        );
+                    }
+                    Err(_) => return Err(String::from("Could not run migrations")),
+                };
+
+                match migrate::run(&v, &failing).await {
+                    Err(ConsumeError::DatabaseError { code, .. }) => {
+                        assert_eq!(code, "23505", "Could not report failed migration")
+                    }
+                    _ => return Err(String::from("Could not fail migration")),
+                };
+
+                match migrate::plan(&v, &failing).await {
+                    Ok(plan) => assert_eq!(
+                        plan.pending.iter().map(|m| m.version).collect::<Vec<_>>(),
+                        vec![3, 4],
+                        "Could not stop at failed migration"
+                    ),
+                    Err(_) => return Err(String::from("Could not plan migrations")),
+                };
+
+                match migrate::plan(&v, &[migrations[0], migrations[0]]).await {
+                    Err(ConsumeError::InvalidQuery(_)) => (),
+                    _ => return Err(String::from("Could not reject duplicate versions")),
+                };
+
+                Ok(())
+            }
+            Err(_) => Err(String::from("Could not drop test tables")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "seed")]
+#[tokio::test]
+async fn consume_seed() -> Result<(), String> {
+    use pgde::seed::{self, Format};
+
+    #[derive(serde::Serialize, serde::Deserialize, RowConsumer, Debug, PartialEq)]
+    struct Account {
+        id: i64,
+        email: String,
+        note: Option<String>,
+    }
+
+    db_env_assertion!();
+
+    let dir = std::env::temp_dir().join(format!("pgde_seed_{}", std::process::id()));
+    let json = dir.join("accounts.json");
+    let csv = dir.join("accounts.csv");
+    let invalid = dir.join("invalid.json");
+
+    let written = std::fs::create_dir_all(&dir)
+        .and_then(|_| {
+            std::fs::write(
+                &json,
+                r#"[{"id": 1, "email": "a@example.com", "note": "tab\there\\"}]"#,
+            )
+        })
+        .and_then(|_| {
+            std::fs::write(
+                &csv,
+                "id,email,note\n2,b@example.com,\n3,c@example.com,\"line\nbreak\"\n",
+            )
+        })
+        .and_then(|_| {
+            std::fs::write(
+                &invalid,
+                r#"[{"id": "x", "email": "d@example.com", "note": null}, {"id": 5, "email": 5, "note": null}]"#,
+            )
+        });
+
+    if written.is_err() {
+        return Err(String::from("Could not write fixtures"));
+    }
+
+    match connect_to_database().await {
+        Ok(v) => match v
+            .batch_execute(
+                "drop table if exists consume_seed; create table consume_seed (id bigint primary key, email text not null, note text);",
+            )
+            .await
+        {
+            Ok(_) => {
+                match seed::load::<Account>(&v, "consume_seed", Format::Json(&json)).await {
+                    Ok(v) => assert_eq!(v, 1, "Could not seed from JSON"),
+                    Err(_) => return Err(String::from("Could not seed from JSON")),
+                };
+
+                match seed::load::<Account>(&v, "consume_seed", Format::Csv(&csv)).await {
+                    Ok(v) => assert_eq!(v, 2, "Could not seed from CSV"),
+                    Err(_) => return Err(String::from("Could not seed from CSV")),
+                };
+
+                match seed::load::<Account>(&v, "consume_seed", Format::Json(&invalid)).await {
+                    Err(ConsumeError::ConversionErrors(v)) => {
+                        assert_eq!(v.len(), 2, "Could not report invalid records")
+                    }
+                    _ => return Err(String::from("Could not reject invalid records")),
+                };
+
+                match seed::load::<Account>(&v, "consume_seed", Format::Csv(&dir.join("missing.csv")))
+                    .await
+                {
+                    Err(ConsumeError::ReadError(_)) => (),
+                    _ => return Err(String::from("Could not report missing fixture")),
+                };
+
+                let _ = std::fs::remove_dir_all(&dir);
+
+                match Account::consume(
+                    &v,
+                    "select id, email, note from consume_seed order by id;",
+                    &[],
+                )
+                .await
+                {
+                    Ok(v) => assert_eq!(
+                        v,
+                        vec![
+                            Account {
+                                id: 1,
+                                email: String::from("a@example.com"),
+                                note: Some(String::from("tab\there\\")),
+                            },
+                            Account {
+                                id: 2,
+                                email: String::from("b@example.com"),
+                                note: None,
+                            },
+                            Account {
+                                id: 3,
+                                email: String::from("c@example.com"),
+                                note: Some(String::from("line\nbreak")),
+                            },
+                        ],
+                        "Could not seed rows"
+                    ),
+                    Err(_) => return Err(String::from("Could not consume seeded rows")),
+                };
+
+                Ok(())
+            }
+            Err(_) => Err(String::from("Could not create test table")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_outbox() -> Result<(), String> {
+    use pgde::outbox::Outbox;
+
+    db_env_assertion!();
+
+    match (connect_to_database().await, connect_to_database().await) {
+        (Ok(mut v), Ok(mut other)) => match v
+            .batch_execute("drop table if exists consume_outbox;")
+            .await
+        {
+            Ok(_) => {
+                let outbox = Outbox::new("consume_outbox");
+
+                if outbox.create_table(&v).await.is_err() {
+                    return Err(String::from("Could not create outbox"));
+                }
+
+                match v.transaction().await {
+                    Ok(txn) => {
+                        for topic in ["a", "b", "c"] {
+                            if outbox.enqueue(txn.client(), topic, "{}").await.is_err() {
+                                return Err(String::from("Could not enqueue message"));
+                            }
+                        }
+
+                        if txn.commit().await.is_err() {
+                            return Err(String::from("Could not commit messages"));
+                        }
+                    }
+                    Err(_) => return Err(String::from("Could not start transaction")),
+                };
+
+                let result = outbox
+                    .process(&mut v, 10, |_, _| {
+                        Box::pin(async move { Err(ConsumeError::WriteError(String::from("down"))) })
+                    })
+                    .await;
+
+                assert!(result.is_err(), "Could not fail relay");
+
+                let claimed = match other.transaction().await {
+                    Ok(v) => v,
+                    Err(_) => return Err(String::from("Could not start transaction")),
+                };
+
+                match claimed.client().query(&outbox.poll_query(), &[&1i64]).await {
+                    Ok(v) => assert_eq!(v.len(), 1, "Could not claim message"),
+                    Err(_) => return Err(String::from("Could not claim message")),
+                };
+
+                let relayed = Arc::new(Mutex::new(Vec::new()));
+                let recorded = relayed.clone();
+
+                let result = outbox
+                    .process(&mut v, 10, move |_, messages| {
+                        Box::pin(async move {
+                            if let Ok(mut v) = recorded.lock() {
+                                v.extend(
+                                    messages
+                                        .iter()
+                                        .map(|m| (m.topic.clone(), m.payload.clone())),
+                                );
+                            }
+
+                            Ok::<(), ConsumeError>(())
+                        })
+                    })
+                    .await;
+
+                match result {
+                    Ok(v) => assert_eq!(v, 2, "Could not skip locked message"),
+                    Err(_) => return Err(String::from("Could not relay messages")),
+                };
+
+                if claimed.rollback().await.is_err() {
+                    return Err(String::from("Could not release claimed message"));
+                }
+
+                match outbox
+                    .process(&mut v, 10, |_, messages| {
+                        let topics = messages.iter().map(|m| m.topic.clone()).collect::<Vec<_>>();
+
+                        Box::pin(async move {
+                            assert_eq!(
+                                topics,
+                                vec![String::from("a")],
+                                "Could not relay released message"
+                            );
+                            Ok::<(), ConsumeError>(())
+                        })
+                    })
+                    .await
+                {
+                    Ok(v) => assert_eq!(v, 1, "Could not relay released message"),
+                    Err(_) => return Err(String::from("Could not relay released message")),
+                };
+
+                match outbox
+                    .process(&mut v, 10, |_, _| {
+                        Box::pin(
+                            async move { Err(ConsumeError::WriteError(String::from("called"))) },
+                        )
+                    })
+                    .await
+                {
+                    Ok(v) => assert_eq!(v, 0, "Could not skip empty outbox"),
+                    Err(_) => return Err(String::from("Could not skip empty outbox")),
+                };
+
+                match relayed.lock() {
+                    Ok(v) => assert_eq!(
+                        v.as_slice(),
+                        &[
+                            (String::from("b"), String::from("{}")),
+                            (String::from("c"), String::from("{}")),
+                        ],
+                        "Could not relay messages in order"
+                    ),
+                    Err(_) => return Err(String::from("Could not read relayed messages")),
+                };
+                Ok(())
+            }
+            Err(v) => Err(v.to_string()),
+        },
+        _ => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_summarize() -> Result<(), String> {
+    use pgde::{summarize, StatValue, UtcTimestamp};
+
+    db_env_assertion!();
+
+    match connect_to_database().await {
+        Ok(v) => {
+            let query = "select * from (values (3, 0.5::numeric, '2000-01-01 00:00:01+00'::timestamptz, '2000-01-03'::date, 'a'), (-2, 'NaN', '2000-01-01 00:00:00+00', null, null), (null, 1.5, '2000-01-01 00:00:02+00', '2000-01-01', 'c')) as t (amount, ratio, taken_at, day, label) where amount is distinct from $1;";
+
+            match summarize::<i32>(&v, query, &[&0i32]).await {
+                Ok(v) => {
+                    assert_eq!(v.rows, 3, "Could not count rows");
+                    assert_eq!(v.invalid_rows, 1, "Could not count invalid rows");
+                    assert_eq!(
+                        v.columns
+                            .iter()
+                            .map(|c| (c.name.as_str(), c.count, c.nulls, c.min, c.max))
+                            .collect::<Vec<_>>(),
+                        vec![
+                            (
+                                "amount",
+                                2,
+                                1,
+                                Some(StatValue::Integer(-2)),
+                                Some(StatValue::Integer(3))
+                            ),
+                            (
+                                "ratio",
+                                3,
+                                0,
+                                Some(StatValue::Float(0.5)),
+                                Some(StatValue::Float(1.5))
+                            ),
+                            (
+                                "taken_at",
+                                3,
+                                0,
+                                Some(StatValue::Timestamp(UtcTimestamp(0))),
+                                Some(StatValue::Timestamp(UtcTimestamp(2_000_000)))
+                            ),
+                            (
+                                "day",
+                                2,
+                                1,
+                                Some(StatValue::Date(0)),
+                                Some(StatValue::Date(2))
+                            ),
+                            ("label", 2, 1, None, None),
+                        ],
+                        "Could not summarize columns"
+                    );
+                }
+                Err(_) => return Err(String::from("Could not summarize query")),
+            };
+
+            Ok(())
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_verify_query() -> Result<(), String> {
+    use pgde::verify_query;
+
+    #[derive(RowConsumer)]
+    struct Pair {
+        id: i32,
+        name: String,
+    }
+
+    db_env_assertion!();
+
+    match connect_to_database().await {
+        Ok(v) => match v
+            .batch_execute(
+                "drop table if exists consume_verify_query; create table consume_verify_query (id integer, name text);",
+            )
+            .await
+        {
+            Ok(_) => {
+                if verify_query::<Pair>(&v, "select id, name from consume_verify_query;")
+                    .await
+                    .is_err()
+                {
+                    return Err(String::from("Could not verify query"));
+                }
+
+                match verify_query::<Pair>(&v, "select id from consume_verify_query;").await {
+                    Err(ConsumeError::ColumnCountMismatch { expected, actual }) => {
+                        assert_eq!((expected, actual), (2, 1), "Could not detect missing column")
+                    }
+                    _ => return Err(String::from("Could not detect missing column")),
+                };
+
+                match verify_query::<Pair>(&v, "select name, id from consume_verify_query;").await {
+                    Err(ConsumeError::ColumnTypeMismatch(v)) => {
+                        assert_eq!(v.len(), 2, "Could not detect mistyped columns")
+                    }
+                    _ => return Err(String::from("Could not detect mistyped columns")),
+                };
+
+                match verify_query::<Pair>(&v, "select id, missing from consume_verify_query;")
+                    .await
+                {
+                    Err(ConsumeError::DatabaseError { code, .. }) => {
+                        assert_eq!(code, "42703", "Could not detect dropped column")
+                    }
+                    _ => return Err(String::from("Could not detect dropped column")),
+                };
+
+                match Pair::consume(&v, "select id, name from consume_verify_query;", &[]).await {
+                    Ok(v) => assert!(v.iter().all(|p| p.id >= 0 && !p.name.is_empty())),
+                    Err(_) => return Err(String::from("Could not consume query")),
+                };
+
+                Ok(())
+            }
+            Err(_) => Err(String::from("Could not create test table")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_check_params() -> Result<(), String> {
+    use pgde::check_params;
+
+    db_env_assertion!();
+
+    let query = "select $1::bigint, $2::text, $3::boolean;";
+
+    match connect_to_database().await {
+        Ok(v) => {
+            match check_params(&v, query, &[&1i64, &"name", &true]).await {
+                Ok(mismatches) => assert!(
+                    mismatches.is_empty(),
+                    "Could not accept matching parameters"
+                ),
+                Err(_) => return Err(String::from("Could not check matching parameters")),
+            }
+
+            match check_params(&v, query, &[&"1", &"name", &1i32]).await {
+                Ok(mismatches) => {
+                    assert_eq!(
+                        mismatches
+                            .iter()
+                            .map(|v| (v.index, v.expected.as_str()))
+                            .collect::<Vec<_>>(),
+                        vec![(0, "int8"), (2, "bool")],
+                        "Could not report mismatched parameters"
+                    );
+                    assert!(
+                        mismatches[0].message.contains("&str"),
+                        "Could not name the Rust type of mismatched parameter"
+                    );
+                }
+                Err(_) => return Err(String::from("Could not check mismatched parameters")),
+            }
+
+            match check_params(&v, query, &[&1i64]).await {
+                Err(ConsumeError::InvalidQuery(_)) => Ok(()),
+                _ => Err(String::from("Checked wrong number of parameters")),
+            }
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "testing")]
+#[tokio::test]
+async fn consume_testing_assertions() -> Result<(), String> {
+    use pgde::testing::{assert_consumes_n, assert_roundtrip};
+
+    #[derive(Debug, PartialEq, QueryParams, RowConsumer)]
+    struct Account {
+        id: i64,
+        email: String,
+        note: Option<String>,
+    }
+
+    db_env_assertion!();
+
+    match connect_to_database().await {
+        Ok(v) => match v
+            .batch_execute(
+                "drop table if exists consume_testing_assertions; create table consume_testing_assertions (id bigint, email text, note text);",
+            )
+            .await
+        {
+            Ok(_) => {
+                for account in [
+                    Account {
+                        id: 1,
+                        email: String::from("a@example.com"),
+                        note: Some(String::from("first")),
+                    },
+                    Account {
+                        id: 2,
+                        email: String::from("b@example.com"),
+                        note: None,
+                    },
+                ] {
+                    assert_roundtrip(&v, "consume_testing_assertions", &account).await;
+                }
+
+                let rows = assert_consumes_n::<Account>(
+                    &v,
+                    "select id, email, note from consume_testing_assertions where id > $1 order by id;",
+                    &[&0i64],
+                    2,
+                )
+                .await;
+
+                assert_eq!(rows[1].note, None, "Could not read default column");
+
+                Ok(())
+            }
+            Err(_) => Err(String::from("Could not create test table")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "testing")]
+#[tokio::test]
+async fn consume_test_db() -> Result<(), String> {
+    use pgde::testing::{assert_consumes_n, TestDb};
+
+    db_env_assertion!();
+
+    match connect_to_database().await {
+        Ok(v) => {
+            let exists = "select count(*) from pg_namespace where nspname = $1;";
+
+            let db = match TestDb::create(&v).await {
+                Ok(v) => v,
+                Err(_) => return Err(String::from("Could not create test schema")),
+            };
+
+            let schema = String::from(db.schema());
+
+            let result = db
+                .run(|client| {
+                    Box::pin(async move {
+                        if let Err(e) = client
+                            .batch_execute("create table consume_test_db (id integer); insert into consume_test_db values (1);")
+                            .await
+                        {
+                            return Err(ConsumeError::from(e));
+                        }
+
+                        String::consume(client, "select table_schema::text from information_schema.tables where table_name = 'consume_test_db';", &[]).await
+                    })
+                })
+                .await;
+
+            match result {
+                Ok(v) => assert_eq!(v, vec![schema.clone()], "Could not isolate test table"),
+                Err(_) => return Err(String::from("Could not run test in schema")),
+            };
+
+            let counts = assert_consumes_n::<i64>(&v, exists, &[&schema], 1).await;
+            assert_eq!(counts, vec![0], "Could not drop test schema");
+
+            match TestDb::create(&v).await {
+                Ok(db) => {
+                    let schema = String::from(db.schema());
+                    drop(db);
+
+                    let counts = assert_consumes_n::<i64>(&v, exists, &[&schema], 1).await;
+                    assert_eq!(counts, vec![0], "Could not drop abandoned test schema");
+                }
+                Err(_) => return Err(String::from("Could not create test schema")),
+            };
+
+            match String::consume(&v, "select current_setting('search_path');", &[]).await {
+                Ok(v) => assert!(
+                    !v[0].contains("pgde_test_"),
+                    "Could not restore search_path"
+                ),
+                Err(_) => return Err(String::from("Could not read search_path")),
+            };
+
+            Ok(())
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "fake")]
+#[tokio::test]
+async fn consume_fake_row() -> Result<(), String> {
+    use pgde::FakeRow;
+    use pgde_derive::FakeRow;
+
+    #[derive(Debug, PartialEq, FakeRow, RowConsumer)]
+    struct Customer {
+        id: i64,
+        #[pgde(max_len = 8)]
+        name: String,
+        email: String,
+        #[pgde(trim)]
+        note: Option<String>,
+        active: bool,
+    }
+
+    db_env_assertion!();
+
+    let customers = Customer::fake_many(42, 50);
+
+    assert_eq!(
+        customers,
+        Customer::fake_many(42, 50),
+        "Could not repeat fake rows"
+    );
+    assert_ne!(
+        customers,
+        Customer::fake_many(43, 50),
+        "Could not vary fake rows"
+    );
+    assert!(
+        customers.iter().all(|v| v.name.chars().count() <= 8),
+        "Could not respect column length"
+    );
+    assert!(
+        customers
+            .iter()
+            .all(|v| v.email.ends_with("@example.com") && v.id > 0),
+        "Could not fake realistic values"
+    );
+    assert!(
+        customers.iter().any(|v| v.note.is_none()) && customers.iter().any(|v| v.note.is_some()),
+        "Could not fake optional values"
+    );
+
+    match connect_to_database().await {
+        Ok(v) => {
+            let query = "select $1::bigint, $2::varchar(8), $3::text, $4::text, $5::boolean;";
+            let first = &customers[0];
+
+            match Customer::consume(
+                &v,
+                query,
+                &[
+                    &first.id,
+                    &first.name,
+                    &first.email,
+                    &first.note,
+                    &first.active,
+                ],
+            )
+            .await
+            {
+                Ok(v) => assert_eq!(v[0], *first, "Could not consume fake row"),
+                Err(_) => return Err(String::from("Could not consume fake row")),
+            };
+
+            Ok(())
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_try_from_row() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(Debug, RowConsumer)]
+    struct Known {
+        id: i32,
+    }
+
+    #[derive(Debug, RowConsumer)]
+    #[pgde(skip_try_from)]
+    struct Custom {
+        id: i32,
+    }
+
+    impl TryFrom<Row> for Custom {
+        type Error = String;
+
+        fn try_from(row: Row) -> Result<Self, Self::Error> {
+            match row.try_get::<_, Option<i32>>(0) {
+                Ok(v) => Ok(Custom {
+                    id: v.unwrap_or(-1),
+                }),
+                Err(v) => Err(v.to_string()),
+            }
+        }
+    }
+
+    fn convert<T: TryFrom<Row>>(rows: Vec<Row>) -> Result<Vec<T>, T::Error> {
+        rows.into_iter().map(T::try_from).collect()
+    }
+
+    match connect_to_database().await {
+        Ok(v) => match v.query("select 1 union all select null::int;", &[]).await {
+            Ok(rows) => {
+                match v.query("select 1 union all select null::int;", &[]).await {
+                    Ok(rows) => match convert::<Custom>(rows) {
+                        Ok(result) => assert_eq!(
+                            result.iter().map(|v| v.id).collect::<Vec<i32>>(),
+                            vec![1, -1],
+                            "Could not convert row with own TryFrom implementation"
+                        ),
+                        Err(v) => return Err(v),
+                    },
+                    Err(v) => return Err(v.to_string()),
+                };
+
+                match Known::try_from(&rows[0]) {
+                    Ok(result) => assert_eq!(result.id, 1, "Could not convert row"),
+                    Err(v) => return Err(v.to_string()),
+                };
+
+                match convert::<Known>(rows) {
+                    Ok(_) => Err(String::from("Converted null into i32")),
+                    Err(v) => {
+                        assert_eq!(
+                            v,
+                            pgde::RowError(vec![String::from(
+                                "Conversion error occurred for field \"id\" on class \"Known\""
+                            )]),
+                            "Could not report conversion error"
+                        );
+
+                        Ok(())
+                    }
+                }
+            }
+            Err(v) => Err(v.to_string()),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "web")]
+#[tokio::test]
+async fn consume_web_json() -> Result<(), String> {
+    use axum_core::response::IntoResponse;
+    use http_body_util::BodyExt;
+
+    db_env_assertion!();
+
+    #[derive(Serialize, RowConsumer)]
+    struct Foo {
+        foo: i32,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => {
+            let response = pgde::web::PgdeJson::consume::<Foo>(&v, "select 1 as foo;", &[]).await;
+            let response = response.into_response();
+
+            assert_eq!(response.status(), 200, "Could not respond with status");
+            assert_eq!(
+                response.headers().get("content-type").map(|v| v.as_bytes()),
+                Some("application/json".as_bytes()),
+                "Could not respond with content type"
+            );
+
+            match response.into_body().collect().await {
+                Ok(body) => {
+                    assert_eq!(
+                        body.to_bytes(),
+                        "[{\"foo\":1}]".as_bytes(),
+                        "Could not respond with rows"
+                    );
+
+                    Ok(())
+                }
+                Err(v) => Err(v.to_string()),
+            }
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_selected() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    #[pgde(table = "consume_selected")]
+    struct Profile {
+        id: i32,
+        name: String,
+        bio: Option<String>,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => match v
+            .batch_execute(
+                "drop table if exists consume_selected;
+                create table consume_selected (
+                    id int,
+                    name text,
+                    bio text
+                );
+                insert into consume_selected values (1, 'a', 'long text'), (2, 'b', 'more text');",
+            )
+            .await
+        {
+            Ok(_) => match Profile::consume_selected(&v, &["name", "id"], "id = $1", &[&2]).await {
+                Ok(result) => {
+                    assert_eq!(result.len(), 1, "Could not filter selected columns");
+                    assert_eq!(result[0].id, 2, "Could not consume selected column");
+                    assert_eq!(result[0].name, "b", "Could not consume selected column");
+                    assert_eq!(result[0].bio, None, "Could not default unselected column");
+
+                    match Profile::consume_selected(&v, &["email"], "", &[]).await {
+                        Err(ConsumeError::InvalidQuery(_)) => Ok(()),
+                        _ => Err(String::from("Selected an unknown column")),
+                    }
+                }
+                Err(_) => Err(String::from("Could not consume selected columns")),
+            },
+            Err(v) => Err(v.to_string()),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_scalar_and_column() -> Result<(), String> {
+    db_env_assertion!();
+
+    match connect_to_database().await {
+        Ok(v) => {
+            match pgde::consume_scalar::<i64>(
+                &v,
+                "select count(*) from generate_series(1, 5);",
+                &[],
+            )
+            .await
+            {
+                Ok(result) => assert_eq!(result, 5, "Could not consume scalar"),
+                Err(_) => return Err(String::from("Could not consume scalar")),
+            };
+
+            match pgde::consume_scalar::<i32>(&v, "select generate_series(1, 2);", &[]).await {
+                Err(ConsumeError::UnexpectedRowCount(2)) => (),
+                _ => return Err(String::from("Consumed scalar from two rows")),
+            };
+
+            match pgde::consume_scalar::<i32>(&v, "select 1, 2;", &[]).await {
+                Err(ConsumeError::InvalidQuery(_)) => (),
+                _ => return Err(String::from("Consumed scalar from two columns")),
+            };
+
+            match pgde::consume_column::<String>(
+                &v,
+                "select v::text from generate_series(1, 3) as v;",
+                &[],
+            )
+            .await
+            {
+                Ok(result) => {
+                    assert_eq!(result, vec!["1", "2", "3"], "Could not consume column");
+                    Ok(())
+                }
+                Err(_) => Err(String::from("Could not consume column")),
+            }
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_exists() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    #[pgde(table = "consume_exists")]
+    struct Item {
+        id: i32,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => match v
+            .batch_execute(
+                "drop table if exists consume_exists;
+                create table consume_exists (
+                    id int
+                );
+                insert into consume_exists values (1), (2);",
+            )
+            .await
+        {
+            Ok(_) => {
+                match (
+                    Item::exists(&v, "id = $1", &[&2]).await,
+                    Item::exists(&v, "id = $1", &[&3]).await,
+                    Item::exists(&v, "", &[]).await,
+                ) {
+                    (Ok(found), Ok(missing), Ok(any)) => {
+                        assert!(found, "Could not find existing row");
+                        assert!(!missing, "Found missing row");
+                        assert!(any, "Could not find any row");
+                    }
+                    _ => return Err(String::from("Could not check existence")),
+                };
+
+                match pgde::query_exists(&v, "select * from consume_exists where id > $1;", &[&5])
+                    .await
+                {
+                    Ok(result) => assert!(!result, "Found missing row"),
+                    Err(_) => return Err(String::from("Could not check existence")),
+                };
+
+                match Item::select().order_by("id").consume(&v).await {
+                    Ok(result) => {
+                        let ids: Vec<i32> = result.iter().map(|v| v.id).collect();
+                        assert_eq!(ids, vec![1, 2], "Could not consume existing rows");
+                        Ok(())
+                    }
+                    Err(_) => Err(String::from("Could not consume existing rows")),
+                }
+            }
+            Err(v) => Err(v.to_string()),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_copy_csv() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer, Debug, PartialEq)]
+    #[pgde(copy)]
+    struct Item {
+        id: i32,
+        name: Option<String>,
+        active: bool,
+        #[pgde(trim)]
+        code: String,
+        #[pgde(null_as = "-1")]
+        rank: i32,
+        #[pgde(nan(none))]
+        score: Option<f64>,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => match v
+            .batch_execute(
+                "drop table if exists consume_copy_csv;
+                create table consume_copy_csv (
+                    id int,
+                    name text,
+                    active bool,
+                    code text,
+                    rank int,
+                    score float8
+                );
+                insert into consume_copy_csv values
+                    (1, 'a, \"b\"', true, ' x ', null, 'NaN'),
+                    (2, null, false, 'y', 2, 1.5),
+                    (3, E'c\td', true, '', 3, null);",
+            )
+            .await
+        {
+            Ok(_) => {
+                let expected = vec![
+                    Item {
+                        id: 1,
+                        name: Some(String::from("a, \"b\"")),
+                        active: true,
+                        code: String::from("x"),
+                        rank: -1,
+                        score: None,
+                    },
+                    Item {
+                        id: 2,
+                        name: None,
+                        active: false,
+                        code: String::from("y"),
+                        rank: 2,
+                        score: Some(1.5),
+                    },
+                    Item {
+                        id: 3,
+                        name: Some(String::from("c\td")),
+                        active: true,
+                        code: String::new(),
+                        rank: 3,
+                        score: None,
+                    },
+                ];
+
+                match Item::consume(&v, "select * from consume_copy_csv order by id;", &[]).await {
+                    Ok(result) => assert_eq!(result, expected, "Could not consume rows"),
+                    Err(_) => return Err(String::from("Could not consume rows")),
+                };
+
+                match Item::consume_copy_csv(
+                    &v,
+                    "copy (select * from consume_copy_csv order by id) to stdout with (format csv, header);",
+                    &CopyOptions::csv().header(true),
+                )
+                .await
+                {
+                    Ok(result) => assert_eq!(result, expected, "Could not consume csv output"),
+                    Err(_) => return Err(String::from("Could not consume csv output")),
+                };
+
+                match Item::consume_copy_csv(
+                    &v,
+                    "copy (select * from consume_copy_csv order by id) to stdout;",
+                    &CopyOptions::text(),
+                )
+                .await
+                {
+                    Ok(result) => {
+                        assert_eq!(result, expected, "Could not consume text output");
+                        Ok(())
+                    }
+                    Err(_) => Err(String::from("Could not consume text output")),
+                }
+            }
+            Err(v) => Err(v.to_string()),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_malformed_input() -> Result<(), String> {
+    use pgde::__private::parse_copy_output;
+    use pgde::{Interval, Number, Seconds, TextValue, UtcTimestamp};
+    use tokio_postgres::types::{FromSql, Type};
+
+    let values: [&[u8]; 6] = [
+        &[],
+        &[0xff],
+        &[0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 23, 0x7f, 0xff, 0xff, 0xff],
+        &[
+            0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 23, 0, 0, 0, 1, 0, 0, 0, 1, 0x7f, 0xff, 0xff, 0xff,
+        ],
+        &[0xff, 0xff, 0x7f, 0xff, 0xc0, 0, 0xff, 0xff, 0, 1, 0, 2],
+        &[0xff; 16],
+    ];
+
+    for raw in values {
+        for ty in [
+            Type::NUMERIC,
+            Type::MONEY,
+            Type::INT8,
+            Type::FLOAT8,
+            Type::BPCHAR,
+        ] {
+            let _ = TextValue::from_sql(&ty, raw);
+        }
+
+        let _ = Number::from_sql(&Type::FLOAT4, raw);
+        let _ = Seconds::from_sql(&Type::NUMERIC, raw);
+        let _ = Interval::from_sql(&Type::INTERVAL, raw);
+        let _ = UtcTimestamp::from_sql(&Type::TIMESTAMPTZ, raw);
+    }
+
+    let csv = CopyOptions::csv();
+    let text = CopyOptions::text();
+
+    assert!(
+        parse_copy_output(&[b"1,\"a"], &csv).is_err(),
+        "Could not reject an unterminated quote"
+    );
+    assert!(
+        parse_copy_output(&[b"1\t\\"], &text).is_err(),
+        "Could not reject a trailing backslash"
+    );
+    assert!(
+        parse_copy_output(&[b"\\777\t\xff"], &text).is_err(),
+        "Could not reject invalid text"
+    );
+
+    match (
+        parse_copy_output(&[b"1,\"a,", b"\"\"b\"\n2,\n"], &csv),
+        parse_copy_output(&[b"1\t\\N\n2\t\\x4", b"1x"], &text),
+    ) {
+        (Ok(csv), Ok(text)) => {
+            assert_eq!(
+                csv,
+                vec![
+                    vec![Some(String::from("1")), Some(String::from("a,\"b"))],
+                    vec![Some(String::from("2")), None],
+                ],
+                "Could not parse chunked CSV output"
+            );
+            assert_eq!(
+                text,
+                vec![
+                    vec![Some(String::from("1")), None],
+                    vec![Some(String::from("2")), Some(String::from("Ax"))],
+                ],
+                "Could not parse chunked text output"
+            );
+            Ok(())
+        }
+        _ => Err(String::from("Could not parse chunked output")),
+    }
+}
+
+#[tokio::test]
+async fn consume_dump_inserts() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer, Debug, PartialEq)]
+    struct Item {
+        id: i32,
+        name: Option<String>,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => match v
+            .batch_execute(
+                "drop table if exists consume_dump_inserts;
+                create table consume_dump_inserts (
+                    id int,
+                    name text
+                );
+                insert into consume_dump_inserts values (1, E'it''s a \\\\ \"test\"\n'), (2, null), (3, 'skipped');",
+            )
+            .await
+        {
+            Ok(_) => {
+                let mut dump = Vec::new();
+
+                match pgde::dump_inserts(
+                    &v,
+                    "select * from consume_dump_inserts where id < $1 order by id;",
+                    &[&3],
+                    &mut dump,
+                )
+                .await
+                {
+                    Ok(count) => assert_eq!(count, 2, "Could not dump rows"),
+                    Err(_) => return Err(String::from("Could not dump rows")),
+                };
+
+                match pgde::dump_inserts(&v, "select 1, id from consume_dump_inserts;", &[], &mut dump)
+                    .await
+                {
+                    Err(ConsumeError::InvalidQuery(_)) => (),
+                    _ => return Err(String::from("Could not reject computed columns")),
+                };
+
+                let dump = match String::from_utf8(dump) {
+                    Ok(v) => v,
+                    Err(_) => return Err(String::from("Could not read dump")),
+                };
+
+                match v
+                    .batch_execute(&format!("truncate consume_dump_inserts; {}", dump))
+                    .await
+                {
+                    Ok(_) => (),
+                    Err(_) => return Err(String::from("Could not replay dump")),
+                };
+
+                match Item::consume(&v, "select * from consume_dump_inserts order by id;", &[]).await {
+                    Ok(result) => {
+                        assert_eq!(
+                            result,
+                            vec![
+                                Item {
+                                    id: 1,
+                                    name: Some(String::from("it's a \\ \"test\"\n")),
+                                },
+                                Item { id: 2, name: None },
+                            ],
+                            "Could not replay dump"
+                        );
+                        Ok(())
+                    }
+                    Err(_) => Err(String::from("Could not consume replayed rows")),
+                }
+            }
+            Err(v) => Err(v.to_string()),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_diff() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer, Debug, PartialEq)]
+    struct Item {
+        id: i32,
+        name: String,
+    }
+
+    impl KeyedBy<i32> for Item {
+        fn key(&self) -> i32 {
+            self.id
+        }
+    }
+
+    match (connect_to_database().await, connect_to_database().await) {
+        (Ok(a), Ok(b)) => match a
+            .batch_execute(
+                "drop schema if exists consume_diff_a cascade;
+                drop schema if exists consume_diff_b cascade;
+                create schema consume_diff_a;
+                create schema consume_diff_b;
+                create table consume_diff_a.item (id int, name text);
+                create table consume_diff_b.item (id int, name text);
+                insert into consume_diff_a.item values (1, 'same'), (2, 'old'), (3, 'removed');
+                insert into consume_diff_b.item values (4, 'added'), (2, 'new'), (1, 'same');",
+            )
+            .await
+        {
+            Ok(_) => {
+                match (
+                    SessionSettings::new()
+                        .search_path(&["consume_diff_a"])
+                        .apply(&a)
+                        .await,
+                    SessionSettings::new()
+                        .search_path(&["consume_diff_b"])
+                        .apply(&b)
+                        .await,
+                ) {
+                    (Ok(_), Ok(_)) => (),
+                    _ => return Err(String::from("Could not set search path")),
+                };
+
+                match pgde::diff::<Item, i32>(&a, &b, "select * from item;", &[]).await {
+                    Ok(result) => {
+                        assert_eq!(
+                            result.added,
+                            vec![Item {
+                                id: 4,
+                                name: String::from("added"),
+                            }],
+                            "Could not find added rows"
+                        );
+                        assert_eq!(
+                            result.removed,
+                            vec![Item {
+                                id: 3,
+                                name: String::from("removed"),
+                            }],
+                            "Could not find removed rows"
+                        );
+                        assert_eq!(
+                            result.changed,
+                            vec![(
+                                Item {
+                                    id: 2,
+                                    name: String::from("old"),
+                                },
+                                Item {
+                                    id: 2,
+                                    name: String::from("new"),
+                                }
+                            )],
+                            "Could not find changed rows"
+                        );
+                    }
+                    Err(_) => return Err(String::from("Could not diff result sets")),
+                };
+
+                match pgde::diff::<Item, i32>(&a, &a, "select * from item;", &[]).await {
+                    Ok(result) => {
+                        assert!(
+                            result.is_empty(),
+                            "Found differences in identical result sets"
+                        );
+                        Ok(())
+                    }
+                    Err(_) => Err(String::from("Could not diff result sets")),
+                }
+            }
+            Err(v) => Err(v.to_string()),
+        },
+        _ => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "watcher")]
+#[tokio::test]
+async fn consume_ref_cache() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer, Clone, Debug, PartialEq)]
+    struct Country {
+        code: String,
+        name: String,
+    }
+
+    impl KeyedBy<String> for Country {
+        fn key(&self) -> String {
+            self.code.clone()
+        }
+    }
+
+    match (connect_to_database().await, connect_to_database().await) {
+        (Ok(v), Ok(admin)) => match v
+            .batch_execute(
+                "drop table if exists consume_ref_cache;
+                create table consume_ref_cache (
+                    code text,
+                    name text
+                );
+                insert into consume_ref_cache values ('nz', 'New Zealand'), ('pe', 'Peru');",
+            )
+            .await
+        {
+            Ok(_) => {
+                let cache = match pgde::RefCache::<String, Country>::new(
+                    Arc::new(v),
+                    "select * from consume_ref_cache;",
+                    Duration::from_millis(20),
+                )
+                .await
+                {
+                    Ok(v) => v,
+                    Err(_) => return Err(String::from("Could not load cache")),
+                };
+
+                assert_eq!(
+                    cache.get(&String::from("pe")).map(|v| v.name),
+                    Some(String::from("Peru")),
+                    "Could not look up cached row"
+                );
+
+                match admin
+                    .batch_execute("insert into consume_ref_cache values ('cl', 'Chile');")
+                    .await
+                {
+                    Ok(_) => (),
+                    Err(_) => return Err(String::from("Could not update reference table")),
+                };
+
+                for _ in 0..50 {
+                    if cache.snapshot().len() == 3 {
+                        break;
+                    }
+
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                }
+
+                assert_eq!(
+                    cache.snapshot().get("cl"),
+                    Some(&Country {
+                        code: String::from("cl"),
+                        name: String::from("Chile"),
+                    }),
+                    "Could not refresh cache in the background"
+                );
+                Ok(())
+            }
+            Err(v) => Err(v.to_string()),
+        },
+        _ => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "watcher")]
+#[tokio::test]
+async fn consume_ref_cache_listen() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer, Clone, Debug, PartialEq)]
+    struct Country {
+        code: String,
+        name: String,
+    }
+
+    impl KeyedBy<String> for Country {
+        fn key(&self) -> String {
+            self.code.clone()
+        }
+    }
+
+    let conn_string = format!(
+        "host={} user={} password={} dbname={}",
+        DATABASE_HOST, DATABASE_USER, DATABASE_PASSWORD, DATABASE_NAME
+    );
+
+    let (listener, mut listener_conn) = match tokio_postgres::connect(&conn_string, NoTls).await {
+        Ok(v) => v,
+        Err(_) => return Err(String::from("Could not connect to database")),
+    };
+
+    let messages = futures_util::stream::poll_fn(move |cx| listener_conn.poll_message(cx));
+
+    match (connect_to_database().await, connect_to_database().await) {
+        (Ok(v), Ok(admin)) => match v
+            .batch_execute(
+                "drop table if exists consume_ref_cache_listen;
+                create table consume_ref_cache_listen (
+                    code text,
+                    name text
+                );
+                insert into consume_ref_cache_listen values ('nz', 'New Zealand');",
+            )
+            .await
+        {
+            Ok(_) => {
+                let cache = match pgde::RefCache::<String, Country>::new(
+                    Arc::new(v),
+                    "select * from consume_ref_cache_listen;",
+                    Duration::from_secs(3600),
+                )
+                .await
+                {
+                    Ok(v) => v.tags(&["countries"]),
+                    Err(_) => return Err(String::from("Could not load cache")),
+                };
+
+                cache.listen("consume_ref_cache_listen", messages);
+
+                match listener
+                    .batch_execute("listen consume_ref_cache_listen;")
+                    .await
+                {
+                    Ok(_) => (),
+                    Err(_) => return Err(String::from("Could not listen for notifications")),
+                };
+
+                match admin
+                    .batch_execute(
+                        "insert into consume_ref_cache_listen values ('pe', 'Peru');
+                        notify consume_ref_cache_listen, 'currencies';",
+                    )
+                    .await
+                {
+                    Ok(_) => (),
+                    Err(_) => return Err(String::from("Could not notify cache")),
+                };
+
+                tokio::time::sleep(Duration::from_millis(200)).await;
+
+                assert_eq!(
+                    cache.snapshot().len(),
+                    1,
+                    "Could not ignore notification for other tags"
+                );
+
+                match admin
+                    .batch_execute("notify consume_ref_cache_listen, 'currencies, countries';")
+                    .await
+                {
+                    Ok(_) => (),
+                    Err(_) => return Err(String::from("Could not notify cache")),
+                };
+
+                for _ in 0..50 {
+                    if cache.snapshot().len() == 2 {
+                        break;
+                    }
+
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                }
+
+                assert_eq!(
+                    cache.get(&String::from("pe")),
+                    Some(Country {
+                        code: String::from("pe"),
+                        name: String::from("Peru"),
+                    }),
+                    "Could not reload cache on notification"
+                );
+                Ok(())
+            }
+            Err(v) => Err(v.to_string()),
+        },
+        _ => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "watcher")]
+#[tokio::test]
+async fn consume_drift_watchdog() -> Result<(), String> {
+    use pgde::DriftWatchdog;
+
+    #[derive(RowConsumer)]
+    struct Pair {
+        id: i32,
+        name: String,
+    }
+
+    db_env_assertion!();
+
+    match connect_to_database().await {
+        Ok(v) => match v
+            .batch_execute(
+                "drop table if exists consume_drift_watchdog; create table consume_drift_watchdog (id integer, name text);",
+            )
+            .await
+        {
+            Ok(_) => {
+                let client = Arc::new(v);
+                let drifted = Arc::new(Mutex::new(Vec::new()));
+                let recorded = drifted.clone();
+
+                let watchdog = DriftWatchdog::new(client.clone())
+                    .register::<Pair>("select id, name from consume_drift_watchdog;")
+                    .register::<Pair>("select * from consume_drift_watchdog;")
+                    .on_drift(move |drift| {
+                        if let Ok(mut v) = recorded.lock() {
+                            v.push(drift.query.clone());
+                        }
+                    });
+
+                assert!(watchdog.check().await.is_empty(), "Could not pass current queries");
+
+                if client
+                    .batch_execute("alter table consume_drift_watchdog add column note text;")
+                    .await
+                    .is_err()
+                {
+                    return Err(String::from("Could not alter test table"));
+                }
+
+                let drifts = watchdog.check().await;
+
+                assert_eq!(drifts.len(), 1, "Could not detect drift");
+
+                match &drifts[0].error {
+                    ConsumeError::ColumnCountMismatch { expected, actual } => {
+                        assert_eq!((*expected, *actual), (2, 3), "Could not report drift")
+                    }
+                    _ => return Err(String::from("Could not report drift")),
+                };
+
+                match drifted.lock() {
+                    Ok(v) => assert_eq!(
+                        v.as_slice(),
+                        &[String::from("select * from consume_drift_watchdog;")],
+                        "Could not notify drift"
+                    ),
+                    Err(_) => return Err(String::from("Could not read drifted queries")),
+                };
+
+                match Pair::consume(&client, "select id, name from consume_drift_watchdog;", &[]).await {
+                    Ok(v) => assert!(v.iter().all(|p| p.id >= 0 && !p.name.is_empty())),
+                    Err(_) => return Err(String::from("Could not consume query")),
+                };
+
+                Ok(())
+            }
+            Err(_) => Err(String::from("Could not create test table")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "watcher")]
+#[tokio::test]
+async fn consume_watcher() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer, Clone, Debug, PartialEq)]
+    struct Setting {
+        name: String,
+        value: i32,
+    }
+
+    impl KeyedBy<String> for Setting {
+        fn key(&self) -> String {
+            self.name.clone()
+        }
+    }
+
+    let conn_string = format!(
+        "host={} user={} password={} dbname={}",
+        DATABASE_HOST, DATABASE_USER, DATABASE_PASSWORD, DATABASE_NAME
+    );
+
+    let (listener, mut listener_conn) = match tokio_postgres::connect(&conn_string, NoTls).await {
+        Ok(v) => v,
+        Err(_) => return Err(String::from("Could not connect to database")),
+    };
+
+    let messages = futures_util::stream::poll_fn(move |cx| listener_conn.poll_message(cx));
+
+    match (connect_to_database().await, connect_to_database().await) {
+        (Ok(v), Ok(admin)) => match v
+            .batch_execute(
+                "drop table if exists consume_watcher;
+                create table consume_watcher (
+                    name text,
+                    value int
+                );
+                insert into consume_watcher values ('a', 1), ('b', 2);",
+            )
+            .await
+        {
+            Ok(_) => {
+                let changes = Arc::new(Mutex::new(Vec::new()));
+                let recorded = changes.clone();
+
+                let watcher = Arc::new(
+                    pgde::Watcher::<Setting>::new(
+                        Arc::new(v),
+                        "select * from consume_watcher order by name;",
+                    )
+                    .on_change(move |diff| {
+                        if let Ok(mut v) = recorded.lock() {
+                            v.push((diff.added.len(), diff.removed.len(), diff.changed.len()));
+                        }
+                    }),
+                );
+
+                match watcher.refresh().await {
+                    Ok(result) => assert_eq!(result.len(), 2, "Could not refresh watcher"),
+                    Err(_) => return Err(String::from("Could not refresh watcher")),
+                };
+
+                let handle = watcher.listen(messages);
+
+                match listener.batch_execute("listen consume_watcher;").await {
+                    Ok(_) => (),
+                    Err(_) => return Err(String::from("Could not listen for notifications")),
+                };
+
+                match admin
+                    .batch_execute(
+                        "update consume_watcher set value = 3 where name = 'b';
+                        insert into consume_watcher values ('c', 4);
+                        notify consume_watcher;",
+                    )
+                    .await
+                {
+                    Ok(_) => (),
+                    Err(_) => return Err(String::from("Could not notify watcher")),
+                };
+
+                for _ in 0..50 {
+                    if watcher.current().len() == 3 {
+                        break;
+                    }
+
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                }
+
+                handle.abort();
+
+                assert_eq!(
+                    watcher.current().as_slice(),
+                    &[
+                        Setting {
+                            name: String::from("a"),
+                            value: 1,
+                        },
+                        Setting {
+                            name: String::from("b"),
+                            value: 3,
+                        },
+                        Setting {
+                            name: String::from("c"),
+                            value: 4,
+                        },
+                    ],
+                    "Could not refresh watcher on notification"
+                );
+
+                let changes = match changes.lock() {
+                    Ok(v) => v.clone(),
+                    Err(_) => return Err(String::from("Could not read changes")),
+                };
+
+                assert_eq!(
+                    changes,
+                    vec![(2, 0, 0), (1, 0, 1)],
+                    "Could not compute changes"
+                );
+                Ok(())
+            }
+            Err(v) => Err(v.to_string()),
+        },
+        _ => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_with_progress() -> Result<(), String> {
+    db_env_assertion!();
+
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let recorded = progress.clone();
+
+    let options = ConsumeOptions::new().on_progress(4, move |rows_done, _| {
+        if let Ok(mut v) = recorded.lock() {
+            v.push(rows_done);
+        }
+    });
+
+    match connect_to_database().await {
+        Ok(v) => {
+            match i32::consume_with_options(&v, "select generate_series(1, 10);", &[], &options)
+                .await
+            {
+                Ok(result) => assert_eq!(result.len(), 10, "Could not consume rows"),
+                Err(_) => return Err(String::from("Could not consume rows")),
+            }
+        }
+        Err(_) => return Err(String::from("Could not connect to database")),
+    };
+
+    let progress = match progress.lock() {
+        Ok(v) => v.clone(),
+        Err(_) => return Err(String::from("Could not read progress")),
+    };
+
+    assert_eq!(progress, vec![4, 8, 10], "Could not report progress");
+    Ok(())
+}
+
+#[tokio::test]
+async fn consume_count_and_execute() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    #[pgde(table = "consume_count_and_execute")]
+    struct Item {
+        id: i32,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => match v
+            .batch_execute(
+                "drop table if exists consume_count_and_execute;
+                create table consume_count_and_execute (
+                    id int
+                );",
+            )
+            .await
+        {
+            Ok(_) => {
+                match pgde::execute(
+                    &v,
+                    "insert into consume_count_and_execute select generate_series(1, $1);",
+                    &[&5],
+                )
+                .await
+                {
+                    Ok(result) => assert_eq!(result, 5, "Could not execute insert"),
+                    Err(_) => return Err(String::from("Could not execute insert")),
+                };
+
+                match (
+                    Item::consume_count(&v, "id > $1", &[&2]).await,
+                    Item::consume_count(&v, "", &[]).await,
+                ) {
+                    (Ok(filtered), Ok(all)) => {
+                        assert_eq!(filtered, 3, "Could not count filtered rows");
+                        assert_eq!(all, 5, "Could not count rows");
+                    }
+                    _ => return Err(String::from("Could not count rows")),
+                };
+
+                match Item::consume(
+                    &v,
+                    "select * from consume_count_and_execute where id = 1;",
+                    &[],
+                )
+                .await
+                {
+                    Ok(result) => {
+                        assert_eq!(result[0].id, 1, "Could not consume rows");
+                        Ok(())
+                    }
+                    Err(_) => Err(String::from("Could not consume rows")),
+                }
+            }
+            Err(v) => Err(v.to_string()),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_ident() -> Result<(), String> {
+    db_env_assertion!();
+
+    let name = "consume_ident\"; drop table consume_ident_target; --";
+
+    let table = match pgde::ident::QualifiedName::new(Some("public"), name) {
+        Ok(v) => v,
+        Err(_) => return Err(String::from("Could not validate table name")),
+    };
+
+    assert!(
+        pgde::ident::Ident::new("").is_err(),
+        "Could not reject empty identifier"
+    );
+    assert!(
+        pgde::ident::Ident::new(&"a".repeat(64)).is_err(),
+        "Could not reject long identifier"
+    );
+
+    match connect_to_database().await {
+        Ok(v) => match v
+            .batch_execute(&format!(
+                "drop table if exists {table};
+                create table if not exists consume_ident_target (id int);
+                create table {table} (id int);
+                insert into {table} values (1);"
+            ))
+            .await
+        {
+            Ok(_) => match i32::consume(&v, &format!("select id from {};", table), &[]).await {
+                Ok(result) => {
+                    assert_eq!(result, vec![1], "Could not consume from quoted table");
+
+                    match pgde::query_exists(
+                        &v,
+                        "select * from pg_tables where tablename = 'consume_ident_target';",
+                        &[],
+                    )
+                    .await
+                    {
+                        Ok(result) => {
+                            assert!(result, "Could not keep target table");
+                            Ok(())
+                        }
+                        Err(_) => Err(String::from("Could not find target table")),
+                    }
+                }
+                Err(_) => Err(String::from("Could not consume from quoted table")),
+            },
+            Err(v) => Err(v.to_string()),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[cfg(feature = "uuid")]
+#[tokio::test]
+async fn consume_pg_reexports() -> Result<(), String> {
+    db_env_assertion!();
+
+    let client: pgde::pg::Client = match connect_to_database().await {
+        Ok(v) => v,
+        Err(_) => return Err(String::from("Could not connect to database")),
+    };
+
+    let id = pgde::pg::Uuid::new_v4();
+
+    match pgde::pg::Uuid::consume(&client, "select $1::uuid;", &[&id]).await {
+        Ok(result) => {
+            assert_eq!(result, vec![id], "Could not consume re-exported type");
+            Ok(())
+        }
+        Err(_) => Err(String::from("Could not consume re-exported type")),
+    }
+}
+
+#[tokio::test]
+async fn consume_enum() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer, Debug, Default, PartialEq)]
+    enum Status {
+        #[default]
+        #[pgde(rename = "active")]
+        Active,
+        #[pgde(rename = "inactive")]
+        Inactive,
+    }
+
+    #[derive(RowConsumer, Debug, Default, PartialEq)]
+    #[pgde(repr = "int")]
+    enum Code {
+        #[default]
+        Ok = 200,
+        NotFound = 404,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => {
+            match Status::consume(
+                &v,
+                "select * from (values ('inactive'::char(10)), ('active')) as t;",
+                &[],
+            )
+            .await
+            {
+                Ok(result) => assert_eq!(
+                    result,
+                    vec![Status::Inactive, Status::Active],
+                    "Could not consume text into enum"
+                ),
+                Err(_) => return Err(String::from("Could not consume text into enum")),
+            };
+
+            match Code::consume(&v, "select * from (values (404), (200)) as t;", &[]).await {
+                Ok(result) => assert_eq!(
+                    result,
+                    vec![Code::NotFound, Code::Ok],
+                    "Could not consume int into enum"
+                ),
+                Err(_) => return Err(String::from("Could not consume int into enum")),
+            };
+
+            match Status::consume(&v, "select 'pending';", &[]).await {
+                Err(ConsumeError::ConversionError) => Ok(()),
+                _ => Err(String::from("Could not reject unknown enum value")),
+            }
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_tagged_enum() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer, Debug, Default, PartialEq)]
+    #[pgde(tag = "kind")]
+    enum Event {
+        #[default]
+        #[pgde(rename = "ping")]
+        Ping,
+        #[pgde(rename = "login")]
+        Login { user_name: String },
+        #[pgde(rename = "purchase")]
+        Purchase { user_name: String, amount: i32 },
+    }
+
+    match connect_to_database().await {
+        Ok(v) => match v
+            .batch_execute(
+                "drop table if exists consume_tagged_enum;
+                create table consume_tagged_enum (
+                    id int,
+                    kind text,
+                    user_name text,
+                    amount int
+                );
+                insert into consume_tagged_enum values
+                    (1, 'login', 'alice', null),
+                    (2, 'purchase', 'alice', 30),
+                    (3, 'ping', null, null);",
+            )
+            .await
+        {
+            Ok(_) => {
+                match Event::consume(&v, "select * from consume_tagged_enum order by id;", &[])
+                    .await
+                {
+                    Ok(result) => assert_eq!(
+                        result,
+                        vec![
+                            Event::Login {
+                                user_name: String::from("alice"),
+                            },
+                            Event::Purchase {
+                                user_name: String::from("alice"),
+                                amount: 30,
+                            },
+                            Event::Ping,
+                        ],
+                        "Could not consume tagged rows into enum"
+                    ),
+                    Err(_) => return Err(String::from("Could not consume tagged rows into enum")),
+                };
+
+                match Event::consume(&v, "select 'refund' as kind;", &[]).await {
+                    Err(ConsumeError::ConversionError) => Ok(()),
+                    _ => Err(String::from("Could not reject unknown tag")),
+                }
+            }
+            Err(v) => Err(v.to_string()),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[tokio::test]
+async fn consume_ambiguous_columns() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer, Debug, Default, PartialEq)]
+    #[pgde(tag = "kind")]
+    enum Event {
+        #[default]
+        #[pgde(rename = "ping")]
+        Ping,
+        #[pgde(rename = "login")]
+        Login { user_name: String },
+    }
+
+    match connect_to_database().await {
+        Ok(v) => match Event::consume(
+            &v,
+            "select 'login' as kind, 'alice' as user_name, 1 as id, 2 as id;",
+            &[],
+        )
+        .await
+        {
+            Ok(result) => {
+                assert_eq!(
+                    result,
+                    vec![Event::Login {
+                        user_name: String::from("alice"),
+                    }],
+                    "Could not ignore duplicates of unread columns"
+                );
+
+                match Event::consume(
+                    &v,
+                    "select 'login' as kind, 'alice' as user_name, 'bob' as user_name;",
+                    &[],
+                )
+                .await
+                {
+                    Err(ConsumeError::AmbiguousColumns(v)) => {
+                        assert_eq!(
+                            v,
+                            vec![String::from("user_name")],
+                            "Could not report ambiguous columns"
+                        );
+                        Ok(())
+                    }
+                    _ => Err(String::from("Could not detect ambiguous columns")),
+                }
+            }
+            Err(_) => Err(String::from("Could not consume tagged row into enum")),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+#[row_consumer(schema = "consume_row_consumer_attribute")]
+mod row_consumer_models {
+    use tokio_postgres::Row;
+
+    #[derive(Debug, PartialEq)]
+    pub struct Account {
+        pub id: i32,
+        pub name: String,
+    }
+}
+
+#[tokio::test]
+async fn consume_row_consumer_attribute() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[row_consumer(table = "item", partial, columns)]
+    struct Item {
+        id: i32,
+        name: Option<String>,
+        #[pgde(trim)]
+        code: String,
+        #[pgde(interval)]
+        retention: Duration,
+    }
+
+    use row_consumer_models::Account;
+
+    assert_eq!(Item::ID, "id", "Could not generate column constants");
+    assert_eq!(Item::NAME, "name", "Could not generate column constants");
+    assert_eq!(
+        Account::SCHEMA,
+        Some("consume_row_consumer_attribute"),
+        "Could not apply module configuration"
+    );
+
+    match connect_to_database().await {
+        Ok(v) => match v
+            .batch_execute(
+                "drop schema if exists consume_row_consumer_attribute cascade;
+                create schema consume_row_consumer_attribute;
+                create table consume_row_consumer_attribute.\"Account\" (id int, name text);
+                insert into consume_row_consumer_attribute.\"Account\" values (1, 'alice');",
+            )
+            .await
+        {
+            Ok(_) => {
+                match Account::select().consume(&v).await {
+                    Ok(result) => assert_eq!(
+                        result,
+                        vec![Account {
+                            id: 1,
+                            name: String::from("alice"),
+                        }],
+                        "Could not consume configured struct"
+                    ),
+                    Err(_) => return Err(String::from("Could not consume configured struct")),
+                };
+
+                match ItemPartial::consume(
+                    &v,
+                    "select 'bob' as name, ' b1 ' as code, interval '1 minute' as retention;",
+                    &[],
+                )
+                .await
+                {
+                    Ok(result) => {
+                        assert_eq!(result[0].id, None, "Could not skip missing column");
+                        assert_eq!(
+                            result[0].name,
+                            Some(Some(String::from("bob"))),
+                            "Could not consume partial struct"
+                        );
+                        assert_eq!(
+                            result[0].code,
+                            Some(String::from("b1")),
+                            "Could not apply field attributes to partial struct"
+                        );
+                        assert_eq!(
+                            result[0].retention,
+                            Some(Duration::from_secs(60)),
+                            "Could not apply field attributes to partial struct"
+                        );
+                    }
+                    Err(_) => return Err(String::from("Could not consume partial struct")),
+                };
+
+                match Item::consume(&v, "select 2, 'carol', 'c2', interval '0';", &[]).await {
+                    Ok(result) => {
+                        assert_eq!(result[0].id, 2, "Could not consume struct");
+                        assert_eq!(
+                            result[0].name,
+                            Some(String::from("carol")),
+                            "Could not consume struct"
+                        );
+                        assert_eq!(
+                            (result[0].code.as_str(), result[0].retention),
+                            ("c2", Duration::ZERO),
+                            "Could not consume struct"
+                        );
+                        Ok(())
+                    }
+                    Err(_) => Err(String::from("Could not consume struct")),
+                }
+            }
+            Err(v) => Err(v.to_string()),
+        },
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}
+
+fn parse_socket_addr(value: String) -> Option<std::net::SocketAddr> {
+    value.parse().ok()
+}
+
+pgde::impl_row_consumer!(
+    SocketAddrDef for std::net::SocketAddr,
+    via = String,
+    with = parse_socket_addr,
+    default = std::net::SocketAddr::from(([0, 0, 0, 0], 0))
+);
+
+#[tokio::test]
+async fn consume_remote() -> Result<(), String> {
+    db_env_assertion!();
+
+    #[derive(RowConsumer)]
+    struct Peer {
+        #[pgde(remote = "SocketAddrDef")]
+        address: std::net::SocketAddr,
+        #[pgde(remote = "SocketAddrDef")]
+        backup: Option<std::net::SocketAddr>,
+    }
+
+    match connect_to_database().await {
+        Ok(v) => {
+            match Peer::consume(&v, "select '127.0.0.1:5432', null::text;", &[]).await {
+                Ok(result) => {
+                    assert_eq!(
+                        result[0].address,
+                        std::net::SocketAddr::from(([127, 0, 0, 1], 5432)),
+                        "Could not consume remote type"
+                    );
+                    assert_eq!(result[0].backup, None, "Could not consume null remote type");
+                }
+                Err(_) => return Err(String::from("Could not consume remote type")),
+            };
+
+            match Peer::consume(&v, "select 'localhost', null::text;", &[]).await {
+                Err(ConsumeError::ConversionError) => Ok(()),
+                _ => Err(String::from("Could not reject unconvertible remote type")),
+            }
+        }
+        Err(_) => Err(String::from("Could not connect to database")),
+    }
+}